@@ -5,6 +5,7 @@ export function MainMenu() {
   const setScreen = useUIStore((s) => s.setScreen);
   const setPlayMode = useUIStore((s) => s.setPlayMode);
   const gameState = useGameStore((s) => s.gameState);
+  const engine = useGameStore((s) => s.engine);
 
   const startGame = (mode: 'hotseat' | 'singleplayer' | 'online') => {
     setPlayMode(mode);
@@ -34,6 +35,7 @@ export function MainMenu() {
         <div style={styles.status}>
           Engine loaded | Turn {gameState.turn_number} |{' '}
           {gameState.current_power} | {gameState.current_phase}
+          {engine && <> | Seed {engine.seed().toString()}</>}
         </div>
       )}
     </div>
@@ -17,6 +17,8 @@ export type Power =
 
 export type Team = 'Axis' | 'Allies';
 
+export type ControllerKind = 'Human' | 'AI' | 'Remote';
+
 export type Phase =
   | 'PurchaseAndRepair'
   | 'CombatMovement'
@@ -49,6 +51,8 @@ export interface UnitInstance {
   moved_this_turn: boolean;
   movement_remaining: number;
   cargo: number[];
+  restricted_to_china: boolean;
+  veteran: boolean;
 }
 
 export interface TerritoryState {
@@ -77,6 +81,8 @@ export interface PowerState {
   at_war: boolean;
   capital_captured: boolean;
   researched_techs: string[];
+  surrendered: boolean;
+  purchase_history: [number, [UnitType, number][]][];
 }
 
 export interface PhaseState {
@@ -96,8 +102,35 @@ export interface GameState {
   undo_checkpoints: number[];
   rng_seed: number;
   rng_counter: number;
+  creation_seed: number | null;
+  rng_mode: 'SharedCounter' | 'PerBattle';
+  sandbox_mode: boolean;
+  settings: GameSettings;
+  winner: Team | null;
+  economic_leader: [Team, number] | null;
+  combat_history: BattleRecord[];
+}
+
+export interface GameSettings {
+  upkeep_per_unit: number;
+  max_combat_rounds: number;
+  strict_purchase_capacity: boolean;
+  victory_condition: VictoryCondition;
+  veteran_units: boolean;
+  max_units_per_territory: number | null;
+  production_rules: { [key in Power]?: AllowedUnits };
+  scorched_earth_damage: number;
 }
 
+export type AllowedUnits = { Only: UnitType[] } | { AllExcept: UnitType[] };
+
+export type VictoryCondition =
+  | 'VictoryCities'
+  | { EconomicDomination: { percent: number } }
+  | 'CapitalCapture';
+
+export type Relation = 'War' | 'Peace';
+
 export interface PoliticalState {
   war_matrix: boolean[][];
   triggers: PoliticalTriggers;
@@ -122,36 +155,176 @@ export type Action =
   | 'ConfirmNonCombatMovement'
   | 'ConfirmMobilization'
   | 'ConfirmIncome'
+  | 'EndOperation'
   | 'Undo'
   | { PurchaseUnit: { unit_type: UnitType; count: number } }
   | { RemovePurchase: { unit_type: UnitType; count: number } }
   | { RepairFacility: { territory_id: number; damage_to_repair: number } }
   | { MoveUnit: { unit_id: number; path: RegionId[] } }
-  | { PlaceUnit: { unit_type: UnitType; territory_id: number } }
-  | { DeclareWar: { against: Power } };
+  | { MoveUnits: { unit_ids: number[]; path: RegionId[] } }
+  | { UndoMove: { unit_id: number } }
+  | { BeginOperation: { name: string } }
+  | { UndoOperation: { name: string } }
+  | { StrategicBombingRaid: { territory_id: number } }
+  | { PlaceUnit: { unit_type: UnitType; territory_id: number; sea_zone?: number | null } }
+  | { DeclareWar: { against: Power } }
+  | { Surrender: { power: Power } }
+  | { SetRelation: { a: Power; b: Power; relation: Relation } };
+
+export interface RngState {
+  seed: bigint;
+  counter: bigint;
+}
+
+export interface BattleRecord {
+  turn: number;
+  location: RegionId;
+  attacker: Power;
+  defender: Power;
+  rounds: number;
+  dice_rolled: number;
+  attacker_losses: UnitType[];
+  defender_losses: UnitType[];
+  attacker_won: boolean;
+  stalemate: boolean;
+}
+
+export interface BattleSide {
+  power: Power;
+  units: UnitType[];
+}
+
+export interface BattleOdds {
+  trials: number;
+  attacker_win_rate: number;
+  defender_win_rate: number;
+  draw_rate: number;
+  attacker_survivors: number[];
+  defender_survivors: number[];
+}
+
+export interface RegionCombatContext {
+  region: RegionId;
+  owner: Power | null;
+  units: { [key in Power]?: UnitInstance[] };
+  coastal: boolean;
+  bombardment_ships: number[];
+  has_aaa: boolean;
+  scramble_bases: number[];
+}
+
+export type InvasionKind = 'Land' | 'Amphibious' | 'Combined';
+
+export interface InvasionPlan {
+  kind: InvasionKind;
+  origin: number;
+  path: RegionId[];
+}
+
+export interface PlannedMove {
+  unit_id: number;
+  path: RegionId[];
+  from: RegionId;
+  to: RegionId;
+  operation: string | null;
+}
+
+export interface IncomeBreakdown {
+  base_income: number;
+  objective_bonus: number;
+  convoy_losses: number;
+  upkeep: number;
+  total: number;
+}
+
+export interface TurnInfo {
+  turn_number: number;
+  power: Power;
+  phase: Phase;
+  current_ipcs: number;
+}
+
+export type ObligationKind =
+  | 'UnresolvedBattle'
+  | 'BattleInProgress'
+  | { AirUnitMustLand: { unit_id: number } }
+  | { UnitsToPlace: { unit_type: UnitType; count: number } }
+  | { PurchasesExceedCapacity: { purchased: number; capacity: number } };
+
+export interface Obligation {
+  kind: ObligationKind;
+  description: string;
+  region: RegionId | null;
+}
 
 export interface LegalAction {
   action: Action;
   description: string;
 }
 
+export type CombatSubPhase =
+  | 'AAFire'
+  | 'AAFireCasualties'
+  | 'ShoreBombardment'
+  | 'ShoreBombardmentCasualties'
+  | 'AttackerSubmarineStrike'
+  | 'DefenderSubmarineStrikeCasualties'
+  | 'DefenderSubmarineStrike'
+  | 'AttackerSubmarineStrikeCasualties'
+  | 'AttackerRolls'
+  | 'DefenderRolls'
+  | 'DefenderSelectsCasualties'
+  | 'AttackerSelectsCasualties'
+  | 'AttackerDecision'
+  | 'BattleOver';
+
+export interface CombatStep {
+  location: RegionId;
+  round: number;
+  sub_phase: CombatSubPhase;
+  rolls: number[];
+  hits: number;
+  pending_attacker_hits: number;
+  pending_defender_hits: number;
+  battle_over: boolean;
+  casualties: number[];
+}
+
 export interface ActionResult {
   applied: {
     action: Action;
   };
   events: GameEvent[];
+  state_checksum: number;
+  combat_step: CombatStep | null;
 }
 
 export type GameEvent =
   | { PhaseChanged: { from: Phase; to: Phase } }
   | { TurnChanged: { power: Power; turn: number } }
   | { WarDeclared: { aggressor: Power; target: Power } }
+  | { PowerSurrendered: { power: Power } }
+  | { RelationChanged: { a: Power; b: Power; relation: Relation } }
   | { VictoryAchieved: { winner: Team } }
   | { UnitsPurchased: { unit_type: UnitType; count: number; cost: number } }
-  | { UnitsPlaced: { unit_type: UnitType; territory_id: number } }
+  | { UnitsPlaced: { unit_type: UnitType; territory_id: number; sea_zone: number | null } }
   | { IncomeCollected: { power: Power; amount: number } }
   | { BattleStarted: { location: RegionId } }
   | { BattleEnded: { location: RegionId; attacker_won: boolean } }
+  | { BattleStalemate: { location: RegionId } }
+  | { AmphibiousAssaultCancelled: { territory_id: number } }
+  | { TerritoryCaptured: { territory_id: number; by: Power } }
   | { CapitalCaptured: { territory_id: number; by: Power } }
   | { TerritoryLiberated: { territory_id: number; to: Power } }
-  | { ConvoyDisrupted: { zone: number; power: Power; lost_ipcs: number } };
+  | { NeutralActivated: { territory_id: number; power: Power } }
+  | { FacilityBombed: { territory_id: number; damage: number; total_damage: number } }
+  | { ConvoyDisrupted: { zone: number; power: Power; lost_ipcs: number } }
+  | { IpcChanged: { power: Power; delta: number; new_total: number; reason: IpcChangeReason } };
+
+export type IpcChangeReason =
+  | 'Purchase'
+  | 'PurchaseRefund'
+  | 'Repair'
+  | 'Income'
+  | 'CapitalCaptured'
+  | 'Surrender';
@@ -4,7 +4,17 @@
  */
 
 import type { WasmEngine } from './pkg/aa_wasm';
-import type { GameState, Action, ActionResult, LegalAction, GameEvent } from '../types/game';
+import type { GameState, Action, ActionResult, LegalAction, GameEvent, TurnInfo, Power, Obligation, RegionId, TerritoryState, SeaZoneState, ControllerKind, PlannedMove, IncomeBreakdown, BattleRecord, BattleSide, BattleOdds, RngState, InvasionPlan, RegionCombatContext } from '../types/game';
+
+/** An error reported by the engine; `code` is a stable category such as 'WRONG_PHASE'. */
+export class EngineCallError extends Error {
+  readonly code: string;
+
+  constructor(code: string, message: string) {
+    super(message);
+    this.code = code;
+  }
+}
 
 export class GameEngine {
   private engine: WasmEngine;
@@ -22,7 +32,17 @@ export class GameEngine {
     const json = this.engine.submitAction(JSON.stringify(action));
     const result = JSON.parse(json);
     if (result.error) {
-      throw new Error(result.message);
+      throw new EngineCallError(result.code, result.message);
+    }
+    return result as ActionResult;
+  }
+
+  /** Submit an action already encoded as MessagePack with `WasmEngine.encodeAction`. */
+  submitActionBinary(bytes: Uint8Array): ActionResult {
+    const json = this.engine.submitActionBinary(bytes);
+    const result = JSON.parse(json);
+    if (result.error) {
+      throw new EngineCallError(result.code, result.message);
     }
     return result as ActionResult;
   }
@@ -35,6 +55,21 @@ export class GameEngine {
     return this.engine.canUndo();
   }
 
+  setSandboxMode(enabled: boolean): void {
+    this.engine.setSandboxMode(enabled);
+  }
+
+  rngState(): RngState {
+    const json = this.engine.rngState();
+    const raw = JSON.parse(json) as { seed: string; counter: string };
+    return { seed: BigInt(raw.seed), counter: BigInt(raw.counter) };
+  }
+
+  /** Testing tool: replay a recorded dice sequence. Requires sandbox mode. */
+  setRngState(seed: bigint, counter: bigint): void {
+    this.engine.setRngState(seed, counter);
+  }
+
   legalActions(): LegalAction[] {
     const json = this.engine.legalActions();
     return JSON.parse(json) as LegalAction[];
@@ -49,6 +84,134 @@ export class GameEngine {
     return this.engine.turnSummary();
   }
 
+  /** The game's seed; `new WasmEngine(seed)` recreates the same start. */
+  seed(): bigint {
+    return this.engine.seed();
+  }
+
+  turnInfo(): TurnInfo {
+    const json = this.engine.turnInfo();
+    return JSON.parse(json) as TurnInfo;
+  }
+
+  pendingObligations(): Obligation[] {
+    const json = this.engine.pendingObligations();
+    return JSON.parse(json) as Obligation[];
+  }
+
+  territoryState(id: number): TerritoryState {
+    const json = this.engine.territoryState(id);
+    return JSON.parse(json) as TerritoryState;
+  }
+
+  seaZoneState(id: number): SeaZoneState {
+    const json = this.engine.seaZoneState(id);
+    return JSON.parse(json) as SeaZoneState;
+  }
+
+  legalDestinations(unitId: number): RegionId[] {
+    const json = this.engine.legalDestinations(unitId);
+    return JSON.parse(json) as RegionId[];
+  }
+
+  /** Regions with enemy units the unit could attack this Combat Movement. */
+  attackableTargets(unitId: number): RegionId[] {
+    const json = this.engine.attackableTargets(unitId);
+    return JSON.parse(json) as RegionId[];
+  }
+
+  /** Why a unit cannot move right now, or null if it can. */
+  movementBlockReason(unitId: number): string | null {
+    return this.engine.movementBlockReason(unitId) ?? null;
+  }
+
+  idleUnitCount(): number {
+    return this.engine.idleUnitCount();
+  }
+
+  recordedMoves(): PlannedMove[] {
+    const json = this.engine.recordedMoves();
+    return JSON.parse(json) as PlannedMove[];
+  }
+
+  plannedPaths(): [number, RegionId[]][] {
+    const json = this.engine.plannedPaths();
+    return JSON.parse(json) as [number, RegionId[]][];
+  }
+
+  combatHistory(): BattleRecord[] {
+    const json = this.engine.combatHistory();
+    return JSON.parse(json) as BattleRecord[];
+  }
+
+  previewPendingCombats(): RegionId[] {
+    const json = this.engine.previewPendingCombats();
+    return JSON.parse(json) as RegionId[];
+  }
+
+  suggestCasualties(): number[] {
+    const json = this.engine.suggestCasualties();
+    return JSON.parse(json) as number[];
+  }
+
+  advanceToPower(power: Power, difficulty: string): GameEvent[] {
+    const json = this.engine.advanceToPower(JSON.stringify(power), difficulty);
+    const result = JSON.parse(json);
+    if (result.error) {
+      throw new EngineCallError(result.code, result.message);
+    }
+    return result as GameEvent[];
+  }
+
+  /** Battle calculator: odds of `attackers` vs `defenders` at `location` over `trials` runs. */
+  simulateBattle(attackers: BattleSide, defenders: BattleSide, location: RegionId, trials: number): BattleOdds {
+    const result = JSON.parse(
+      this.engine.simulateBattle(JSON.stringify(attackers), JSON.stringify(defenders), JSON.stringify(location), trials),
+    );
+    if (result.error) {
+      throw new EngineCallError(result.code, result.message);
+    }
+    return result as BattleOdds;
+  }
+
+  incomeBreakdown(power: Power): IncomeBreakdown {
+    const result = JSON.parse(this.engine.incomeBreakdown(JSON.stringify(power)));
+    if (result.error) {
+      throw new EngineCallError(result.code, result.message);
+    }
+    return result as IncomeBreakdown;
+  }
+
+  /** The cheapest invasion of a territory by a power's land units, or null if there is none. */
+  invasionRoute(target: number, power: Power): InvasionPlan | null {
+    const result = JSON.parse(this.engine.invasionRoute(target, JSON.stringify(power)));
+    if (result?.error) {
+      throw new EngineCallError(result.code, result.message);
+    }
+    return result as InvasionPlan | null;
+  }
+
+  /** Owner, units, coastline, bombardment, AAA and scramble cover of a region, or null if it is not on the map. */
+  regionCombatContext(region: RegionId): RegionCombatContext | null {
+    const result = JSON.parse(this.engine.regionCombatContext(JSON.stringify(region)));
+    if (result?.error) {
+      throw new EngineCallError(result.code, result.message);
+    }
+    return result as RegionCombatContext | null;
+  }
+
+  controllerFor(power: Power): ControllerKind {
+    const result = JSON.parse(this.engine.controllerFor(JSON.stringify(power)));
+    if (result.error) {
+      throw new EngineCallError(result.code, result.message);
+    }
+    return result as ControllerKind;
+  }
+
+  setController(power: Power, kind: ControllerKind): void {
+    this.engine.setController(JSON.stringify(power), JSON.stringify(kind));
+  }
+
   serializeForSave(): Uint8Array {
     return this.engine.serializeForSave();
   }
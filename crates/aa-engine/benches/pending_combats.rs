@@ -0,0 +1,38 @@
+//! Full-board scan vs. recorded-move lookup for finding pending battles,
+//! measured at the end of an AI-planned German Combat Movement phase.
+
+use aa_engine::action::Action;
+use aa_engine::ai::{ai_next_action, AiDifficulty};
+use aa_engine::movement::{identify_pending_combats, identify_pending_combats_from_moves};
+use aa_engine::Engine;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn planned_combat_moves() -> Engine {
+    let mut engine = Engine::new_game(42);
+    engine.submit_action(Action::ConfirmPurchases).unwrap();
+    loop {
+        let action = ai_next_action(engine.state(), engine.map(), AiDifficulty::Normal);
+        if matches!(action, Action::ConfirmCombatMovement) {
+            return engine;
+        }
+        engine.submit_action(action).unwrap();
+    }
+}
+
+fn pending_combats(c: &mut Criterion) {
+    let engine = planned_combat_moves();
+    let state = engine.state();
+    let power = state.current_power;
+
+    let mut group = c.benchmark_group("identify_pending_combats");
+    group.bench_function("full_scan", |b| {
+        b.iter(|| identify_pending_combats(black_box(state), power))
+    });
+    group.bench_function("from_moves", |b| {
+        b.iter(|| identify_pending_combats_from_moves(black_box(state), power))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, pending_combats);
+criterion_main!(benches);
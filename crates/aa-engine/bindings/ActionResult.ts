@@ -1,7 +1,17 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CombatStep } from "./CombatStep";
 import type { GameEvent } from "./GameEvent";
 
 /**
  * The result of successfully applying an action.
  */
-export type ActionResult = { events: Array<GameEvent>, };
+export type ActionResult = { events: Array<GameEvent>, 
+/**
+ * Checksum of the state after the action (see `GameState::checksum`),
+ * for clients to detect divergence from the server.
+ */
+state_checksum: bigint, 
+/**
+ * Outcome of a combat action, for incremental battle display.
+ */
+combat_step: CombatStep | null, };
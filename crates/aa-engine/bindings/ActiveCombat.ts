@@ -2,6 +2,7 @@
 import type { CombatSubPhase } from "./CombatSubPhase";
 import type { Power } from "./Power";
 import type { RegionId } from "./RegionId";
+import type { UnitType } from "./UnitType";
 
 /**
  * Tracks the state of an active battle.
@@ -43,6 +44,14 @@ pending_attacker_hits: number,
  * Hits pending allocation to the attacker.
  */
 pending_defender_hits: number, 
+/**
+ * Portion of `pending_attacker_hits` scored by submarines (cannot hit air).
+ */
+pending_attacker_sub_hits: number, 
+/**
+ * Portion of `pending_defender_hits` scored by submarines (cannot hit air).
+ */
+pending_defender_sub_hits: number, 
 /**
  * Whether this is an amphibious assault.
  */
@@ -55,6 +64,10 @@ bombardment_units: Array<number>,
  * Dice results from the last roll (for UI display).
  */
 last_roll: Array<number>, 
+/**
+ * Hits scored by the last roll.
+ */
+last_roll_hits: number, 
 /**
  * Regions the attacker can retreat to.
  */
@@ -66,4 +79,29 @@ enemy_has_destroyer: boolean,
 /**
  * Units that have been submerged this battle.
  */
-submerged_units: Array<number>, };
+submerged_units: Array<number>, 
+/**
+ * Dice consumed from this round's per-battle stream.
+ */
+round_dice_rolled: bigint, 
+/**
+ * The battle was cut off at the round limit without a winner.
+ */
+stalemate: boolean, 
+/**
+ * Aircraft whose carrier was sunk with no room on another carrier.
+ * They fight on but are lost when the battle ends.
+ */
+orphaned_aircraft: Array<number>, 
+/**
+ * Dice rolled by either side over the whole battle.
+ */
+dice_rolled: bigint, 
+/**
+ * Attacking units destroyed so far, by type.
+ */
+attacker_losses: Array<UnitType>, 
+/**
+ * Defending units destroyed so far, by type.
+ */
+defender_losses: Array<UnitType>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UnitType } from "./UnitType";
+
+/**
+ * Which unit types a power may build.
+ */
+export type AllowedUnits = { "Only": Array<UnitType> } | { "AllExcept": Array<UnitType> };
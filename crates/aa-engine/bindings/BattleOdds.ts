@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome distribution over all trials.
+ */
+export type BattleOdds = { trials: number, 
+/**
+ * Share of trials the attacker won (took or cleared the region).
+ */
+attacker_win_rate: number, 
+/**
+ * Share of trials the defender held with units left.
+ */
+defender_win_rate: number, 
+/**
+ * Share of trials with no winner: mutual destruction or a stalemate.
+ */
+draw_rate: number, 
+/**
+ * `attacker_survivors[n]` counts trials ending with `n` attacking units alive.
+ */
+attacker_survivors: Array<number>, 
+/**
+ * `defender_survivors[n]` counts trials ending with `n` defending units alive.
+ */
+defender_survivors: Array<number>, };
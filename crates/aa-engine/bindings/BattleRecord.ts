@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Power } from "./Power";
+import type { RegionId } from "./RegionId";
+import type { UnitType } from "./UnitType";
+
+/**
+ * Summary of a resolved battle, kept in `GameState::combat_history` for
+ * post-game statistics.
+ */
+export type BattleRecord = { 
+/**
+ * Game turn the battle was fought on.
+ */
+turn: number, location: RegionId, attacker: Power, defender: Power, 
+/**
+ * Rounds fought, counting the last one.
+ */
+rounds: number, 
+/**
+ * Dice rolled by both sides, AA fire and bombardment included.
+ */
+dice_rolled: bigint, attacker_losses: Array<UnitType>, defender_losses: Array<UnitType>, attacker_won: boolean, stalemate: boolean, };
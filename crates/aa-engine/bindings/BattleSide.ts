@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Power } from "./Power";
+import type { UnitType } from "./UnitType";
+
+/**
+ * One side of a simulated battle.
+ */
+export type BattleSide = { power: Power, units: Array<UnitType>, };
@@ -1,6 +1,16 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IncomeBreakdown } from "./IncomeBreakdown";
 
 /**
  * State for the Collect Income phase.
  */
-export type CollectIncomeState = { base_income: number, objective_bonus: number, convoy_losses: number, total_collected: number, };
+export type CollectIncomeState = { base_income: number, objective_bonus: number, convoy_losses: number, 
+/**
+ * Unit upkeep paid under the optional upkeep rule.
+ */
+upkeep: number, total_collected: number, 
+/**
+ * Income owed, worked out from territory ownership as the phase began.
+ * Ownership changes after that wait for the next turn's collection.
+ */
+owed: IncomeBreakdown | null, };
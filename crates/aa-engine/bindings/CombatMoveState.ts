@@ -1,7 +1,16 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { PlannedMove } from "./PlannedMove";
+import type { Power } from "./Power";
 
 /**
  * State for the Combat Movement phase.
  */
-export type CombatMoveState = { moves: Array<PlannedMove>, };
+export type CombatMoveState = { moves: Array<PlannedMove>, 
+/**
+ * Territory owners as combat movement began, indexed by territory ID.
+ */
+owners_at_start: Array<Power | null>, 
+/**
+ * The operation new moves join, between BeginOperation and EndOperation.
+ */
+open_operation: string | null, };
@@ -1,7 +1,24 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlannedMove } from "./PlannedMove";
+import type { Power } from "./Power";
 import type { RegionId } from "./RegionId";
 
 /**
  * State for the Conduct Combat phase.
  */
-export type CombatState = { pending_battles: Array<RegionId>, resolved_battles: Array<RegionId>, current_battle: RegionId | null, };
+export type CombatState = { pending_battles: Array<RegionId>, resolved_battles: Array<RegionId>, current_battle: RegionId | null, 
+/**
+ * Territories already hit by a strategic bombing raid this turn.
+ */
+bombed_territories: Array<number>, 
+/**
+ * Territory owners as combat movement began (carried over from
+ * `CombatMoveState`), for deciding where attackers may retreat.
+ * Empty when unknown.
+ */
+owners_before_combat: Array<Power | null>, 
+/**
+ * This turn's combat moves (carried over from `CombatMoveState`), for
+ * working out where attackers came from.
+ */
+combat_moves: Array<PlannedMove>, };
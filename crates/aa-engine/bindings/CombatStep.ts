@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CombatSubPhase } from "./CombatSubPhase";
+import type { RegionId } from "./RegionId";
+
+/**
+ * Compact outcome of a single combat action, attached to its
+ * `ActionResult` so clients can update without re-reading `ActiveCombat`.
+ */
+export type CombatStep = { location: RegionId, round: number, 
+/**
+ * The sub-phase the battle is now waiting on.
+ */
+sub_phase: CombatSubPhase, 
+/**
+ * Dice rolled by this action; empty if it rolled none.
+ */
+rolls: Array<number>, 
+/**
+ * Hits scored by `rolls`.
+ */
+hits: number, pending_attacker_hits: number, pending_defender_hits: number, battle_over: boolean, 
+/**
+ * Units taken as casualties by this action.
+ */
+casualties: Array<number>, };
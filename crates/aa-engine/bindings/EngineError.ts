@@ -3,4 +3,4 @@
 /**
  * All possible errors from the game engine.
  */
-export type EngineError = { "NotYourTurn": { current: string, } } | { "WrongPhase": { expected: string, actual: string, } } | { "InsufficientIPCs": { needed: number, available: number, } } | { "IllegalMove": { reason: string, } } | { "InvalidAction": { reason: string, } } | { "UnitNotFound": { unit_id: number, } } | { "TerritoryNotFound": { territory_id: number, } } | { "CannotUndo": { reason: string, } } | { "Serialization": string } | { "Deserialization": string } | { "SetupError": string } | { "Internal": string };
+export type EngineError = { "NotYourTurn": { current: string, } } | { "WrongPhase": { expected: string, actual: string, } } | { "InsufficientIPCs": { needed: number, available: number, } } | { "IllegalMove": { reason: string, } } | { "InvalidAction": { reason: string, } } | { "UnitNotFound": { unit_id: number, } } | { "TerritoryNotFound": { territory_id: number, } } | { "CannotUndo": { reason: string, } } | { "Serialization": string } | { "Deserialization": string } | { "SetupError": string } | { "EventsDropped": { oldest: bigint, } } | { "Internal": string };
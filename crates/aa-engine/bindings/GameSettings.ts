@@ -0,0 +1,44 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AllowedUnits } from "./AllowedUnits";
+import type { Power } from "./Power";
+import type { VictoryCondition } from "./VictoryCondition";
+
+/**
+ * Optional rule settings chosen when the game is created.
+ */
+export type GameSettings = { 
+/**
+ * IPCs deducted from income per unit a power has on the board.
+ */
+upkeep_per_unit: number, 
+/**
+ * Rounds after which an undecided battle ends in a stalemate.
+ */
+max_combat_rounds: number, 
+/**
+ * Reject confirming purchases the factories can't place, rather than
+ * only warning about them.
+ */
+strict_purchase_capacity: boolean, 
+/**
+ * How the game is won.
+ */
+victory_condition: VictoryCondition, 
+/**
+ * House rule: units that survive a battle become veterans and get +1
+ * on attack and defense.
+ */
+veteran_units: boolean, 
+/**
+ * Most units one power may have in a single territory; `None` for no limit.
+ */
+max_units_per_territory: number | null, 
+/**
+ * What each listed power may build; unlisted powers may build anything.
+ */
+production_rules: { [key in Power]?: AllowedUnits }, 
+/**
+ * Scorched earth: damage a captured industrial complex takes as it
+ * changes hands, to be repaired before full use. 0 leaves it intact.
+ */
+scorched_earth_damage: number, };
@@ -1,10 +1,14 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BattleRecord } from "./BattleRecord";
+import type { GameSettings } from "./GameSettings";
 import type { Phase } from "./Phase";
 import type { PhaseState } from "./PhaseState";
 import type { PoliticalState } from "./PoliticalState";
 import type { Power } from "./Power";
 import type { PowerState } from "./PowerState";
+import type { RngMode } from "./RngMode";
 import type { SeaZoneState } from "./SeaZoneState";
+import type { Team } from "./Team";
 import type { TerritoryState } from "./TerritoryState";
 import type { UnitType } from "./UnitType";
 
@@ -15,4 +19,36 @@ export type GameState = { turn_number: number, current_power: Power, current_pha
 /**
  * Units purchased this turn, to be placed during Mobilize phase.
  */
-pending_purchases: Array<[UnitType, number]>, rng_seed: bigint, rng_counter: bigint, };
+pending_purchases: Array<[UnitType, number]>, rng_seed: bigint, rng_counter: bigint, 
+/**
+ * The seed the game was created with. `rng_seed` starts out equal to it
+ * but can be moved by `Engine::set_rng_state`. `None` in older saves.
+ */
+creation_seed: bigint | null, 
+/**
+ * Saves predating per-battle dice fall back to the shared counter.
+ */
+rng_mode: RngMode, 
+/**
+ * Scenario-setup mode: allows editing actions such as `SetRelation`
+ * that are never legal in normal play.
+ */
+sandbox_mode: boolean, 
+/**
+ * Optional house rules; the defaults play the standard game.
+ */
+settings: GameSettings, 
+/**
+ * The team that won, once victory has been declared at the end of a
+ * round (or by surrender).
+ */
+winner: Team | null, 
+/**
+ * Under economic victory, the team that held the IPC threshold at the
+ * end of the last round and the turn number it first did so.
+ */
+economic_leader: [Team, number] | null, 
+/**
+ * Every battle resolved so far, oldest first.
+ */
+combat_history: Array<BattleRecord>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Income breakdown for display.
+ */
+export type IncomeBreakdown = { base_income: number, objective_bonus: number, convoy_losses: number, upkeep: number, total: number, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InvasionKind } from "./InvasionKind";
+import type { RegionId } from "./RegionId";
+
+/**
+ * The cheapest route found for an invasion.
+ */
+export type InvasionPlan = { kind: InvasionKind, 
+/**
+ * Territory holding the land units that make the attack.
+ */
+origin: number, 
+/**
+ * Every region on the way, from `origin` to the target.
+ */
+path: Array<RegionId>, };
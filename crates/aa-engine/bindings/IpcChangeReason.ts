@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Why a power's IPC total changed.
+ */
+export type IpcChangeReason = "Purchase" | "PurchaseRefund" | "Repair" | "Income" | "CapitalCaptured" | "Surrender";
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ObligationKind } from "./ObligationKind";
+import type { RegionId } from "./RegionId";
+
+/**
+ * A single outstanding obligation for the current power.
+ */
+export type Obligation = { kind: ObligationKind, description: string, 
+/**
+ * The region the obligation is tied to, if any.
+ */
+region: RegionId | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UnitType } from "./UnitType";
+
+/**
+ * What kind of obligation is outstanding.
+ */
+export type ObligationKind = "UnresolvedBattle" | "BattleInProgress" | { "AirUnitMustLand": { unit_id: number, } } | { "UnitsToPlace": { unit_type: UnitType, count: number, } } | { "PurchasesExceedCapacity": { purchased: number, capacity: number, } };
@@ -4,4 +4,8 @@ import type { RegionId } from "./RegionId";
 /**
  * A planned unit move during combat movement.
  */
-export type PlannedMove = { unit_id: number, path: Array<RegionId>, from: RegionId, to: RegionId, };
+export type PlannedMove = { unit_id: number, path: Array<RegionId>, from: RegionId, to: RegionId, 
+/**
+ * The named operation this move was made in, if any.
+ */
+operation: string | null, };
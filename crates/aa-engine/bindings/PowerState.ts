@@ -1,7 +1,16 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Power } from "./Power";
+import type { UnitType } from "./UnitType";
 
 /**
  * Per-power mutable state.
  */
-export type PowerState = { power: Power, ipcs: number, ipcs_europe: number, ipcs_pacific: number, at_war: boolean, capital_captured: boolean, researched_techs: Array<string>, };
+export type PowerState = { power: Power, ipcs: number, ipcs_europe: number, ipcs_pacific: number, at_war: boolean, capital_captured: boolean, researched_techs: Array<string>, 
+/**
+ * The power has conceded and no longer takes turns.
+ */
+surrendered: boolean, 
+/**
+ * Every confirmed purchase, oldest first, tagged with its turn number.
+ */
+purchase_history: Array<[number, Array<[UnitType, number]>]>, };
@@ -4,4 +4,10 @@ import type { UnitType } from "./UnitType";
 /**
  * State for the Purchase & Repair phase.
  */
-export type PurchaseState = { purchases: Array<[UnitType, number]>, repairs: Array<[number, number]>, ipcs_spent: number, };
+export type PurchaseState = { purchases: Array<[UnitType, number]>, repairs: Array<[number, number]>, 
+/**
+ * IPCs committed this phase to purchases and repairs combined. They are
+ * debited from the treasury as they are committed, so saves that predate
+ * this field still see the right amount available.
+ */
+ipcs_committed: number, };
@@ -0,0 +1,37 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Power } from "./Power";
+import type { RegionId } from "./RegionId";
+import type { UnitInstance } from "./UnitInstance";
+
+/**
+ * What a battle UI needs to know about a region, read as an attack there
+ * by the power to move.
+ */
+export type RegionCombatContext = { region: RegionId, 
+/**
+ * `None` for sea zones and unowned territories.
+ */
+owner: Power | null, 
+/**
+ * Every unit in the region, by owner.
+ */
+units: { [key in Power]?: Array<UnitInstance> }, 
+/**
+ * A territory on the sea, or a sea zone off a coast.
+ */
+coastal: boolean, 
+/**
+ * The mover's ships in adjoining sea zones that could bombard an
+ * amphibious assault here.
+ */
+bombardment_ships: Array<number>, 
+/**
+ * Whether the defenders have AAA to fire on attacking aircraft.
+ */
+has_aaa: boolean, 
+/**
+ * For a sea zone, the coastal territories where the defending side
+ * holds an operational air base with fighters or tactical bombers to
+ * scramble. Always empty for land, where scrambling does not happen.
+ */
+scramble_bases: Array<number>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Diplomatic relation between two powers, as set by `Action::SetRelation`.
+ */
+export type Relation = "War" | "Peace";
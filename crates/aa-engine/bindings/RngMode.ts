@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How combat dice streams are derived from the game seed.
+ */
+export type RngMode = "SharedCounter" | "PerBattle";
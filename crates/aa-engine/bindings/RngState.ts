@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where the game's dice stream stands: the seed and how many rolls have
+ * been drawn from the shared stream.
+ */
+export type RngState = { seed: bigint, counter: bigint, };
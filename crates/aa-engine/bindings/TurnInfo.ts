@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Phase } from "./Phase";
+import type { Power } from "./Power";
+
+/**
+ * Structured snapshot of whose turn it is, for UI headers and save metadata.
+ */
+export type TurnInfo = { turn_number: number, power: Power, phase: Phase, current_ipcs: number, };
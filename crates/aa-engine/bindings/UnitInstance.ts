@@ -5,4 +5,13 @@ import type { UnitType } from "./UnitType";
 /**
  * A specific unit on the board.
  */
-export type UnitInstance = { id: number, unit_type: UnitType, owner: Power, hits_taken: number, moved_this_turn: boolean, movement_remaining: number, cargo: Array<number>, };
+export type UnitInstance = { id: number, unit_type: UnitType, owner: Power, hits_taken: number, moved_this_turn: boolean, movement_remaining: number, cargo: Array<number>, 
+/**
+ * Set on the AVG ("Flying Tigers") fighter, which may never leave
+ * territories originally controlled by China.
+ */
+restricted_to_china: boolean, 
+/**
+ * Survived a battle under the veteran rule; fights at +1.
+ */
+veteran: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The win condition chosen when the game is created. Surrender of a whole
+ * team ends the game under every variant.
+ */
+export type VictoryCondition = "VictoryCities" | { "EconomicDomination": { percent: number, } } | "CapitalCapture";
@@ -3,8 +3,10 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::error::EngineError;
 use crate::phase::Phase;
 use crate::power::Power;
+use crate::state::Relation;
 use crate::territory::{RegionId, SeaZoneId, TerritoryId};
 use crate::unit::{UnitId, UnitType};
 
@@ -32,9 +34,24 @@ pub enum Action {
         unit_id: UnitId,
         path: Vec<RegionId>,
     },
+    /// Move several units together along one path (e.g. mech infantry with tank escort).
+    MoveUnits {
+        unit_ids: Vec<UnitId>,
+        path: Vec<RegionId>,
+    },
     UndoMove {
         unit_id: UnitId,
     },
+    /// Start a named operation: the combat moves that follow, up to
+    /// EndOperation, can be taken back together with UndoOperation.
+    BeginOperation {
+        name: String,
+    },
+    EndOperation,
+    /// Take back every move made in the named operation.
+    UndoOperation {
+        name: String,
+    },
     ConfirmCombatMovement,
 
     // -- Combat Phase --
@@ -53,6 +70,11 @@ pub enum Action {
         unit_id: UnitId,
     },
     ContinueCombatRound,
+    /// Raid the industrial complex in an enemy territory with the strategic
+    /// bombers moved there during combat movement.
+    StrategicBombingRaid {
+        territory_id: TerritoryId,
+    },
 
     // -- Non-Combat Movement Phase --
     MoveUnitNonCombat {
@@ -68,7 +90,12 @@ pub enum Action {
     // -- Mobilize Phase --
     PlaceUnit {
         unit_type: UnitType,
+        /// The producing territory.
         territory_id: TerritoryId,
+        /// Where a naval unit launches: a sea zone bordering `territory_id`.
+        /// Must be `None` for land and air units.
+        #[serde(default)]
+        sea_zone: Option<SeaZoneId>,
     },
     ConfirmMobilization,
 
@@ -79,9 +106,24 @@ pub enum Action {
     DeclareWar {
         against: Power,
     },
+    /// Concede the game for a power. The server layer checks that the
+    /// request comes from that power's controller.
+    Surrender {
+        power: Power,
+    },
+    /// Force the relation between two powers. Only legal in sandbox mode,
+    /// for building custom scenarios.
+    SetRelation {
+        a: Power,
+        b: Power,
+        relation: Relation,
+    },
 
     // -- Meta --
     Undo,
+    /// Confirm whatever phase is current: the same as that phase's own
+    /// confirm action (see `Phase::confirm_action`), and the only one for
+    /// Conduct Combat.
     ConfirmPhase,
 }
 
@@ -92,6 +134,37 @@ pub struct ActionResult {
     #[ts(skip)]
     pub applied: AppliedAction,
     pub events: Vec<GameEvent>,
+    /// Checksum of the state after the action (see `GameState::checksum`),
+    /// for clients to detect divergence from the server.
+    #[serde(default)]
+    pub state_checksum: u64,
+    /// Outcome of a combat action, for incremental battle display.
+    #[serde(default)]
+    pub combat_step: Option<crate::combat::CombatStep>,
+}
+
+impl ActionResult {
+    /// Build a result; `state_checksum` is filled in once the action is fully applied.
+    pub fn new(applied: AppliedAction, events: Vec<GameEvent>) -> Self {
+        ActionResult {
+            applied,
+            events,
+            state_checksum: 0,
+            combat_step: None,
+        }
+    }
+
+    /// Attach a combat step summary.
+    pub fn with_combat_step(mut self, step: crate::combat::CombatStep) -> Self {
+        self.combat_step = Some(step);
+        self
+    }
+
+    /// The events that happened in `region` (battles, captures, facility
+    /// damage, convoy raids, placements), in order.
+    pub fn events_for_region(&self, region: RegionId) -> Vec<&GameEvent> {
+        self.events.iter().filter(|e| e.region() == Some(region)).collect()
+    }
 }
 
 /// A record of an applied action (stored for undo).
@@ -106,6 +179,9 @@ pub struct AppliedAction {
 pub enum InverseAction {
     /// Simple reverse (e.g., remove a purchase = re-add it)
     Simple(Action),
+    /// Several simple reverses applied in order (e.g., taking back every
+    /// unit of a group move)
+    Sequence(Vec<Action>),
     /// Restore a partial state snapshot
     RestoreSnapshot(Vec<u8>),
     /// This action cannot be undone (e.g., dice rolls)
@@ -128,6 +204,14 @@ pub enum GameEvent {
         aggressor: Power,
         target: Power,
     },
+    PowerSurrendered {
+        power: Power,
+    },
+    RelationChanged {
+        a: Power,
+        b: Power,
+        relation: Relation,
+    },
     BattleStarted {
         location: RegionId,
     },
@@ -135,6 +219,20 @@ pub enum GameEvent {
         location: RegionId,
         attacker_won: bool,
     },
+    /// A battle hit the round limit and ended with both sides in place.
+    BattleStalemate {
+        location: RegionId,
+    },
+    /// The transports carrying an amphibious assault were sunk before it
+    /// could be fought.
+    AmphibiousAssaultCancelled {
+        territory_id: TerritoryId,
+    },
+    /// A territory changed hands after a battle.
+    TerritoryCaptured {
+        territory_id: TerritoryId,
+        by: Power,
+    },
     CapitalCaptured {
         territory_id: TerritoryId,
         by: Power,
@@ -143,6 +241,16 @@ pub enum GameEvent {
         territory_id: TerritoryId,
         to: Power,
     },
+    /// A neutral territory joined `power`, garrison and all.
+    NeutralActivated {
+        territory_id: TerritoryId,
+        power: Power,
+    },
+    FacilityBombed {
+        territory_id: TerritoryId,
+        damage: u32,
+        total_damage: u32,
+    },
     ConvoyDisrupted {
         zone: SeaZoneId,
         power: Power,
@@ -159,11 +267,130 @@ pub enum GameEvent {
     UnitsPlaced {
         unit_type: UnitType,
         territory_id: TerritoryId,
+        /// Set when a naval unit was launched into this sea zone.
+        sea_zone: Option<SeaZoneId>,
     },
     IncomeCollected {
         power: Power,
         amount: u32,
     },
+    /// A power's treasury changed by `delta`, leaving `new_total`.
+    IpcChanged {
+        power: Power,
+        delta: i32,
+        new_total: u32,
+        reason: IpcChangeReason,
+    },
+}
+
+/// Why a power's IPC total changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum IpcChangeReason {
+    Purchase,
+    /// A queued purchase was taken back.
+    PurchaseRefund,
+    Repair,
+    /// Income collected, already net of convoy losses and upkeep.
+    Income,
+    /// The treasury was seized with (or taken from) a captured capital.
+    CapitalCaptured,
+    /// The treasury was forfeited on surrender.
+    Surrender,
+}
+
+impl GameEvent {
+    /// The region the event happened in, for events tied to one place.
+    pub fn region(&self) -> Option<RegionId> {
+        match *self {
+            GameEvent::BattleStarted { location }
+            | GameEvent::BattleEnded { location, .. }
+            | GameEvent::BattleStalemate { location } => Some(location),
+            GameEvent::AmphibiousAssaultCancelled { territory_id }
+            | GameEvent::TerritoryCaptured { territory_id, .. }
+            | GameEvent::CapitalCaptured { territory_id, .. }
+            | GameEvent::TerritoryLiberated { territory_id, .. }
+            | GameEvent::NeutralActivated { territory_id, .. }
+            | GameEvent::FacilityBombed { territory_id, .. }
+            | GameEvent::UnitsPlaced { territory_id, sea_zone: None, .. } => Some(RegionId::Land(territory_id)),
+            GameEvent::UnitsPlaced { sea_zone: Some(zone), .. } => Some(RegionId::Sea(zone)),
+            GameEvent::ConvoyDisrupted { zone, .. } => Some(RegionId::Sea(zone)),
+            GameEvent::PhaseChanged { .. }
+            | GameEvent::TurnChanged { .. }
+            | GameEvent::WarDeclared { .. }
+            | GameEvent::PowerSurrendered { .. }
+            | GameEvent::RelationChanged { .. }
+            | GameEvent::VictoryAchieved { .. }
+            | GameEvent::UnitsPurchased { .. }
+            | GameEvent::IncomeCollected { .. }
+            | GameEvent::IpcChanged { .. } => None,
+        }
+    }
+}
+
+/// The most recent events, each numbered in order, so a client that missed
+/// some `ActionResult`s can catch up. Older events fall off once `capacity`
+/// is reached.
+#[derive(Clone, Debug)]
+pub struct EventBuffer {
+    events: std::collections::VecDeque<(u64, GameEvent)>,
+    next_sequence: u64,
+    capacity: usize,
+}
+
+impl EventBuffer {
+    /// Events kept by default.
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// A buffer holding up to `capacity` events, which must be at least 1.
+    pub fn with_capacity(capacity: usize) -> Result<Self, EngineError> {
+        if capacity == 0 {
+            return Err(EngineError::SetupError("An event buffer must hold at least one event".into()));
+        }
+        Ok(EventBuffer {
+            events: std::collections::VecDeque::with_capacity(capacity),
+            next_sequence: 0,
+            capacity,
+        })
+    }
+
+    /// Record events, numbering them after those already seen.
+    pub fn extend(&mut self, events: impl IntoIterator<Item = GameEvent>) {
+        for event in events {
+            if self.events.len() == self.capacity {
+                self.events.pop_front();
+            }
+            self.events.push_back((self.next_sequence, event));
+            self.next_sequence += 1;
+        }
+    }
+
+    /// Events numbered `sequence` or later, and the sequence number to ask
+    /// for next time. Fails with `EventsDropped` if some of them have already
+    /// fallen off, so the caller knows to fetch the full state instead.
+    pub fn since(&self, sequence: u64) -> Result<(Vec<GameEvent>, u64), EngineError> {
+        let oldest = self.next_sequence - self.events.len() as u64;
+        if sequence < oldest {
+            return Err(EngineError::EventsDropped { oldest });
+        }
+        let events = self
+            .events
+            .iter()
+            .filter(|(seq, _)| *seq >= sequence)
+            .map(|(_, event)| event.clone())
+            .collect();
+        Ok((events, self.next_sequence))
+    }
+}
+
+impl Default for EventBuffer {
+    fn default() -> Self {
+        EventBuffer {
+            events: std::collections::VecDeque::with_capacity(Self::DEFAULT_CAPACITY),
+            next_sequence: 0,
+            capacity: Self::DEFAULT_CAPACITY,
+        }
+    }
 }
 
 /// A legal action with a human-readable description.
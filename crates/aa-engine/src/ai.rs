@@ -8,16 +8,14 @@
 //! - Mobilize: place units at threatened factories near the front
 
 use crate::action::Action;
-use crate::combat::{ActiveCombat, CombatSubPhase};
+use crate::combat::{self, ActiveCombat, CombatSubPhase};
 use crate::data::GameMap;
-use crate::error::EngineError;
 use crate::mobilize;
-use crate::movement;
 use crate::phase::{Phase, PhaseState};
 use crate::power::Power;
 use crate::state::GameState;
-use crate::territory::{FacilityType, RegionId, TerritoryId};
-use crate::unit::{get_unit_stats, UnitDomain, UnitId, UnitInstance, UnitType};
+use crate::territory::{RegionId, TerritoryId};
+use crate::unit::{UnitDomain, UnitId, UnitInstance, UnitType};
 
 /// Difficulty level for the AI.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -27,13 +25,89 @@ pub enum AiDifficulty {
     Hard,
 }
 
-/// Generate the next AI action for the current game state.
+/// Limits on the work behind one AI decision, so a caller on the main
+/// thread is never blocked for long. Once either limit is reached the AI
+/// settles for the best move it has found so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AiBudget {
+    /// Candidates weighed before settling.
+    pub max_iterations: u32,
+    /// Wall-clock time allowed, if any. A time limit makes the AI's choice
+    /// depend on the machine, so replays and lockstep games should leave it
+    /// off. Not enforced in WebAssembly builds, which have no clock.
+    pub max_millis: Option<u64>,
+}
+
+impl AiBudget {
+    /// The default budget for each difficulty; harder AIs look further.
+    /// Only iterations are limited, so the same position always gets the
+    /// same decision.
+    pub fn for_difficulty(difficulty: AiDifficulty) -> Self {
+        let max_iterations = match difficulty {
+            AiDifficulty::Easy => 1_000,
+            AiDifficulty::Normal => 10_000,
+            AiDifficulty::Hard => 100_000,
+        };
+        AiBudget { max_iterations, max_millis: None }
+    }
+}
+
+/// An `AiBudget` being spent, one candidate at a time.
+struct Thinking {
+    budget: AiBudget,
+    iterations: u32,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: std::time::Instant,
+}
+
+impl Thinking {
+    fn new(budget: AiBudget) -> Self {
+        Thinking {
+            budget,
+            iterations: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    /// Count one more candidate. Returns false once the budget is spent.
+    fn tick(&mut self) -> bool {
+        self.iterations += 1;
+        self.iterations <= self.budget.max_iterations && !self.out_of_time()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn out_of_time(&self) -> bool {
+        self.budget
+            .max_millis
+            .is_some_and(|limit| self.started.elapsed().as_millis() >= limit as u128)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn out_of_time(&self) -> bool {
+        false
+    }
+}
+
+/// Generate the next AI action for the current game state, within the
+/// default budget for `difficulty`.
 /// Returns None if the AI has no more actions for this sub-step
 /// (i.e., should confirm/advance phase).
 pub fn ai_next_action(state: &GameState, map: &GameMap, difficulty: AiDifficulty) -> Action {
+    ai_next_action_with_budget(state, map, difficulty, AiBudget::for_difficulty(difficulty))
+}
+
+/// Generate the next AI action, doing no more work than `budget` allows.
+pub fn ai_next_action_with_budget(
+    state: &GameState,
+    map: &GameMap,
+    difficulty: AiDifficulty,
+    budget: AiBudget,
+) -> Action {
+    let mut thinking = Thinking::new(budget);
     match state.current_phase {
-        Phase::PurchaseAndRepair => ai_purchase(state, map, difficulty),
-        Phase::CombatMovement => ai_combat_movement(state, map, difficulty),
+        Phase::PurchaseAndRepair => ai_purchase(state, map, difficulty, &mut thinking),
+        Phase::CombatMovement => ai_combat_movement(state, map, difficulty, &mut thinking),
         Phase::ConductCombat => ai_conduct_combat(state, map),
         Phase::NonCombatMovement => ai_non_combat_movement(state, map, difficulty),
         Phase::Mobilize => ai_mobilize(state, map),
@@ -100,37 +174,52 @@ fn phase_confirm_action(phase: Phase) -> Action {
 // Purchase Phase AI
 // =========================================================================
 
-fn ai_purchase(state: &GameState, map: &GameMap, difficulty: AiDifficulty) -> Action {
+fn ai_purchase(state: &GameState, map: &GameMap, difficulty: AiDifficulty, thinking: &mut Thinking) -> Action {
     let power = state.current_power;
-    let power_state = &state.powers[power as usize];
-    let available_ipcs = power_state.ipcs;
+
+    // Nothing affordable (e.g. France with a captured capital): skip the phase
+    if crate::phase::can_skip_phase(state) {
+        return Action::ConfirmPurchases;
+    }
 
     // Check if we already have purchases pending
     if let PhaseState::Purchase(ref ps) = state.phase_state {
-        let spent = ps.ipcs_spent;
-        let remaining = available_ipcs.saturating_sub(spent);
+        let remaining = crate::purchase::available_ipcs(state);
 
         if remaining < 3 {
             // Can't afford anything, confirm
             return Action::ConfirmPurchases;
         }
 
-        // China can only buy infantry
-        if power == Power::China {
-            let count = remaining / 3;
-            if count > 0 {
-                return Action::PurchaseUnit {
-                    unit_type: UnitType::Infantry,
-                    count,
-                };
-            }
+        // Don't buy more than the factories can place
+        let purchased: u32 = ps.purchases.iter().map(|&(_, n)| n).sum();
+        let slots = mobilize::total_placement_capacity(state, map, power).saturating_sub(purchased);
+        if slots == 0 {
             return Action::ConfirmPurchases;
         }
 
-        // Strategic purchase based on situation
-        let purchase = compute_purchase_plan(state, map, power, remaining, difficulty);
+        // A power that may build only one type (China) spends everything on it
+        if let Some(crate::purchase::AllowedUnits::Only(types)) = state.settings.production_rules.get(&power) {
+            if let [unit_type] = types[..] {
+                let count = (remaining / state.unit_stats(unit_type).cost).min(slots);
+                if count == 0 {
+                    return Action::ConfirmPurchases;
+                }
+                return Action::PurchaseUnit { unit_type, count };
+            }
+        }
+
+        // Strategic purchase based on situation, falling back to the cheapest
+        // land unit this power may build when the plan picks one it may not
+        let purchase = compute_purchase_plan(state, map, power, remaining, difficulty, thinking)
+            .filter(|&(unit_type, _)| crate::purchase::can_produce(state, power, unit_type))
+            .or_else(|| cheapest_buildable_land_unit(state, power).map(|unit_type| (unit_type, u32::MAX)));
         if let Some((unit_type, count)) = purchase {
-            return Action::PurchaseUnit { unit_type, count };
+            let affordable = remaining.checked_div(state.unit_stats(unit_type).cost).unwrap_or(u32::MAX);
+            let count = count.min(affordable).min(slots);
+            if count > 0 {
+                return Action::PurchaseUnit { unit_type, count };
+            }
         }
 
         return Action::ConfirmPurchases;
@@ -139,6 +228,16 @@ fn ai_purchase(state: &GameState, map: &GameMap, difficulty: AiDifficulty) -> Ac
     Action::ConfirmPurchases
 }
 
+/// The cheapest land unit `power` may build under the production rules.
+fn cheapest_buildable_land_unit(state: &GameState, power: Power) -> Option<UnitType> {
+    UnitType::all()
+        .iter()
+        .copied()
+        .filter(|&ut| state.unit_stats(ut).domain == UnitDomain::Land)
+        .filter(|&ut| crate::purchase::can_produce(state, power, ut))
+        .min_by_key(|&ut| state.unit_stats(ut).cost)
+}
+
 /// Compute what to buy given remaining IPCs.
 fn compute_purchase_plan(
     state: &GameState,
@@ -146,13 +245,14 @@ fn compute_purchase_plan(
     power: Power,
     ipcs: u32,
     difficulty: AiDifficulty,
+    thinking: &mut Thinking,
 ) -> Option<(UnitType, u32)> {
     if ipcs < 3 {
         return None;
     }
 
     // Count threats to our territories (enemy units adjacent to our territories)
-    let threat_level = assess_threat_level(state, map, power);
+    let threat_level = assess_threat_level(state, map, power, thinking);
 
     // Purchase mix based on threat and difficulty
     let (inf_ratio, art_ratio, tank_ratio, _fighter_ratio) = match difficulty {
@@ -198,8 +298,9 @@ fn compute_purchase_plan(
     }
 }
 
-/// Assess how threatened our territories are (0-100 scale).
-fn assess_threat_level(state: &GameState, map: &GameMap, power: Power) -> u32 {
+/// Assess how threatened our territories are (0-100 scale). Out of budget,
+/// the territories checked so far stand for the rest.
+fn assess_threat_level(state: &GameState, map: &GameMap, power: Power, thinking: &mut Thinking) -> u32 {
     let mut threat = 0u32;
     let mut territory_count = 0u32;
 
@@ -207,6 +308,9 @@ fn assess_threat_level(state: &GameState, map: &GameMap, power: Power) -> u32 {
         if territory.owner != Some(power) {
             continue;
         }
+        if !thinking.tick() {
+            break;
+        }
         territory_count += 1;
         let tid = i as TerritoryId;
         let tdef = map.territory(tid);
@@ -218,7 +322,7 @@ fn assess_threat_level(state: &GameState, map: &GameMap, power: Power) -> u32 {
                     .units
                     .iter()
                     .filter(|u| is_enemy(state, power, u.owner))
-                    .map(|u| get_unit_stats(u.unit_type).attack as u32)
+                    .map(|u| state.unit_stats(u.unit_type).attack as u32)
                     .sum();
                 threat += enemy_strength;
             }
@@ -245,17 +349,24 @@ fn is_enemy(state: &GameState, us: Power, them: Power) -> bool {
 // Combat Movement Phase AI
 // =========================================================================
 
-fn ai_combat_movement(state: &GameState, map: &GameMap, _difficulty: AiDifficulty) -> Action {
+fn ai_combat_movement(
+    state: &GameState,
+    map: &GameMap,
+    _difficulty: AiDifficulty,
+    thinking: &mut Thinking,
+) -> Action {
     let power = state.current_power;
+    // The most lopsided attack seen so far, with its margin
+    let mut best: Option<(u32, Action)> = None;
 
     // Find units that haven't moved yet and could attack something
-    for (i, territory) in state.territories.iter().enumerate() {
+    'search: for (i, territory) in state.territories.iter().enumerate() {
         let tid = i as TerritoryId;
         for unit in &territory.units {
             if unit.owner != power || unit.moved_this_turn {
                 continue;
             }
-            let stats = get_unit_stats(unit.unit_type);
+            let stats = state.unit_stats(unit.unit_type);
             if stats.domain != UnitDomain::Land && stats.domain != UnitDomain::Air {
                 continue;
             }
@@ -266,23 +377,31 @@ fn ai_combat_movement(state: &GameState, map: &GameMap, _difficulty: AiDifficult
             // Find adjacent enemy territories worth attacking
             let tdef = map.territory(tid);
             for &adj_tid in &tdef.adjacent_land {
+                // Out of budget: settle for the best attack found so far
+                if !thinking.tick() {
+                    break 'search;
+                }
                 if let Some(adj) = state.territories.get(adj_tid as usize) {
                     if let Some(adj_owner) = adj.owner {
                         if is_enemy(state, power, adj_owner) {
                             // Simple heuristic: attack if we likely win
-                            let our_strength = territory_attack_strength(territory, power);
-                            let their_defense = territory_defense_strength(adj);
+                            let our_strength = territory_attack_strength(state, territory, power);
+                            let their_defense = territory_defense_strength(state, adj);
 
                             if our_strength > their_defense * 3 / 2 {
+                                let margin = our_strength * 2 - their_defense * 3;
+                                if best.as_ref().is_some_and(|(m, _)| *m >= margin) {
+                                    continue;
+                                }
                                 // Only move one unit at a time
                                 let path = vec![
                                     RegionId::Land(tid),
                                     RegionId::Land(adj_tid),
                                 ];
-                                return Action::MoveUnit {
+                                best = Some((margin, Action::MoveUnit {
                                     unit_id: unit.id,
                                     path,
-                                };
+                                }));
                             }
                         }
                     }
@@ -291,10 +410,11 @@ fn ai_combat_movement(state: &GameState, map: &GameMap, _difficulty: AiDifficult
         }
     }
 
-    Action::ConfirmCombatMovement
+    best.map_or(Action::ConfirmCombatMovement, |(_, action)| action)
 }
 
 fn territory_attack_strength(
+    state: &GameState,
     territory: &crate::territory::TerritoryState,
     power: Power,
 ) -> u32 {
@@ -302,15 +422,15 @@ fn territory_attack_strength(
         .units
         .iter()
         .filter(|u| u.owner == power)
-        .map(|u| get_unit_stats(u.unit_type).attack as u32)
+        .map(|u| state.unit_stats(u.unit_type).attack as u32)
         .sum()
 }
 
-fn territory_defense_strength(territory: &crate::territory::TerritoryState) -> u32 {
+fn territory_defense_strength(state: &GameState, territory: &crate::territory::TerritoryState) -> u32 {
     territory
         .units
         .iter()
-        .map(|u| get_unit_stats(u.unit_type).defense as u32)
+        .map(|u| state.unit_stats(u.unit_type).defense as u32)
         .sum()
 }
 
@@ -325,17 +445,17 @@ fn ai_conduct_combat(state: &GameState, _map: &GameMap) -> Action {
             return ai_handle_battle(state, active);
         }
 
-        // If there are pending battles, select the first one
-        if let Some(&location) = cs.pending_battles.first() {
-            // Check it's not already resolved
-            if !cs.resolved_battles.contains(&location) {
-                return Action::SelectBattle { location };
-            }
-        }
-
-        // Find unresolved battles
+        // Select the first battle that can be fought now; amphibious
+        // assaults wait for the naval battle that carries them
         for &loc in &cs.pending_battles {
-            if !cs.resolved_battles.contains(&loc) {
+            if cs.resolved_battles.contains(&loc) {
+                continue;
+            }
+            let blocked = match loc {
+                RegionId::Land(tid) => crate::combat::blocking_naval_battle(state, tid).is_some(),
+                RegionId::Sea(_) => false,
+            };
+            if !blocked {
                 return Action::SelectBattle { location: loc };
             }
         }
@@ -345,33 +465,26 @@ fn ai_conduct_combat(state: &GameState, _map: &GameMap) -> Action {
     Action::ConfirmPhase
 }
 
-fn ai_handle_battle(state: &GameState, active: &ActiveCombat) -> Action {
+pub(crate) fn ai_handle_battle(state: &GameState, active: &ActiveCombat) -> Action {
     match active.sub_phase {
         CombatSubPhase::AttackerRolls
         | CombatSubPhase::AAFire
         | CombatSubPhase::ShoreBombardment
-        | CombatSubPhase::AttackerSubmarineStrike
-        | CombatSubPhase::DefenderSubmarineStrike => Action::RollAttack,
-        CombatSubPhase::DefenderRolls => Action::RollDefense,
+        | CombatSubPhase::AttackerSubmarineStrike => Action::RollAttack,
+        CombatSubPhase::DefenderSubmarineStrike | CombatSubPhase::DefenderRolls => Action::RollDefense,
         CombatSubPhase::AttackerSelectsCasualties
-        | CombatSubPhase::AttackerSubmarineStrikeCasualties => {
+        | CombatSubPhase::AttackerSubmarineStrikeCasualties
+        | CombatSubPhase::AAFireCasualties => {
             // We need to find our units to select casualties from
-            let casualties = select_cheapest_casualties_by_id(
-                state,
-                &active.attacker_units,
-                active.pending_defender_hits as usize,
-            );
+            let casualties =
+                combat::suggest_casualties(state, active, false, active.pending_defender_hits);
             Action::SelectCasualties { casualties }
         }
         CombatSubPhase::DefenderSelectsCasualties
         | CombatSubPhase::DefenderSubmarineStrikeCasualties
-        | CombatSubPhase::AAFireCasualties
         | CombatSubPhase::ShoreBombardmentCasualties => {
-            let casualties = select_cheapest_casualties_by_id(
-                state,
-                &active.defender_units,
-                active.pending_attacker_hits as usize,
-            );
+            let casualties =
+                combat::suggest_casualties(state, active, true, active.pending_attacker_hits);
             Action::SelectCasualties { casualties }
         }
         CombatSubPhase::AttackerDecision => {
@@ -389,13 +502,13 @@ fn ai_handle_battle(state: &GameState, active: &ActiveCombat) -> Action {
 }
 
 /// Select the cheapest units as casualties (preserve expensive units).
-fn select_cheapest_casualties(units: &[UnitInstance], hits: usize) -> Vec<UnitId> {
+fn select_cheapest_casualties(state: &GameState, units: &[UnitInstance], hits: usize) -> Vec<UnitId> {
     if hits == 0 || units.is_empty() {
         return Vec::new();
     }
 
     let mut sorted: Vec<&UnitInstance> = units.iter().collect();
-    sorted.sort_by_key(|u| get_unit_stats(u.unit_type).cost);
+    sorted.sort_by_key(|u| state.unit_stats(u.unit_type).cost);
 
     sorted
         .iter()
@@ -404,31 +517,6 @@ fn select_cheapest_casualties(units: &[UnitInstance], hits: usize) -> Vec<UnitId
         .collect()
 }
 
-/// Select cheapest casualties from unit IDs by looking them up in game state.
-fn select_cheapest_casualties_by_id(
-    state: &GameState,
-    unit_ids: &[UnitId],
-    hits: usize,
-) -> Vec<UnitId> {
-    if hits == 0 || unit_ids.is_empty() {
-        return Vec::new();
-    }
-
-    // Collect unit instances
-    let mut units: Vec<(UnitId, u32)> = unit_ids
-        .iter()
-        .filter_map(|&uid| {
-            movement::find_unit(state, uid)
-                .map(|(_, u)| (uid, get_unit_stats(u.unit_type).cost))
-        })
-        .collect();
-
-    // Sort by cost (cheapest first)
-    units.sort_by_key(|&(_, cost)| cost);
-
-    units.iter().take(hits).map(|&(id, _)| id).collect()
-}
-
 // =========================================================================
 // Non-Combat Movement Phase AI
 // =========================================================================
@@ -451,13 +539,26 @@ fn ai_mobilize(state: &GameState, map: &GameMap) -> Action {
         for (ut, count) in &ms.units_to_place {
             let placed = ms.placements.iter().filter(|(put, _)| put == ut).count() as u32;
             if placed < *count {
-                // Find eligible territory
+                // Find an eligible territory whose factory still has room
                 let eligible =
                     mobilize::eligible_placement_territories(state, map, power, *ut);
-                if let Some(&tid) = eligible.first() {
+                let is_naval = state.unit_stats(*ut).domain == crate::unit::UnitDomain::Sea;
+                let open = eligible.into_iter().find_map(|tid| {
+                    // Ships launch into the first sea zone off the factory
+                    let sea_zone = if is_naval {
+                        map.territory(tid).adjacent_sea.first().copied()
+                    } else {
+                        None
+                    };
+                    mobilize::validate_place_unit(state, map, *ut, tid, sea_zone)
+                        .is_ok()
+                        .then_some((tid, sea_zone))
+                });
+                if let Some((tid, sea_zone)) = open {
                     return Action::PlaceUnit {
                         unit_type: *ut,
                         territory_id: tid,
+                        sea_zone,
                     };
                 }
             }
@@ -498,6 +599,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_purchase_fallback_respects_production_rules() {
+        use crate::purchase::AllowedUnits;
+
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        state.powers[Power::Germany as usize].ipcs = 40;
+        state.settings.production_rules.insert(
+            Power::Germany,
+            AllowedUnits::AllExcept(vec![
+                UnitType::Infantry,
+                UnitType::Artillery,
+                UnitType::Tank,
+                UnitType::Fighter,
+            ]),
+        );
+        assert_eq!(cheapest_buildable_land_unit(engine.state(), Power::Germany), Some(UnitType::MechInfantry));
+
+        let action = ai_next_action(engine.state(), engine.map(), AiDifficulty::Normal);
+        let Action::PurchaseUnit { unit_type, count } = action else {
+            panic!("Expected a purchase, got {:?}", action);
+        };
+        assert_eq!(unit_type, UnitType::MechInfantry);
+        assert!(count * engine.state().unit_stats(unit_type).cost <= 40);
+        engine.submit_action(Action::PurchaseUnit { unit_type, count }).unwrap();
+    }
+
+    #[test]
+    fn test_tiny_budget_still_gives_a_legal_action() {
+        let mut engine = Engine::new_game(42);
+        let budget = AiBudget { max_iterations: 1, max_millis: Some(1) };
+        // Through the purchase phase and the first decision of combat movement
+        while engine.state().current_phase == Phase::PurchaseAndRepair {
+            let action = ai_next_action_with_budget(engine.state(), engine.map(), AiDifficulty::Hard, budget);
+            engine.submit_action(action).unwrap();
+        }
+        let action = ai_next_action_with_budget(engine.state(), engine.map(), AiDifficulty::Hard, budget);
+        assert!(engine.is_action_legal(&action).is_ok(), "{:?}", action);
+    }
+
+    #[test]
+    fn test_budget_cut_short_keeps_best_attack_found() {
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let unlimited = AiBudget { max_iterations: u32::MAX, max_millis: None };
+        let mut full = Thinking::new(unlimited);
+        let best = ai_combat_movement(engine.state(), engine.map(), AiDifficulty::Hard, &mut full);
+        assert!(matches!(best, Action::MoveUnit { .. }));
+
+        // One candidate short of a full search
+        let budget = AiBudget { max_iterations: full.iterations - 1, max_millis: None };
+        let action = ai_next_action_with_budget(engine.state(), engine.map(), AiDifficulty::Hard, budget);
+        assert!(matches!(action, Action::MoveUnit { .. }), "{:?}", action);
+        assert!(AiBudget::for_difficulty(AiDifficulty::Hard).max_millis.is_none());
+    }
+
     #[test]
     fn test_ai_plays_full_turn_without_panic() {
         let engine = Engine::new_game(42);
@@ -571,7 +728,8 @@ mod tests {
             UnitInstance::new(4, UnitType::Artillery, Power::Germany),  // cost 4
         ];
 
-        let casualties = select_cheapest_casualties(&units, 2);
+        let state = crate::setup::create_initial_state(42, &GameMap::new());
+        let casualties = select_cheapest_casualties(&state, &units, 2);
         assert_eq!(casualties.len(), 2);
         // Should pick infantry (3) and artillery (4) as cheapest
         assert!(casualties.contains(&2)); // Infantry
@@ -584,14 +742,20 @@ mod tests {
             UnitInstance::new(1, UnitType::Infantry, Power::Germany),
         ];
 
-        let casualties = select_cheapest_casualties(&units, 5);
+        let state = crate::setup::create_initial_state(42, &GameMap::new());
+        let casualties = select_cheapest_casualties(&state, &units, 5);
         assert_eq!(casualties.len(), 1); // Can only lose what we have
     }
 
     #[test]
     fn test_ai_threat_assessment() {
         let engine = Engine::new_game(42);
-        let threat = assess_threat_level(engine.state(), engine.map(), Power::Germany);
+        let threat = assess_threat_level(
+            engine.state(),
+            engine.map(),
+            Power::Germany,
+            &mut Thinking::new(AiBudget::for_difficulty(AiDifficulty::Normal)),
+        );
         // Germany should have some threat at game start (UK, France, Soviet neighbors)
         assert!(threat <= 100);
     }
@@ -3,7 +3,7 @@
 //! Applies a validated action to the game state and returns the result.
 //! Detailed application logic will be implemented in later phases.
 
-use crate::action::{Action, ActionResult, AppliedAction, GameEvent, InverseAction};
+use crate::action::{Action, ActionResult, AppliedAction, GameEvent, InverseAction, IpcChangeReason};
 use crate::data::GameMap;
 use crate::error::EngineError;
 use crate::movement;
@@ -14,13 +14,27 @@ use crate::phase::{
 use crate::power;
 use crate::state::GameState;
 use crate::territory::RegionId;
+use crate::unit::{UnitId, UnitType};
 
 /// Apply a validated action to the game state.
-pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Result<ActionResult, EngineError> {
+pub fn apply_action(state: &mut GameState, action: Action, map: &GameMap) -> Result<ActionResult, EngineError> {
+    let mut result = apply_action_inner(state, action, map)?;
+    result.state_checksum = state.checksum();
+    Ok(result)
+}
+
+/// `apply_action` without the state checksum, for internal callers such as
+/// the battle calculator that throw the result away.
+pub(crate) fn apply_action_inner(state: &mut GameState, action: Action, map: &GameMap) -> Result<ActionResult, EngineError> {
     // Undo is handled separately — it must NOT be pushed to the action_log
     if matches!(action, Action::Undo) {
         return apply_undo(state);
     }
+    // ConfirmPhase is applied, and logged, as the current phase's own confirm
+    let action = match action {
+        Action::ConfirmPhase => state.current_phase.confirm_action(),
+        action => action,
+    };
 
     let mut events = Vec::new();
 
@@ -42,22 +56,32 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
 
             // For ConfirmIncome, collect income before transitioning
             if matches!(action, Action::ConfirmIncome) {
-                crate::income::apply_collect_income(state, _map);
+                events.extend(crate::income::apply_collect_income(state, map));
             }
 
-            // For ConfirmPurchases, save purchases to state
+            // For ConfirmPurchases, save purchases to state and to the power's history
             if matches!(action, Action::ConfirmPurchases) {
                 state.pending_purchases = purchased_units.clone();
+                let turn = state.turn_number;
+                state.powers[state.current_power as usize]
+                    .purchase_history
+                    .push((turn, purchased_units.clone()));
             }
 
             // For ConfirmCombatMovement, identify pending combats
             if matches!(action, Action::ConfirmCombatMovement) {
-                let combats = movement::identify_pending_combats(state, state.current_power);
+                events.extend(crate::politics::check_mongolia_activation(state));
+                let combats = movement::identify_pending_combats_from_moves(state, state.current_power);
+                movement::release_unopposed_landings(state, state.current_power, &combats);
                 // Save undo checkpoint at phase boundary
                 state.undo_checkpoints.push(state.action_log.len());
                 state.current_phase = Phase::ConductCombat;
                 let mut combat_state = CombatState::new();
                 combat_state.pending_battles = combats;
+                if let PhaseState::CombatMove(ref mut cms) = state.phase_state {
+                    combat_state.owners_before_combat = std::mem::take(&mut cms.owners_at_start);
+                    combat_state.combat_moves = std::mem::take(&mut cms.moves);
+                }
                 state.phase_state = PhaseState::Combat(combat_state);
                 events.push(GameEvent::PhaseChanged {
                     from: old_phase,
@@ -69,7 +93,7 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                     inverse: InverseAction::Irreversible,
                 };
                 state.action_log.push(applied.clone());
-                return Ok(ActionResult { applied, events });
+                return Ok(ActionResult::new(applied, events));
             }
 
             // Save undo checkpoint at phase boundary
@@ -79,6 +103,14 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                 state.current_phase = next_phase;
                 state.phase_state = create_phase_state(next_phase);
 
+                if let PhaseState::CombatMove(ref mut cms) = state.phase_state {
+                    cms.owners_at_start = state.territories.iter().map(|t| t.owner).collect();
+                }
+
+                if next_phase == Phase::CollectIncome {
+                    crate::income::snapshot_income(state, map);
+                }
+
                 // If transitioning to Mobilize, populate units_to_place from pending_purchases
                 if next_phase == Phase::Mobilize && !state.pending_purchases.is_empty() {
                     if let PhaseState::Mobilize(ref mut ms) = state.phase_state {
@@ -101,15 +133,10 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                 // Clear pending purchases
                 state.pending_purchases.clear();
 
-                let next = power::next_power(state.current_power);
-                let old_power = state.current_power;
-                state.current_power = next;
-                state.current_phase = Phase::PurchaseAndRepair;
-                state.phase_state = PhaseState::Purchase(PurchaseState::new());
+                // Capital ships ending the turn at a friendly naval base are repaired
+                crate::special::repair_ships_at_naval_bases(state, map, state.current_power);
 
-                if next == power::TURN_ORDER[0] {
-                    state.turn_number += 1;
-                }
+                let (next, round_complete) = begin_next_power_turn(state);
 
                 events.push(GameEvent::PhaseChanged {
                     from: old_phase,
@@ -119,11 +146,13 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                     power: next,
                     turn: state.turn_number,
                 });
-                let _ = old_power; // suppress unused warning
+                if round_complete {
+                    events.extend(check_round_victory(state, map));
+                }
             }
         }
         Action::PurchaseUnit { unit_type, count } => {
-            let stats = crate::unit::get_unit_stats(*unit_type);
+            let stats = state.unit_stats(*unit_type);
             let cost = stats.cost * count;
 
             if let PhaseState::Purchase(ref mut ps) = state.phase_state {
@@ -133,7 +162,7 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                 } else {
                     ps.purchases.push((*unit_type, *count));
                 }
-                ps.ipcs_spent += cost;
+                ps.ipcs_committed += cost;
             }
 
             // Deduct IPCs from the power
@@ -145,6 +174,12 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                 count: *count,
                 cost,
             });
+            events.push(crate::income::ipc_changed(
+                state,
+                state.current_power,
+                -(cost as i32),
+                IpcChangeReason::Purchase,
+            ));
 
             let applied = AppliedAction {
                 action: action.clone(),
@@ -154,11 +189,11 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                 }),
             };
             state.action_log.push(applied.clone());
-            return Ok(ActionResult { applied, events });
+            return Ok(ActionResult::new(applied, events));
         }
 
         Action::RemovePurchase { unit_type, count } => {
-            let stats = crate::unit::get_unit_stats(*unit_type);
+            let stats = state.unit_stats(*unit_type);
             let refund = stats.cost * count;
 
             if let PhaseState::Purchase(ref mut ps) = state.phase_state {
@@ -168,12 +203,18 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                         ps.purchases.retain(|(ut, _)| *ut != *unit_type);
                     }
                 }
-                ps.ipcs_spent = ps.ipcs_spent.saturating_sub(refund);
+                ps.ipcs_committed = ps.ipcs_committed.saturating_sub(refund);
             }
 
             // Refund IPCs
             let power_idx = state.current_power as usize;
             state.powers[power_idx].ipcs += refund;
+            events.push(crate::income::ipc_changed(
+                state,
+                state.current_power,
+                refund as i32,
+                IpcChangeReason::PurchaseRefund,
+            ));
 
             let applied = AppliedAction {
                 action: action.clone(),
@@ -183,7 +224,7 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                 }),
             };
             state.action_log.push(applied.clone());
-            return Ok(ActionResult { applied, events });
+            return Ok(ActionResult::new(applied, events));
         }
 
         Action::RepairFacility {
@@ -203,7 +244,7 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
             // Record in phase state
             if let PhaseState::Purchase(ref mut ps) = state.phase_state {
                 ps.repairs.push((*territory_id, *damage_to_repair));
-                ps.ipcs_spent += cost;
+                ps.ipcs_committed += cost;
             }
 
             // Apply damage reduction to the facility
@@ -216,23 +257,45 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
             // Deduct IPCs
             let power_idx = state.current_power as usize;
             state.powers[power_idx].ipcs -= cost;
+            events.push(crate::income::ipc_changed(
+                state,
+                state.current_power,
+                -(cost as i32),
+                IpcChangeReason::Repair,
+            ));
 
             let applied = AppliedAction {
                 action: action.clone(),
                 inverse: InverseAction::Irreversible, // Repair undo is complex; mark irreversible for now
             };
             state.action_log.push(applied.clone());
-            return Ok(ActionResult { applied, events });
+            return Ok(ActionResult::new(applied, events));
         }
 
         Action::MoveUnit { unit_id, ref path } => {
             return apply_move_unit(state, *unit_id, path.clone());
         }
 
+        Action::MoveUnits { unit_ids, ref path } => {
+            return apply_move_units(state, unit_ids.clone(), path.clone());
+        }
+
         Action::UndoMove { unit_id } => {
             return apply_undo_move(state, *unit_id);
         }
 
+        Action::BeginOperation { ref name } => {
+            return apply_operation_marker(state, &action, Some(name.clone()));
+        }
+
+        Action::EndOperation => {
+            return apply_operation_marker(state, &action, None);
+        }
+
+        Action::UndoOperation { ref name } => {
+            return apply_undo_operation(state, name.clone());
+        }
+
         Action::MoveUnitNonCombat { unit_id, ref path } => {
             return apply_move_unit_noncombat(state, *unit_id, path.clone());
         }
@@ -242,35 +305,45 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
         }
 
         Action::SelectBattle { location } => {
-            return apply_select_battle_action(state, *location);
+            return apply_select_battle_action(state, map, *location);
         }
 
         Action::RollAttack => {
-            return apply_roll_attack_action(state);
+            return apply_roll_attack_action(state, map);
+        }
+
+        Action::StrategicBombingRaid { territory_id } => {
+            let events = combat::apply_strategic_bombing(state, *territory_id)?;
+            let applied = AppliedAction {
+                action: action.clone(),
+                inverse: InverseAction::Irreversible,
+            };
+            state.action_log.push(applied.clone());
+            return Ok(ActionResult::new(applied, events));
         }
 
         Action::RollDefense => {
-            return apply_roll_defense_action(state);
+            return apply_roll_defense_action(state, map);
         }
 
         Action::SelectCasualties { casualties } => {
-            return apply_select_casualties_action(state, casualties.clone());
+            return apply_select_casualties_action(state, map, casualties.clone());
         }
 
         Action::AttackerRetreat { to } => {
-            return apply_attacker_retreat_action(state, *to);
+            return apply_attacker_retreat_action(state, map, *to);
         }
 
         Action::SubmergeSubmarine { unit_id } => {
-            return apply_submerge_action(state, *unit_id);
+            return apply_submerge_action(state, map, *unit_id);
         }
 
         Action::ContinueCombatRound => {
-            return apply_continue_combat_action(state);
+            return apply_continue_combat_action(state, map);
         }
 
-        Action::PlaceUnit { unit_type, territory_id } => {
-            return apply_place_unit(state, *unit_type, *territory_id);
+        Action::PlaceUnit { unit_type, territory_id, sea_zone } => {
+            return apply_place_unit(state, *unit_type, *territory_id, *sea_zone);
         }
 
         Action::DeclareWar { against } => {
@@ -280,7 +353,52 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
                 inverse: InverseAction::Irreversible,
             };
             state.action_log.push(applied.clone());
-            return Ok(ActionResult { applied, events: war_events });
+            return Ok(ActionResult::new(applied, war_events));
+        }
+
+        Action::SetRelation { a, b, relation } => {
+            let events = crate::politics::apply_set_relation(state, *a, *b, *relation);
+            let applied = AppliedAction {
+                action: action.clone(),
+                inverse: InverseAction::Irreversible,
+            };
+            state.action_log.push(applied.clone());
+            return Ok(ActionResult::new(applied, events));
+        }
+
+        Action::Surrender { power } => {
+            let mut events = crate::politics::apply_surrender(state, map, *power);
+
+            // A power surrendering on its own turn hands play to the next power
+            if *power == state.current_power {
+                let old_phase = state.current_phase;
+                state.pending_purchases.clear();
+                state.undo_checkpoints.push(state.action_log.len());
+                let (next, round_complete) = begin_next_power_turn(state);
+                events.push(GameEvent::PhaseChanged {
+                    from: old_phase,
+                    to: Phase::PurchaseAndRepair,
+                });
+                events.push(GameEvent::TurnChanged {
+                    power: next,
+                    turn: state.turn_number,
+                });
+                if round_complete {
+                    events.extend(check_round_victory(state, map));
+                }
+            }
+
+            // A whole team conceding ends the game without waiting for the round
+            if let Some(result) = crate::victory::check_surrender_victory(state) {
+                events.extend(declare_victory(state, &result));
+            }
+
+            let applied = AppliedAction {
+                action: action.clone(),
+                inverse: InverseAction::Irreversible,
+            };
+            state.action_log.push(applied.clone());
+            return Ok(ActionResult::new(applied, events));
         }
 
         // Undo handled above
@@ -293,7 +411,71 @@ pub fn apply_action(state: &mut GameState, action: Action, _map: &GameMap) -> Re
     };
     state.action_log.push(applied.clone());
 
-    Ok(ActionResult { applied, events })
+    Ok(ActionResult::new(applied, events))
+}
+
+/// Hand the turn to the next power that has not surrendered, starting at
+/// Purchase & Repair. The turn number advances whenever the order wraps.
+/// Returns the new power and whether a full round has just been completed.
+fn begin_next_power_turn(state: &mut GameState) -> (power::Power, bool) {
+    let mut next = state.current_power;
+    let mut round_complete = false;
+    for _ in 0..power::TURN_ORDER.len() {
+        next = power::next_power(next);
+        if next == power::TURN_ORDER[0] {
+            state.turn_number += 1;
+            round_complete = true;
+        }
+        if !state.powers[next as usize].surrendered {
+            break;
+        }
+    }
+
+    state.current_power = next;
+    state.current_phase = Phase::PurchaseAndRepair;
+    state.phase_state = PhaseState::Purchase(PurchaseState::new());
+    begin_turn(state);
+    (next, round_complete)
+}
+
+/// End-of-round victory check, run once the last power in the turn order has
+/// finished. Captures during the round never end the game on their own.
+fn check_round_victory(state: &mut GameState, map: &GameMap) -> Vec<GameEvent> {
+    let events = match crate::victory::check_victory(state, map) {
+        Some(result) => declare_victory(state, &result),
+        None => Vec::new(),
+    };
+    crate::victory::track_economic_leader(state, map);
+    events
+}
+
+/// Record the winner, reporting it only the first time.
+fn declare_victory(state: &mut GameState, result: &crate::victory::VictoryResult) -> Vec<GameEvent> {
+    if state.winner.is_some() {
+        return Vec::new();
+    }
+    state.winner = Some(result.winner);
+    vec![crate::victory::victory_event(result)]
+}
+
+/// Start-of-turn reset for the current power: its units may move again with
+/// their full movement. Called exactly once per turn, from the turn advance.
+/// Other per-turn bookkeeping (strategic bombing, battles) lives in the phase
+/// state and starts fresh with each phase.
+pub fn begin_turn(state: &mut GameState) {
+    let power = state.current_power;
+    let movement: std::collections::HashMap<UnitType, u8> =
+        UnitType::all().iter().map(|&t| (t, state.unit_stats(t).movement)).collect();
+    let territory_units = state.territories.iter_mut().flat_map(|t| t.units.iter_mut());
+    let sea_units = state.sea_zones.iter_mut().flat_map(|z| z.units.iter_mut());
+    for unit in territory_units.chain(sea_units).filter(|u| u.owner == power) {
+        unit.moved_this_turn = false;
+        unit.movement_remaining = movement[&unit.unit_type];
+        // Last turn's landings no longer take up room
+        if unit.unit_type == crate::unit::UnitType::Transport {
+            unit.cargo.clear();
+        }
+    }
 }
 
 /// Apply an undo operation by popping the last action and reversing it.
@@ -302,9 +484,15 @@ fn apply_undo(state: &mut GameState) -> Result<ActionResult, EngineError> {
         reason: "No actions to undo".into(),
     })?;
 
+    let mut events = Vec::new();
     match applied.inverse {
         InverseAction::Simple(inverse_action) => {
-            apply_inverse_simple(state, inverse_action)?;
+            events.extend(apply_inverse_simple(state, inverse_action)?);
+        }
+        InverseAction::Sequence(inverse_actions) => {
+            for inverse_action in inverse_actions {
+                events.extend(apply_inverse_simple(state, inverse_action)?);
+            }
         }
         InverseAction::RestoreSnapshot(bytes) => {
             apply_inverse_snapshot(state, &bytes)?;
@@ -318,21 +506,23 @@ fn apply_undo(state: &mut GameState) -> Result<ActionResult, EngineError> {
         }
     }
 
-    Ok(ActionResult {
-        applied: AppliedAction {
+    Ok(ActionResult::new(
+        AppliedAction {
             action: Action::Undo,
             inverse: InverseAction::Irreversible,
         },
-        events: Vec::new(),
-    })
+        events,
+    ))
 }
 
-/// Apply a simple inverse action to reverse a previous action.
-fn apply_inverse_simple(state: &mut GameState, action: Action) -> Result<(), EngineError> {
+/// Apply a simple inverse action to reverse a previous action, returning
+/// the events it causes (treasury changes for purchases taken back).
+fn apply_inverse_simple(state: &mut GameState, action: Action) -> Result<Vec<GameEvent>, EngineError> {
+    let mut events = Vec::new();
     match &action {
         Action::RemovePurchase { unit_type, count } => {
             // This is the inverse of PurchaseUnit — remove from queue, refund IPCs
-            let stats = crate::unit::get_unit_stats(*unit_type);
+            let stats = state.unit_stats(*unit_type);
             let refund = stats.cost * count;
 
             if let PhaseState::Purchase(ref mut ps) = state.phase_state {
@@ -342,15 +532,21 @@ fn apply_inverse_simple(state: &mut GameState, action: Action) -> Result<(), Eng
                         ps.purchases.retain(|(ut, _)| *ut != *unit_type);
                     }
                 }
-                ps.ipcs_spent = ps.ipcs_spent.saturating_sub(refund);
+                ps.ipcs_committed = ps.ipcs_committed.saturating_sub(refund);
             }
 
             let power_idx = state.current_power as usize;
             state.powers[power_idx].ipcs += refund;
+            events.push(crate::income::ipc_changed(
+                state,
+                state.current_power,
+                refund as i32,
+                IpcChangeReason::PurchaseRefund,
+            ));
         }
         Action::PurchaseUnit { unit_type, count } => {
             // This is the inverse of RemovePurchase — re-add to queue, deduct IPCs
-            let stats = crate::unit::get_unit_stats(*unit_type);
+            let stats = state.unit_stats(*unit_type);
             let cost = stats.cost * count;
 
             if let PhaseState::Purchase(ref mut ps) = state.phase_state {
@@ -359,17 +555,42 @@ fn apply_inverse_simple(state: &mut GameState, action: Action) -> Result<(), Eng
                 } else {
                     ps.purchases.push((*unit_type, *count));
                 }
-                ps.ipcs_spent += cost;
+                ps.ipcs_committed += cost;
             }
 
             let power_idx = state.current_power as usize;
             state.powers[power_idx].ipcs -= cost;
+            events.push(crate::income::ipc_changed(
+                state,
+                state.current_power,
+                -(cost as i32),
+                IpcChangeReason::Purchase,
+            ));
+        }
+        Action::UndoMove { unit_id } => {
+            // The inverse of MoveUnit — the unit is back and free to move
+            undo_planned_move(state, *unit_id)?;
+        }
+        Action::MoveUnit { unit_id, path } => {
+            // The inverse of UndoMove — the move is made again
+            relocate_combat_unit(state, *unit_id, path)?;
+        }
+        Action::BeginOperation { name } => {
+            // Replayed moves are tagged with the open operation
+            if let PhaseState::CombatMove(ref mut cms) = state.phase_state {
+                cms.open_operation = Some(name.clone());
+            }
+        }
+        Action::EndOperation => {
+            if let PhaseState::CombatMove(ref mut cms) = state.phase_state {
+                cms.open_operation = None;
+            }
         }
         _ => {
             // Other inverse actions not yet implemented
         }
     }
-    Ok(())
+    Ok(events)
 }
 
 /// Restore phase state from a MessagePack snapshot.
@@ -386,6 +607,46 @@ fn apply_move_unit(
     unit_id: u32,
     path: Vec<RegionId>,
 ) -> Result<ActionResult, EngineError> {
+    relocate_combat_unit(state, unit_id, &path)?;
+
+    let applied = AppliedAction {
+        action: Action::MoveUnit { unit_id, path },
+        inverse: InverseAction::Simple(Action::UndoMove { unit_id }),
+    };
+    state.action_log.push(applied.clone());
+
+    Ok(ActionResult::new(applied, Vec::new()))
+}
+
+/// Apply a MoveUnits group action. Each unit is recorded as its own planned
+/// move, so individual units can still be taken back with UndoMove, while
+/// Undo takes the whole group back, last unit first.
+fn apply_move_units(
+    state: &mut GameState,
+    unit_ids: Vec<UnitId>,
+    path: Vec<RegionId>,
+) -> Result<ActionResult, EngineError> {
+    for &unit_id in &unit_ids {
+        relocate_combat_unit(state, unit_id, &path)?;
+    }
+
+    let applied = AppliedAction {
+        inverse: InverseAction::Sequence(
+            unit_ids.iter().rev().map(|&unit_id| Action::UndoMove { unit_id }).collect(),
+        ),
+        action: Action::MoveUnits { unit_ids, path },
+    };
+    state.action_log.push(applied.clone());
+
+    Ok(ActionResult::new(applied, Vec::new()))
+}
+
+/// Move a unit along a combat path and record the planned move.
+fn relocate_combat_unit(
+    state: &mut GameState,
+    unit_id: u32,
+    path: &[RegionId],
+) -> Result<(), EngineError> {
     let from = path[0];
     let to = *path.last().unwrap();
 
@@ -398,6 +659,17 @@ fn apply_move_unit(
     let movement_used = (path.len() as u8).saturating_sub(1);
     unit.movement_remaining = unit.movement_remaining.saturating_sub(movement_used);
 
+    // A land unit crossing water rides in a transport that sailed the route;
+    // the cargo entry marks its battle as an amphibious assault
+    if movement::is_amphibious_move(state, unit.unit_type, path) {
+        let route = &path[1..path.len() - 1];
+        let transport_id = movement::transport_for_route(state, unit.owner, route)
+            .ok_or(EngineError::Internal("No transport for amphibious move".into()))?;
+        if let Some((_, transport)) = movement::find_unit_mut(state, transport_id) {
+            transport.cargo.push(unit_id);
+        }
+    }
+
     // Place at destination
     movement::place_unit_at(state, to, unit);
 
@@ -405,22 +677,14 @@ fn apply_move_unit(
     if let PhaseState::CombatMove(ref mut cms) = state.phase_state {
         cms.moves.push(PlannedMove {
             unit_id,
-            path: path.clone(),
+            path: path.to_vec(),
             from,
             to,
+            operation: cms.open_operation.clone(),
         });
     }
 
-    let applied = AppliedAction {
-        action: Action::MoveUnit { unit_id, path },
-        inverse: InverseAction::Simple(Action::UndoMove { unit_id }),
-    };
-    state.action_log.push(applied.clone());
-
-    Ok(ActionResult {
-        applied,
-        events: Vec::new(),
-    })
+    Ok(())
 }
 
 /// Apply an UndoMove action: return unit to its original position.
@@ -428,6 +692,23 @@ fn apply_undo_move(
     state: &mut GameState,
     unit_id: u32,
 ) -> Result<ActionResult, EngineError> {
+    let planned = undo_planned_move(state, unit_id)?;
+
+    let applied = AppliedAction {
+        action: Action::UndoMove { unit_id },
+        inverse: InverseAction::Simple(Action::MoveUnit {
+            unit_id,
+            path: planned.path,
+        }),
+    };
+    state.action_log.push(applied.clone());
+
+    Ok(ActionResult::new(applied, Vec::new()))
+}
+
+/// Return a unit to where its planned combat move started and drop the
+/// move. Returns the move that was taken back.
+fn undo_planned_move(state: &mut GameState, unit_id: u32) -> Result<PlannedMove, EngineError> {
     // Find the planned move
     let planned = if let PhaseState::CombatMove(ref cms) = state.phase_state {
         cms.moves.iter().find(|m| m.unit_id == unit_id).cloned()
@@ -445,9 +726,18 @@ fn apply_undo_move(
 
     // Restore movement
     unit.moved_this_turn = false;
-    let stats = crate::unit::get_unit_stats(unit.unit_type);
+    let stats = state.unit_stats(unit.unit_type);
     unit.movement_remaining = stats.movement;
 
+    // Off the transport that carried it, if any
+    if movement::is_amphibious_move(state, unit.unit_type, &planned.path) {
+        for zone in state.sea_zones.iter_mut() {
+            for transport in zone.units.iter_mut() {
+                transport.cargo.retain(|&id| id != unit_id);
+            }
+        }
+    }
+
     // Place back at origin
     movement::place_unit_at(state, planned.from, unit);
 
@@ -456,20 +746,70 @@ fn apply_undo_move(
         cms.moves.retain(|m| m.unit_id != unit_id);
     }
 
-    let inverse_path = planned.path.clone();
+    Ok(planned)
+}
+
+/// Apply BeginOperation (`open` is its name) or EndOperation. Undoing
+/// either restores the phase state from before it.
+fn apply_operation_marker(
+    state: &mut GameState,
+    action: &Action,
+    open: Option<String>,
+) -> Result<ActionResult, EngineError> {
+    let snapshot = rmp_serde::to_vec(&state.phase_state)
+        .map_err(|e| EngineError::Serialization(e.to_string()))?;
+
+    if let PhaseState::CombatMove(ref mut cms) = state.phase_state {
+        cms.open_operation = open;
+    }
+
     let applied = AppliedAction {
-        action: Action::UndoMove { unit_id },
-        inverse: InverseAction::Simple(Action::MoveUnit {
-            unit_id,
-            path: inverse_path,
-        }),
+        action: action.clone(),
+        inverse: InverseAction::RestoreSnapshot(snapshot),
+    };
+    state.action_log.push(applied.clone());
+
+    Ok(ActionResult::new(applied, Vec::new()))
+}
+
+/// Apply an UndoOperation action: take back the operation's moves, latest
+/// first, and close it if it is still open. Undo replays the moves under the
+/// operation's name and reopens whatever operation was open before.
+fn apply_undo_operation(state: &mut GameState, name: String) -> Result<ActionResult, EngineError> {
+    let (moves, previously_open): (Vec<PlannedMove>, Option<String>) = match state.phase_state {
+        PhaseState::CombatMove(ref mut cms) => {
+            let previously_open = cms.open_operation.clone();
+            if cms.open_operation.as_deref() == Some(name.as_str()) {
+                cms.open_operation = None;
+            }
+            let moves = cms
+                .moves
+                .iter()
+                .filter(|m| m.operation.as_deref() == Some(name.as_str()))
+                .cloned()
+                .collect();
+            (moves, previously_open)
+        }
+        _ => (Vec::new(), None),
+    };
+    for planned in moves.iter().rev() {
+        undo_planned_move(state, planned.unit_id)?;
+    }
+
+    let mut redo = vec![Action::BeginOperation { name: name.clone() }];
+    redo.extend(moves.into_iter().map(|m| Action::MoveUnit { unit_id: m.unit_id, path: m.path }));
+    redo.push(match previously_open {
+        Some(open) => Action::BeginOperation { name: open },
+        None => Action::EndOperation,
+    });
+
+    let applied = AppliedAction {
+        action: Action::UndoOperation { name },
+        inverse: InverseAction::Sequence(redo),
     };
     state.action_log.push(applied.clone());
 
-    Ok(ActionResult {
-        applied,
-        events: Vec::new(),
-    })
+    Ok(ActionResult::new(applied, Vec::new()))
 }
 
 /// Apply a MoveUnitNonCombat action.
@@ -497,6 +837,7 @@ fn apply_move_unit_noncombat(
             path: path.clone(),
             from,
             to,
+            operation: None,
         });
     }
 
@@ -510,10 +851,7 @@ fn apply_move_unit_noncombat(
     };
     state.action_log.push(applied.clone());
 
-    Ok(ActionResult {
-        applied,
-        events: Vec::new(),
-    })
+    Ok(ActionResult::new(applied, Vec::new()))
 }
 
 /// Apply a LandAirUnit action.
@@ -539,10 +877,7 @@ fn apply_land_air_unit(
     };
     state.action_log.push(applied.clone());
 
-    Ok(ActionResult {
-        applied,
-        events: Vec::new(),
-    })
+    Ok(ActionResult::new(applied, Vec::new()))
 }
 
 // =========================================================================
@@ -554,27 +889,19 @@ fn apply_place_unit(
     state: &mut GameState,
     unit_type: crate::unit::UnitType,
     territory_id: crate::territory::TerritoryId,
+    sea_zone: Option<crate::territory::SeaZoneId>,
 ) -> Result<ActionResult, EngineError> {
     // Create a new unit and place it
     let unit_id = next_unit_id(state);
     let unit = crate::unit::UnitInstance::new(unit_id, unit_type, state.current_power);
 
-    let stats = crate::unit::get_unit_stats(unit_type);
-    if stats.domain == crate::unit::UnitDomain::Sea {
-        // Naval units go to an adjacent sea zone
-        // Find the first adjacent sea zone
-        // Note: In a full implementation, the player would choose the sea zone
-        // For now, place in the first adjacent sea zone
-        // Actually, we'll place the unit in the territory and the UI handles display
-        // The action's territory_id refers to the IC territory
-        if let Some(territory) = state.territories.get_mut(territory_id as usize) {
-            territory.units.push(unit);
-        }
-    } else {
-        if let Some(territory) = state.territories.get_mut(territory_id as usize) {
-            territory.units.push(unit);
-        }
-    }
+    // The action names the producing territory; ships launch into the sea
+    // zone the player chose next to it (checked by validation)
+    let region = match sea_zone {
+        Some(zone) => RegionId::Sea(zone),
+        None => RegionId::Land(territory_id),
+    };
+    movement::place_unit_at(state, region, unit);
 
     // Record in phase state
     if let PhaseState::Mobilize(ref mut ms) = state.phase_state {
@@ -584,19 +911,20 @@ fn apply_place_unit(
     let events = vec![GameEvent::UnitsPlaced {
         unit_type,
         territory_id,
+        sea_zone,
     }];
 
     let applied = AppliedAction {
-        action: Action::PlaceUnit { unit_type, territory_id },
+        action: Action::PlaceUnit { unit_type, territory_id, sea_zone },
         inverse: InverseAction::Simple(Action::Undo), // Simplified undo
     };
     state.action_log.push(applied.clone());
 
-    Ok(ActionResult { applied, events })
+    Ok(ActionResult::new(applied, events))
 }
 
 /// Get the next available unit ID.
-fn next_unit_id(state: &GameState) -> u32 {
+pub(crate) fn next_unit_id(state: &GameState) -> u32 {
     let mut max_id = 0u32;
     for territory in &state.territories {
         for unit in &territory.units {
@@ -619,167 +947,136 @@ use crate::combat;
 
 fn apply_select_battle_action(
     state: &mut GameState,
+    map: &GameMap,
     location: RegionId,
 ) -> Result<ActionResult, EngineError> {
-    let (active_combat, events) = combat::apply_select_battle(state, location)?;
-
-    if let PhaseState::Combat(ref mut cs) = state.phase_state {
-        cs.active_combat = Some(active_combat);
-    }
+    let (active_combat, mut events) = combat::apply_select_battle(state, location)?;
+    let step = combat::CombatStep::new(&active_combat);
+    // A battle can be over as soon as it starts
+    events.extend(store_or_finalize_combat(state, map, active_combat));
 
     let applied = AppliedAction {
         action: Action::SelectBattle { location },
         inverse: InverseAction::Irreversible,
     };
     state.action_log.push(applied.clone());
-    Ok(ActionResult { applied, events })
+    Ok(ActionResult::new(applied, events).with_combat_step(step))
 }
 
 fn apply_roll_attack_action(
     state: &mut GameState,
+    map: &GameMap,
 ) -> Result<ActionResult, EngineError> {
     let mut active_combat = extract_active_combat(state)?;
-    let events = combat::apply_roll_attack(state, &mut active_combat)?;
-    store_active_combat(state, active_combat);
+    let mut events = combat::apply_roll_attack(state, &mut active_combat)?;
+    let step = combat::CombatStep::after_roll(&active_combat);
+    events.extend(store_or_finalize_combat(state, map, active_combat));
 
     let applied = AppliedAction {
         action: Action::RollAttack,
         inverse: InverseAction::Irreversible,
     };
     state.action_log.push(applied.clone());
-    Ok(ActionResult { applied, events })
+    Ok(ActionResult::new(applied, events).with_combat_step(step))
 }
 
 fn apply_roll_defense_action(
     state: &mut GameState,
+    map: &GameMap,
 ) -> Result<ActionResult, EngineError> {
     let mut active_combat = extract_active_combat(state)?;
-    let events = combat::apply_roll_defense(state, &mut active_combat)?;
-    store_active_combat(state, active_combat);
+    let mut events = combat::apply_roll_defense(state, &mut active_combat)?;
+    let step = combat::CombatStep::after_roll(&active_combat);
+    events.extend(store_or_finalize_combat(state, map, active_combat));
 
     let applied = AppliedAction {
         action: Action::RollDefense,
         inverse: InverseAction::Irreversible,
     };
     state.action_log.push(applied.clone());
-    Ok(ActionResult { applied, events })
+    Ok(ActionResult::new(applied, events).with_combat_step(step))
 }
 
 fn apply_select_casualties_action(
     state: &mut GameState,
+    map: &GameMap,
     casualties: Vec<u32>,
 ) -> Result<ActionResult, EngineError> {
     let mut active_combat = extract_active_combat(state)?;
 
     let defender_side = matches!(
         active_combat.sub_phase,
-        combat::CombatSubPhase::AAFireCasualties
-        | combat::CombatSubPhase::DefenderSelectsCasualties
+        combat::CombatSubPhase::DefenderSelectsCasualties
         | combat::CombatSubPhase::ShoreBombardmentCasualties
         | combat::CombatSubPhase::DefenderSubmarineStrikeCasualties
     );
 
-    let events = combat::apply_casualties(state, &mut active_combat, &casualties, defender_side)?;
-
-    // Check if battle ended after casualties
-    if active_combat.sub_phase == combat::CombatSubPhase::BattleOver {
-        let battle_events = combat::finalize_battle(state, &active_combat);
-        let location = active_combat.location;
-        if let PhaseState::Combat(ref mut cs) = state.phase_state {
-            cs.resolved_battles.push(location);
-            cs.current_battle = None;
-            cs.active_combat = None;
-        }
-        let applied = AppliedAction {
-            action: Action::SelectCasualties { casualties },
-            inverse: InverseAction::Irreversible,
-        };
-        state.action_log.push(applied.clone());
-        let mut all_events = events;
-        all_events.extend(battle_events);
-        return Ok(ActionResult { applied, events: all_events });
-    }
-
-    store_active_combat(state, active_combat);
+    let mut events = combat::apply_casualties(state, &mut active_combat, &casualties, defender_side)?;
+    let step = combat::CombatStep {
+        casualties: casualties.clone(),
+        ..combat::CombatStep::new(&active_combat)
+    };
+    events.extend(store_or_finalize_combat(state, map, active_combat));
 
     let applied = AppliedAction {
         action: Action::SelectCasualties { casualties },
         inverse: InverseAction::Irreversible,
     };
     state.action_log.push(applied.clone());
-    Ok(ActionResult { applied, events })
+    Ok(ActionResult::new(applied, events).with_combat_step(step))
 }
 
 fn apply_attacker_retreat_action(
     state: &mut GameState,
+    map: &GameMap,
     retreat_to: RegionId,
 ) -> Result<ActionResult, EngineError> {
     let mut active_combat = extract_active_combat(state)?;
     let mut events = combat::apply_retreat(state, &mut active_combat, retreat_to)?;
-    let battle_events = combat::finalize_battle(state, &active_combat);
-    events.extend(battle_events);
-
-    let location = active_combat.location;
-    if let PhaseState::Combat(ref mut cs) = state.phase_state {
-        cs.resolved_battles.push(location);
-        cs.current_battle = None;
-        cs.active_combat = None;
-    }
+    let step = combat::CombatStep::new(&active_combat);
+    events.extend(store_or_finalize_combat(state, map, active_combat));
 
     let applied = AppliedAction {
         action: Action::AttackerRetreat { to: retreat_to },
         inverse: InverseAction::Irreversible,
     };
     state.action_log.push(applied.clone());
-    Ok(ActionResult { applied, events })
+    Ok(ActionResult::new(applied, events).with_combat_step(step))
 }
 
 fn apply_submerge_action(
     state: &mut GameState,
+    map: &GameMap,
     unit_id: u32,
 ) -> Result<ActionResult, EngineError> {
     let mut active_combat = extract_active_combat(state)?;
     combat::apply_submerge(state, &mut active_combat, unit_id)?;
-
-    if active_combat.sub_phase == combat::CombatSubPhase::BattleOver {
-        let events = combat::finalize_battle(state, &active_combat);
-        let location = active_combat.location;
-        if let PhaseState::Combat(ref mut cs) = state.phase_state {
-            cs.resolved_battles.push(location);
-            cs.current_battle = None;
-            cs.active_combat = None;
-        }
-        let applied = AppliedAction {
-            action: Action::SubmergeSubmarine { unit_id },
-            inverse: InverseAction::Irreversible,
-        };
-        state.action_log.push(applied.clone());
-        return Ok(ActionResult { applied, events });
-    }
-
-    store_active_combat(state, active_combat);
+    let step = combat::CombatStep::new(&active_combat);
+    let events = store_or_finalize_combat(state, map, active_combat);
 
     let applied = AppliedAction {
         action: Action::SubmergeSubmarine { unit_id },
         inverse: InverseAction::Irreversible,
     };
     state.action_log.push(applied.clone());
-    Ok(ActionResult { applied, events: Vec::new() })
+    Ok(ActionResult::new(applied, events).with_combat_step(step))
 }
 
 fn apply_continue_combat_action(
     state: &mut GameState,
+    map: &GameMap,
 ) -> Result<ActionResult, EngineError> {
     let mut active_combat = extract_active_combat(state)?;
     combat::continue_combat_round(state, &mut active_combat);
-    store_active_combat(state, active_combat);
+    let step = combat::CombatStep::new(&active_combat);
+    let events = store_or_finalize_combat(state, map, active_combat);
 
     let applied = AppliedAction {
         action: Action::ContinueCombatRound,
         inverse: InverseAction::Irreversible,
     };
     state.action_log.push(applied.clone());
-    Ok(ActionResult { applied, events: Vec::new() })
+    Ok(ActionResult::new(applied, events).with_combat_step(step))
 }
 
 /// Extract active combat from phase state.
@@ -796,6 +1093,27 @@ fn extract_active_combat(state: &mut GameState) -> Result<combat::ActiveCombat,
     }
 }
 
+/// Store the active combat back into phase state, or, if the battle is over,
+/// finalize it and mark its location resolved.
+fn store_or_finalize_combat(
+    state: &mut GameState,
+    map: &GameMap,
+    combat: combat::ActiveCombat,
+) -> Vec<GameEvent> {
+    if combat.sub_phase != combat::CombatSubPhase::BattleOver {
+        store_active_combat(state, combat);
+        return Vec::new();
+    }
+
+    let events = combat::finalize_battle(state, map, &combat);
+    if let PhaseState::Combat(ref mut cs) = state.phase_state {
+        cs.resolved_battles.push(combat.location);
+        cs.current_battle = None;
+        cs.active_combat = None;
+    }
+    events
+}
+
 /// Store active combat back into phase state.
 fn store_active_combat(state: &mut GameState, combat: combat::ActiveCombat) {
     if let PhaseState::Combat(ref mut cs) = state.phase_state {
@@ -14,7 +14,7 @@ use crate::error::EngineError;
 use crate::movement;
 use crate::state::GameState;
 use crate::territory::{FacilityType, TerritoryId};
-use crate::unit::{get_unit_stats, UnitId, UnitType};
+use crate::unit::{UnitId, UnitType};
 
 /// The target of a bombing raid.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS)]
@@ -60,7 +60,7 @@ pub fn resolve_escort_interceptor(
                 let roll = rng.roll_d6();
                 escort_rolls.push(roll);
                 // Escorts hit on 1 (or use normal attack value)
-                let stats = get_unit_stats(unit.unit_type);
+                let stats = state.unit_stats(unit.unit_type);
                 if roll <= stats.attack {
                     escort_hits += 1;
                 }
@@ -76,7 +76,7 @@ pub fn resolve_escort_interceptor(
             if unit.unit_type == UnitType::Fighter {
                 let roll = rng.roll_d6();
                 interceptor_rolls.push(roll);
-                let stats = get_unit_stats(unit.unit_type);
+                let stats = state.unit_stats(unit.unit_type);
                 if roll <= stats.defense {
                     interceptor_hits += 1;
                 }
@@ -216,13 +216,14 @@ pub fn resolve_bombing_raid(
     }
 
     // Apply escort casualties (from interceptor hits) - escort fighters lost
-    // Note: bomber casualties from interceptors also possible, but simplified
+    // first, then bombers once the escorts are gone
     let mut bombers_lost_from_interceptors = Vec::new();
     let mut remaining_escorts = escorts.clone();
+    let mut remaining_bombers = bombers.clone();
     for _ in 0..interceptor_hits {
-        if let Some(eid) = remaining_escorts.pop() {
-            movement::remove_unit(state, eid);
-            bombers_lost_from_interceptors.push(eid);
+        if let Some(uid) = remaining_escorts.pop().or_else(|| remaining_bombers.pop()) {
+            movement::remove_unit(state, uid);
+            bombers_lost_from_interceptors.push(uid);
         }
     }
 
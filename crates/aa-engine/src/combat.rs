@@ -7,18 +7,22 @@
 //! - Combat pairing bonuses (infantry+artillery, tac bomber+tank/fighter)
 //! - Multi-hit units (battleships, carriers)
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 use crate::action::GameEvent;
-use crate::dice::DeterministicRng;
+use crate::bombing;
+use crate::data::GameMap;
+use crate::dice::{self, DeterministicRng, RngMode};
 use crate::error::EngineError;
 use crate::movement;
 use crate::phase::PhaseState;
 use crate::power::Power;
 use crate::state::GameState;
-use crate::territory::{RegionId, TerritoryId};
-use crate::unit::{get_unit_stats, UnitDomain, UnitId, UnitInstance, UnitType};
+use crate::territory::{Facility, FacilityType, RegionId, SeaZoneId, TerritoryId};
+use crate::unit::{SpecialAbility, UnitDomain, UnitId, UnitInstance, UnitType};
 
 /// Sub-phase within a single battle.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS)]
@@ -76,18 +80,46 @@ pub struct ActiveCombat {
     pub pending_attacker_hits: u32,
     /// Hits pending allocation to the attacker.
     pub pending_defender_hits: u32,
+    /// Portion of `pending_attacker_hits` scored by submarines (cannot hit air).
+    #[serde(default)]
+    pub pending_attacker_sub_hits: u32,
+    /// Portion of `pending_defender_hits` scored by submarines (cannot hit air).
+    #[serde(default)]
+    pub pending_defender_sub_hits: u32,
     /// Whether this is an amphibious assault.
     pub is_amphibious: bool,
     /// Units eligible for shore bombardment (by ID).
     pub bombardment_units: Vec<UnitId>,
     /// Dice results from the last roll (for UI display).
     pub last_roll: Vec<u8>,
+    /// Hits scored by the last roll.
+    #[serde(default)]
+    pub last_roll_hits: u32,
     /// Regions the attacker can retreat to.
     pub retreat_options: Vec<RegionId>,
     /// Whether enemy has a destroyer (cancels sub abilities).
     pub enemy_has_destroyer: bool,
     /// Units that have been submerged this battle.
     pub submerged_units: Vec<UnitId>,
+    /// Dice consumed from this round's per-battle stream.
+    #[serde(default)]
+    pub round_dice_rolled: u64,
+    /// The battle was cut off at the round limit without a winner.
+    #[serde(default)]
+    pub stalemate: bool,
+    /// Aircraft whose carrier was sunk with no room on another carrier.
+    /// They fight on but are lost when the battle ends.
+    #[serde(default)]
+    pub orphaned_aircraft: Vec<UnitId>,
+    /// Dice rolled by either side over the whole battle.
+    #[serde(default)]
+    pub dice_rolled: u64,
+    /// Attacking units destroyed so far, by type.
+    #[serde(default)]
+    pub attacker_losses: Vec<UnitType>,
+    /// Defending units destroyed so far, by type.
+    #[serde(default)]
+    pub defender_losses: Vec<UnitType>,
 }
 
 impl ActiveCombat {
@@ -109,20 +141,222 @@ impl ActiveCombat {
             sub_phase: CombatSubPhase::AAFire,
             pending_attacker_hits: 0,
             pending_defender_hits: 0,
+            pending_attacker_sub_hits: 0,
+            pending_defender_sub_hits: 0,
             is_amphibious: false,
             bombardment_units: Vec::new(),
             last_roll: Vec::new(),
+            last_roll_hits: 0,
             retreat_options: Vec::new(),
             enemy_has_destroyer: false,
             submerged_units: Vec::new(),
+            round_dice_rolled: 0,
+            stalemate: false,
+            orphaned_aircraft: Vec::new(),
+            dice_rolled: 0,
+            attacker_losses: Vec::new(),
+            defender_losses: Vec::new(),
+        }
+    }
+}
+
+/// Summary of a resolved battle, kept in `GameState::combat_history` for
+/// post-game statistics.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BattleRecord {
+    /// Game turn the battle was fought on.
+    pub turn: u32,
+    pub location: RegionId,
+    pub attacker: Power,
+    pub defender: Power,
+    /// Rounds fought, counting the last one.
+    pub rounds: u32,
+    /// Dice rolled by both sides, AA fire and bombardment included.
+    pub dice_rolled: u64,
+    pub attacker_losses: Vec<UnitType>,
+    pub defender_losses: Vec<UnitType>,
+    pub attacker_won: bool,
+    pub stalemate: bool,
+}
+
+/// Compact outcome of a single combat action, attached to its
+/// `ActionResult` so clients can update without re-reading `ActiveCombat`.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CombatStep {
+    pub location: RegionId,
+    pub round: u32,
+    /// The sub-phase the battle is now waiting on.
+    pub sub_phase: CombatSubPhase,
+    /// Dice rolled by this action; empty if it rolled none.
+    pub rolls: Vec<u8>,
+    /// Hits scored by `rolls`.
+    pub hits: u32,
+    pub pending_attacker_hits: u32,
+    pub pending_defender_hits: u32,
+    pub battle_over: bool,
+    /// Units taken as casualties by this action.
+    pub casualties: Vec<UnitId>,
+}
+
+impl CombatStep {
+    /// Summarize `combat` after an action that rolled no dice.
+    pub fn new(combat: &ActiveCombat) -> Self {
+        CombatStep {
+            location: combat.location,
+            round: combat.round,
+            sub_phase: combat.sub_phase.clone(),
+            rolls: Vec::new(),
+            hits: 0,
+            pending_attacker_hits: combat.pending_attacker_hits,
+            pending_defender_hits: combat.pending_defender_hits,
+            battle_over: combat.sub_phase == CombatSubPhase::BattleOver,
+            casualties: Vec::new(),
+        }
+    }
+
+    /// Summarize `combat` after a roll, including its dice.
+    pub fn after_roll(combat: &ActiveCombat) -> Self {
+        CombatStep {
+            rolls: combat.last_roll.clone(),
+            hits: combat.last_roll_hits,
+            ..Self::new(combat)
+        }
+    }
+}
+
+/// What a battle UI needs to know about a region, read as an attack there
+/// by the power to move.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RegionCombatContext {
+    pub region: RegionId,
+    /// `None` for sea zones and unowned territories.
+    pub owner: Option<Power>,
+    /// Every unit in the region, by owner.
+    pub units: BTreeMap<Power, Vec<UnitInstance>>,
+    /// A territory on the sea, or a sea zone off a coast.
+    pub coastal: bool,
+    /// The mover's ships in adjoining sea zones that could bombard an
+    /// amphibious assault here.
+    pub bombardment_ships: Vec<UnitId>,
+    /// Whether the defenders have AAA to fire on attacking aircraft.
+    pub has_aaa: bool,
+    /// For a sea zone, the coastal territories where the defending side
+    /// holds an operational air base with fighters or tactical bombers to
+    /// scramble. Always empty for land, where scrambling does not happen.
+    pub scramble_bases: Vec<TerritoryId>,
+}
+
+/// The combat context of `region`, or `None` if it is not on the map.
+pub fn region_combat_context(state: &GameState, map: &GameMap, region: RegionId) -> Option<RegionCombatContext> {
+    let attacker = state.current_power;
+    let defending = |power: Power| state.political.are_at_war(attacker, power);
+
+    let (owner, units, neighbors, zones): (_, _, &[TerritoryId], &[SeaZoneId]) = match region {
+        RegionId::Land(tid) => {
+            let territory = state.territories.get(tid as usize)?;
+            (territory.owner, &territory.units, map.land_neighbors(tid), map.coastal_zones(tid))
         }
+        RegionId::Sea(zone) => {
+            let sea_zone = state.sea_zones.get(zone as usize)?;
+            (None, &sea_zone.units, map.coastal_territories(zone), &[])
+        }
+    };
+
+    let mut by_owner: BTreeMap<Power, Vec<UnitInstance>> = BTreeMap::new();
+    for unit in units {
+        by_owner.entry(unit.owner).or_default().push(unit.clone());
     }
+
+    let coastal = match region {
+        RegionId::Land(_) => !zones.is_empty(),
+        RegionId::Sea(_) => !neighbors.is_empty(),
+    };
+
+    let bombardment_ships = zones
+        .iter()
+        .flat_map(|&zone| &state.sea_zones[zone as usize].units)
+        .filter(|u| u.owner == attacker && state.unit_stats(u.unit_type).can_bombard)
+        .map(|u| u.id)
+        .collect();
+
+    let has_aaa = units.iter().any(|u| u.unit_type == UnitType::AAA && defending(u.owner));
+
+    let scramble_from: &[TerritoryId] = match region {
+        RegionId::Land(_) => &[],
+        RegionId::Sea(_) => neighbors,
+    };
+    let scramble_bases = scramble_from
+        .iter()
+        .copied()
+        .filter(|&tid| {
+            let territory = &state.territories[tid as usize];
+            let held = territory.owner.is_some_and(defending);
+            let base = territory
+                .facilities
+                .iter()
+                .any(|f| f.facility_type == FacilityType::AirBase && f.operational);
+            let aircraft = territory
+                .units
+                .iter()
+                .any(|u| defending(u.owner) && is_carrier_aircraft(u.unit_type));
+            held && base && aircraft
+        })
+        .collect();
+
+    Some(RegionCombatContext {
+        region,
+        owner,
+        units: by_owner,
+        coastal,
+        bombardment_ships,
+        has_aaa,
+        scramble_bases,
+    })
 }
 
 // =========================================================================
 // Combat Resolution Functions
 // =========================================================================
 
+/// Open the dice stream for the combat's current round.
+fn combat_rng(state: &GameState, combat: &ActiveCombat) -> DeterministicRng {
+    match state.rng_mode {
+        RngMode::SharedCounter => DeterministicRng::new(state.rng_seed, state.rng_counter),
+        RngMode::PerBattle => {
+            let key = dice::BattleKey {
+                turn: state.turn_number,
+                attacker: combat.attacker,
+                location: combat.location,
+                prior_battles: state
+                    .combat_history
+                    .iter()
+                    .filter(|record| record.location == combat.location)
+                    .count() as u32,
+            };
+            DeterministicRng::new(dice::battle_seed(state.rng_seed, key, combat.round), combat.round_dice_rolled)
+        }
+    }
+}
+
+/// Record the dice consumed from a combat stream.
+fn record_combat_rolls(state: &mut GameState, combat: &mut ActiveCombat, rng: &DeterministicRng) {
+    match state.rng_mode {
+        RngMode::SharedCounter => {
+            combat.dice_rolled += rng.counter() - state.rng_counter;
+            state.rng_counter = rng.counter();
+        }
+        RngMode::PerBattle => {
+            combat.dice_rolled += rng.counter() - combat.round_dice_rolled;
+            // Keep the shared counter as a running total of dice rolled
+            state.rng_counter += rng.counter() - combat.round_dice_rolled;
+            combat.round_dice_rolled = rng.counter();
+        }
+    }
+}
+
 /// Start a battle at the given location. Sets up the ActiveCombat and determines
 /// the initial sub-phase.
 pub fn start_battle(
@@ -144,8 +378,26 @@ pub fn start_battle(
     combat.enemy_has_destroyer = check_for_destroyers(state, &defender_units) ||
                                   check_for_destroyers(state, &attacker_units);
 
+    // Units offloaded from transports stop being cargo once their assault
+    // begins; they can't retreat back to sea
+    if let RegionId::Land(tid) = location {
+        combat.is_amphibious = release_landed_cargo(state, tid, attacker);
+    }
+
+    // Defending aircraft at sea are on their carriers' decks; attacking
+    // aircraft flew in and are airborne
+    if let RegionId::Sea(zone) = location {
+        base_carrier_aircraft(state, zone, &defender_units);
+    }
+
     // Calculate retreat options (where attacker's units came from)
-    combat.retreat_options = calculate_retreat_options(state, location, attacker);
+    combat.retreat_options = calculate_retreat_options(state, location, &combat.attacker_units);
+
+    // Transports with nothing to defend them are sunk without a fight
+    if only_defenseless_transports(state, &combat) {
+        sink_defenseless_transports(state, &mut combat);
+        return Ok(combat);
+    }
 
     // Determine initial sub-phase
     combat.sub_phase = determine_initial_sub_phase(state, &combat);
@@ -153,22 +405,218 @@ pub fn start_battle(
     Ok(combat)
 }
 
-/// Gather attacker and defender units at a location.
+/// Whether every defender at sea is a transport and the attacker brought
+/// something that can fire on them.
+fn only_defenseless_transports(state: &GameState, combat: &ActiveCombat) -> bool {
+    let unit_stats = |uid: &UnitId| movement::find_unit(state, *uid).map(|(_, u)| state.unit_stats(u.unit_type));
+    matches!(combat.location, RegionId::Sea(_))
+        && !combat.defender_units.is_empty()
+        && combat.defender_units.iter().all(|uid| {
+            unit_stats(uid).is_some_and(|s| s.special_abilities.contains(&SpecialAbility::DefenselessTransport))
+        })
+        && combat.attacker_units.iter().any(|uid| unit_stats(uid).is_some_and(|s| s.attack > 0))
+}
+
+/// Sink the defending transports, and anything aboard, and end the battle
+/// before any dice are rolled.
+fn sink_defenseless_transports(state: &mut GameState, combat: &mut ActiveCombat) {
+    for uid in std::mem::take(&mut combat.defender_units) {
+        if let Some((_, transport)) = movement::remove_unit(state, uid) {
+            combat.defender_losses.push(transport.unit_type);
+            let RegionId::Sea(zone) = combat.location else { continue };
+            for cargo_id in cargo_still_aboard(state, zone, &transport.cargo) {
+                if let Some((_, cargo)) = movement::remove_unit(state, cargo_id) {
+                    combat.defender_losses.push(cargo.unit_type);
+                }
+            }
+        }
+    }
+    combat.sub_phase = CombatSubPhase::BattleOver;
+}
+
+fn is_carrier_aircraft(unit_type: UnitType) -> bool {
+    matches!(unit_type, UnitType::Fighter | UnitType::TacticalBomber)
+}
+
+/// Record which of `units` in `zone` sit on which carrier: each carrier's
+/// `cargo` lists the aircraft on its deck, filled from the owner's own
+/// aircraft first and then allied ones, up to its capacity.
+fn base_carrier_aircraft(state: &mut GameState, zone: SeaZoneId, units: &[UnitId]) {
+    let deck_size = state.unit_stats(UnitType::Carrier).can_carry_air as usize;
+    let zone_units = &mut state.sea_zones[zone as usize].units;
+    let aircraft: Vec<(UnitId, Power)> = zone_units
+        .iter()
+        .filter(|u| units.contains(&u.id) && is_carrier_aircraft(u.unit_type))
+        .map(|u| (u.id, u.owner))
+        .collect();
+    let mut carriers: Vec<&mut UnitInstance> = zone_units
+        .iter_mut()
+        .filter(|u| units.contains(&u.id) && u.unit_type == UnitType::Carrier)
+        .collect();
+    for carrier in carriers.iter_mut() {
+        carrier.cargo.clear();
+    }
+
+    for own_first in [true, false] {
+        for &(id, owner) in &aircraft {
+            if carriers.iter().any(|c| c.cargo.contains(&id)) {
+                continue;
+            }
+            let deck = carriers.iter_mut().find(|c| {
+                (c.owner == owner) == own_first
+                    && c.cargo.len() < deck_size
+            });
+            if let Some(carrier) = deck {
+                carrier.cargo.push(id);
+            }
+        }
+    }
+}
+
+/// Move the aircraft from a sunk carrier onto other carriers of the same
+/// side in `zone` with free deck space. Returns the ones left without a deck;
+/// aircraft no longer in `zone` were not aboard and are left alone.
+fn rehome_carrier_aircraft(
+    state: &mut GameState,
+    zone: SeaZoneId,
+    aircraft: &[UnitId],
+    side: &[UnitId],
+) -> Vec<UnitId> {
+    let deck_size = state.unit_stats(UnitType::Carrier).can_carry_air as usize;
+    let mut orphans = Vec::new();
+    for &id in aircraft {
+        if !state.sea_zones[zone as usize].units.iter().any(|u| u.id == id) {
+            continue;
+        }
+        let deck = state.sea_zones[zone as usize].units.iter_mut().find(|c| {
+            c.unit_type == UnitType::Carrier
+                && side.contains(&c.id)
+                && c.cargo.len() < deck_size
+        });
+        match deck {
+            Some(carrier) => carrier.cargo.push(id),
+            None => orphans.push(id),
+        }
+    }
+    orphans
+}
+
+/// The unresolved naval battle that an amphibious assault on `territory`
+/// depends on, if any. Units offloaded for an assault stay in their
+/// transport's `cargo` until the land battle begins, which is what links the
+/// two battles: the sea zone must be fought over first.
+pub fn blocking_naval_battle(state: &GameState, territory: TerritoryId) -> Option<SeaZoneId> {
+    let PhaseState::Combat(ref cs) = state.phase_state else {
+        return None;
+    };
+    let landed = &state.territories[territory as usize].units;
+    cs.pending_battles
+        .iter()
+        .chain(cs.current_battle.iter())
+        .find_map(|&region| {
+            let RegionId::Sea(zone) = region else {
+                return None;
+            };
+            let carries_assault = state.sea_zones[zone as usize].units.iter().any(|u| {
+                u.owner == state.current_power
+                    && u.cargo.iter().any(|id| landed.iter().any(|l| l.id == *id))
+            });
+            carries_assault.then_some(zone)
+        })
+}
+
+/// The units in a transport's `cargo` that go down with it: those in its
+/// sea zone, or landed for an assault that has not begun. Units already
+/// ashore are not aboard whatever the cargo list says.
+fn cargo_still_aboard(state: &GameState, zone: SeaZoneId, cargo: &[UnitId]) -> Vec<UnitId> {
+    let assault_pending = |region: &RegionId| match &state.phase_state {
+        PhaseState::Combat(cs) => cs.pending_battles.contains(region),
+        _ => false,
+    };
+    cargo
+        .iter()
+        .copied()
+        .filter(|&id| match movement::find_unit(state, id) {
+            Some((RegionId::Sea(at), _)) => at == zone,
+            Some((region, _)) => assault_pending(&region),
+            None => false,
+        })
+        .collect()
+}
+
+/// Drop `attacker`'s transport cargo entries for units now in `territory`.
+/// Returns whether any were found, i.e. whether the battle is amphibious.
+fn release_landed_cargo(state: &mut GameState, territory: TerritoryId, attacker: Power) -> bool {
+    let landed: Vec<UnitId> = state.territories[territory as usize]
+        .units
+        .iter()
+        .filter(|u| u.owner == attacker)
+        .map(|u| u.id)
+        .collect();
+    let mut released = false;
+    for zone in state.sea_zones.iter_mut() {
+        for unit in zone.units.iter_mut().filter(|u| u.owner == attacker) {
+            let before = unit.cargo.len();
+            unit.cargo.retain(|id| !landed.contains(id));
+            released |= unit.cargo.len() != before;
+        }
+    }
+    released
+}
+
+/// Drop pending land battles the attacker no longer has units for, which
+/// happens when the transports carrying an amphibious assault are sunk.
+fn cancel_stranded_assaults(state: &mut GameState) -> Vec<GameEvent> {
+    let attacker = state.current_power;
+    let territories = &state.territories;
+    let PhaseState::Combat(ref mut cs) = state.phase_state else {
+        return Vec::new();
+    };
+    let mut events = Vec::new();
+    cs.pending_battles.retain(|&region| {
+        let RegionId::Land(tid) = region else {
+            return true;
+        };
+        let has_attackers = territories[tid as usize]
+            .units
+            .iter()
+            .any(|u| u.owner == attacker && u.moved_this_turn);
+        if !has_attackers {
+            events.push(GameEvent::AmphibiousAssaultCancelled { territory_id: tid });
+        }
+        has_attackers
+    });
+    events
+}
+
+/// Gather attacker and defender units at a location. Only the attacker's own
+/// units attack and only units at war with the attacker defend; anyone else
+/// present (allies of the attacker, or powers not at war with it) is a
+/// bystander and takes no part in the battle.
 fn gather_combatants(
     state: &GameState,
     location: RegionId,
     attacker: Power,
 ) -> Result<(Vec<UnitId>, Vec<UnitId>, Power), EngineError> {
     let units = get_units_at(state, location);
+    let enemies = state.political.combat_enemies(attacker);
+    let raided = match (&state.phase_state, location) {
+        (PhaseState::Combat(cs), RegionId::Land(tid)) => cs.bombed_territories.contains(&tid),
+        _ => false,
+    };
 
     let mut attacker_units = Vec::new();
     let mut defender_units = Vec::new();
     let mut defender_power: Option<Power> = None;
 
     for unit in &units {
-        if unit.owner == attacker || state.political.are_friendly(attacker, unit.owner) {
-            attacker_units.push(unit.id);
-        } else if state.political.are_at_war(attacker, unit.owner) {
+        if unit.owner == attacker {
+            // Bombers that raided the factory here sit out the battle
+            let raider = raided && unit.unit_type == UnitType::StrategicBomber && unit.moved_this_turn;
+            if !raider {
+                attacker_units.push(unit.id);
+            }
+        } else if enemies.contains(&unit.owner) {
             if defender_power.is_none() {
                 defender_power = Some(unit.owner);
             }
@@ -203,70 +651,151 @@ fn check_for_destroyers(state: &GameState, unit_ids: &[UnitId]) -> bool {
     false
 }
 
-/// Calculate retreat options for the attacker.
+/// Calculate retreat options for the attacker: the regions its attacking
+/// units entered the battle from this turn (read from the combat moves carried
+/// into `CombatState`), as long as they are still friendly. A land region must also
+/// have been friendly when combat movement began, or been captured by the
+/// attacker since. Units that came by sea into a land battle have nowhere to
+/// retreat to.
 fn calculate_retreat_options(
     state: &GameState,
     location: RegionId,
-    attacker: Power,
+    attacker_units: &[UnitId],
 ) -> Vec<RegionId> {
-    // Attacker can retreat to any region from which their units came.
-    // For simplicity, find friendly regions adjacent to the battle.
-    let options = Vec::new();
+    let mut options = Vec::new();
+    let battle_pending = |region: &RegionId| match &state.phase_state {
+        PhaseState::Combat(cs) => cs.pending_battles.contains(region),
+        _ => false,
+    };
 
-    match location {
-        RegionId::Land(tid) => {
-            // Look at combat move state for origins
-            if let PhaseState::Combat(ref cs) = state.phase_state {
-                // Fall through to adjacent friendly territories
-                let _ = cs;
+    let moves = match &state.phase_state {
+        PhaseState::Combat(cs) => cs.combat_moves.as_slice(),
+        _ => &[],
+    };
+    for planned in moves {
+        if planned.to != location || !attacker_units.contains(&planned.unit_id) {
+            continue;
+        }
+        let Some(&from) = planned.path.iter().rev().nth(1) else {
+            continue;
+        };
+        let usable = match (location, from) {
+            (RegionId::Land(_), RegionId::Land(tid)) => {
+                movement::is_friendly_territory(state, tid, state.current_power)
+                    && held_before_combat(state, tid)
             }
-            // For land battles, adjacent friendly territories the attacker controls
-            // In a real implementation we'd track where units came from.
-            // For now, use adjacent friendly territories.
-            for (i, t) in state.territories.iter().enumerate() {
-                let tid2 = i as TerritoryId;
-                if tid2 != tid && movement::is_friendly_territory(state, tid2, attacker) {
-                    if t.units.iter().any(|u| u.owner == attacker) || movement::is_friendly_territory(state, tid2, attacker) {
-                        // Check adjacency via simple index comparison (we don't have map here)
-                        // We'll store retreat options when starting the battle
-                    }
-                }
+            (RegionId::Sea(_), RegionId::Sea(_)) => {
+                !movement::region_has_units_at_war(state, from, state.current_power)
             }
-        }
-        RegionId::Sea(_sid) => {
-            // Naval: retreat to adjacent sea zone with no enemies
+            _ => false,
+        };
+        // Never into a battle still to be fought
+        if usable && !battle_pending(&from) && !options.contains(&from) {
+            options.push(from);
         }
     }
 
     options
 }
 
+/// Whether the current power's side held `tid` when combat movement began,
+/// or the current power has captured it this turn. Assumed true when no
+/// snapshot was taken.
+fn held_before_combat(state: &GameState, tid: TerritoryId) -> bool {
+    let power = state.current_power;
+    let territory = &state.territories[tid as usize];
+    if territory.just_captured && territory.owner == Some(power) {
+        return true;
+    }
+    let PhaseState::Combat(ref cs) = state.phase_state else {
+        return true;
+    };
+    match cs.owners_before_combat.get(tid as usize) {
+        Some(owner) => owner.is_some_and(|o| state.political.are_friendly(power, o)),
+        None => true,
+    }
+}
+
 /// Determine what the first sub-phase should be.
 fn determine_initial_sub_phase(state: &GameState, combat: &ActiveCombat) -> CombatSubPhase {
-    // Check for AA guns in land combat
-    if let RegionId::Land(_tid) = combat.location {
-        let has_aaa = combat.defender_units.iter().any(|&uid| {
-            movement::find_unit(state, uid)
-                .map(|(_, u)| u.unit_type == UnitType::AAA)
-                .unwrap_or(false)
-        });
-        let has_air_attackers = combat.attacker_units.iter().any(|&uid| {
-            movement::find_unit(state, uid)
-                .map(|(_, u)| get_unit_stats(u.unit_type).domain == UnitDomain::Air)
-                .unwrap_or(false)
-        });
-        if has_aaa && has_air_attackers {
-            return CombatSubPhase::AAFire;
-        }
-    }
+    opening_step(state, combat, CombatSubPhase::AAFire)
+}
+
+/// The opening fire that comes before the main rolls, in order. AA fire and
+/// bombardment only open the first round.
+const OPENING_FIRE: [CombatSubPhase; 4] = [
+    CombatSubPhase::AAFire,
+    CombatSubPhase::ShoreBombardment,
+    CombatSubPhase::AttackerSubmarineStrike,
+    CombatSubPhase::DefenderSubmarineStrike,
+];
+
+/// The first step of the opening fire, from `from` on, that applies to this
+/// battle, or the main attack roll when none does.
+fn opening_step(state: &GameState, combat: &ActiveCombat, from: CombatSubPhase) -> CombatSubPhase {
+    OPENING_FIRE
+        .iter()
+        .skip_while(|&step| *step != from)
+        .find(|&step| opening_step_applies(state, combat, step))
+        .cloned()
+        .unwrap_or(CombatSubPhase::AttackerRolls)
+}
 
-    // Check for submarine surprise strike
-    if has_submarines_and_no_destroyer(state, combat, true) ||
-       has_submarines_and_no_destroyer(state, combat, false) {
-        return CombatSubPhase::AttackerSubmarineStrike;
+/// Whether the units for an opening-fire step are in this battle.
+fn opening_step_applies(state: &GameState, combat: &ActiveCombat, step: &CombatSubPhase) -> bool {
+    match step {
+        CombatSubPhase::AAFire => {
+            let has_aaa = combat.defender_units.iter().any(|&uid| {
+                movement::find_unit(state, uid)
+                    .map(|(_, u)| u.unit_type == UnitType::AAA)
+                    .unwrap_or(false)
+            });
+            let has_air_attackers = combat.attacker_units.iter().any(|&uid| {
+                movement::find_unit(state, uid)
+                    .map(|(_, u)| state.unit_stats(u.unit_type).domain == UnitDomain::Air)
+                    .unwrap_or(false)
+            });
+            matches!(combat.location, RegionId::Land(_)) && has_aaa && has_air_attackers
+        }
+        CombatSubPhase::ShoreBombardment => combat.is_amphibious && !combat.bombardment_units.is_empty(),
+        CombatSubPhase::AttackerSubmarineStrike => has_submarines_and_no_destroyer(state, combat, true),
+        CombatSubPhase::DefenderSubmarineStrike => has_submarines_and_no_destroyer(state, combat, false),
+        _ => false,
     }
+}
 
-    CombatSubPhase::AttackerRolls
+/// Move a battle on from the step it has just played to the next one that
+/// applies. A roll with hits goes to the matching casualty step and one
+/// without skips it; opening fire is only fought when the units for it are
+/// present; a round with losses on both sides ends once both are taken.
+/// Every resolver advances through here so a round's order lives in one place.
+pub fn advance_sub_phase(state: &GameState, combat: &mut ActiveCombat) {
+    use CombatSubPhase::*;
+    // Hits each side has scored and the other has still to take
+    let hits_on_defender = combat.pending_attacker_hits > 0;
+    let hits_on_attacker = combat.pending_defender_hits > 0;
+
+    combat.sub_phase = match combat.sub_phase {
+        AAFire if hits_on_attacker => AAFireCasualties,
+        ShoreBombardment if hits_on_defender => ShoreBombardmentCasualties,
+        AttackerSubmarineStrike if hits_on_defender => DefenderSubmarineStrikeCasualties,
+        DefenderSubmarineStrike if hits_on_attacker => AttackerSubmarineStrikeCasualties,
+        AttackerRolls => DefenderRolls,
+        DefenderRolls if hits_on_defender => DefenderSelectsCasualties,
+        DefenderRolls | DefenderSelectsCasualties if hits_on_attacker => AttackerSelectsCasualties,
+        AttackerDecision => return continue_combat_round(state, combat),
+        BattleOver => BattleOver,
+        _ if check_battle_end(combat) => BattleOver,
+        AAFire | AAFireCasualties => opening_step(state, combat, ShoreBombardment),
+        ShoreBombardment | ShoreBombardmentCasualties => opening_step(state, combat, AttackerSubmarineStrike),
+        AttackerSubmarineStrike | DefenderSubmarineStrikeCasualties => {
+            opening_step(state, combat, DefenderSubmarineStrike)
+        }
+        DefenderSubmarineStrike | AttackerSubmarineStrikeCasualties => AttackerRolls,
+        DefenderRolls | DefenderSelectsCasualties | AttackerSelectsCasualties => {
+            return enter_attacker_decision(state, combat)
+        }
+    };
 }
 
 /// Check if one side has subs and the other lacks destroyers.
@@ -293,11 +822,12 @@ fn has_submarines_and_no_destroyer(state: &GameState, combat: &ActiveCombat, att
 
 /// Process AA fire: AAA units fire at air units.
 /// Each AAA fires up to 3 shots at attacking air units, hitting on 1.
+/// The hits are the defender's, taken by the attacker.
 pub fn resolve_aa_fire(
     state: &mut GameState,
     combat: &mut ActiveCombat,
 ) -> Vec<u8> {
-    let mut rng = DeterministicRng::new(state.rng_seed, state.rng_counter);
+    let mut rng = combat_rng(state, combat);
 
     // Count AAA units among defenders
     let aaa_count = combat.defender_units.iter().filter(|&&uid| {
@@ -309,7 +839,7 @@ pub fn resolve_aa_fire(
     // Count attacking air units
     let air_count = combat.attacker_units.iter().filter(|&&uid| {
         movement::find_unit(state, uid)
-            .map(|(_, u)| get_unit_stats(u.unit_type).domain == UnitDomain::Air)
+            .map(|(_, u)| state.unit_stats(u.unit_type).domain == UnitDomain::Air)
             .unwrap_or(false)
     }).count();
 
@@ -318,43 +848,27 @@ pub fn resolve_aa_fire(
     let rolls = rng.roll_multiple_d6(max_shots);
     let hits = rolls.iter().filter(|&&r| r == 1).count() as u32;
 
-    state.rng_counter = rng.counter();
-    combat.pending_attacker_hits += hits;
+    record_combat_rolls(state, combat, &rng);
+    combat.pending_defender_hits += hits;
     combat.last_roll = rolls.clone();
-
-    if hits > 0 {
-        combat.sub_phase = CombatSubPhase::AAFireCasualties;
-    } else {
-        // Skip to next phase
-        combat.sub_phase = advance_past_aa(state, combat);
-    }
+    combat.last_roll_hits = hits;
+    advance_sub_phase(state, combat);
 
     rolls
 }
 
-/// Advance past AA fire to the next applicable sub-phase.
-fn advance_past_aa(state: &GameState, combat: &ActiveCombat) -> CombatSubPhase {
-    if combat.is_amphibious && !combat.bombardment_units.is_empty() {
-        return CombatSubPhase::ShoreBombardment;
-    }
-    if has_submarines_and_no_destroyer(state, combat, true) {
-        return CombatSubPhase::AttackerSubmarineStrike;
-    }
-    CombatSubPhase::AttackerRolls
-}
-
 /// Resolve shore bombardment.
 pub fn resolve_shore_bombardment(
     state: &mut GameState,
     combat: &mut ActiveCombat,
 ) -> Vec<u8> {
-    let mut rng = DeterministicRng::new(state.rng_seed, state.rng_counter);
+    let mut rng = combat_rng(state, combat);
     let mut all_rolls = Vec::new();
     let mut hits = 0u32;
 
     for &uid in &combat.bombardment_units {
         if let Some((_, unit)) = movement::find_unit(state, uid) {
-            let stats = get_unit_stats(unit.unit_type);
+            let stats = state.unit_stats(unit.unit_type);
             if stats.can_bombard {
                 let roll = rng.roll_d6();
                 all_rolls.push(roll);
@@ -365,19 +879,11 @@ pub fn resolve_shore_bombardment(
         }
     }
 
-    state.rng_counter = rng.counter();
-    combat.pending_defender_hits = hits;
+    record_combat_rolls(state, combat, &rng);
+    combat.pending_attacker_hits += hits;
     combat.last_roll = all_rolls.clone();
-
-    if hits > 0 {
-        combat.sub_phase = CombatSubPhase::ShoreBombardmentCasualties;
-    } else {
-        combat.sub_phase = if has_submarines_and_no_destroyer(state, combat, true) {
-            CombatSubPhase::AttackerSubmarineStrike
-        } else {
-            CombatSubPhase::AttackerRolls
-        };
-    }
+    combat.last_roll_hits = hits;
+    advance_sub_phase(state, combat);
 
     all_rolls
 }
@@ -388,7 +894,7 @@ pub fn resolve_submarine_strike(
     combat: &mut ActiveCombat,
     attacker_side: bool,
 ) -> Vec<u8> {
-    let mut rng = DeterministicRng::new(state.rng_seed, state.rng_counter);
+    let mut rng = combat_rng(state, combat);
     let sub_units = if attacker_side {
         &combat.attacker_units
     } else {
@@ -401,8 +907,12 @@ pub fn resolve_submarine_strike(
     for &uid in sub_units {
         if let Some((_, unit)) = movement::find_unit(state, uid) {
             if unit.unit_type == UnitType::Submarine {
-                let stats = get_unit_stats(unit.unit_type);
-                let value = if attacker_side { stats.attack } else { stats.defense };
+                let value = if attacker_side {
+                    let attack = state.unit_stats(unit.unit_type).attack;
+                    attack + veteran_bonus(state, unit, attack)
+                } else {
+                    effective_defense_value(state, unit)
+                };
                 let roll = rng.roll_d6();
                 rolls.push(roll);
                 if roll <= value {
@@ -412,33 +922,41 @@ pub fn resolve_submarine_strike(
         }
     }
 
-    state.rng_counter = rng.counter();
+    record_combat_rolls(state, combat, &rng);
 
     if attacker_side {
         combat.pending_attacker_hits += hits;
-        if hits > 0 {
-            combat.sub_phase = CombatSubPhase::DefenderSubmarineStrikeCasualties;
-        } else if has_submarines_and_no_destroyer(state, combat, false) {
-            combat.sub_phase = CombatSubPhase::DefenderSubmarineStrike;
-        } else {
-            combat.sub_phase = CombatSubPhase::AttackerRolls;
-        }
+        combat.pending_attacker_sub_hits += hits;
     } else {
         combat.pending_defender_hits += hits;
-        if hits > 0 {
-            combat.sub_phase = CombatSubPhase::AttackerSubmarineStrikeCasualties;
-        } else {
-            combat.sub_phase = CombatSubPhase::AttackerRolls;
-        }
+        combat.pending_defender_sub_hits += hits;
     }
 
     combat.last_roll = rolls.clone();
+    combat.last_roll_hits = hits;
+    advance_sub_phase(state, combat);
     rolls
 }
 
-/// Calculate attack value for a unit, including pairing bonuses.
+/// +1 for a veteran unit when the veteran rule is on. Units that never
+/// fight in a role (a carrier's attack) stay at zero.
+fn veteran_bonus(state: &GameState, unit: &UnitInstance, base: u8) -> u8 {
+    if state.settings.veteran_units && unit.veteran && base > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Calculate defense value for a unit, including the veteran bonus.
+pub fn effective_defense_value(state: &GameState, unit: &UnitInstance) -> u8 {
+    let defense = state.unit_stats(unit.unit_type).defense;
+    defense + veteran_bonus(state, unit, defense)
+}
+
+/// Calculate attack value for a unit, including pairing and veteran bonuses.
 pub fn effective_attack_value(state: &GameState, unit: &UnitInstance, friendly_units: &[UnitId]) -> u8 {
-    let stats = get_unit_stats(unit.unit_type);
+    let stats = state.unit_stats(unit.unit_type);
     let mut value = stats.attack;
 
     match unit.unit_type {
@@ -483,7 +1001,7 @@ pub fn effective_attack_value(state: &GameState, unit: &UnitInstance, friendly_u
         _ => {}
     }
 
-    value
+    value + veteran_bonus(state, unit, value)
 }
 
 /// Roll attack for all attacker units.
@@ -491,9 +1009,10 @@ pub fn resolve_attack_roll(
     state: &mut GameState,
     combat: &mut ActiveCombat,
 ) -> Vec<u8> {
-    let mut rng = DeterministicRng::new(state.rng_seed, state.rng_counter);
+    let mut rng = combat_rng(state, combat);
     let mut rolls = Vec::new();
     let mut hits = 0u32;
+    let mut sub_hits = 0u32;
 
     // Skip submarines (already fired in surprise strike if applicable) if they had surprise strike
     let skip_subs = has_submarines_and_no_destroyer(state, combat, true);
@@ -515,14 +1034,19 @@ pub fn resolve_attack_roll(
             rolls.push(roll);
             if roll <= attack_value {
                 hits += 1;
+                if unit.unit_type == UnitType::Submarine {
+                    sub_hits += 1;
+                }
             }
         }
     }
 
-    state.rng_counter = rng.counter();
+    record_combat_rolls(state, combat, &rng);
     combat.pending_attacker_hits += hits;
+    combat.pending_attacker_sub_hits += sub_hits;
     combat.last_roll = rolls.clone();
-    combat.sub_phase = CombatSubPhase::DefenderRolls;
+    combat.last_roll_hits = hits;
+    advance_sub_phase(state, combat);
 
     rolls
 }
@@ -532,9 +1056,10 @@ pub fn resolve_defense_roll(
     state: &mut GameState,
     combat: &mut ActiveCombat,
 ) -> Vec<u8> {
-    let mut rng = DeterministicRng::new(state.rng_seed, state.rng_counter);
+    let mut rng = combat_rng(state, combat);
     let mut rolls = Vec::new();
     let mut hits = 0u32;
+    let mut sub_hits = 0u32;
 
     let skip_subs = has_submarines_and_no_defender_destroyer(state, combat);
 
@@ -554,27 +1079,24 @@ pub fn resolve_defense_roll(
             if unit.unit_type == UnitType::AAA {
                 continue;
             }
-            let stats = get_unit_stats(unit.unit_type);
+            let defense_value = effective_defense_value(state, unit);
             let roll = rng.roll_d6();
             rolls.push(roll);
-            if roll <= stats.defense {
+            if roll <= defense_value {
                 hits += 1;
+                if unit.unit_type == UnitType::Submarine {
+                    sub_hits += 1;
+                }
             }
         }
     }
 
-    state.rng_counter = rng.counter();
+    record_combat_rolls(state, combat, &rng);
     combat.pending_defender_hits += hits;
+    combat.pending_defender_sub_hits += sub_hits;
     combat.last_roll = rolls.clone();
-
-    // Now both sides need to select casualties
-    if combat.pending_attacker_hits > 0 {
-        combat.sub_phase = CombatSubPhase::DefenderSelectsCasualties;
-    } else if combat.pending_defender_hits > 0 {
-        combat.sub_phase = CombatSubPhase::AttackerSelectsCasualties;
-    } else {
-        combat.sub_phase = CombatSubPhase::AttackerDecision;
-    }
+    combat.last_roll_hits = hits;
+    advance_sub_phase(state, combat);
 
     rolls
 }
@@ -584,42 +1106,54 @@ fn has_submarines_and_no_defender_destroyer(state: &GameState, combat: &ActiveCo
     has_submarines_and_no_destroyer(state, combat, false)
 }
 
-/// Apply casualties selected by a player.
-pub fn apply_casualties(
-    state: &mut GameState,
-    combat: &mut ActiveCombat,
+/// Check a casualty selection against the pending hits for one side.
+/// Hits scored by submarines cannot be assigned to air units; with `air_only`
+/// (AA fire hits) only air units may be chosen.
+pub fn validate_casualty_selection(
+    state: &GameState,
+    combat: &ActiveCombat,
     casualties: &[UnitId],
     defender_side: bool,
-) -> Result<Vec<GameEvent>, EngineError> {
-    let events = Vec::new();
-    let required_hits = if defender_side {
-        combat.pending_attacker_hits
+    air_only: bool,
+) -> Result<(), EngineError> {
+    let (required_hits, sub_hits, remaining_units) = if defender_side {
+        (combat.pending_attacker_hits, combat.pending_attacker_sub_hits, &combat.defender_units)
     } else {
-        combat.pending_defender_hits
+        (combat.pending_defender_hits, combat.pending_defender_sub_hits, &combat.attacker_units)
     };
+    let sub_hits = sub_hits.min(required_hits);
+    let other_hits = required_hits - sub_hits;
 
-    // Validate casualty count
-    let mut hits_absorbed = 0u32;
-    for &uid in casualties {
-        if let Some((_, unit)) = movement::find_unit(state, uid) {
-            let stats = get_unit_stats(unit.unit_type);
-            if stats.hit_points > 1 && unit.hits_taken == 0 {
-                // Multi-hit unit taking first hit (damaged, not destroyed)
-                hits_absorbed += 1;
-            } else {
-                hits_absorbed += 1;
-            }
+    let is_air = |uid: UnitId| {
+        movement::find_unit(state, uid)
+            .map(|(_, u)| state.unit_stats(u.unit_type).domain == UnitDomain::Air)
+            .unwrap_or(false)
+    };
+
+    if air_only {
+        if let Some(&uid) = casualties.iter().find(|&&uid| !is_air(uid)) {
+            return Err(EngineError::InvalidAction {
+                reason: format!("Unit {} is not an air unit; AA fire hits only aircraft", uid),
+            });
         }
     }
 
-    // Allow fewer casualties if not enough units remain
-    let remaining_units = if defender_side {
-        &combat.defender_units
+    // Each selected unit absorbs one hit (multi-hit units are damaged first)
+    let hits_absorbed = casualties
+        .iter()
+        .filter(|&&uid| movement::find_unit(state, uid).is_some())
+        .count() as u32;
+    let air_selected = casualties.iter().filter(|&&uid| is_air(uid)).count() as u32;
+
+    // Allow fewer casualties if not enough eligible units remain
+    let max_possible = if air_only {
+        remaining_units.iter().filter(|&&uid| is_air(uid)).count() as u32
     } else {
-        &combat.attacker_units
+        remaining_units.len() as u32
     };
-    let max_possible = remaining_units.len() as u32;
-    let expected = required_hits.min(max_possible);
+    let non_air = remaining_units.iter().filter(|&&uid| !is_air(uid)).count() as u32;
+    let sub_absorbable = sub_hits.min(non_air);
+    let expected = sub_absorbable + other_hits.min(max_possible.saturating_sub(sub_absorbable));
 
     if hits_absorbed < expected {
         return Err(EngineError::InvalidAction {
@@ -630,24 +1164,136 @@ pub fn apply_casualties(
         });
     }
 
+    if air_selected > other_hits {
+        return Err(EngineError::InvalidAction {
+            reason: format!(
+                "Submarine hits cannot be assigned to air units: at most {} air casualties allowed",
+                other_hits
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+/// Casualty ordering: (tier, cost, strength, unit ID). The ID makes it a
+/// total order, so ties between identical units always resolve to the lowest
+/// ID regardless of storage order.
+type CasualtyKey = (u8, u32, u8, UnitId);
+
+/// Suggest a casualty list for `hits` hits on one side of a battle.
+///
+/// Follows the usual loss order: free hits on undamaged multi-hit units first,
+/// then the cheapest units (weakest in the current role, then lowest ID, on
+/// ties), with transports taken last. Submarine hits are assigned to non-air units,
+/// and AA fire hits only to air units.
+/// Purely advisory; the player may submit any valid selection instead.
+pub fn suggest_casualties(
+    state: &GameState,
+    combat: &ActiveCombat,
+    defender_side: bool,
+    hits: u32,
+) -> Vec<UnitId> {
+    let (units, sub_hits) = if defender_side {
+        (&combat.defender_units, combat.pending_attacker_sub_hits)
+    } else {
+        (&combat.attacker_units, combat.pending_defender_sub_hits)
+    };
+
+    let air_only = combat.sub_phase == CombatSubPhase::AAFireCasualties;
+
+    let mut candidates: Vec<(UnitId, CasualtyKey, bool)> = units
+        .iter()
+        .filter_map(|&uid| movement::find_unit(state, uid))
+        .filter(|(_, u)| !air_only || state.unit_stats(u.unit_type).domain == UnitDomain::Air)
+        .map(|(_, u)| {
+            let stats = state.unit_stats(u.unit_type);
+            let tier = if stats.hit_points > 1 && u.hits_taken == 0 {
+                0
+            } else if u.unit_type == UnitType::Transport {
+                2
+            } else {
+                1
+            };
+            let strength = if defender_side { stats.defense } else { stats.attack };
+            (u.id, (tier, stats.cost, strength, u.id), stats.domain == UnitDomain::Air)
+        })
+        .collect();
+    candidates.sort_by_key(|&(_, key, _)| key);
+
+    let mut selected: Vec<UnitId> = candidates
+        .iter()
+        .filter(|&&(_, _, is_air)| !is_air)
+        .take(sub_hits.min(hits) as usize)
+        .map(|&(id, _, _)| id)
+        .collect();
+    let remaining = hits as usize - selected.len().min(hits as usize);
+    let rest: Vec<UnitId> = candidates
+        .iter()
+        .filter(|&&(id, _, _)| !selected.contains(&id))
+        .take(remaining)
+        .map(|&(id, _, _)| id)
+        .collect();
+    selected.extend(rest);
+    selected
+}
+
+/// Apply casualties selected by a player.
+pub fn apply_casualties(
+    state: &mut GameState,
+    combat: &mut ActiveCombat,
+    casualties: &[UnitId],
+    defender_side: bool,
+) -> Result<Vec<GameEvent>, EngineError> {
+    let events = Vec::new();
+    let air_only = combat.sub_phase == CombatSubPhase::AAFireCasualties;
+    validate_casualty_selection(state, combat, casualties, defender_side, air_only)?;
+
     // Apply casualties
     for &uid in casualties {
         let unit_info = movement::find_unit(state, uid).map(|(_, u)| (u.unit_type, u.hits_taken));
         if let Some((unit_type, hits_taken)) = unit_info {
-            let stats = get_unit_stats(unit_type);
+            let stats = state.unit_stats(unit_type);
             if stats.hit_points > 1 && hits_taken == 0 {
                 // Damage the unit (first hit on a multi-hit unit)
                 if let Some((_, unit_mut)) = movement::find_unit_mut(state, uid) {
                     unit_mut.hits_taken = 1;
                 }
             } else {
-                // Destroy the unit
-                let _ = movement::remove_unit(state, uid);
                 // Remove from combat tracking
                 if defender_side {
                     combat.defender_units.retain(|&id| id != uid);
+                    combat.defender_losses.push(unit_type);
                 } else {
                     combat.attacker_units.retain(|&id| id != uid);
+                    combat.attacker_losses.push(unit_type);
+                }
+                // Destroy the unit, along with anything still aboard it.
+                // Aircraft on a sunk carrier may move to another deck instead
+                if let Some((region, lost)) = movement::remove_unit(state, uid) {
+                    match (region, unit_type) {
+                        (RegionId::Sea(zone), UnitType::Carrier) => {
+                            let side = if defender_side {
+                                combat.defender_units.clone()
+                            } else {
+                                combat.attacker_units.clone()
+                            };
+                            let orphans = rehome_carrier_aircraft(state, zone, &lost.cargo, &side);
+                            combat.orphaned_aircraft.extend(orphans);
+                        }
+                        (RegionId::Sea(zone), _) => {
+                            for cargo_id in cargo_still_aboard(state, zone, &lost.cargo) {
+                                if let Some((_, cargo)) = movement::remove_unit(state, cargo_id) {
+                                    if defender_side {
+                                        combat.defender_losses.push(cargo.unit_type);
+                                    } else {
+                                        combat.attacker_losses.push(cargo.unit_type);
+                                    }
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
                 }
             }
         }
@@ -656,35 +1302,42 @@ pub fn apply_casualties(
     // Clear pending hits
     if defender_side {
         combat.pending_attacker_hits = 0;
+        combat.pending_attacker_sub_hits = 0;
     } else {
         combat.pending_defender_hits = 0;
+        combat.pending_defender_sub_hits = 0;
     }
 
-    // Advance sub-phase
-    if defender_side {
-        // Defender just selected casualties from attacker's hits
-        if combat.pending_defender_hits > 0 {
-            combat.sub_phase = CombatSubPhase::AttackerSelectsCasualties;
-        } else {
-            // Check if battle is over
-            if check_battle_end(combat) {
-                combat.sub_phase = CombatSubPhase::BattleOver;
-            } else {
-                combat.sub_phase = CombatSubPhase::AttackerDecision;
-            }
-        }
-    } else {
-        // Attacker just selected casualties from defender's hits
-        if check_battle_end(combat) {
-            combat.sub_phase = CombatSubPhase::BattleOver;
-        } else {
-            combat.sub_phase = CombatSubPhase::AttackerDecision;
-        }
-    }
+    advance_sub_phase(state, combat);
 
     Ok(events)
 }
 
+/// Whether the attacker has a real choice at the end of a round: a retreat
+/// destination, or a submarine that may submerge.
+fn attacker_has_decision(state: &GameState, combat: &ActiveCombat) -> bool {
+    if !combat.retreat_options.is_empty() {
+        return true;
+    }
+    let defender_has_destroyer = check_for_destroyers(state, &combat.defender_units);
+    !defender_has_destroyer
+        && combat.attacker_units.iter().any(|&uid| {
+            !combat.submerged_units.contains(&uid)
+                && movement::find_unit(state, uid)
+                    .is_some_and(|(_, u)| u.unit_type == UnitType::Submarine)
+        })
+}
+
+/// End the round at the attacker's continue/retreat/submerge decision, or go
+/// straight into the next round when the attacker has no choice to make.
+fn enter_attacker_decision(state: &GameState, combat: &mut ActiveCombat) {
+    if attacker_has_decision(state, combat) {
+        combat.sub_phase = CombatSubPhase::AttackerDecision;
+    } else {
+        continue_combat_round(state, combat);
+    }
+}
+
 /// Check if a battle should end.
 pub fn check_battle_end(combat: &ActiveCombat) -> bool {
     // Filter out submerged units
@@ -752,60 +1405,133 @@ pub fn apply_submerge(
     Ok(())
 }
 
-/// Continue to the next combat round.
+/// Continue to the next combat round. A battle that has already fought
+/// `max_combat_rounds` rounds ends in a stalemate instead.
 pub fn continue_combat_round(
     state: &GameState,
     combat: &mut ActiveCombat,
 ) {
-    combat.round += 1;
-    combat.pending_attacker_hits = 0;
+    if combat.round >= state.settings.max_combat_rounds {
+        combat.stalemate = true;
+        combat.sub_phase = CombatSubPhase::BattleOver;
+        return;
+    }
+
+    combat.round += 1;
+    combat.round_dice_rolled = 0;
+    combat.pending_attacker_hits = 0;
     combat.pending_defender_hits = 0;
+    combat.pending_attacker_sub_hits = 0;
+    combat.pending_defender_sub_hits = 0;
 
-    // Determine if sub surprise strike applies this round
-    if has_submarines_and_no_destroyer(state, combat, true) {
-        combat.sub_phase = CombatSubPhase::AttackerSubmarineStrike;
-    } else {
-        combat.sub_phase = CombatSubPhase::AttackerRolls;
-    }
+    // Only submarine surprise strikes open the later rounds
+    combat.sub_phase = opening_step(state, combat, CombatSubPhase::AttackerSubmarineStrike);
 }
 
 /// Finalize a battle: handle territory capture for land battles.
 pub fn finalize_battle(
     state: &mut GameState,
+    map: &GameMap,
     combat: &ActiveCombat,
 ) -> Vec<GameEvent> {
     let mut events = Vec::new();
 
+    let mut attacker_losses = combat.attacker_losses.clone();
+    let mut defender_losses = combat.defender_losses.clone();
+
+    // Aircraft left without a carrier deck go down with it
+    for &uid in &combat.orphaned_aircraft {
+        if let Some((_, unit)) = movement::remove_unit(state, uid) {
+            if combat.attacker_units.contains(&uid) {
+                attacker_losses.push(unit.unit_type);
+            } else {
+                defender_losses.push(unit.unit_type);
+            }
+        }
+    }
+
     let active_defenders: Vec<_> = combat.defender_units.iter()
         .filter(|uid| !combat.submerged_units.contains(uid))
+        .filter(|uid| !combat.orphaned_aircraft.contains(uid))
         .collect();
     let active_attackers: Vec<_> = combat.attacker_units.iter()
         .filter(|uid| !combat.submerged_units.contains(uid))
+        .filter(|uid| !combat.orphaned_aircraft.contains(uid))
         .collect();
 
     let attacker_won = active_defenders.is_empty() && !active_attackers.is_empty();
 
+    // Under the veteran rule, every unit that lived through the battle
+    // fights at +1 from now on
+    if state.settings.veteran_units {
+        for &&uid in active_attackers.iter().chain(&active_defenders) {
+            if let Some((_, unit)) = movement::find_unit_mut(state, uid) {
+                unit.veteran = true;
+            }
+        }
+    }
+
+    if combat.stalemate {
+        events.push(GameEvent::BattleStalemate {
+            location: combat.location,
+        });
+    }
     events.push(GameEvent::BattleEnded {
         location: combat.location,
         attacker_won,
     });
+    state.combat_history.push(BattleRecord {
+        turn: state.turn_number,
+        location: combat.location,
+        attacker: combat.attacker,
+        defender: combat.defender,
+        rounds: combat.round,
+        dice_rolled: combat.dice_rolled,
+        attacker_losses,
+        defender_losses,
+        attacker_won,
+        stalemate: combat.stalemate,
+    });
 
     // Handle territory capture
     if attacker_won {
         if let RegionId::Land(tid) = combat.location {
             let has_land_unit = active_attackers.iter().any(|&&uid| {
                 movement::find_unit(state, uid)
-                    .map(|(_, u)| get_unit_stats(u.unit_type).domain == UnitDomain::Land)
+                    .map(|(_, u)| state.unit_stats(u.unit_type).domain == UnitDomain::Land)
                     .unwrap_or(false)
             });
 
-            if has_land_unit {
-                state.territories[tid as usize].owner = Some(combat.attacker);
-                state.territories[tid as usize].just_captured = true;
+            // Only territory held by a power at war with the attacker changes
+            // hands; a battle on a bystander's soil leaves it with its owner.
+            // Unowned neutral territory simply passes to the capturer
+            let capturable = state.territories[tid as usize]
+                .owner
+                .is_none_or(|owner| state.political.are_at_war(combat.attacker, owner));
+
+            if has_land_unit && capturable {
+                let territory = &mut state.territories[tid as usize];
+                territory.owner = Some(combat.attacker);
+                // Facilities change hands with the territory; `just_captured`
+                // keeps them from being used until the capturer's next turn
+                territory.just_captured = true;
+                let ipc_value = map.territory(tid).ipc_value;
+                let scorched = state.settings.scorched_earth_damage;
+                for facility in territory.facilities.iter_mut() {
+                    facility.on_capture(ipc_value, scorched);
+                }
+                events.push(GameEvent::TerritoryCaptured {
+                    territory_id: tid,
+                    by: combat.attacker,
+                });
             }
         }
     }
 
+    if let RegionId::Sea(_) = combat.location {
+        events.extend(cancel_stranded_assaults(state));
+    }
+
     events
 }
 
@@ -885,6 +1611,157 @@ pub fn apply_roll_defense(
     Ok(Vec::new())
 }
 
+// =========================================================================
+// Strategic bombing raids
+// =========================================================================
+
+fn is_industrial_complex(facility: &Facility) -> bool {
+    matches!(
+        facility.facility_type,
+        FacilityType::MinorIndustrialComplex | FacilityType::MajorIndustrialComplex
+    )
+}
+
+/// Strategic bombers the current power moved into `territory_id` this turn.
+fn raiding_bombers(state: &GameState, territory_id: TerritoryId) -> Vec<UnitId> {
+    state.territories[territory_id as usize]
+        .units
+        .iter()
+        .filter(|u| {
+            u.owner == state.current_power
+                && u.moved_this_turn
+                && u.unit_type == UnitType::StrategicBomber
+        })
+        .map(|u| u.id)
+        .collect()
+}
+
+/// Validate a strategic bombing raid: the target must be an enemy territory
+/// with an industrial complex, not raided yet this turn, and the current
+/// power must have moved strategic bombers into it.
+pub fn validate_strategic_bombing(
+    state: &GameState,
+    territory_id: TerritoryId,
+) -> Result<(), EngineError> {
+    let cs = match &state.phase_state {
+        PhaseState::Combat(cs) => cs,
+        _ => return Err(EngineError::WrongPhase {
+            expected: "ConductCombat".into(),
+            actual: format!("{:?}", state.current_phase),
+        }),
+    };
+    if cs.active_combat.is_some() {
+        return Err(EngineError::InvalidAction {
+            reason: "A battle is already in progress. Resolve it first.".into(),
+        });
+    }
+
+    let territory = state
+        .territories
+        .get(territory_id as usize)
+        .ok_or(EngineError::TerritoryNotFound { territory_id })?;
+    let enemy_owned = territory
+        .owner
+        .is_some_and(|o| state.political.are_at_war(state.current_power, o));
+    if !enemy_owned {
+        return Err(EngineError::InvalidAction {
+            reason: "Strategic bombing targets must be enemy territories".into(),
+        });
+    }
+    if !territory.facilities.iter().any(is_industrial_complex) {
+        return Err(EngineError::InvalidAction {
+            reason: "No industrial complex to bomb in this territory".into(),
+        });
+    }
+    if cs.bombed_territories.contains(&territory_id) {
+        return Err(EngineError::InvalidAction {
+            reason: "This territory has already been bombed this turn".into(),
+        });
+    }
+    if cs.resolved_battles.contains(&RegionId::Land(territory_id)) {
+        return Err(EngineError::InvalidAction {
+            reason: "The bombers here have already fought in its battle".into(),
+        });
+    }
+    if raiding_bombers(state, territory_id).is_empty() {
+        return Err(EngineError::InvalidAction {
+            reason: "No strategic bombers moved into this territory".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Resolve a strategic bombing raid with `bombing::resolve_bombing_raid`:
+/// the raiders' escorts and the defender's fighters in the territory fight
+/// first, then the factory's AA fires, and each surviving bomber deals
+/// 1d6 + 2 damage. Accumulated damage never exceeds the facility's repair
+/// ceiling (twice the territory's IPC value). When only aircraft came, the
+/// fighters among them are escorts and no battle follows; otherwise the
+/// raiding bombers sit out the battle (see `gather_combatants`).
+pub fn apply_strategic_bombing(
+    state: &mut GameState,
+    territory_id: TerritoryId,
+) -> Result<Vec<GameEvent>, EngineError> {
+    let power = state.current_power;
+    let bombers = raiding_bombers(state, territory_id);
+    let territory = &state.territories[territory_id as usize];
+    let arrivals: Vec<&UnitInstance> = territory
+        .units
+        .iter()
+        .filter(|u| u.owner == power && u.moved_this_turn)
+        .collect();
+    let air_raid = arrivals
+        .iter()
+        .all(|u| state.unit_stats(u.unit_type).domain == UnitDomain::Air);
+    let escorts = if air_raid {
+        arrivals
+            .iter()
+            .filter(|u| u.unit_type == UnitType::Fighter)
+            .map(|u| u.id)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let interceptors = territory
+        .units
+        .iter()
+        .filter(|u| u.unit_type == UnitType::Fighter && state.political.are_at_war(power, u.owner))
+        .map(|u| u.id)
+        .collect();
+
+    let before = industrial_complex_damage(state, territory_id);
+    bombing::resolve_bombing_raid(
+        state,
+        bombers,
+        escorts,
+        interceptors,
+        bombing::BombingTarget::IndustrialComplex(territory_id),
+    )?;
+    let total_damage = industrial_complex_damage(state, territory_id);
+
+    if let PhaseState::Combat(ref mut cs) = state.phase_state {
+        cs.bombed_territories.push(territory_id);
+        if air_raid {
+            cs.pending_battles.retain(|&r| r != RegionId::Land(territory_id));
+        }
+    }
+
+    Ok(vec![GameEvent::FacilityBombed {
+        territory_id,
+        damage: total_damage - before,
+        total_damage,
+    }])
+}
+
+/// Damage on the industrial complex in `territory_id`, or 0 if it has none.
+fn industrial_complex_damage(state: &GameState, territory_id: TerritoryId) -> u32 {
+    state.territories[territory_id as usize]
+        .facilities
+        .iter()
+        .find(|f| is_industrial_complex(f))
+        .map_or(0, |f| f.damage)
+}
+
 // =========================================================================
 // Tests
 // =========================================================================
@@ -892,12 +1769,26 @@ pub fn apply_roll_defense(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::action::Action;
     use crate::data::GameMap;
     use crate::data::territory_ids as t;
     use crate::data::sea_zone_ids as sz;
-    use crate::phase::CombatState;
+    use crate::phase::{CombatState, PlannedMove};
     use crate::territory::SeaZoneId;
 
+    /// Helper: record that a unit made a one-step combat move this turn.
+    fn record_combat_move(state: &mut GameState, unit_id: UnitId, from: TerritoryId, to: TerritoryId) {
+        if let PhaseState::Combat(ref mut cs) = state.phase_state {
+            cs.combat_moves.push(PlannedMove {
+                unit_id,
+                path: vec![RegionId::Land(from), RegionId::Land(to)],
+                from: RegionId::Land(from),
+                to: RegionId::Land(to),
+                operation: None,
+            });
+        }
+    }
+
     /// Helper: set up state in ConductCombat phase with given units at a territory.
     fn setup_land_combat(
         attacker: Power,
@@ -1011,13 +1902,19 @@ mod tests {
 
         // Roll defense
         let _rolls = resolve_defense_roll(&mut state, &mut combat);
-        // Should be in some casualty selection or decision phase
-        assert!(matches!(
+        // Should be in some casualty selection or decision phase, or, if
+        // both sides missed, straight into the next round
+        assert!(
+            matches!(
+                combat.sub_phase,
+                CombatSubPhase::DefenderSelectsCasualties
+                    | CombatSubPhase::AttackerSelectsCasualties
+                    | CombatSubPhase::AttackerDecision
+            ) || (combat.sub_phase == CombatSubPhase::AttackerRolls && combat.round == 2),
+            "{:?} in round {}",
             combat.sub_phase,
-            CombatSubPhase::DefenderSelectsCasualties
-                | CombatSubPhase::AttackerSelectsCasualties
-                | CombatSubPhase::AttackerDecision
-        ));
+            combat.round
+        );
     }
 
     #[test]
@@ -1064,6 +1961,62 @@ mod tests {
         assert_eq!(rolls.len(), 1); // One sub
     }
 
+    #[test]
+    fn test_same_region_rolls_differently_on_another_turn() {
+        let roll_on_turn = |turn: u32| {
+            let mut state = setup_land_combat(
+                Power::Germany,
+                (100..110).map(|id| (id, UnitType::Infantry)).collect(),
+                Power::UnitedKingdom,
+                vec![(200, UnitType::Infantry)],
+                t::FRANCE,
+            );
+            state.rng_mode = RngMode::PerBattle;
+            state.turn_number = turn;
+            let (mut combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+            apply_roll_attack(&mut state, &mut combat).unwrap();
+            combat.last_roll
+        };
+        assert_eq!(roll_on_turn(1), roll_on_turn(1));
+        assert_ne!(roll_on_turn(1), roll_on_turn(2));
+    }
+
+    #[test]
+    fn test_destroyer_sinks_lone_transport_without_rolling() {
+        let map = GameMap::new();
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Destroyer)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Transport)],
+            sz::SZ_NORTH_SEA,
+        );
+        // Infantry it carried earlier is ashore and does not go down with it
+        state.sea_zones[sz::SZ_NORTH_SEA as usize].units[1].cargo = vec![201];
+        state.territories[t::SCOTLAND as usize]
+            .units
+            .push(UnitInstance::new(201, UnitType::Infantry, Power::UnitedKingdom));
+        let counter = state.rng_counter;
+
+        let location = RegionId::Sea(sz::SZ_NORTH_SEA);
+        let result =
+            crate::apply::apply_action_inner(&mut state, crate::action::Action::SelectBattle { location }, &map)
+                .unwrap();
+
+        assert!(result.combat_step.unwrap().battle_over);
+        assert!(movement::find_unit(&state, 200).is_none());
+        assert!(movement::find_unit(&state, 100).is_some());
+        assert!(movement::find_unit(&state, 201).is_some());
+        assert_eq!(state.rng_counter, counter);
+        let record = state.combat_history.last().unwrap();
+        assert!(record.attacker_won);
+        assert_eq!(record.dice_rolled, 0);
+        assert_eq!(record.defender_losses, vec![UnitType::Transport]);
+        let PhaseState::Combat(cs) = &state.phase_state else { panic!() };
+        assert!(cs.active_combat.is_none());
+        assert!(cs.resolved_battles.contains(&location));
+    }
+
     #[test]
     fn test_submarine_no_surprise_with_destroyer() {
         // Defender has destroyer, so no surprise strike
@@ -1080,6 +2033,125 @@ mod tests {
         assert_eq!(combat.sub_phase, CombatSubPhase::AttackerRolls);
     }
 
+    /// The first dice seed for which `resolve` scores `hits` hits in this
+    /// battle, so a test can play a known miss or hit through the resolver.
+    fn seed_scoring(
+        state: &GameState,
+        combat: &ActiveCombat,
+        hits: u32,
+        resolve: impl Fn(&mut GameState, &mut ActiveCombat) -> Vec<u8>,
+    ) -> u64 {
+        (0..1000)
+            .find(|&seed| {
+                let (mut state, mut combat) = (state.clone(), combat.clone());
+                state.rng_seed = seed;
+                resolve(&mut state, &mut combat);
+                combat.last_roll_hits == hits
+            })
+            .expect("no seed scores that many hits")
+    }
+
+    /// Germany's infantry and fighter against a British infantry and AAA in
+    /// France, with a German battleship (300) off the coast to bombard.
+    fn aa_and_bombardment_battle() -> (GameState, ActiveCombat) {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry), (101, UnitType::Fighter)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry), (201, UnitType::AAA)],
+            t::FRANCE,
+        );
+        state.sea_zones[sz::SZ_ENGLISH_CHANNEL as usize]
+            .units
+            .push(UnitInstance::new(300, UnitType::Battleship, Power::Germany));
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+        combat.is_amphibious = true;
+        combat.bombardment_units = vec![300];
+        (state, combat)
+    }
+
+    #[test]
+    fn test_zero_hit_opening_fire_advances_to_main_roll() {
+        // AA fire misses, then the bombardment misses
+        let (mut state, mut combat) = aa_and_bombardment_battle();
+        assert_eq!(combat.sub_phase, CombatSubPhase::AAFire);
+        state.rng_seed = seed_scoring(&state, &combat, 0, resolve_aa_fire);
+        assert_eq!(resolve_aa_fire(&mut state, &mut combat).len(), 1);
+        assert_eq!(combat.pending_defender_hits, 0);
+        assert_eq!(combat.sub_phase, CombatSubPhase::ShoreBombardment);
+
+        state.rng_seed = seed_scoring(&state, &combat, 0, resolve_shore_bombardment);
+        assert_eq!(resolve_shore_bombardment(&mut state, &mut combat).len(), 1);
+        assert_eq!(combat.pending_attacker_hits, 0);
+        assert_eq!(combat.sub_phase, CombatSubPhase::AttackerRolls);
+
+        // A submarine surprise strike on each side, both missing
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Submarine)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Submarine)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        assert_eq!(combat.sub_phase, CombatSubPhase::AttackerSubmarineStrike);
+        state.rng_seed = seed_scoring(&state, &combat, 0, |s, c| resolve_submarine_strike(s, c, true));
+        resolve_submarine_strike(&mut state, &mut combat, true);
+        assert_eq!(combat.sub_phase, CombatSubPhase::DefenderSubmarineStrike);
+
+        state.rng_seed = seed_scoring(&state, &combat, 0, |s, c| resolve_submarine_strike(s, c, false));
+        resolve_submarine_strike(&mut state, &mut combat, false);
+        assert_eq!((combat.pending_attacker_hits, combat.pending_defender_hits), (0, 0));
+        assert_eq!(combat.sub_phase, CombatSubPhase::AttackerRolls);
+    }
+
+    #[test]
+    fn test_opening_fire_hits_go_to_the_side_that_takes_them() {
+        let (mut state, mut combat) = aa_and_bombardment_battle();
+
+        // AA hits are the defender's and come off the attacking aircraft
+        state.rng_seed = seed_scoring(&state, &combat, 1, resolve_aa_fire);
+        resolve_aa_fire(&mut state, &mut combat);
+        assert_eq!((combat.pending_attacker_hits, combat.pending_defender_hits), (0, 1));
+        assert_eq!(combat.sub_phase, CombatSubPhase::AAFireCasualties);
+        apply_casualties(&mut state, &mut combat, &[101], false).unwrap();
+        assert_eq!(combat.attacker_units, vec![100]);
+        assert_eq!(combat.sub_phase, CombatSubPhase::ShoreBombardment);
+
+        // Bombardment hits are the attacker's and come off the defenders
+        state.rng_seed = seed_scoring(&state, &combat, 1, resolve_shore_bombardment);
+        resolve_shore_bombardment(&mut state, &mut combat);
+        assert_eq!((combat.pending_attacker_hits, combat.pending_defender_hits), (1, 0));
+        assert_eq!(combat.sub_phase, CombatSubPhase::ShoreBombardmentCasualties);
+
+        // A submarine surprise strike is the attacker's, taken by the defender
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Submarine)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Cruiser)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        state.rng_seed = seed_scoring(&state, &combat, 1, |s, c| resolve_submarine_strike(s, c, true));
+        resolve_submarine_strike(&mut state, &mut combat, true);
+        assert_eq!((combat.pending_attacker_hits, combat.pending_defender_hits), (1, 0));
+        assert_eq!(combat.sub_phase, CombatSubPhase::DefenderSubmarineStrikeCasualties);
+    }
+
+    #[test]
+    fn test_aa_hits_cannot_be_taken_by_infantry() {
+        let (mut state, mut combat) = aa_and_bombardment_battle();
+        state.rng_seed = seed_scoring(&state, &combat, 1, resolve_aa_fire);
+        resolve_aa_fire(&mut state, &mut combat);
+        assert_eq!(combat.sub_phase, CombatSubPhase::AAFireCasualties);
+
+        let result = apply_casualties(&mut state, &mut combat, &[100], false);
+        assert!(matches!(result, Err(EngineError::InvalidAction { .. })));
+        assert_eq!(combat.attacker_units, vec![100, 101]);
+        assert_eq!(combat.sub_phase, CombatSubPhase::AAFireCasualties);
+    }
+
     #[test]
     fn test_casualty_selection() {
         let mut state = setup_land_combat(
@@ -1192,6 +2264,134 @@ mod tests {
         assert!(normandy_units.iter().any(|u| u.id == 101));
     }
 
+    #[test]
+    fn test_round_auto_advances_without_attacker_choices() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry), (101, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry), (201, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+        assert!(combat.retreat_options.is_empty());
+
+        // Defender loses one unit; the attacker can neither retreat nor submerge
+        combat.sub_phase = CombatSubPhase::DefenderSelectsCasualties;
+        combat.pending_attacker_hits = 1;
+        apply_casualties(&mut state, &mut combat, &[200], true).unwrap();
+        assert_eq!(combat.round, 2);
+        assert_eq!(combat.sub_phase, CombatSubPhase::AttackerRolls);
+    }
+
+    #[test]
+    fn test_round_stops_for_decision_when_retreat_available() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry), (101, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry), (201, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        // Infantry 100 attacked from German-held Normandy this turn
+        state.territories[t::NORMANDY_BORDEAUX as usize].owner = Some(Power::Germany);
+        record_combat_move(&mut state, 100, t::NORMANDY_BORDEAUX, t::FRANCE);
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+        assert_eq!(combat.retreat_options, vec![RegionId::Land(t::NORMANDY_BORDEAUX)]);
+
+        combat.sub_phase = CombatSubPhase::DefenderSelectsCasualties;
+        combat.pending_attacker_hits = 1;
+        apply_casualties(&mut state, &mut combat, &[200], true).unwrap();
+        assert_eq!(combat.round, 1);
+        assert_eq!(combat.sub_phase, CombatSubPhase::AttackerDecision);
+    }
+
+    #[test]
+    fn test_retreat_only_to_an_option_and_all_together() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry), (101, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        state.territories[t::NORMANDY_BORDEAUX as usize].owner = Some(Power::Germany);
+        record_combat_move(&mut state, 100, t::NORMANDY_BORDEAUX, t::FRANCE);
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+        combat.sub_phase = CombatSubPhase::AttackerDecision;
+        if let PhaseState::Combat(ref mut cs) = state.phase_state {
+            cs.active_combat = Some(combat.clone());
+        }
+
+        let elsewhere = Action::AttackerRetreat { to: RegionId::Land(t::GERMANY) };
+        assert!(crate::validate::validate_action(&state, &elsewhere).is_err());
+        let to = RegionId::Land(t::NORMANDY_BORDEAUX);
+        crate::validate::validate_action(&state, &Action::AttackerRetreat { to }).unwrap();
+
+        apply_retreat(&mut state, &mut combat, to).unwrap();
+        let normandy = &state.territories[t::NORMANDY_BORDEAUX as usize].units;
+        assert!(normandy.iter().any(|u| u.id == 100));
+        assert!(normandy.iter().any(|u| u.id == 101));
+        assert!(state.territories[t::FRANCE as usize].units.iter().all(|u| u.owner != Power::Germany));
+    }
+
+    #[test]
+    fn test_no_retreat_into_a_pending_battle() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry), (101, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        for (unit_id, from) in [(100, t::NORMANDY_BORDEAUX), (101, t::WESTERN_GERMANY)] {
+            state.territories[from as usize].owner = Some(Power::Germany);
+            record_combat_move(&mut state, unit_id, from, t::FRANCE);
+        }
+        if let PhaseState::Combat(ref mut cs) = state.phase_state {
+            cs.pending_battles.push(RegionId::Land(t::NORMANDY_BORDEAUX));
+        }
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+        assert_eq!(combat.retreat_options, vec![RegionId::Land(t::WESTERN_GERMANY)]);
+        combat.sub_phase = CombatSubPhase::AttackerDecision;
+        if let PhaseState::Combat(ref mut cs) = state.phase_state {
+            cs.active_combat = Some(combat);
+        }
+
+        let into_battle = Action::AttackerRetreat { to: RegionId::Land(t::NORMANDY_BORDEAUX) };
+        assert!(crate::validate::validate_action(&state, &into_battle).is_err());
+        let clear = Action::AttackerRetreat { to: RegionId::Land(t::WESTERN_GERMANY) };
+        crate::validate::validate_action(&state, &clear).unwrap();
+    }
+
+    #[test]
+    fn test_retreat_needs_territory_held_before_combat_or_captured() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry), (101, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        // Both neighbours were French when combat movement began
+        let snapshot: Vec<_> = state.territories.iter().map(|t| t.owner).collect();
+        if let PhaseState::Combat(ref mut cs) = state.phase_state {
+            cs.owners_before_combat = snapshot;
+        }
+        // Normandy fell to Germany earlier this combat phase; Southern France
+        // shows as German but was never taken
+        let normandy = &mut state.territories[t::NORMANDY_BORDEAUX as usize];
+        normandy.owner = Some(Power::Germany);
+        normandy.just_captured = true;
+        state.territories[t::SOUTHERN_FRANCE as usize].owner = Some(Power::Germany);
+        for (unit_id, from) in [(100, t::NORMANDY_BORDEAUX), (101, t::SOUTHERN_FRANCE)] {
+            record_combat_move(&mut state, unit_id, from, t::FRANCE);
+        }
+
+        let (combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+        assert_eq!(combat.retreat_options, vec![RegionId::Land(t::NORMANDY_BORDEAUX)]);
+    }
+
     #[test]
     fn test_submerge() {
         let mut state = setup_naval_combat(
@@ -1226,11 +2426,20 @@ mod tests {
             sub_phase: CombatSubPhase::AttackerDecision,
             pending_attacker_hits: 0,
             pending_defender_hits: 0,
+            pending_attacker_sub_hits: 0,
+            pending_defender_sub_hits: 0,
             is_amphibious: false,
             bombardment_units: vec![],
             last_roll: vec![],
+            last_roll_hits: 0,
             retreat_options: vec![],
             enemy_has_destroyer: false,
+            round_dice_rolled: 0,
+            stalemate: false,
+            orphaned_aircraft: vec![],
+            dice_rolled: 0,
+            attacker_losses: vec![],
+            defender_losses: vec![],
         };
         assert!(check_battle_end(&combat));
     }
@@ -1248,11 +2457,20 @@ mod tests {
             sub_phase: CombatSubPhase::AttackerDecision,
             pending_attacker_hits: 0,
             pending_defender_hits: 0,
+            pending_attacker_sub_hits: 0,
+            pending_defender_sub_hits: 0,
             is_amphibious: false,
             bombardment_units: vec![],
             last_roll: vec![],
+            last_roll_hits: 0,
             retreat_options: vec![],
             enemy_has_destroyer: false,
+            round_dice_rolled: 0,
+            stalemate: false,
+            orphaned_aircraft: vec![],
+            dice_rolled: 0,
+            attacker_losses: vec![],
+            defender_losses: vec![],
         };
         assert!(check_battle_end(&combat));
     }
@@ -1293,8 +2511,11 @@ mod tests {
         }
 
         if !check_battle_end(&combat) {
-            // Continue to round 2
-            continue_combat_round(&state, &mut combat);
+            // Continue to round 2; with no retreat to offer, a round with
+            // no hits has already moved on
+            if combat.sub_phase == CombatSubPhase::AttackerDecision {
+                continue_combat_round(&state, &mut combat);
+            }
             assert_eq!(combat.round, 2);
             assert!(matches!(
                 combat.sub_phase,
@@ -1324,17 +2545,617 @@ mod tests {
             sub_phase: CombatSubPhase::BattleOver,
             pending_attacker_hits: 0,
             pending_defender_hits: 0,
+            pending_attacker_sub_hits: 0,
+            pending_defender_sub_hits: 0,
             is_amphibious: false,
             bombardment_units: vec![],
             last_roll: vec![],
+            last_roll_hits: 0,
             retreat_options: vec![],
             enemy_has_destroyer: false,
             submerged_units: vec![],
+            round_dice_rolled: 0,
+            stalemate: false,
+            orphaned_aircraft: vec![],
+            dice_rolled: 0,
+            attacker_losses: vec![],
+            defender_losses: vec![],
         };
 
-        let events = finalize_battle(&mut state, &combat);
+        let events = finalize_battle(&mut state, &GameMap::new(), &combat);
         assert_eq!(state.territories[t::FRANCE as usize].owner, Some(Power::Germany));
         assert!(state.territories[t::FRANCE as usize].just_captured);
         assert!(events.iter().any(|e| matches!(e, GameEvent::BattleEnded { attacker_won: true, .. })));
     }
+
+    #[test]
+    fn test_capture_event_filters_by_region() {
+        use crate::action::{Action, ActionResult, AppliedAction, InverseAction};
+
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![],
+            t::FRANCE,
+        );
+        let mut combat = ActiveCombat::new(
+            RegionId::Land(t::FRANCE),
+            Power::Germany,
+            vec![100],
+            Power::UnitedKingdom,
+            vec![],
+        );
+        combat.sub_phase = CombatSubPhase::BattleOver;
+
+        let events = finalize_battle(&mut state, &GameMap::new(), &combat);
+        let applied = AppliedAction {
+            action: Action::ConfirmPhase,
+            inverse: InverseAction::Irreversible,
+        };
+        let result = ActionResult::new(applied, events);
+
+        let in_france = result.events_for_region(RegionId::Land(t::FRANCE));
+        assert!(in_france.iter().any(|e| matches!(
+            e,
+            GameEvent::TerritoryCaptured { territory_id: t::FRANCE, by: Power::Germany }
+        )));
+        assert!(result.events_for_region(RegionId::Land(t::GERMANY)).is_empty());
+    }
+
+    #[test]
+    fn test_sunk_carrier_drowns_its_fighters() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Battleship)],
+            Power::UnitedKingdom,
+            vec![
+                (200, UnitType::Carrier),
+                (201, UnitType::Fighter),
+                (202, UnitType::Fighter),
+            ],
+            sz::SZ_NORTH_SEA,
+        );
+
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        let (_, carrier) = movement::find_unit(&state, 200).unwrap();
+        assert_eq!(carrier.cargo, vec![201, 202]);
+
+        // Second hit on an already damaged carrier sinks it
+        movement::find_unit_mut(&mut state, 200).unwrap().1.hits_taken = 1;
+        combat.pending_attacker_hits = 1;
+        combat.sub_phase = CombatSubPhase::DefenderSelectsCasualties;
+        apply_casualties(&mut state, &mut combat, &[200], true).unwrap();
+        assert_eq!(combat.orphaned_aircraft, vec![201, 202]);
+
+        combat.sub_phase = CombatSubPhase::BattleOver;
+        let events = finalize_battle(&mut state, &GameMap::new(), &combat);
+        assert!(movement::find_unit(&state, 201).is_none());
+        assert!(movement::find_unit(&state, 202).is_none());
+        assert!(events.iter().any(|e| matches!(e, GameEvent::BattleEnded { attacker_won: true, .. })));
+    }
+
+    #[test]
+    fn test_sunk_carrier_spares_aircraft_that_flew_off() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Battleship)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Carrier), (201, UnitType::Fighter)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        assert_eq!(movement::find_unit(&state, 200).unwrap().1.cargo, vec![201]);
+
+        // Taking off clears the deck
+        let (_, fighter) = movement::remove_unit(&mut state, 201).unwrap();
+        assert!(movement::find_unit(&state, 200).unwrap().1.cargo.is_empty());
+        movement::place_unit_at(&mut state, RegionId::Land(t::SCOTLAND), fighter);
+
+        // A stale deck entry does not take the fighter down with the carrier
+        movement::find_unit_mut(&mut state, 200).unwrap().1.cargo = vec![201];
+        movement::find_unit_mut(&mut state, 200).unwrap().1.hits_taken = 1;
+        combat.pending_attacker_hits = 1;
+        combat.sub_phase = CombatSubPhase::DefenderSelectsCasualties;
+        apply_casualties(&mut state, &mut combat, &[200], true).unwrap();
+
+        assert!(combat.orphaned_aircraft.is_empty());
+        assert!(movement::find_unit(&state, 201).is_some());
+    }
+
+    /// Win an uncontested battle for France with a lone infantry.
+    fn survive_battle(veteran_units: bool) -> GameState {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![],
+            t::FRANCE,
+        );
+        state.settings.veteran_units = veteran_units;
+        let mut combat = ActiveCombat::new(
+            RegionId::Land(t::FRANCE),
+            Power::Germany,
+            vec![100],
+            Power::UnitedKingdom,
+            vec![],
+        );
+        combat.sub_phase = CombatSubPhase::BattleOver;
+        finalize_battle(&mut state, &GameMap::new(), &combat);
+        state
+    }
+
+    #[test]
+    fn test_veteran_infantry_attacks_at_two() {
+        let state = survive_battle(true);
+        let (_, inf) = movement::find_unit(&state, 100).unwrap();
+        assert!(inf.veteran);
+        assert_eq!(effective_attack_value(&state, inf, &[100]), 2);
+        assert_eq!(effective_defense_value(&state, inf), 3);
+    }
+
+    #[test]
+    fn test_veteran_rule_off_leaves_stats_unchanged() {
+        let state = survive_battle(false);
+        let (_, inf) = movement::find_unit(&state, 100).unwrap();
+        assert!(!inf.veteran);
+        assert_eq!(effective_attack_value(&state, inf, &[100]), 1);
+        assert_eq!(effective_defense_value(&state, inf), 2);
+    }
+
+    #[test]
+    fn test_sunk_carrier_fighters_transfer_to_spare_deck() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Battleship)],
+            Power::UnitedKingdom,
+            vec![
+                (200, UnitType::Carrier),
+                (201, UnitType::Fighter),
+                (203, UnitType::Carrier),
+            ],
+            sz::SZ_NORTH_SEA,
+        );
+
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        assert_eq!(movement::find_unit(&state, 200).unwrap().1.cargo, vec![201]);
+        movement::find_unit_mut(&mut state, 200).unwrap().1.hits_taken = 1;
+        combat.pending_attacker_hits = 1;
+        combat.sub_phase = CombatSubPhase::DefenderSelectsCasualties;
+        apply_casualties(&mut state, &mut combat, &[200], true).unwrap();
+
+        assert!(combat.orphaned_aircraft.is_empty());
+        assert_eq!(movement::find_unit(&state, 203).unwrap().1.cargo, vec![201]);
+    }
+
+    #[test]
+    fn test_capturing_neutral_assigns_owner() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![],
+            t::SPAIN,
+        );
+        state.territories[t::SPAIN as usize].owner = None;
+
+        let mut combat = ActiveCombat::new(
+            RegionId::Land(t::SPAIN),
+            Power::Germany,
+            vec![100],
+            Power::UnitedKingdom,
+            vec![],
+        );
+        combat.sub_phase = CombatSubPhase::BattleOver;
+
+        finalize_battle(&mut state, &GameMap::new(), &combat);
+        assert_eq!(state.territories[t::SPAIN as usize].owner, Some(Power::Germany));
+        assert!(state.territories[t::SPAIN as usize].just_captured);
+    }
+
+    #[test]
+    fn test_battle_dice_independent_of_order() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry), (101, UnitType::Tank)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        state.territories[t::NORMANDY_BORDEAUX as usize].owner = Some(Power::UnitedKingdom);
+        let mut inf = UnitInstance::new(102, UnitType::Infantry, Power::Germany);
+        inf.moved_this_turn = true;
+        state.territories[t::NORMANDY_BORDEAUX as usize].units.push(inf);
+        state.territories[t::NORMANDY_BORDEAUX as usize]
+            .units
+            .push(UnitInstance::new(201, UnitType::Infantry, Power::UnitedKingdom));
+
+        let roll_battle = |state: &mut GameState, tid: TerritoryId| {
+            let mut combat = start_battle(state, RegionId::Land(tid), Power::Germany).unwrap();
+            let attack = resolve_attack_roll(state, &mut combat);
+            let defense = resolve_defense_roll(state, &mut combat);
+            (attack, defense)
+        };
+
+        let mut first = state.clone();
+        let france_a = roll_battle(&mut first, t::FRANCE);
+        let normandy_a = roll_battle(&mut first, t::NORMANDY_BORDEAUX);
+
+        let mut second = state.clone();
+        let normandy_b = roll_battle(&mut second, t::NORMANDY_BORDEAUX);
+        let france_b = roll_battle(&mut second, t::FRANCE);
+
+        assert_eq!(france_a, france_b);
+        assert_eq!(normandy_a, normandy_b);
+        assert_eq!(first.rng_counter, second.rng_counter);
+    }
+
+    #[test]
+    fn test_legacy_saves_use_shared_counter() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        let mut json: serde_json::Value = serde_json::to_value(&state).unwrap();
+        json.as_object_mut().unwrap().remove("rng_mode");
+        let legacy: GameState = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.rng_mode, RngMode::SharedCounter);
+
+        state.rng_mode = RngMode::SharedCounter;
+        let mut combat = start_battle(&mut state, RegionId::Land(t::FRANCE), Power::Germany).unwrap();
+        let rolls = resolve_attack_roll(&mut state, &mut combat);
+        let mut expected = DeterministicRng::new(42, 0);
+        assert_eq!(rolls, expected.roll_multiple_d6(1));
+        assert_eq!(state.rng_counter, 1);
+    }
+
+    #[test]
+    fn test_sub_hits_cannot_target_air() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Submarine)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Fighter), (201, UnitType::Carrier)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        combat.pending_attacker_hits = 1;
+        combat.pending_attacker_sub_hits = 1;
+        combat.sub_phase = CombatSubPhase::DefenderSubmarineStrikeCasualties;
+
+        // The fighter cannot absorb a submarine hit
+        let result = apply_casualties(&mut state, &mut combat, &[200], true);
+        assert!(result.is_err());
+
+        // The carrier can
+        let result = apply_casualties(&mut state, &mut combat, &[201], true);
+        assert!(result.is_ok());
+        assert_eq!(combat.pending_attacker_sub_hits, 0);
+    }
+
+    #[test]
+    fn test_sub_hits_only_air_left_are_lost() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Submarine)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Fighter)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        combat.pending_attacker_hits = 1;
+        combat.pending_attacker_sub_hits = 1;
+        combat.sub_phase = CombatSubPhase::DefenderSubmarineStrikeCasualties;
+
+        // No eligible targets: the hit is wasted and no casualty is required
+        assert!(apply_casualties(&mut state, &mut combat, &[], true).is_ok());
+        assert!(movement::find_unit(&state, 200).is_some());
+    }
+
+    #[test]
+    fn test_mixed_fleet_hits_split_between_air_and_sea() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Submarine), (101, UnitType::Cruiser)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Fighter), (201, UnitType::Destroyer)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        // One hit from the sub, one from the cruiser
+        combat.pending_attacker_hits = 2;
+        combat.pending_attacker_sub_hits = 1;
+        combat.sub_phase = CombatSubPhase::DefenderSelectsCasualties;
+
+        // Fighter takes the cruiser hit, destroyer takes the sub hit
+        let result = apply_casualties(&mut state, &mut combat, &[200, 201], true);
+        assert!(result.is_ok());
+        assert!(combat.defender_units.is_empty());
+    }
+
+    #[test]
+    fn test_attack_roll_tracks_sub_hits() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Submarine), (101, UnitType::Cruiser)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Destroyer)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        let rolls = resolve_attack_roll(&mut state, &mut combat);
+        // The enemy destroyer cancels the surprise strike, so both units roll
+        assert_eq!(rolls.len(), 2);
+        let sub_hit = (rolls[0] <= 2) as u32;
+        assert_eq!(combat.pending_attacker_sub_hits, sub_hit);
+        assert!(combat.pending_attacker_sub_hits <= combat.pending_attacker_hits);
+    }
+
+    #[test]
+    fn test_suggest_casualties_takes_infantry_before_tank() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Tank), (201, UnitType::Infantry), (202, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        let (combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+
+        let suggestion = suggest_casualties(&state, &combat, true, 1);
+        assert_eq!(suggestion.len(), 1);
+        assert_ne!(suggestion[0], 200, "tank should be preserved");
+        assert!(suggestion[0] == 201 || suggestion[0] == 202);
+    }
+
+    #[test]
+    fn test_suggest_casualties_breaks_ties_by_lowest_id() {
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(202, UnitType::Infantry), (200, UnitType::Infantry), (201, UnitType::Infantry)],
+            t::FRANCE,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Land(t::FRANCE)).unwrap();
+        assert_eq!(suggest_casualties(&state, &combat, true, 1), vec![200]);
+
+        // Storage order doesn't change the pick
+        combat.defender_units.reverse();
+        state.territories[t::FRANCE as usize].units.reverse();
+        assert_eq!(suggest_casualties(&state, &combat, true, 1), vec![200]);
+        assert_eq!(suggest_casualties(&state, &combat, true, 2), vec![200, 201]);
+    }
+
+    #[test]
+    fn test_suggest_casualties_transports_last_and_subs_skip_air() {
+        let mut state = setup_naval_combat(
+            Power::Germany,
+            vec![(100, UnitType::Submarine)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Transport), (201, UnitType::Destroyer), (202, UnitType::Fighter)],
+            sz::SZ_NORTH_SEA,
+        );
+        let (mut combat, _) = apply_select_battle(&mut state, RegionId::Sea(sz::SZ_NORTH_SEA)).unwrap();
+        combat.pending_attacker_hits = 2;
+        combat.pending_attacker_sub_hits = 2;
+
+        let suggestion = suggest_casualties(&state, &combat, true, 2);
+        assert_eq!(suggestion, vec![201, 200]);
+        assert!(validate_casualty_selection(&state, &combat, &suggestion, true, false).is_ok());
+    }
+
+    #[test]
+    fn test_suggest_casualties_aa_hits_take_aircraft() {
+        // Infantry is cheaper, but an AA hit can only come off the fighter
+        let (mut state, mut combat) = aa_and_bombardment_battle();
+        state.rng_seed = seed_scoring(&state, &combat, 1, resolve_aa_fire);
+        resolve_aa_fire(&mut state, &mut combat);
+        assert_eq!(combat.sub_phase, CombatSubPhase::AAFireCasualties);
+
+        let suggestion = suggest_casualties(&state, &combat, false, combat.pending_defender_hits);
+        assert_eq!(suggestion, vec![101]);
+        assert!(validate_casualty_selection(&state, &combat, &suggestion, false, true).is_ok());
+    }
+
+    #[test]
+    fn test_scorched_earth_damages_captured_factory() {
+        use crate::territory::FacilityType;
+
+        let capture = |scorched_earth_damage: u32| {
+            let map = GameMap::new();
+            let mut state = setup_land_combat(
+                Power::Germany,
+                vec![(100, UnitType::Infantry)],
+                Power::UnitedKingdom,
+                vec![],
+                t::UNITED_KINGDOM,
+            );
+            state.settings.scorched_earth_damage = scorched_earth_damage;
+            let mut combat = ActiveCombat::new(
+                RegionId::Land(t::UNITED_KINGDOM),
+                Power::Germany,
+                vec![100],
+                Power::UnitedKingdom,
+                vec![],
+            );
+            combat.sub_phase = CombatSubPhase::BattleOver;
+            finalize_battle(&mut state, &map, &combat);
+            assert_eq!(state.territories[t::UNITED_KINGDOM as usize].owner, Some(Power::Germany));
+            state.territories[t::UNITED_KINGDOM as usize]
+                .facilities
+                .iter()
+                .find(|f| f.facility_type == FacilityType::MajorIndustrialComplex)
+                .unwrap()
+                .damage
+        };
+
+        assert_eq!(capture(0), 0);
+        assert_eq!(capture(4), 4);
+    }
+
+    #[test]
+    fn test_capturing_major_ic_on_low_value_territory_downgrades_it() {
+        use crate::territory::{Facility, FacilityType};
+
+        let map = GameMap::new();
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::SovietUnion,
+            vec![],
+            t::VOLOGDA,
+        );
+        crate::politics::apply_declare_war(&mut state, Power::SovietUnion);
+        let ipc_value = map.territory(t::VOLOGDA).ipc_value;
+        assert!(ipc_value < crate::territory::MAJOR_IC_MIN_IPC_VALUE);
+        let territory = &mut state.territories[t::VOLOGDA as usize];
+        territory.facilities = vec![Facility::new(FacilityType::MajorIndustrialComplex, 5)];
+        territory.facilities[0].damage = 4;
+
+        let mut combat = ActiveCombat::new(
+            RegionId::Land(t::VOLOGDA),
+            Power::Germany,
+            vec![100],
+            Power::SovietUnion,
+            vec![],
+        );
+        combat.sub_phase = CombatSubPhase::BattleOver;
+        finalize_battle(&mut state, &map, &combat);
+
+        let territory = &state.territories[t::VOLOGDA as usize];
+        assert_eq!(territory.owner, Some(Power::Germany));
+        assert_eq!(territory.facilities.len(), 1);
+        let ic = &territory.facilities[0];
+        assert_eq!(ic.facility_type, FacilityType::MinorIndustrialComplex);
+        assert_eq!(ic.max_damage, ipc_value * 2);
+        assert_eq!(ic.damage, ic.max_damage);
+        assert!(crate::mobilize::get_production_capacity(&state, &map, t::VOLOGDA) <= 3);
+        // Not usable by the capturer until their next turn
+        assert!(territory.just_captured);
+        assert!(crate::mobilize::eligible_placement_territories(&state, &map, Power::Germany, UnitType::Infantry)
+            .iter()
+            .all(|&tid| tid != t::VOLOGDA));
+    }
+
+    fn bombing_state(target: TerritoryId, bombers: u32) -> GameState {
+        let map = GameMap::new();
+        let mut state = crate::setup::create_initial_state(42, &map);
+        state.current_power = Power::Germany;
+        state.current_phase = crate::phase::Phase::ConductCombat;
+        state.phase_state = PhaseState::Combat(CombatState::new());
+        for i in 0..bombers {
+            let mut bomber = UnitInstance::new(99_100 + i, UnitType::StrategicBomber, Power::Germany);
+            bomber.moved_this_turn = true;
+            state.territories[target as usize].units.push(bomber);
+        }
+        state
+    }
+
+    #[test]
+    fn test_strategic_bombing_caps_at_twice_ipc() {
+        // The UK (6 IPCs) has a major IC; with no fighters to intercept, six
+        // bombers come through the AA fire with at least 12 damage to deal
+        let mut state = bombing_state(t::UNITED_KINGDOM, 6);
+        state.territories[t::UNITED_KINGDOM as usize]
+            .units
+            .retain(|u| u.unit_type != UnitType::Fighter);
+        assert!(validate_strategic_bombing(&state, t::UNITED_KINGDOM).is_ok());
+
+        let events = apply_strategic_bombing(&mut state, t::UNITED_KINGDOM).unwrap();
+        let ic = state.territories[t::UNITED_KINGDOM as usize]
+            .facilities
+            .iter()
+            .find(|f| f.facility_type == FacilityType::MajorIndustrialComplex)
+            .unwrap();
+        assert_eq!(ic.damage, 12);
+        assert!(matches!(
+            events[0],
+            GameEvent::FacilityBombed { damage: 12, total_damage: 12, .. }
+        ));
+
+        // Damage persists for repair, and the same factory can't be raided twice
+        assert!(validate_strategic_bombing(&state, t::UNITED_KINGDOM).is_err());
+        state.current_power = Power::UnitedKingdom;
+        state.current_phase = crate::phase::Phase::PurchaseAndRepair;
+        state.phase_state = PhaseState::Purchase(crate::phase::PurchaseState::new());
+        assert!(crate::validate::validate_action(
+            &state,
+            &crate::action::Action::RepairFacility { territory_id: t::UNITED_KINGDOM, damage_to_repair: 12 },
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_interceptors_fight_before_the_bombs_fall() {
+        // Six British fighters meet a lone unescorted bomber
+        let mut state = bombing_state(t::UNITED_KINGDOM, 1);
+        let territory = &mut state.territories[t::UNITED_KINGDOM as usize];
+        territory.units.retain(|u| u.unit_type != UnitType::Fighter);
+        for i in 0..6 {
+            territory.units.push(UnitInstance::new(99_200 + i, UnitType::Fighter, Power::UnitedKingdom));
+        }
+
+        let events = apply_strategic_bombing(&mut state, t::UNITED_KINGDOM).unwrap();
+        assert!(movement::find_unit(&state, 99_100).is_none());
+        assert!(matches!(events[0], GameEvent::FacilityBombed { damage: 0, .. }));
+    }
+
+    #[test]
+    fn test_raiding_bombers_sit_out_the_battle() {
+        let mut state = bombing_state(t::UNITED_KINGDOM, 1);
+        let mut infantry = UnitInstance::new(99_300, UnitType::Infantry, Power::Germany);
+        infantry.moved_this_turn = true;
+        state.territories[t::UNITED_KINGDOM as usize].units.push(infantry);
+        if let PhaseState::Combat(ref mut cs) = state.phase_state {
+            cs.pending_battles.push(RegionId::Land(t::UNITED_KINGDOM));
+        }
+
+        apply_strategic_bombing(&mut state, t::UNITED_KINGDOM).unwrap();
+        // Ground troops came too, so the battle still has to be fought
+        let (combat, _) = apply_select_battle(&mut state, RegionId::Land(t::UNITED_KINGDOM)).unwrap();
+        assert_eq!(combat.attacker_units, vec![99_300]);
+    }
+
+    #[test]
+    fn test_strategic_bombing_requires_factory() {
+        // Scotland has only an air base
+        let state = bombing_state(t::SCOTLAND, 1);
+        let err = validate_strategic_bombing(&state, t::SCOTLAND).unwrap_err();
+        assert!(err.to_string().contains("industrial complex"));
+    }
+
+    #[test]
+    fn test_third_party_units_are_bystanders() {
+        // German infantry attacks British infantry in Italian-held Southern
+        // France, where an Italian infantry is also stationed
+        let mut state = setup_land_combat(
+            Power::Germany,
+            vec![(100, UnitType::Infantry)],
+            Power::UnitedKingdom,
+            vec![(200, UnitType::Infantry)],
+            t::SOUTHERN_FRANCE,
+        );
+        let territory = &mut state.territories[t::SOUTHERN_FRANCE as usize];
+        territory.owner = Some(Power::Italy);
+        territory.units.push(UnitInstance::new(300, UnitType::Infantry, Power::Italy));
+
+        let combat = start_battle(&mut state, RegionId::Land(t::SOUTHERN_FRANCE), Power::Germany).unwrap();
+        assert_eq!(combat.attacker_units, vec![100]);
+        assert_eq!(combat.defender_units, vec![200]);
+        assert_eq!(combat.defender, Power::UnitedKingdom);
+
+        // The attacker wins, but the territory stays with its Italian owner
+        movement::remove_unit(&mut state, 200);
+        let mut won = combat.clone();
+        won.defender_units.clear();
+        let map = GameMap::new();
+        let events = finalize_battle(&mut state, &map, &won);
+        assert!(matches!(events[0], GameEvent::BattleEnded { attacker_won: true, .. }));
+        assert_eq!(state.territories[t::SOUTHERN_FRANCE as usize].owner, Some(Power::Italy));
+        assert!(movement::find_unit(&state, 300).is_some());
+    }
 }
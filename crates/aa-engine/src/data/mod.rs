@@ -9,9 +9,10 @@ pub mod strait_ids;
 pub mod territories;
 pub mod sea_zones;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
 
-use crate::territory::{TerritoryDef, SeaZoneDef, TerritoryId, SeaZoneId, RegionId};
+use crate::territory::{TerritoryDef, SeaZoneDef, TerritoryId, SeaZoneId, RegionId, StraitId};
 use strait_ids::StraitDef;
 
 /// The complete static game map. Constructed once and stored on `Engine`.
@@ -20,15 +21,33 @@ pub struct GameMap {
     pub territories: Vec<TerritoryDef>,
     pub sea_zones: Vec<SeaZoneDef>,
     pub straits: Vec<StraitDef>,
+    /// Strait joining each pair of sea zones, keyed both ways round.
+    sea_straits: HashMap<(SeaZoneId, SeaZoneId), StraitId>,
+    /// All-pairs land distances (row-major, `u32::MAX` = unreachable), filled on first use.
+    land_distances: OnceLock<Vec<u32>>,
+    /// All-pairs sea distances (row-major, `u32::MAX` = unreachable), filled on first use.
+    sea_distances: OnceLock<Vec<u32>>,
 }
 
 impl GameMap {
     /// Construct the full map from compiled-in data.
     pub fn new() -> Self {
+        let straits = strait_ids::build_strait_defs();
+
+        let mut sea_straits = HashMap::new();
+        for strait in &straits {
+            let (a, b) = strait.connects_seas;
+            sea_straits.insert((a, b), strait.id);
+            sea_straits.insert((b, a), strait.id);
+        }
+
         GameMap {
             territories: territories::build_territory_defs(),
             sea_zones: sea_zones::build_sea_zone_defs(),
-            straits: strait_ids::build_strait_defs(),
+            straits,
+            sea_straits,
+            land_distances: OnceLock::new(),
+            sea_distances: OnceLock::new(),
         }
     }
 
@@ -242,29 +261,59 @@ impl GameMap {
     // ------------------------------------------------------------------
 
     /// Shortest land distance between two territories.
-    /// Returns `None` if not connected by land.
+    /// Returns `None` if not connected by land. Served from the distance cache.
     pub fn land_distance(&self, from: TerritoryId, to: TerritoryId) -> Option<u32> {
-        if from == to {
-            return Some(0);
-        }
-        self.find_land_path(from, to)
-            .map(|path| (path.len() - 1) as u32)
+        let n = self.territories.len();
+        let d = self.land_distance_table()[from as usize * n + to as usize];
+        (d != u32::MAX).then_some(d)
     }
 
     /// Shortest sea distance between two sea zones.
-    /// Returns `None` if not connected by sea.
+    /// Returns `None` if not connected by sea. Served from the distance cache.
     pub fn sea_distance(&self, from: SeaZoneId, to: SeaZoneId) -> Option<u32> {
-        if from == to {
-            return Some(0);
-        }
-        self.find_sea_path(from, to)
-            .map(|path| (path.len() - 1) as u32)
+        let n = self.sea_zones.len();
+        let d = self.sea_distance_table()[from as usize * n + to as usize];
+        (d != u32::MAX).then_some(d)
+    }
+
+    /// Build the land and sea distance caches up front, so the first AI
+    /// query doesn't pay for them. Calling this more than once is free.
+    pub fn precompute_distances(&self) {
+        self.land_distance_table();
+        self.sea_distance_table();
+    }
+
+    fn land_distance_table(&self) -> &[u32] {
+        self.land_distances.get_or_init(|| {
+            let n = self.territories.len();
+            let mut table = Vec::with_capacity(n * n);
+            for origin in 0..n {
+                table.extend(bfs_distances(n, origin, |i| &self.territories[i].adjacent_land));
+            }
+            table
+        })
+    }
+
+    fn sea_distance_table(&self) -> &[u32] {
+        self.sea_distances.get_or_init(|| {
+            let n = self.sea_zones.len();
+            let mut table = Vec::with_capacity(n * n);
+            for origin in 0..n {
+                table.extend(bfs_distances(n, origin, |i| &self.sea_zones[i].adjacent_sea));
+            }
+            table
+        })
     }
 
     // ------------------------------------------------------------------
     // Straits / Canals
     // ------------------------------------------------------------------
 
+    /// The strait ships must use to pass directly between two sea zones.
+    pub fn strait_between(&self, a: SeaZoneId, b: SeaZoneId) -> Option<StraitId> {
+        self.sea_straits.get(&(a, b)).copied()
+    }
+
     /// Check if a strait is passable given a control predicate.
     /// `controls_fn` takes a TerritoryId and returns whether the relevant power
     /// controls (or is friendly to the controller of) that territory.
@@ -280,6 +329,124 @@ impl GameMap {
     }
 }
 
+// ------------------------------------------------------------------
+// Integrity
+// ------------------------------------------------------------------
+
+impl GameMap {
+    /// Check the map's structural invariants: every ID matches its index,
+    /// every neighbour ID is in range, adjacency is symmetric (land-land,
+    /// sea-sea and land-sea) and nothing is adjacent to itself. Returns every
+    /// problem found, so a modded map can be rejected with a full report.
+    pub fn self_check(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        for (i, def) in self.territories.iter().enumerate() {
+            if def.id as usize != i {
+                problems.push(format!(
+                    "Territory '{}' has id {} but is at index {}",
+                    def.name, def.id, i
+                ));
+            }
+            for &neighbor in &def.adjacent_land {
+                match self.territories.get(neighbor as usize) {
+                    None => problems.push(format!(
+                        "Territory '{}' lists unknown territory {}",
+                        def.name, neighbor
+                    )),
+                    Some(other) if !other.adjacent_land.contains(&def.id) => problems.push(format!(
+                        "Land adjacency not symmetric: '{}' lists '{}' but not vice versa",
+                        def.name, other.name
+                    )),
+                    Some(_) => {}
+                }
+            }
+            for &sz_id in &def.adjacent_sea {
+                match self.sea_zones.get(sz_id as usize) {
+                    None => problems.push(format!(
+                        "Territory '{}' lists unknown sea zone {}",
+                        def.name, sz_id
+                    )),
+                    Some(zone) if !zone.adjacent_land.contains(&def.id) => problems.push(format!(
+                        "Territory '{}' lists sea zone '{}' but sea zone doesn't list territory back",
+                        def.name, zone.name
+                    )),
+                    Some(_) => {}
+                }
+            }
+            if def.adjacent_land.contains(&def.id) {
+                problems.push(format!("Territory '{}' is self-adjacent (land)", def.name));
+            }
+        }
+
+        for (i, def) in self.sea_zones.iter().enumerate() {
+            if def.id as usize != i {
+                problems.push(format!(
+                    "Sea zone '{}' has id {} but is at index {}",
+                    def.name, def.id, i
+                ));
+            }
+            for &neighbor in &def.adjacent_sea {
+                match self.sea_zones.get(neighbor as usize) {
+                    None => problems.push(format!(
+                        "Sea zone '{}' lists unknown sea zone {}",
+                        def.name, neighbor
+                    )),
+                    Some(other) if !other.adjacent_sea.contains(&def.id) => problems.push(format!(
+                        "Sea adjacency not symmetric: '{}' lists '{}' but not vice versa",
+                        def.name, other.name
+                    )),
+                    Some(_) => {}
+                }
+            }
+            for &t_id in &def.adjacent_land {
+                match self.territories.get(t_id as usize) {
+                    None => problems.push(format!(
+                        "Sea zone '{}' lists unknown territory {}",
+                        def.name, t_id
+                    )),
+                    Some(tdef) if !tdef.adjacent_sea.contains(&def.id) => problems.push(format!(
+                        "Sea zone '{}' lists territory '{}' but territory doesn't list sea zone back",
+                        def.name, tdef.name
+                    )),
+                    Some(_) => {}
+                }
+            }
+            if def.adjacent_sea.contains(&def.id) {
+                problems.push(format!("Sea zone '{}' is self-adjacent", def.name));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+/// Single-source BFS over a graph of `n` nodes; unreachable nodes get `u32::MAX`.
+fn bfs_distances<'a, F>(n: usize, origin: usize, neighbors: F) -> Vec<u32>
+where
+    F: Fn(usize) -> &'a Vec<u16>,
+{
+    let mut dist = vec![u32::MAX; n];
+    let mut queue = VecDeque::new();
+    dist[origin] = 0;
+    queue.push_back(origin);
+
+    while let Some(current) = queue.pop_front() {
+        for &neighbor in neighbors(current) {
+            let neighbor = neighbor as usize;
+            if dist[neighbor] == u32::MAX {
+                dist[neighbor] = dist[current] + 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    dist
+}
+
 impl Default for GameMap {
     fn default() -> Self {
         Self::new()
@@ -315,110 +482,58 @@ mod tests {
         assert_eq!(m.sea_zones.len(), sea_zone_ids::SEA_ZONE_COUNT);
     }
 
-    #[test]
-    fn territory_ids_match_indices() {
-        let m = map();
-        for (i, def) in m.territories.iter().enumerate() {
-            assert_eq!(
-                def.id as usize, i,
-                "Territory '{}' has id {} but is at index {}",
-                def.name, def.id, i
-            );
-        }
-    }
-
-    #[test]
-    fn sea_zone_ids_match_indices() {
-        let m = map();
-        for (i, def) in m.sea_zones.iter().enumerate() {
-            assert_eq!(
-                def.id as usize, i,
-                "Sea zone '{}' has id {} but is at index {}",
-                def.name, def.id, i
-            );
-        }
-    }
-
     #[test]
     fn strait_count_matches() {
         let m = map();
         assert_eq!(m.straits.len(), strait_ids::STRAIT_COUNT);
     }
 
-    // ----- Adjacency symmetry -----
+    // ----- Structural invariants (ids, symmetry, self-adjacency) -----
 
     #[test]
-    fn land_adjacency_is_symmetric() {
-        let m = map();
-        for def in &m.territories {
-            for &neighbor in &def.adjacent_land {
-                let neighbor_def = &m.territories[neighbor as usize];
-                assert!(
-                    neighbor_def.adjacent_land.contains(&def.id),
-                    "Land adjacency not symmetric: '{}' lists '{}' but not vice versa",
-                    def.name, neighbor_def.name
-                );
-            }
-        }
+    fn self_check_passes_on_default_map() {
+        assert_eq!(map().self_check(), Ok(()));
     }
 
     #[test]
-    fn sea_adjacency_is_symmetric() {
-        let m = map();
-        for def in &m.sea_zones {
-            for &neighbor in &def.adjacent_sea {
-                let neighbor_def = &m.sea_zones[neighbor as usize];
-                assert!(
-                    neighbor_def.adjacent_sea.contains(&def.id),
-                    "Sea adjacency not symmetric: '{}' lists '{}' but not vice versa",
-                    def.name, neighbor_def.name
-                );
-            }
-        }
-    }
+    fn self_check_reports_broken_adjacency() {
+        let mut m = map();
+        m.territories[t::GERMANY as usize].adjacent_land.push(t::GERMANY);
+        m.sea_zones[sz::SZ_NORTH_SEA as usize]
+            .adjacent_land
+            .retain(|&tid| tid != t::UNITED_KINGDOM);
 
-    #[test]
-    fn land_sea_adjacency_is_symmetric() {
-        let m = map();
-        // If a territory lists a sea zone, that sea zone must list the territory
-        for tdef in &m.territories {
-            for &sz_id in &tdef.adjacent_sea {
-                let sz_def = &m.sea_zones[sz_id as usize];
-                assert!(
-                    sz_def.adjacent_land.contains(&tdef.id),
-                    "Territory '{}' lists sea zone '{}' but sea zone doesn't list territory back",
-                    tdef.name, sz_def.name
-                );
-            }
-        }
-        // And vice versa
-        for sz_def in &m.sea_zones {
-            for &t_id in &sz_def.adjacent_land {
-                let tdef = &m.territories[t_id as usize];
-                assert!(
-                    tdef.adjacent_sea.contains(&sz_def.id),
-                    "Sea zone '{}' lists territory '{}' but territory doesn't list sea zone back",
-                    sz_def.name, tdef.name
-                );
-            }
-        }
+        let problems = m.self_check().unwrap_err();
+        assert!(problems.iter().any(|p| p.contains("self-adjacent")));
+        assert!(problems.iter().any(|p| p.contains("doesn't list territory back")));
     }
 
     #[test]
-    fn no_self_adjacency() {
-        let m = map();
-        for def in &m.territories {
+    fn self_check_reports_every_kind_of_problem() {
+        let mut m = map();
+        m.territories[t::FRANCE as usize].id = t::GERMANY;
+        m.sea_zones[sz::SZ_BALTIC_SEA as usize].id = sz::SZ_NORTH_SEA;
+        m.territories[t::POLAND as usize].adjacent_land.push(t::FRANCE);
+        m.territories[t::POLAND as usize].adjacent_land.push(9999);
+        m.sea_zones[sz::SZ_WESTERN_MED as usize].adjacent_sea.push(sz::SZ_BALTIC_SEA);
+        m.sea_zones[sz::SZ_WESTERN_MED as usize].adjacent_sea.push(sz::SZ_WESTERN_MED);
+        m.sea_zones[sz::SZ_WESTERN_MED as usize].adjacent_land.push(t::POLAND);
+
+        let problems = m.self_check().unwrap_err();
+        for expected in [
+            "Territory 'France' has id",
+            "Sea zone 'Sea Zone 108' has id",
+            "Land adjacency not symmetric: 'Poland' lists 'France'",
+            "lists unknown territory 9999",
+            "Sea adjacency not symmetric",
+            "is self-adjacent",
+            "lists territory 'Poland' but territory doesn't list sea zone back",
+        ] {
             assert!(
-                !def.adjacent_land.contains(&def.id),
-                "Territory '{}' is self-adjacent (land)",
-                def.name
-            );
-        }
-        for def in &m.sea_zones {
-            assert!(
-                !def.adjacent_sea.contains(&def.id),
-                "Sea zone '{}' is self-adjacent",
-                def.name
+                problems.iter().any(|p| p.contains(expected)),
+                "missing {:?} in {:?}",
+                expected,
+                problems
             );
         }
     }
@@ -571,9 +686,37 @@ mod tests {
     // ----- Straits / Canals -----
 
     #[test]
-    fn four_straits_defined() {
+    fn six_straits_defined() {
         let m = map();
-        assert_eq!(m.straits.len(), 4);
+        assert_eq!(m.straits.len(), 6);
+    }
+
+    #[test]
+    fn gibraltar_gates_both_atlantic_approaches() {
+        let m = map();
+        assert_eq!(
+            m.strait_between(sz::SZ_OFF_GIBRALTAR, sz::SZ_WESTERN_MED),
+            Some(strait_ids::STRAIT_GIBRALTAR)
+        );
+        assert_eq!(
+            m.strait_between(sz::SZ_WESTERN_MED, sz::SZ_OFF_MOROCCO),
+            Some(strait_ids::STRAIT_GIBRALTAR_SOUTH)
+        );
+        assert_eq!(m.straits[strait_ids::STRAIT_GIBRALTAR_SOUTH as usize].controlled_by, t::GIBRALTAR);
+    }
+
+    #[test]
+    fn strait_between_looks_up_either_direction() {
+        let m = map();
+        assert_eq!(
+            m.strait_between(sz::SZ_BLACK_SEA, sz::SZ_AEGEAN_SEA),
+            Some(strait_ids::STRAIT_TURKISH)
+        );
+        assert_eq!(
+            m.strait_between(sz::SZ_AEGEAN_SEA, sz::SZ_BLACK_SEA),
+            Some(strait_ids::STRAIT_TURKISH)
+        );
+        assert_eq!(m.strait_between(sz::SZ_NORTH_SEA, sz::SZ_SKAGERRAK), None);
     }
 
     #[test]
@@ -600,4 +743,36 @@ mod tests {
         assert!(!m.strait_is_passable(strait_ids::STRAIT_SUEZ, |_| false));
     }
 
+
+    #[test]
+    fn test_cached_distances_match_bfs() {
+        let m = map();
+        m.precompute_distances();
+
+        let land_pairs = [
+            (t::GERMANY, t::WESTERN_GERMANY),
+            (t::GERMANY, t::HOLLAND_BELGIUM),
+            (t::FRANCE, t::GERMANY),
+            (t::GERMANY, t::UNITED_KINGDOM),
+            (t::GERMANY, t::GERMANY),
+        ];
+        for (a, b) in land_pairs {
+            let bfs = if a == b {
+                Some(0)
+            } else {
+                m.find_land_path(a, b).map(|p| (p.len() - 1) as u32)
+            };
+            assert_eq!(m.land_distance(a, b), bfs, "land {} -> {}", a, b);
+        }
+
+        let sea_pairs = [
+            (sz::SZ_NORTH_SEA, sz::SZ_BALTIC_SEA),
+            (sz::SZ_NORTH_SEA, sz::SZ_CENTRAL_ATLANTIC),
+            (sz::SZ_JAPAN_EAST, sz::SZ_CORAL_SEA),
+        ];
+        for (a, b) in sea_pairs {
+            let bfs = m.find_sea_path(a, b).map(|p| (p.len() - 1) as u32);
+            assert_eq!(m.sea_distance(a, b), bfs, "sea {} -> {}", a, b);
+        }
+    }
 }
@@ -7,12 +7,14 @@
 use crate::territory::{StraitId, TerritoryId, SeaZoneId};
 
 /// Total number of straits / canals.
-pub const STRAIT_COUNT: usize = 4;
+pub const STRAIT_COUNT: usize = 6;
 
 pub const STRAIT_TURKISH: StraitId = 0;
 pub const STRAIT_SUEZ: StraitId = 1;
 pub const STRAIT_PANAMA: StraitId = 2;
 pub const STRAIT_DANISH: StraitId = 3;
+pub const STRAIT_GIBRALTAR: StraitId = 4;
+pub const STRAIT_GIBRALTAR_SOUTH: StraitId = 5;
 
 /// Static definition of a strait or canal.
 #[derive(Clone, Debug)]
@@ -27,7 +29,7 @@ pub struct StraitDef {
     pub connects_land: Option<(TerritoryId, TerritoryId)>,
 }
 
-/// Build the 4 strait/canal definitions.
+/// Build the strait/canal definitions.
 pub fn build_strait_defs() -> Vec<StraitDef> {
     use super::territory_ids as t;
     use super::sea_zone_ids as sz;
@@ -66,5 +68,21 @@ pub fn build_strait_defs() -> Vec<StraitDef> {
             connects_seas: (sz::SZ_BALTIC_SEA, sz::SZ_SKAGERRAK),
             connects_land: None,
         },
+        // Strait of Gibraltar: Gibraltar controls passage into the Mediterranean.
+        // The Western Med borders two Atlantic zones, so the strait gates both.
+        StraitDef {
+            id: STRAIT_GIBRALTAR,
+            name: "Strait of Gibraltar",
+            controlled_by: t::GIBRALTAR,
+            connects_seas: (sz::SZ_OFF_GIBRALTAR, sz::SZ_WESTERN_MED),
+            connects_land: None,
+        },
+        StraitDef {
+            id: STRAIT_GIBRALTAR_SOUTH,
+            name: "Strait of Gibraltar",
+            controlled_by: t::GIBRALTAR,
+            connects_seas: (sz::SZ_OFF_MOROCCO, sz::SZ_WESTERN_MED),
+            connects_land: None,
+        },
     ]
 }
@@ -1,12 +1,69 @@
 //! Deterministic RNG for reproducible dice rolls.
 //!
 //! Uses ChaCha8 seeded from the game state's RNG seed.
-//! Every dice roll consumes from the same stream, ensuring
-//! deterministic replay from the same seed + action sequence.
+//! Non-combat rolls consume from one shared stream; combat rolls come from a
+//! per-battle stream (see `RngMode`), ensuring deterministic replay from the
+//! same seed + action sequence regardless of battle order.
 
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::power::Power;
+use crate::territory::RegionId;
+
+/// How combat dice streams are derived from the game seed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum RngMode {
+    /// Legacy saves: every roll consumes from the shared `rng_counter` stream.
+    #[default]
+    SharedCounter,
+    /// Each battle round rolls from its own stream keyed by the battle and round,
+    /// so outcomes do not depend on the order battles are resolved in.
+    PerBattle,
+}
+
+/// Identifies one battle for its dice streams. A region is fought over at
+/// most once in a power's turn, so the key is unique without depending on
+/// the order that turn's battles are resolved in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BattleKey {
+    pub turn: u32,
+    pub attacker: Power,
+    pub location: RegionId,
+    /// Battles already fought at `location` this game.
+    pub prior_battles: u32,
+}
+
+/// Derive the seed for one battle round's dice stream.
+pub fn battle_seed(seed: u64, battle: BattleKey, round: u32) -> u64 {
+    let region = match battle.location {
+        RegionId::Land(id) => id as u64,
+        RegionId::Sea(id) => (1 << 16) | id as u64,
+    };
+    let x = mix(seed ^ (region << 32) ^ round as u64);
+    mix(x ^ ((battle.turn as u64) << 32) ^ ((battle.attacker as u64) << 24) ^ battle.prior_battles as u64)
+}
+
+/// SplitMix64 finalizer: stable across platforms and Rust versions.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Where the game's dice stream stands: the seed and how many rolls have
+/// been drawn from the shared stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RngState {
+    pub seed: u64,
+    pub counter: u64,
+}
 
 /// A deterministic dice roller backed by ChaCha8.
 pub struct DeterministicRng {
@@ -76,4 +133,22 @@ mod tests {
         rng.roll_multiple_d6(5);
         assert_eq!(rng.counter(), 6);
     }
+
+    #[test]
+    fn test_battle_seed_varies_by_battle_and_round() {
+        let key = BattleKey {
+            turn: 1,
+            attacker: Power::Germany,
+            location: RegionId::Land(5),
+            prior_battles: 0,
+        };
+        let a = battle_seed(42, key, 1);
+        assert_eq!(a, battle_seed(42, key, 1));
+        assert_ne!(a, battle_seed(42, key, 2));
+        assert_ne!(a, battle_seed(42, BattleKey { location: RegionId::Sea(5), ..key }, 1));
+        assert_ne!(a, battle_seed(43, key, 1));
+        assert_ne!(a, battle_seed(42, BattleKey { turn: 2, ..key }, 1));
+        assert_ne!(a, battle_seed(42, BattleKey { attacker: Power::Italy, ..key }, 1));
+        assert_ne!(a, battle_seed(42, BattleKey { prior_battles: 1, ..key }, 1));
+    }
 }
@@ -41,6 +41,31 @@ pub enum EngineError {
     #[error("Game setup error: {0}")]
     SetupError(String),
 
+    #[error("Events before {oldest} are no longer held")]
+    EventsDropped { oldest: u64 },
+
     #[error("Internal engine error: {0}")]
     Internal(String),
 }
+
+impl EngineError {
+    /// Stable machine-readable code for the error category, for clients that
+    /// need to react to specific failures without parsing messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            EngineError::NotYourTurn { .. } => "NOT_YOUR_TURN",
+            EngineError::WrongPhase { .. } => "WRONG_PHASE",
+            EngineError::InsufficientIPCs { .. } => "INSUFFICIENT_IPCS",
+            EngineError::IllegalMove { .. } => "ILLEGAL_MOVE",
+            EngineError::InvalidAction { .. } => "INVALID_ACTION",
+            EngineError::UnitNotFound { .. } => "UNIT_NOT_FOUND",
+            EngineError::TerritoryNotFound { .. } => "TERRITORY_NOT_FOUND",
+            EngineError::CannotUndo { .. } => "CANNOT_UNDO",
+            EngineError::Serialization(_) => "SERIALIZATION",
+            EngineError::Deserialization(_) => "DESERIALIZATION",
+            EngineError::SetupError(_) => "SETUP_ERROR",
+            EngineError::EventsDropped { .. } => "EVENTS_DROPPED",
+            EngineError::Internal(_) => "INTERNAL",
+        }
+    }
+}
@@ -1,9 +1,13 @@
 //! Income collection: calculate IPC income from territories, national objectives, and convoy disruption.
 
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::action::{GameEvent, IpcChangeReason};
 use crate::data::GameMap;
 use crate::power::Power;
 use crate::state::GameState;
-use crate::territory::SeaZoneId;
+use crate::territory::{SeaZoneId, TerritoryId};
 use crate::unit::UnitType;
 
 /// Calculate total income for a power.
@@ -11,24 +15,32 @@ pub fn calculate_income(state: &GameState, map: &GameMap, power: Power) -> Incom
     let base = calculate_base_income(state, map, power);
     let objectives = calculate_national_objectives(state, map, power);
     let convoy_losses = calculate_convoy_disruption(state, map, power);
+    let upkeep = calculate_upkeep(state, power);
 
     IncomeBreakdown {
         base_income: base,
         objective_bonus: objectives,
         convoy_losses,
-        total: (base + objectives).saturating_sub(convoy_losses),
+        upkeep,
+        total: (base + objectives)
+            .saturating_sub(convoy_losses)
+            .saturating_sub(upkeep),
     }
 }
 
 /// Income breakdown for display.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct IncomeBreakdown {
     pub base_income: u32,
     pub objective_bonus: u32,
     pub convoy_losses: u32,
+    pub upkeep: u32,
     pub total: u32,
 }
 
-/// Calculate base IPC income from controlled territories.
+/// Calculate base IPC income from controlled territories, including any
+/// captured neutrals (most of which are worth nothing).
 fn calculate_base_income(state: &GameState, map: &GameMap, power: Power) -> u32 {
     let mut income = 0;
     for (i, territory) in state.territories.iter().enumerate() {
@@ -56,65 +68,181 @@ fn calculate_national_objectives(state: &GameState, map: &GameMap, power: Power)
     }
 }
 
-/// Calculate convoy disruption losses.
-fn calculate_convoy_disruption(state: &GameState, map: &GameMap, power: Power) -> u32 {
-    let mut losses = 0u32;
+/// Unit upkeep owed under the optional upkeep rule: `upkeep_per_unit` for
+/// every unit the power has on the board.
+fn calculate_upkeep(state: &GameState, power: Power) -> u32 {
+    let per_unit = state.settings.upkeep_per_unit;
+    if per_unit == 0 {
+        return 0;
+    }
+    let land = state.territories.iter().flat_map(|t| &t.units);
+    let sea = state.sea_zones.iter().flat_map(|sz| &sz.units);
+    let count = land.chain(sea).filter(|u| u.owner == power).count() as u32;
+    count * per_unit
+}
+
+/// Convoy losses suffered by one power in one convoy zone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConvoyLoss {
+    pub zone: SeaZoneId,
+    pub lost_ipcs: u32,
+    /// IPC damage attributed to each raiding power; sums to `lost_ipcs`.
+    pub raiders: Vec<(Power, u32)>,
+}
+
+/// IPC damage a single raiding unit inflicts on a convoy.
+pub fn convoy_raid_damage(unit_type: UnitType) -> u32 {
+    match unit_type {
+        UnitType::Submarine => 2,
+        UnitType::Destroyer | UnitType::Cruiser | UnitType::Battleship | UnitType::Carrier => 1,
+        _ => 0,
+    }
+}
+
+/// Territories whose income runs through a convoy zone: those that list the
+/// zone as a convoy route, or every adjacent territory if none do.
+fn convoy_linked_territories(map: &GameMap, zone: SeaZoneId) -> Vec<TerritoryId> {
+    let linked: Vec<TerritoryId> = map
+        .territories
+        .iter()
+        .filter(|t| t.convoys_from.contains(&zone))
+        .map(|t| t.id)
+        .collect();
+    if linked.is_empty() {
+        map.sea_zone(zone).adjacent_land.clone()
+    } else {
+        linked
+    }
+}
+
+/// Convoy losses for a power, per raided zone. Every enemy raider in a zone
+/// adds its damage; the total is capped at the IPC value of the power's
+/// territories linked to that zone and split between the raiding powers in
+/// proportion to what each contributed.
+pub fn convoy_losses(state: &GameState, map: &GameMap, power: Power) -> Vec<ConvoyLoss> {
+    let mut losses = Vec::new();
 
-    // Check each sea zone for enemy subs/warships that disrupt convoys
     for (sz_idx, sz_state) in state.sea_zones.iter().enumerate() {
         let sz_id = sz_idx as SeaZoneId;
-        let sz_def = map.sea_zone(sz_id);
-
-        if !sz_def.is_convoy_zone {
+        if !map.sea_zone(sz_id).is_convoy_zone {
             continue;
         }
 
-        // Check for enemy units in this convoy zone
-        let mut enemy_disruption = 0u32;
+        // Raw damage contributed by each raiding power
+        let mut raw: Vec<(Power, u32)> = Vec::new();
         for unit in &sz_state.units {
-            if unit.owner != power && state.political.are_at_war(power, unit.owner) {
-                match unit.unit_type {
-                    UnitType::Submarine => enemy_disruption += 2,
-                    UnitType::Destroyer | UnitType::Cruiser | UnitType::Battleship | UnitType::Carrier => {
-                        enemy_disruption += 1;
-                    }
-                    _ => {}
-                }
+            if unit.owner == power || !state.political.are_at_war(power, unit.owner) {
+                continue;
+            }
+            let damage = convoy_raid_damage(unit.unit_type);
+            if damage == 0 {
+                continue;
+            }
+            match raw.iter_mut().find(|(p, _)| *p == unit.owner) {
+                Some((_, d)) => *d += damage,
+                None => raw.push((unit.owner, damage)),
             }
         }
+        let raw_total: u32 = raw.iter().map(|&(_, d)| d).sum();
+        if raw_total == 0 {
+            continue;
+        }
 
-        if enemy_disruption > 0 {
-            // Disruption is capped at the IPC value of adjacent territories owned by this power
-            let mut adjacent_ipc = 0u32;
-            for &tid in &sz_def.adjacent_land {
-                let territory = &state.territories[tid as usize];
-                if territory.owner == Some(power) {
-                    let tdef = map.territory(tid);
-                    adjacent_ipc += tdef.ipc_value;
-                }
+        let cap: u32 = convoy_linked_territories(map, sz_id)
+            .into_iter()
+            .filter(|&tid| state.territories[tid as usize].owner == Some(power))
+            .map(|tid| map.territory(tid).ipc_value)
+            .sum();
+        let lost = raw_total.min(cap);
+        if lost == 0 {
+            continue;
+        }
+
+        // Proportional split; rounding leftovers go to the biggest raiders
+        let mut raiders: Vec<(Power, u32)> =
+            raw.iter().map(|&(p, d)| (p, d * lost / raw_total)).collect();
+        let mut order: Vec<usize> = (0..raw.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(raw[i].1));
+        let mut leftover = lost - raiders.iter().map(|&(_, d)| d).sum::<u32>();
+        for i in order.into_iter().cycle() {
+            if leftover == 0 {
+                break;
             }
-            losses += enemy_disruption.min(adjacent_ipc);
+            raiders[i].1 += 1;
+            leftover -= 1;
         }
+
+        losses.push(ConvoyLoss {
+            zone: sz_id,
+            lost_ipcs: lost,
+            raiders,
+        });
     }
 
     losses
 }
 
+/// Calculate total convoy disruption losses.
+fn calculate_convoy_disruption(state: &GameState, map: &GameMap, power: Power) -> u32 {
+    convoy_losses(state, map, power).iter().map(|l| l.lost_ipcs).sum()
+}
+
+/// Build the `IpcChanged` event for a treasury change that has already been
+/// applied to `state`.
+pub(crate) fn ipc_changed(state: &GameState, power: Power, delta: i32, reason: IpcChangeReason) -> GameEvent {
+    GameEvent::IpcChanged {
+        power,
+        delta,
+        new_total: state.powers[power as usize].ipcs,
+        reason,
+    }
+}
+
+/// Fix the current power's income as Collect Income begins, so territory
+/// won or lost later in the phase only counts from the next turn.
+pub fn snapshot_income(state: &mut GameState, map: &GameMap) {
+    let breakdown = calculate_income(state, map, state.current_power);
+    if let crate::phase::PhaseState::CollectIncome(ref mut cis) = state.phase_state {
+        cis.owed = Some(breakdown);
+    }
+}
+
 /// Apply income collection: add IPCs to power's treasury.
-pub fn apply_collect_income(state: &mut GameState, map: &GameMap) {
+/// Pays the income fixed by `snapshot_income` when the phase began, or the
+/// income as of now if there is none.
+/// Returns a `ConvoyDisrupted` event for each zone where income was lost,
+/// followed by the `IpcChanged` for the income itself.
+pub fn apply_collect_income(state: &mut GameState, map: &GameMap) -> Vec<GameEvent> {
     let power = state.current_power;
-    let breakdown = calculate_income(state, map, power);
+    let breakdown = match &state.phase_state {
+        crate::phase::PhaseState::CollectIncome(crate::phase::CollectIncomeState { owed: Some(owed), .. }) => {
+            owed.clone()
+        }
+        _ => calculate_income(state, map, power),
+    };
+    let mut events: Vec<GameEvent> = convoy_losses(state, map, power)
+        .into_iter()
+        .map(|loss| GameEvent::ConvoyDisrupted {
+            zone: loss.zone,
+            power,
+            lost_ipcs: loss.lost_ipcs,
+        })
+        .collect();
 
     let power_idx = power as usize;
     state.powers[power_idx].ipcs += breakdown.total;
+    events.push(ipc_changed(state, power, breakdown.total as i32, IpcChangeReason::Income));
 
     // Update phase state
     if let crate::phase::PhaseState::CollectIncome(ref mut cis) = state.phase_state {
         cis.base_income = breakdown.base_income;
         cis.objective_bonus = breakdown.objective_bonus;
         cis.convoy_losses = breakdown.convoy_losses;
+        cis.upkeep = breakdown.upkeep;
         cis.total_collected = breakdown.total;
     }
+
+    events
 }
 
 // =========================================================================
@@ -323,7 +451,10 @@ fn french_objectives(_state: &GameState, _map: &GameMap) -> u32 {
 mod tests {
     use super::*;
     use crate::data::GameMap;
+    use crate::data::sea_zone_ids as sz;
+    use crate::data::territory_ids as t;
     use crate::setup;
+    use crate::unit::UnitInstance;
 
     #[test]
     fn test_base_income_germany() {
@@ -355,6 +486,17 @@ mod tests {
         assert_eq!(breakdown.total, (breakdown.base_income + breakdown.objective_bonus).saturating_sub(breakdown.convoy_losses));
     }
 
+    #[test]
+    fn test_captured_neutral_counts_toward_income() {
+        let mut map = GameMap::new();
+        map.territories[t::SPAIN as usize].ipc_value = 2;
+        let mut state = setup::create_initial_state(42, &map);
+        let before = calculate_base_income(&state, &map, Power::Germany);
+
+        state.territories[t::SPAIN as usize].owner = Some(Power::Germany);
+        assert_eq!(calculate_base_income(&state, &map, Power::Germany), before + 2);
+    }
+
     #[test]
     fn test_convoy_disruption_no_enemies() {
         let map = GameMap::new();
@@ -367,6 +509,66 @@ mod tests {
         assert_eq!(losses, 0);
     }
 
+    fn convoy_test_state(map: &GameMap) -> GameState {
+        let mut state = setup::create_initial_state(42, map);
+        for sz in state.sea_zones.iter_mut() {
+            sz.units.clear();
+        }
+        state.political.war_matrix[Power::Germany as usize][Power::SovietUnion as usize] = true;
+        state.political.war_matrix[Power::SovietUnion as usize][Power::Germany as usize] = true;
+        state
+    }
+
+    #[test]
+    fn test_convoy_loss_capped_at_linked_territory_value() {
+        let map = GameMap::new();
+        let mut state = convoy_test_state(&map);
+        // Novgorod (2 IPCs) is the Soviet territory supplied through the Baltic
+        for id in [99901, 99902] {
+            state.sea_zones[sz::SZ_BALTIC_SEA as usize]
+                .units
+                .push(UnitInstance::new(id, UnitType::Submarine, Power::Germany));
+        }
+
+        let losses = convoy_losses(&state, &map, Power::SovietUnion);
+        assert_eq!(losses.len(), 1);
+        assert_eq!(losses[0].zone, sz::SZ_BALTIC_SEA);
+        assert_eq!(losses[0].lost_ipcs, map.territory(t::NOVGOROD).ipc_value);
+        assert_eq!(losses[0].raiders, vec![(Power::Germany, 2)]);
+    }
+
+    #[test]
+    fn test_single_sub_causes_partial_convoy_loss() {
+        let map = GameMap::new();
+        let mut state = convoy_test_state(&map);
+        state.sea_zones[sz::SZ_NORTH_SEA as usize]
+            .units
+            .push(UnitInstance::new(99901, UnitType::Submarine, Power::Germany));
+
+        let losses = convoy_losses(&state, &map, Power::UnitedKingdom);
+        assert_eq!(losses.len(), 1);
+        assert_eq!(losses[0].lost_ipcs, 2);
+        assert!(losses[0].lost_ipcs < map.territory(t::UNITED_KINGDOM).ipc_value);
+    }
+
+    #[test]
+    fn test_convoy_loss_split_between_raiders() {
+        let map = GameMap::new();
+        let mut state = convoy_test_state(&map);
+        let zone = &mut state.sea_zones[sz::SZ_NORTH_SEA as usize].units;
+        zone.push(UnitInstance::new(99901, UnitType::Submarine, Power::Germany));
+        zone.push(UnitInstance::new(99902, UnitType::Submarine, Power::Germany));
+        zone.push(UnitInstance::new(99903, UnitType::Submarine, Power::Italy));
+        zone.push(UnitInstance::new(99904, UnitType::Destroyer, Power::Italy));
+
+        // 7 raw damage against United Kingdom's 6 IPCs
+        let losses = convoy_losses(&state, &map, Power::UnitedKingdom);
+        assert_eq!(losses[0].lost_ipcs, 6);
+        let total: u32 = losses[0].raiders.iter().map(|&(_, d)| d).sum();
+        assert_eq!(total, 6);
+        assert_eq!(losses[0].raiders, vec![(Power::Germany, 4), (Power::Italy, 2)]);
+    }
+
     #[test]
     fn test_national_objectives_china_zero() {
         let map = GameMap::new();
@@ -389,5 +591,47 @@ mod tests {
 
         assert!(final_ipcs > initial_ipcs, "IPCs should increase after collecting income");
     }
-}
 
+    #[test]
+    fn test_upkeep_reduces_collected_income() {
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        // Leave Germany with exactly four units on the board
+        for t in state.territories.iter_mut() {
+            t.units.retain(|u| u.owner != Power::Germany);
+        }
+        for sz in state.sea_zones.iter_mut() {
+            sz.units.retain(|u| u.owner != Power::Germany);
+        }
+        for id in 0..4 {
+            state.territories[t::GERMANY as usize]
+                .units
+                .push(UnitInstance::new(99_900 + id, UnitType::Infantry, Power::Germany));
+        }
+        let standard = calculate_income(&state, &map, Power::Germany).total;
+        state.settings.upkeep_per_unit = 2;
+
+        let before = state.powers[Power::Germany as usize].ipcs;
+        state.phase_state = crate::phase::PhaseState::CollectIncome(Default::default());
+        apply_collect_income(&mut state, &map);
+        assert_eq!(state.powers[Power::Germany as usize].ipcs, before + standard - 8);
+        match &state.phase_state {
+            crate::phase::PhaseState::CollectIncome(cis) => {
+                assert_eq!(cis.upkeep, 8);
+                assert_eq!(cis.total_collected, standard - 8);
+            }
+            _ => panic!("expected collect income state"),
+        }
+    }
+
+    #[test]
+    fn test_upkeep_floors_income_at_zero() {
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        state.settings.upkeep_per_unit = 100;
+        let before = state.powers[Power::Germany as usize].ipcs;
+        apply_collect_income(&mut state, &map);
+        assert_eq!(state.powers[Power::Germany as usize].ipcs, before);
+        assert_eq!(calculate_income(&state, &map, Power::Germany).total, 0);
+    }
+}
@@ -22,12 +22,16 @@ pub mod validate;
 
 pub mod income;
 pub mod mobilize;
+pub mod obligations;
 pub mod politics;
 pub mod special;
 pub mod victory;
 
 pub mod ai;
 pub mod save;
+pub mod replay;
+pub mod simulate;
+pub mod route;
 pub mod multiplayer;
 
 pub mod data;
@@ -41,20 +45,79 @@ use state::GameState;
 pub struct Engine {
     state: GameState,
     map: GameMap,
+    /// Who controls each power, indexed by `Power as usize`. Session-local,
+    /// so it is not part of `GameState`.
+    controllers: [multiplayer::ControllerKind; 9],
+    /// Recent events for clients catching up; session-local like `controllers`.
+    events: action::EventBuffer,
+}
+
+/// A copy of everything about an `Engine` that actions change, taken by
+/// `Engine::snapshot` so a caller can undo actions it failed to commit.
+#[derive(Clone, Debug)]
+pub struct EngineSnapshot {
+    state: GameState,
+    controllers: [multiplayer::ControllerKind; 9],
+    events: action::EventBuffer,
 }
 
 impl Engine {
     /// Create a new game with default Global 1940 2nd Edition setup.
     pub fn new_game(seed: u64) -> Self {
         let map = GameMap::new();
+        map.precompute_distances();
         let state = setup::create_initial_state(seed, &map);
-        Engine { state, map }
+        Engine { state, map, controllers: Default::default(), events: Default::default() }
+    }
+
+    /// Create a new game using optional house-rule settings.
+    pub fn new_game_with_settings(seed: u64, settings: state::GameSettings) -> Self {
+        let mut engine = Self::new_game(seed);
+        engine.state.settings = settings;
+        engine
+    }
+
+    /// Create a new game whose units use `stats` in place of the built-in
+    /// table.
+    pub fn new_game_with_stats(seed: u64, stats: unit::UnitStatsTable) -> Self {
+        let mut engine = Self::new_game(seed);
+        engine.state.stats_table = Some(Box::new(stats));
+        engine
     }
 
     /// Restore an engine from a previously serialized game state.
     pub fn from_state(state: GameState) -> Self {
         let map = GameMap::new();
-        Engine { state, map }
+        map.precompute_distances();
+        Engine { state, map, controllers: Default::default(), events: Default::default() }
+    }
+
+    /// Restore an engine from a serialized game state and the options it was
+    /// played with.
+    pub fn from_state_with_options(state: GameState, options: save::GameOptions) -> Self {
+        let mut engine = Self::from_state(state);
+        engine.controllers = options.controllers;
+        engine
+    }
+
+    /// Restore an engine from a save file, with its options if it has them.
+    pub fn from_save(save: save::SaveFile) -> Self {
+        match save.options {
+            Some(options) => Self::from_state_with_options(save.state, options),
+            None => Self::from_state(save.state),
+        }
+    }
+
+    /// The game's controller assignments.
+    pub fn options(&self) -> save::GameOptions {
+        save::GameOptions {
+            controllers: self.controllers,
+        }
+    }
+
+    /// A save file of the game as it stands, options included.
+    pub fn save_file(&self, name: String, timestamp: u64) -> save::SaveFile {
+        save::SaveFile::from_state(&self.state, name, timestamp).with_options(self.options())
     }
 
     /// Get a reference to the static game map.
@@ -62,13 +125,81 @@ impl Engine {
         &self.map
     }
 
+    /// Apply a pre-game bid for `power` (see `setup::apply_bid`). Only allowed
+    /// before the first action of the game.
+    pub fn apply_bid(
+        &mut self,
+        power: power::Power,
+        extra_ipcs: u32,
+        placements: Vec<(unit::UnitType, territory::RegionId)>,
+    ) -> Result<(), EngineError> {
+        if !self.state.action_log.is_empty() {
+            return Err(EngineError::InvalidAction {
+                reason: "Bids can only be applied before the first action".into(),
+            });
+        }
+        setup::apply_bid(&mut self.state, &self.map, power, extra_ipcs, &placements)
+    }
+
+    /// Enable or disable sandbox mode, which unlocks scenario-editing actions
+    /// such as `Action::SetRelation`.
+    pub fn set_sandbox_mode(&mut self, enabled: bool) {
+        self.state.sandbox_mode = enabled;
+    }
+
+    /// Who controls `power`'s turns.
+    pub fn controller_for(&self, power: power::Power) -> multiplayer::ControllerKind {
+        self.controllers[power as usize]
+    }
+
+    /// Hand `power`'s turns to a different controller.
+    pub fn set_controller(&mut self, power: power::Power, kind: multiplayer::ControllerKind) {
+        self.controllers[power as usize] = kind;
+    }
+
     /// Submit a player action. The engine validates, applies, and returns the result.
     pub fn submit_action(&mut self, action: Action) -> Result<ActionResult, EngineError> {
         validate::validate_action_with_map(&self.state, &action, Some(&self.map))?;
+        // Debug builds check the result's integrity and reject the action,
+        // state untouched, if it breaks an invariant
+        #[cfg(debug_assertions)]
+        let before = self.state.clone();
         let result = apply::apply_action(&mut self.state, action, &self.map)?;
+        #[cfg(debug_assertions)]
+        if let Err(violations) = self.state.validate_integrity_with_map(&self.map) {
+            self.state = before;
+            return Err(EngineError::Internal(format!("GameState integrity violated: {:?}", violations)));
+        }
+        self.events.extend(result.events.iter().cloned());
         Ok(result)
     }
 
+    /// Events from `sequence` on, for a client that missed some action
+    /// results, plus the sequence to pass next time. Start from 0. Only the
+    /// most recent `EventBuffer::DEFAULT_CAPACITY` events are kept; asking
+    /// for older ones fails with `EngineError::EventsDropped`.
+    pub fn events_since(&self, sequence: u64) -> Result<(Vec<GameEvent>, u64), EngineError> {
+        self.events.since(sequence)
+    }
+
+    /// Save the game state together with the session-local controllers and
+    /// event buffer.
+    pub fn snapshot(&self) -> EngineSnapshot {
+        EngineSnapshot {
+            state: self.state.clone(),
+            controllers: self.controllers,
+            events: self.events.clone(),
+        }
+    }
+
+    /// Put the engine back as it was when `snapshot` was taken, forgetting
+    /// the events of every action since.
+    pub fn restore(&mut self, snapshot: EngineSnapshot) {
+        self.state = snapshot.state;
+        self.controllers = snapshot.controllers;
+        self.events = snapshot.events;
+    }
+
     /// Get a reference to the current game state.
     pub fn state(&self) -> &GameState {
         &self.state
@@ -79,6 +210,40 @@ impl Engine {
         &mut self.state
     }
 
+    /// Checksum of the current state; matches `ActionResult::state_checksum`.
+    pub fn state_checksum(&self) -> u64 {
+        self.state.checksum()
+    }
+
+    /// The seed this game was created with. A new game from the same seed,
+    /// fed the same actions, plays out identically. Unaffected by
+    /// `set_rng_state`; saves too old to record it report the current seed.
+    pub fn seed(&self) -> u64 {
+        self.state.creation_seed.unwrap_or(self.state.rng_seed)
+    }
+
+    /// The current position of the dice stream, for bug reproductions.
+    pub fn rng_state(&self) -> dice::RngState {
+        dice::RngState {
+            seed: self.state.rng_seed,
+            counter: self.state.rng_counter,
+        }
+    }
+
+    /// Testing tool: move the dice stream to `seed` and `counter` so a
+    /// recorded roll sequence can be replayed. Every later roll changes, so
+    /// this is only allowed in sandbox mode.
+    pub fn set_rng_state(&mut self, seed: u64, counter: u64) -> Result<(), EngineError> {
+        if !self.state.sandbox_mode {
+            return Err(EngineError::InvalidAction {
+                reason: "The RNG can only be set in sandbox mode".into(),
+            });
+        }
+        self.state.rng_seed = seed;
+        self.state.rng_counter = counter;
+        Ok(())
+    }
+
     /// Check whether the last action can be undone.
     pub fn can_undo(&self) -> bool {
         self.state
@@ -97,8 +262,22 @@ impl Engine {
     pub fn legal_actions(&self) -> Vec<LegalAction> {
         let mut actions = Vec::new();
 
+        // Battles still to fight must be fought before Conduct Combat can be
+        // confirmed; with none at all the phase is a formality
+        let (battles_left, battles_fought) = match &self.state.phase_state {
+            phase::PhaseState::Combat(cs) => (
+                !cs.pending_battles.is_empty() || cs.active_combat.is_some(),
+                !cs.resolved_battles.is_empty(),
+            ),
+            _ => (false, false),
+        };
+
         // Add the phase-specific confirm action
         let (confirm_action, description) = match self.state.current_phase {
+            phase::Phase::PurchaseAndRepair if phase::can_skip_phase(&self.state) => (
+                Action::ConfirmPurchases,
+                "Skip purchases (nothing affordable) and advance to Combat Movement",
+            ),
             phase::Phase::PurchaseAndRepair => (
                 Action::ConfirmPurchases,
                 "Confirm purchases and advance to Combat Movement",
@@ -107,6 +286,10 @@ impl Engine {
                 Action::ConfirmCombatMovement,
                 "Confirm combat moves and advance to Conduct Combat",
             ),
+            phase::Phase::ConductCombat if !battles_fought => (
+                Action::ConfirmPhase,
+                "No battles to fight; advance to Non-Combat Movement",
+            ),
             phase::Phase::ConductCombat => (
                 Action::ConfirmPhase,
                 "Confirm combat results and advance to Non-Combat Movement",
@@ -124,10 +307,26 @@ impl Engine {
                 "Collect income and end turn",
             ),
         };
-        actions.push(LegalAction {
-            action: confirm_action,
-            description: description.to_string(),
-        });
+        if !battles_left {
+            actions.push(LegalAction {
+                action: confirm_action,
+                description: description.to_string(),
+            });
+        }
+
+        // Each battle the attacker may fight next, in whatever order they like
+        if let phase::PhaseState::Combat(cs) = &self.state.phase_state {
+            for &location in &cs.pending_battles {
+                let action = Action::SelectBattle { location };
+                if self.is_action_legal(&action).is_err() {
+                    continue;
+                }
+                actions.push(LegalAction {
+                    action,
+                    description: format!("Resolve the battle in {}", self.region_name(location)),
+                });
+            }
+        }
 
         // Add Undo if available
         if self.can_undo() {
@@ -140,10 +339,214 @@ impl Engine {
         actions
     }
 
-    /// Check if a victory condition has been met.
+    /// Everything the current power can do right now: the `legal_actions`
+    /// plus the purchases, moves, landings, raids, placements and battle
+    /// steps open in the current phase, each checked against the rules. Used
+    /// by the client to offer choices without trying every action itself.
+    pub fn available_actions(&self) -> Vec<LegalAction> {
+        let mut candidates: Vec<(Action, String)> = Vec::new();
+        let power = self.state.current_power;
+
+        match &self.state.phase_state {
+            phase::PhaseState::Purchase(ps) => {
+                for &unit_type in unit::UnitType::all() {
+                    candidates.push((
+                        Action::PurchaseUnit { unit_type, count: 1 },
+                        format!(
+                            "Buy a {:?} for {} IPCs",
+                            unit_type,
+                            self.state.unit_stats(unit_type).cost
+                        ),
+                    ));
+                }
+                for &(unit_type, _) in &ps.purchases {
+                    candidates.push((
+                        Action::RemovePurchase { unit_type, count: 1 },
+                        format!("Take back a queued {:?}", unit_type),
+                    ));
+                }
+            }
+            phase::PhaseState::CombatMove(_) | phase::PhaseState::NonCombatMove(_) => {
+                let combat_move = self.state.current_phase == phase::Phase::CombatMovement;
+                for (_, unit_id) in self.idle_units(power) {
+                    let Some((_, unit)) = movement::find_unit(&self.state, unit_id) else {
+                        continue;
+                    };
+                    let unit_type = unit.unit_type;
+                    for path in movement::legal_paths(&self.state, &self.map, unit_id) {
+                        let (Some(&from), Some(&to)) = (path.first(), path.last()) else {
+                            continue;
+                        };
+                        let description = format!(
+                            "Move {:?} from {} to {}",
+                            unit_type,
+                            self.region_name(from),
+                            self.region_name(to)
+                        );
+                        let action = if combat_move {
+                            Action::MoveUnit { unit_id, path }
+                        } else {
+                            Action::MoveUnitNonCombat { unit_id, path }
+                        };
+                        candidates.push((action, description));
+                    }
+                }
+                // Aircraft that flew this turn may come down anywhere in range
+                if !combat_move {
+                    let regions: Vec<territory::RegionId> = (0..self.state.territories.len())
+                        .map(|i| territory::RegionId::Land(i as territory::TerritoryId))
+                        .chain((0..self.state.sea_zones.len()).map(|i| territory::RegionId::Sea(i as territory::SeaZoneId)))
+                        .collect();
+                    for (from, unit_id) in self.state.units_of_power(power) {
+                        let Some((_, unit)) = movement::find_unit(&self.state, unit_id) else {
+                            continue;
+                        };
+                        let is_air = self.state.unit_stats(unit.unit_type).domain == unit::UnitDomain::Air;
+                        if !is_air || !unit.moved_this_turn {
+                            continue;
+                        }
+                        for &to in regions.iter().filter(|&&to| to != from) {
+                            candidates.push((
+                                Action::LandAirUnit { unit_id, territory_id: to },
+                                format!("Land {:?} in {}", unit.unit_type, self.region_name(to)),
+                            ));
+                        }
+                    }
+                }
+            }
+            phase::PhaseState::Combat(cs) => {
+                for (index, territory) in self.state.territories.iter().enumerate() {
+                    let bombers = territory
+                        .units
+                        .iter()
+                        .any(|u| u.owner == power && u.unit_type == unit::UnitType::StrategicBomber);
+                    if bombers {
+                        let territory_id = index as territory::TerritoryId;
+                        candidates.push((
+                            Action::StrategicBombingRaid { territory_id },
+                            format!("Bomb the industry in {}", self.map.territory(territory_id).name),
+                        ));
+                    }
+                }
+                if let Some(combat) = &cs.active_combat {
+                    candidates.push((Action::RollAttack, "Roll the next attack".to_string()));
+                    candidates.push((Action::RollDefense, "Roll for the defender".to_string()));
+                    candidates.push((Action::ContinueCombatRound, "Press the attack".to_string()));
+                    match ai::ai_handle_battle(&self.state, combat) {
+                        Action::SelectCasualties { casualties } => candidates.push((
+                            Action::SelectCasualties { casualties: casualties.clone() },
+                            format!("Remove {} suggested casualties", casualties.len()),
+                        )),
+                        Action::ConfirmPhase => {
+                            candidates.push((Action::ConfirmPhase, "Finish the battle".to_string()))
+                        }
+                        _ => {}
+                    }
+                    for &to in &combat.retreat_options {
+                        candidates.push((
+                            Action::AttackerRetreat { to },
+                            format!("Retreat to {}", self.region_name(to)),
+                        ));
+                    }
+                    for &unit_id in &combat.attacker_units {
+                        candidates.push((
+                            Action::SubmergeSubmarine { unit_id },
+                            format!("Submerge submarine {}", unit_id),
+                        ));
+                    }
+                }
+            }
+            phase::PhaseState::Mobilize(ms) => {
+                for &(unit_type, _) in &ms.units_to_place {
+                    let is_naval = self.state.unit_stats(unit_type).domain == unit::UnitDomain::Sea;
+                    for (index, territory) in self.state.territories.iter().enumerate() {
+                        if territory.owner != Some(power) {
+                            continue;
+                        }
+                        let territory_id = index as territory::TerritoryId;
+                        let name = &self.map.territory(territory_id).name;
+                        if !is_naval {
+                            candidates.push((
+                                Action::PlaceUnit { unit_type, territory_id, sea_zone: None },
+                                format!("Place a {:?} in {}", unit_type, name),
+                            ));
+                            continue;
+                        }
+                        for &zone in &self.map.territory(territory_id).adjacent_sea {
+                            candidates.push((
+                                Action::PlaceUnit { unit_type, territory_id, sea_zone: Some(zone) },
+                                format!(
+                                    "Place a {:?} from {} in {}",
+                                    unit_type,
+                                    name,
+                                    self.map.sea_zone(zone).name
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let mut actions = self.legal_actions();
+        actions.extend(
+            candidates
+                .into_iter()
+                .filter(|(action, _)| self.is_action_legal(action).is_ok())
+                .map(|(action, description)| LegalAction { action, description }),
+        );
+        actions
+    }
+
+    fn region_name(&self, region: territory::RegionId) -> &str {
+        match region {
+            territory::RegionId::Land(tid) => &self.map.territory(tid).name,
+            territory::RegionId::Sea(zone) => &self.map.sea_zone(zone).name,
+        }
+    }
+
+    /// The most recent purchase `power` confirmed, with the turn it was made
+    /// on, for "repeat last purchase". `None` before its first purchase.
+    pub fn last_purchase(&self, power: power::Power) -> Option<&(u32, Vec<(unit::UnitType, u32)>)> {
+        self.state.powers[power as usize].purchase_history.last()
+    }
+
+    /// Every battle resolved this game, oldest first, for the stats screen.
+    pub fn combat_history(&self) -> Vec<combat::BattleRecord> {
+        self.state.combat_history.clone()
+    }
+
+    /// Battle calculator: fight a hypothetical battle `trials` times on a
+    /// copy of the current game and report the odds. See `simulate`.
+    pub fn simulate_battle(
+        &self,
+        attacker: &simulate::BattleSide,
+        defender: &simulate::BattleSide,
+        location: territory::RegionId,
+        trials: u32,
+    ) -> Result<simulate::BattleOdds, EngineError> {
+        simulate::simulate_battle(&self.state, &self.map, attacker, defender, location, trials)
+    }
+
+    /// The cheapest way for `power`'s land units to reach and attack
+    /// `target`, over land, by sea, or both. See `route`.
+    pub fn invasion_route(&self, target: territory::TerritoryId, power: power::Power) -> Option<route::InvasionPlan> {
+        route::invasion_route(&self.state, &self.map, target, power)
+    }
+
+    /// Fog-of-war view of the game for `power`; see `GameState::state_view_for`.
+    pub fn state_view_for(&self, power: power::Power) -> GameState {
+        self.state.state_view_for(&self.map, power)
+    }
+
+    /// The victory declared so far, if any. Board conditions are only checked
+    /// at the end of a complete round, so a capture mid-round never shows up
+    /// here until every power has played.
     pub fn check_victory(&self) -> Option<GameEvent> {
-        victory::check_victory(&self.state, &self.map)
-            .map(|r| victory::victory_event(&r))
+        self.state
+            .winner
+            .map(|winner| GameEvent::VictoryAchieved { winner })
     }
 
     /// Serialize the game state to JSON (for WASM bridge).
@@ -161,12 +564,171 @@ impl Engine {
         rmp_serde::from_slice(data).map_err(|e| EngineError::Deserialization(e.to_string()))
     }
 
-    /// Get a summary string for the current game state (for save file headers, etc.)
+    /// Get structured information about the current turn.
+    pub fn turn_info(&self) -> state::TurnInfo {
+        state::TurnInfo {
+            turn_number: self.state.turn_number,
+            power: self.state.current_power,
+            phase: self.state.current_phase,
+            current_ipcs: self.state.powers[self.state.current_power as usize].ipcs,
+        }
+    }
+
+    /// Regions the unit could legally move to in the current movement phase
+    /// (see `movement::legal_destinations`).
+    pub fn legal_destinations(&self, unit_id: unit::UnitId) -> Vec<territory::RegionId> {
+        movement::legal_destinations(&self.state, &self.map, unit_id)
+    }
+
+    /// Regions with enemy units the unit could attack this Combat Movement
+    /// (see `movement::attackable_targets`).
+    pub fn attackable_targets(&self, unit_id: unit::UnitId) -> Vec<territory::RegionId> {
+        movement::attackable_targets(&self.state, &self.map, unit_id)
+    }
+
+    /// Owner, units, coastline, bombardment, AAA and scramble cover of
+    /// `region`, for battle UIs; `None` if it is not on the map. See
+    /// `combat::region_combat_context`.
+    pub fn region_combat_context(&self, region: territory::RegionId) -> Option<combat::RegionCombatContext> {
+        combat::region_combat_context(&self.state, &self.map, region)
+    }
+
+    /// Why the unit cannot move right now, or `None` if it can (see
+    /// `movement::movement_block_reason`).
+    pub fn movement_block_reason(&self, unit_id: unit::UnitId) -> Option<String> {
+        movement::movement_block_reason(&self.state, &self.map, unit_id)
+    }
+
+    /// What `power` would collect if income were collected now, component
+    /// by component. Uses the same calculation as the Collect Income phase.
+    pub fn income_breakdown(&self, power: power::Power) -> income::IncomeBreakdown {
+        income::calculate_income(&self.state, &self.map, power)
+    }
+
+    /// Combat moves recorded so far this phase, each of which can be taken
+    /// back with `Action::UndoMove`. Empty outside the Combat Movement phase.
+    pub fn recorded_moves(&self) -> Vec<phase::PlannedMove> {
+        match self.state.phase_state {
+            phase::PhaseState::CombatMove(ref cms) => cms.moves.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// `power`'s units that have not moved yet this turn, during its
+    /// movement phases. Empty in other phases or on another power's turn.
+    pub fn idle_units(&self, power: power::Power) -> Vec<(territory::RegionId, unit::UnitId)> {
+        let moving = matches!(
+            self.state.current_phase,
+            phase::Phase::CombatMovement | phase::Phase::NonCombatMovement
+        );
+        if !moving || self.state.current_power != power {
+            return Vec::new();
+        }
+        self.state
+            .find_units(|u, _| u.owner == power && !u.moved_this_turn)
+    }
+
+    /// Number of units `idle_units` would return.
+    pub fn idle_unit_count(&self, power: power::Power) -> usize {
+        self.idle_units(power).len()
+    }
+
+    /// Full path of every move recorded this movement phase, in the order
+    /// the moves were made, for animating units in transit. Empty outside
+    /// the two movement phases.
+    pub fn planned_paths(&self) -> Vec<(unit::UnitId, Vec<territory::RegionId>)> {
+        let moves = match self.state.phase_state {
+            phase::PhaseState::CombatMove(ref cms) => &cms.moves,
+            phase::PhaseState::NonCombatMove(ref ncms) => &ncms.moves,
+            _ => return Vec::new(),
+        };
+        moves.iter().map(|m| (m.unit_id, m.path.clone())).collect()
+    }
+
+    /// Battles the current combat moves would trigger if combat movement were
+    /// confirmed now. Empty outside the Combat Movement phase.
+    pub fn preview_pending_combats(&self) -> Vec<territory::RegionId> {
+        if self.state.current_phase != phase::Phase::CombatMovement {
+            return Vec::new();
+        }
+        movement::identify_pending_combats(&self.state, self.state.current_power)
+    }
+
+    /// Suggest casualties for the side currently choosing them in the active
+    /// battle, for an "auto-assign" button. Empty if no selection is pending.
+    pub fn suggest_casualties(&self) -> Vec<unit::UnitId> {
+        use combat::CombatSubPhase;
+        let phase::PhaseState::Combat(ref cs) = self.state.phase_state else {
+            return Vec::new();
+        };
+        let Some(ref active) = cs.active_combat else {
+            return Vec::new();
+        };
+        match active.sub_phase {
+            CombatSubPhase::AttackerSelectsCasualties
+            | CombatSubPhase::AttackerSubmarineStrikeCasualties
+            | CombatSubPhase::AAFireCasualties => {
+                combat::suggest_casualties(&self.state, active, false, active.pending_defender_hits)
+            }
+            CombatSubPhase::DefenderSelectsCasualties
+            | CombatSubPhase::DefenderSubmarineStrikeCasualties
+            | CombatSubPhase::ShoreBombardmentCasualties => {
+                combat::suggest_casualties(&self.state, active, true, active.pending_attacker_hits)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Let the AI play every power's turn until it is `target`'s turn, returning
+    /// all events produced along the way. Stops early if the game is won.
+    pub fn advance_to_power(
+        &mut self,
+        target: power::Power,
+        difficulty: ai::AiDifficulty,
+    ) -> Result<Vec<GameEvent>, EngineError> {
+        if self.state.powers[target as usize].surrendered {
+            return Err(EngineError::InvalidAction {
+                reason: format!("{} has surrendered and will never take a turn", target.name()),
+            });
+        }
+
+        let mut events = Vec::new();
+        // Each other power gets at most one turn before the target comes up
+        for _ in 0..power::TURN_ORDER.len() {
+            if self.state.current_power == target || self.check_victory().is_some() {
+                return Ok(events);
+            }
+            let power = self.state.current_power;
+            for action in ai::ai_play_turn(&self.state, &self.map, difficulty) {
+                events.extend(self.submit_action(action)?.events);
+            }
+            if self.state.current_power == power {
+                return Err(EngineError::Internal(format!(
+                    "AI did not finish {}'s turn",
+                    power.name()
+                )));
+            }
+        }
+
+        if self.state.current_power == target || self.check_victory().is_some() {
+            Ok(events)
+        } else {
+            Err(EngineError::Internal(format!(
+                "{} was not reached after a full round of turns",
+                target.name()
+            )))
+        }
+    }
+
+    /// List what the current power still has to resolve before advancing.
+    pub fn pending_obligations(&self) -> Vec<obligations::Obligation> {
+        obligations::pending_obligations(&self.state, &self.map)
+    }
+
+    /// Get a summary string for the current game state (for logs).
     pub fn turn_summary(&self) -> String {
-        format!(
-            "Turn {} - {:?} - {:?}",
-            self.state.turn_number, self.state.current_power, self.state.current_phase
-        )
+        let info = self.turn_info();
+        format!("Turn {} - {:?} - {:?}", info.turn_number, info.power, info.phase)
     }
 }
 
@@ -285,6 +847,25 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_integrity_failure_leaves_state_untouched() {
+        let mut engine = Engine::new_game(42);
+        let unit = engine
+            .state_mut()
+            .territories
+            .iter_mut()
+            .find_map(|t| t.units.first_mut())
+            .unwrap();
+        unit.hits_taken = 9;
+        let before = engine.serialize_state().unwrap();
+
+        let result = engine.submit_action(Action::ConfirmPurchases);
+        assert!(matches!(result, Err(error::EngineError::Internal(_))));
+        assert_eq!(engine.serialize_state().unwrap(), before);
+        assert_eq!(engine.state().current_phase, Phase::PurchaseAndRepair);
+    }
+
     #[test]
     fn test_undo_not_available() {
         let mut engine = Engine::new_game(42);
@@ -337,9 +918,16 @@ mod tests {
         engine.submit_action(Action::ConfirmMobilization).unwrap();
 
         let result = engine.submit_action(Action::ConfirmIncome).unwrap();
-        assert_eq!(result.events.len(), 2);
+        // Convoy losses and the income itself are reported first (British
+        // ships raid the North Sea)
+        let events: Vec<_> = result
+            .events
+            .iter()
+            .filter(|e| !matches!(e, GameEvent::ConvoyDisrupted { .. } | GameEvent::IpcChanged { .. }))
+            .collect();
+        assert_eq!(events.len(), 2);
 
-        match &result.events[0] {
+        match events[0] {
             GameEvent::PhaseChanged { from, to } => {
                 assert_eq!(*from, Phase::CollectIncome);
                 assert_eq!(*to, Phase::PurchaseAndRepair);
@@ -347,7 +935,7 @@ mod tests {
             other => panic!("Expected PhaseChanged, got {:?}", other),
         }
 
-        match &result.events[1] {
+        match events[1] {
             GameEvent::TurnChanged { power, turn } => {
                 assert_eq!(*power, Power::SovietUnion);
                 assert_eq!(*turn, 1);
@@ -409,4 +997,1605 @@ mod tests {
         assert_eq!(actions.len(), 1);
         assert!(matches!(actions[0].action, Action::ConfirmIncome));
     }
+
+    #[test]
+    fn test_mech_tank_group_move() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+        use unit::UnitType;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+
+        let germany = &engine.state().territories[t::GERMANY as usize].units;
+        let mech = germany.iter().find(|u| u.unit_type == UnitType::MechInfantry).unwrap().id;
+        let tank = germany.iter().find(|u| u.unit_type == UnitType::Tank).unwrap().id;
+        let path = vec![
+            RegionId::Land(t::GERMANY),
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+        ];
+
+        // Alone, the mech only moves 1
+        let lone = Action::MoveUnit { unit_id: mech, path: path.clone() };
+        assert!(engine.is_action_legal(&lone).is_err());
+
+        // Escorted by a tank, it moves 2
+        engine
+            .submit_action(Action::MoveUnits { unit_ids: vec![mech, tank], path })
+            .unwrap();
+        let holland = &engine.state().territories[t::HOLLAND_BELGIUM as usize].units;
+        assert!(holland.iter().any(|u| u.id == mech));
+        assert!(holland.iter().any(|u| u.id == tank));
+
+        // Undo takes the whole group back
+        assert!(engine.can_undo());
+        engine.submit_action(Action::Undo).unwrap();
+        let germany = &engine.state().territories[t::GERMANY as usize].units;
+        assert!(germany.iter().any(|u| u.id == mech && !u.moved_this_turn));
+        assert!(germany.iter().any(|u| u.id == tank && !u.moved_this_turn));
+        let holland = &engine.state().territories[t::HOLLAND_BELGIUM as usize].units;
+        assert!(!holland.iter().any(|u| u.id == mech || u.id == tank));
+    }
+
+    #[test]
+    fn test_damaged_ships_repair_at_turn_end() {
+        use data::sea_zone_ids as sz;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        let mut docked = UnitInstance::new(99_001, UnitType::Battleship, Power::Germany);
+        docked.hits_taken = 1;
+        let mut at_sea = UnitInstance::new(99_002, UnitType::Battleship, Power::Germany);
+        at_sea.hits_taken = 1;
+        engine.state_mut().sea_zones[sz::SZ_BALTIC_SEA as usize].units.push(docked);
+        engine.state_mut().sea_zones[sz::SZ_CENTRAL_ATLANTIC as usize].units.push(at_sea);
+
+        advance_through_phases(&mut engine);
+
+        let (_, docked) = movement::find_unit(engine.state(), 99_001).unwrap();
+        let (_, at_sea) = movement::find_unit(engine.state(), 99_002).unwrap();
+        assert!(!docked.is_damaged());
+        assert!(at_sea.is_damaged());
+    }
+
+    #[test]
+    fn test_turn_info_at_game_start() {
+        let engine = Engine::new_game(42);
+        let info = engine.turn_info();
+        assert_eq!(info.turn_number, 1);
+        assert_eq!(info.power, Power::Germany);
+        assert_eq!(info.phase, Phase::PurchaseAndRepair);
+        assert_eq!(info.current_ipcs, 30);
+        assert_eq!(engine.turn_summary(), "Turn 1 - Germany - PurchaseAndRepair");
+    }
+
+    #[test]
+    fn test_surrendered_power_skipped_in_turn_order() {
+        let mut engine = Engine::new_game(42);
+        let result = engine.submit_action(Action::Surrender { power: Power::Italy }).unwrap();
+        assert!(result
+            .events
+            .iter()
+            .any(|e| matches!(e, GameEvent::PowerSurrendered { power: Power::Italy })));
+        let northern_italy = &engine.state().territories[data::territory_ids::NORTHERN_ITALY as usize];
+        assert!(northern_italy.owner.is_none() && northern_italy.facilities.is_empty());
+
+        // Germany through UK, then Italy is skipped
+        for _ in 0..6 {
+            advance_through_phases(&mut engine);
+        }
+        assert_eq!(engine.state().current_power, Power::ANZAC);
+    }
+
+    #[test]
+    fn test_surrender_on_own_turn_passes_play() {
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::Surrender { power: Power::Germany }).unwrap();
+        assert_eq!(engine.state().current_power, Power::SovietUnion);
+        assert_eq!(engine.state().current_phase, Phase::PurchaseAndRepair);
+        assert_eq!(engine.state().turn_number, 1);
+    }
+
+    #[test]
+    fn test_empty_conduct_combat_only_needs_confirming() {
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        let phase::PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+        assert!(cs.pending_battles.is_empty());
+
+        let actions = engine.legal_actions();
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(actions[0].action, Action::ConfirmPhase));
+        assert!(actions[0].description.starts_with("No battles"));
+        engine.submit_action(Action::ConfirmPhase).unwrap();
+        assert_eq!(engine.state().current_phase, Phase::NonCombatMovement);
+
+        // With a battle waiting, confirming is not on offer
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = phase::CombatState::new();
+        cs.pending_battles.push(territory::RegionId::Land(data::territory_ids::FRANCE));
+        state.phase_state = phase::PhaseState::Combat(cs);
+        assert!(engine
+            .legal_actions()
+            .iter()
+            .all(|la| !matches!(la.action, Action::ConfirmPhase)));
+        assert!(engine.is_action_legal(&Action::ConfirmPhase).is_err());
+    }
+
+    #[test]
+    fn test_zero_ipc_power_can_only_skip_purchase() {
+        let mut engine = Engine::new_game(42);
+        engine.state_mut().current_power = Power::France;
+        engine.state_mut().powers[Power::France as usize].ipcs = 0;
+        assert!(phase::can_skip_phase(engine.state()));
+
+        let actions = engine.legal_actions();
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(actions[0].action, Action::ConfirmPurchases));
+        assert!(actions[0].description.starts_with("Skip"));
+
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        assert_eq!(engine.state().current_phase, Phase::CombatMovement);
+    }
+
+    #[test]
+    fn test_funded_power_cannot_skip_purchase() {
+        let engine = Engine::new_game(42);
+        assert!(!phase::can_skip_phase(engine.state()));
+        assert!(engine.legal_actions()[0].description.starts_with("Confirm"));
+    }
+
+    #[test]
+    fn test_advance_to_power_plays_intervening_ai_turns() {
+        let mut engine = Engine::new_game(42);
+        assert_eq!(engine.state().current_power, Power::Germany);
+
+        let events = engine
+            .advance_to_power(Power::UnitedStates, ai::AiDifficulty::Normal)
+            .unwrap();
+
+        assert_eq!(engine.state().current_power, Power::UnitedStates);
+        assert_eq!(engine.state().current_phase, Phase::PurchaseAndRepair);
+        let turn_changes: Vec<Power> = events
+            .iter()
+            .filter_map(|e| match e {
+                GameEvent::TurnChanged { power, .. } => Some(*power),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            turn_changes,
+            vec![Power::SovietUnion, Power::Japan, Power::UnitedStates]
+        );
+
+        // Already there: nothing to do
+        assert!(engine
+            .advance_to_power(Power::UnitedStates, ai::AiDifficulty::Normal)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_bid_adds_ipcs() {
+        let mut engine = Engine::new_game(42);
+        let before = engine.state().powers[Power::Germany as usize].ipcs;
+        engine.apply_bid(Power::Germany, 9, vec![]).unwrap();
+        assert_eq!(engine.state().powers[Power::Germany as usize].ipcs, before + 9);
+    }
+
+    #[test]
+    fn test_bid_places_units_and_rejects_enemy_territory() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+        use unit::UnitType;
+
+        let mut engine = Engine::new_game(42);
+        let before = engine.state().powers[Power::Germany as usize].ipcs;
+        let infantry_before = engine.state().territories[t::GERMANY as usize].units.len();
+
+        // Bid units must go in territory Germany controls
+        let err = engine.apply_bid(Power::Germany, 9, vec![(UnitType::Infantry, RegionId::Land(t::FRANCE))]);
+        assert!(err.is_err());
+        assert_eq!(engine.state().powers[Power::Germany as usize].ipcs, before);
+
+        // 2 infantry (6 IPCs) placed, 3 IPCs left over
+        engine
+            .apply_bid(Power::Germany, 9, vec![(UnitType::Infantry, RegionId::Land(t::GERMANY)); 2])
+            .unwrap();
+        assert_eq!(engine.state().territories[t::GERMANY as usize].units.len(), infantry_before + 2);
+        assert_eq!(engine.state().powers[Power::Germany as usize].ipcs, before + 3);
+
+        // Too late once play has started
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        assert!(engine.apply_bid(Power::Germany, 9, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_bid_places_naval_units_in_the_chosen_sea_zone() {
+        use data::sea_zone_ids as sz;
+        use data::territory_ids as t;
+        use territory::RegionId;
+        use unit::UnitType;
+
+        let mut engine = Engine::new_game(42);
+        let subs = |engine: &Engine, sid: territory::SeaZoneId| {
+            engine.state().sea_zones[sid as usize]
+                .units
+                .iter()
+                .filter(|u| u.owner == Power::Germany && u.unit_type == UnitType::Submarine)
+                .count()
+        };
+        let baltic_before = subs(&engine, sz::SZ_BALTIC_SEA);
+
+        // Ships go in a sea zone, never a territory
+        assert!(engine.apply_bid(Power::Germany, 6, vec![(UnitType::Submarine, RegionId::Land(t::GERMANY))]).is_err());
+        // ...and only in one bordering a territory Germany controls
+        assert!(engine.apply_bid(Power::Germany, 6, vec![(UnitType::Submarine, RegionId::Sea(sz::SZ_JAPAN_EAST))]).is_err());
+        // Land units stay on land
+        assert!(engine.apply_bid(Power::Germany, 6, vec![(UnitType::Infantry, RegionId::Sea(sz::SZ_BALTIC_SEA))]).is_err());
+
+        engine
+            .apply_bid(Power::Germany, 6, vec![(UnitType::Submarine, RegionId::Sea(sz::SZ_BALTIC_SEA))])
+            .unwrap();
+        assert_eq!(subs(&engine, sz::SZ_BALTIC_SEA), baltic_before + 1);
+    }
+
+    #[test]
+    fn test_set_relation_rejected_outside_sandbox() {
+        let mut engine = Engine::new_game(42);
+        let action = Action::SetRelation {
+            a: Power::UnitedStates,
+            b: Power::Germany,
+            relation: state::Relation::War,
+        };
+        assert!(engine.submit_action(action).is_err());
+        assert!(!engine.state().political.are_at_war(Power::UnitedStates, Power::Germany));
+    }
+
+    #[test]
+    fn test_sandbox_war_enables_us_attack() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        engine.set_sandbox_mode(true);
+        let state = engine.state_mut();
+        state.current_power = Power::UnitedStates;
+        state.current_phase = Phase::CombatMovement;
+        state.phase_state = phase::PhaseState::CombatMove(phase::CombatMoveState::new());
+        // A US unit set up alongside German units: at peace there is no battle
+        let mut infantry = UnitInstance::new(99_001, UnitType::Infantry, Power::UnitedStates);
+        infantry.moved_this_turn = true;
+        state.territories[t::BALTIC_STATES as usize].units.push(infantry);
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        let pending = |engine: &Engine| match &engine.state().phase_state {
+            phase::PhaseState::Combat(cs) => cs.pending_battles.clone(),
+            _ => panic!("expected combat phase"),
+        };
+        assert!(pending(&engine).is_empty());
+
+        let result = engine
+            .submit_action(Action::SetRelation {
+                a: Power::UnitedStates,
+                b: Power::Germany,
+                relation: state::Relation::War,
+            })
+            .unwrap();
+        assert!(matches!(result.events[0], GameEvent::RelationChanged { .. }));
+        assert!(engine.state().political.are_at_war(Power::Germany, Power::UnitedStates));
+        assert!(engine.state().powers[Power::UnitedStates as usize].at_war);
+        assert_eq!(pending(&engine), vec![RegionId::Land(t::BALTIC_STATES)]);
+    }
+
+    #[test]
+    fn test_war_must_be_declared_before_moving_in() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        assert!(!engine.state().political.are_at_war(Power::Germany, Power::SovietUnion));
+        let infantry = engine.state().territories[t::POLAND as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == unit::UnitType::Infantry)
+            .unwrap()
+            .id;
+        let into_belarus = Action::MoveUnit {
+            unit_id: infantry,
+            path: vec![RegionId::Land(t::POLAND), RegionId::Land(t::BELARUS)],
+        };
+
+        let err = engine.submit_action(into_belarus.clone()).unwrap_err();
+        assert!(err.to_string().contains("Declare war on SovietUnion"));
+
+        engine
+            .submit_action(Action::DeclareWar { against: Power::SovietUnion })
+            .unwrap();
+        engine.submit_action(into_belarus).unwrap();
+    }
+
+    #[test]
+    fn test_stacking_limit_caps_moves_and_placements() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let state = engine.state_mut();
+        state.settings.max_units_per_territory = Some(5);
+        let western = &mut state.territories[t::WESTERN_GERMANY as usize].units;
+        western.clear();
+        for id in 99_001..99_006 {
+            western.push(UnitInstance::new(id, UnitType::Infantry, Power::Germany));
+        }
+        let infantry = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == UnitType::Infantry)
+            .unwrap()
+            .id;
+        let err = engine
+            .submit_action(Action::MoveUnit {
+                unit_id: infantry,
+                path: vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)],
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("No more than 5 units"));
+
+        // Mobilizing fills Germany up to the cap and no further
+        let state = engine.state_mut();
+        state.territories[t::GERMANY as usize].units.clear();
+        state.current_phase = Phase::Mobilize;
+        state.phase_state = phase::PhaseState::Mobilize(phase::MobilizeState {
+            placements: Vec::new(),
+            units_to_place: vec![(UnitType::Infantry, 6)],
+        });
+        let place = Action::PlaceUnit { unit_type: UnitType::Infantry, territory_id: t::GERMANY, sea_zone: None };
+        for _ in 0..5 {
+            engine.submit_action(place.clone()).unwrap();
+        }
+        assert!(engine.submit_action(place).is_err());
+    }
+
+    #[test]
+    fn test_mobilized_ship_goes_to_the_chosen_sea() {
+        use data::sea_zone_ids as sz;
+        use data::territory_ids as t;
+        use territory::RegionId;
+        use unit::UnitType;
+
+        let mut engine = Engine::new_game(42);
+        let ships_before = engine.state().sea_zones[sz::SZ_NORTH_SEA as usize].units.len();
+        let state = engine.state_mut();
+        state.current_power = Power::UnitedKingdom;
+        state.current_phase = Phase::Mobilize;
+        state.phase_state = phase::PhaseState::Mobilize(phase::MobilizeState {
+            placements: Vec::new(),
+            units_to_place: vec![(UnitType::Destroyer, 1)],
+        });
+
+        // Ships need a sea zone, and it must border the factory
+        let place = |sea_zone| Action::PlaceUnit {
+            unit_type: UnitType::Destroyer,
+            territory_id: t::UNITED_KINGDOM,
+            sea_zone,
+        };
+        assert!(engine.submit_action(place(None)).is_err());
+        assert!(engine.submit_action(place(Some(sz::SZ_BALTIC_SEA))).is_err());
+
+        // The United Kingdom borders two zones; the second one is honoured
+        assert_eq!(
+            engine.map.territory(t::UNITED_KINGDOM).adjacent_sea,
+            vec![sz::SZ_ENGLISH_CHANNEL, sz::SZ_NORTH_SEA]
+        );
+        let result = engine.submit_action(place(Some(sz::SZ_NORTH_SEA))).unwrap();
+        assert!(result.events.iter().any(|e| matches!(
+            e,
+            GameEvent::UnitsPlaced { territory_id: t::UNITED_KINGDOM, sea_zone: Some(sz::SZ_NORTH_SEA), .. }
+        )));
+        assert_eq!(result.events[0].region(), Some(RegionId::Sea(sz::SZ_NORTH_SEA)));
+
+        assert!(engine.state().territories[t::UNITED_KINGDOM as usize]
+            .units
+            .iter()
+            .all(|u| u.unit_type != UnitType::Destroyer));
+        let ships = &engine.state().sea_zones[sz::SZ_NORTH_SEA as usize].units;
+        assert_eq!(ships.len(), ships_before + 1);
+        let placed = ships.last().unwrap();
+        assert_eq!(placed.unit_type, UnitType::Destroyer);
+        assert!(movement::unit_can_occupy(engine.state(), placed.unit_type, RegionId::Sea(sz::SZ_NORTH_SEA)));
+    }
+
+    #[test]
+    fn test_confirm_income_reports_ipc_change() {
+        use action::IpcChangeReason;
+
+        let mut engine = Engine::new_game(42);
+        let expected = income::calculate_income(engine.state(), &engine.map, Power::Germany).total;
+        let before = engine.state().powers[Power::Germany as usize].ipcs;
+        let state = engine.state_mut();
+        state.current_phase = Phase::CollectIncome;
+        state.phase_state = phase::PhaseState::CollectIncome(Default::default());
+
+        let events = engine.submit_action(Action::ConfirmIncome).unwrap().events;
+        let change = events
+            .iter()
+            .find_map(|e| match *e {
+                GameEvent::IpcChanged { power, delta, new_total, reason } => Some((power, delta, new_total, reason)),
+                _ => None,
+            })
+            .expect("income should report an IPC change");
+        assert_eq!(change, (Power::Germany, expected as i32, before + expected, IpcChangeReason::Income));
+    }
+
+    #[test]
+    fn test_modded_infantry_cost_drives_purchases() {
+        let mut stats = unit::UnitStatsTable::default();
+        let mut infantry = stats.get(unit::UnitType::Infantry);
+        infantry.cost = 11;
+        stats.set(infantry);
+
+        // Germany opens with 30 IPCs
+        let modded = Engine::new_game_with_stats(42, stats);
+        let three = Action::PurchaseUnit { unit_type: unit::UnitType::Infantry, count: 3 };
+        assert!(matches!(
+            modded.is_action_legal(&three),
+            Err(EngineError::InsufficientIPCs { needed: 33, available: 30 })
+        ));
+        let tanks = Action::PurchaseUnit { unit_type: unit::UnitType::Tank, count: 5 };
+        assert!(modded.is_action_legal(&tanks).is_ok());
+
+        let standard = Engine::new_game(42);
+        assert!(standard.is_action_legal(&three).is_ok());
+        assert_eq!(standard.state().unit_stats(unit::UnitType::Infantry).cost, 3);
+    }
+
+    #[test]
+    fn test_stats_table_missing_a_type_uses_the_builtin_stats() {
+        let stats: unit::UnitStatsTable = serde_json::from_str(r#"{"stats":{}}"#).unwrap();
+        let engine = Engine::new_game_with_stats(42, stats);
+        assert_eq!(engine.state().unit_stats(unit::UnitType::Infantry).cost, 3);
+    }
+
+    #[test]
+    fn test_stats_table_is_part_of_the_checksum() {
+        let mut stats = unit::UnitStatsTable::default();
+        let builtin = Engine::new_game_with_stats(42, stats.clone());
+        let mut infantry = stats.get(unit::UnitType::Infantry);
+        infantry.cost = 4;
+        stats.set(infantry);
+        let modded = Engine::new_game_with_stats(42, stats);
+        assert_ne!(builtin.state_checksum(), modded.state_checksum());
+    }
+
+    #[test]
+    fn test_france_combat_context() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+        use unit::UnitType;
+
+        let engine = Engine::new_game(42);
+        let context = engine.region_combat_context(RegionId::Land(t::FRANCE)).unwrap();
+        assert_eq!(context.owner, Some(Power::France));
+        assert_eq!(context.units.keys().collect::<Vec<_>>(), vec![&Power::France]);
+        assert_eq!(context.units[&Power::France].len(), 8);
+        assert!(context.has_aaa);
+        assert!(!context.coastal);
+        assert!(context.bombardment_ships.is_empty());
+        // Germany's air base in Western Germany is no help to France
+        assert!(context.scramble_bases.is_empty());
+
+        // A French air base on the coast with a fighter on it scrambles to
+        // the sea zones it borders, never to a land battle
+        let mut engine = engine;
+        let normandy = &mut engine.state_mut().territories[t::NORMANDY_BORDEAUX as usize];
+        normandy
+            .facilities
+            .push(territory::Facility::new(territory::FacilityType::AirBase, 2));
+        normandy
+            .units
+            .push(unit::UnitInstance::new(99_001, UnitType::Fighter, Power::France));
+        let context = engine.region_combat_context(RegionId::Land(t::FRANCE)).unwrap();
+        assert!(context.scramble_bases.is_empty());
+        let biscay = RegionId::Sea(data::sea_zone_ids::SZ_BAY_OF_BISCAY);
+        let context = engine.region_combat_context(biscay).unwrap();
+        assert_eq!(context.scramble_bases, vec![t::NORMANDY_BORDEAUX]);
+
+        // A base bombed out of operation cannot scramble
+        engine.state_mut().territories[t::NORMANDY_BORDEAUX as usize].facilities[0].operational = false;
+        let context = engine.region_combat_context(biscay).unwrap();
+        assert!(context.scramble_bases.is_empty());
+
+        assert!(engine.region_combat_context(RegionId::Land(u16::MAX)).is_none());
+    }
+
+    #[test]
+    fn test_income_counts_ownership_as_collect_income_begins() {
+        use data::territory_ids::{FRANCE, NORWAY};
+
+        let mut engine = Engine::new_game(42);
+        let value = |tid: territory::TerritoryId| engine.map.territory(tid).ipc_value;
+        let (france, norway) = (value(FRANCE), value(NORWAY));
+        let base = income::calculate_income(engine.state(), &engine.map, Power::Germany).base_income;
+
+        // France was taken in this turn's combat and Norway lost before income
+        let state = engine.state_mut();
+        state.territories[FRANCE as usize].owner = Some(Power::Germany);
+        state.territories[NORWAY as usize].owner = Some(Power::UnitedKingdom);
+        state.current_phase = Phase::Mobilize;
+        state.phase_state = phase::PhaseState::Mobilize(Default::default());
+        engine.submit_action(Action::ConfirmMobilization).unwrap();
+        assert_eq!(engine.state().current_phase, Phase::CollectIncome);
+
+        // Changes once the phase is under way wait for the next collection
+        let state = engine.state_mut();
+        state.territories[FRANCE as usize].owner = Some(Power::France);
+        state.territories[NORWAY as usize].owner = Some(Power::Germany);
+        let phase::PhaseState::CollectIncome(cis) = &engine.state().phase_state else { unreachable!() };
+        let owed = cis.owed.clone().unwrap();
+        assert_eq!(owed.base_income, base + france - norway);
+
+        let before = engine.state().powers[Power::Germany as usize].ipcs;
+        engine.submit_action(Action::ConfirmIncome).unwrap();
+        assert_eq!(engine.state().powers[Power::Germany as usize].ipcs, before + owed.total);
+    }
+
+    #[test]
+    fn test_iceland_can_only_be_invaded_from_a_transport() {
+        use data::sea_zone_ids::{SZ_NORWEGIAN_SEA, SZ_OFF_ICELAND};
+        use data::territory_ids::{ICELAND, NORWAY};
+        use phase::PhaseState;
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let state = engine.state_mut();
+        for zone in [SZ_NORWEGIAN_SEA, SZ_OFF_ICELAND] {
+            state.sea_zones[zone as usize].units.clear();
+        }
+        state.sea_zones[SZ_NORWEGIAN_SEA as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Transport, Power::Germany));
+        state.territories[NORWAY as usize]
+            .units
+            .push(UnitInstance::new(99_002, UnitType::Tank, Power::Germany));
+        state.territories[ICELAND as usize].units =
+            vec![UnitInstance::new(99_003, UnitType::Infantry, Power::UnitedKingdom)];
+
+        let overland = vec![RegionId::Land(NORWAY), RegionId::Land(ICELAND)];
+        let by_sea = vec![
+            RegionId::Land(NORWAY),
+            RegionId::Sea(SZ_NORWEGIAN_SEA),
+            RegionId::Sea(SZ_OFF_ICELAND),
+            RegionId::Land(ICELAND),
+        ];
+        assert!(engine.is_action_legal(&Action::MoveUnit { unit_id: 99_002, path: overland }).is_err());
+        // The transport has to make the crossing too
+        let landing = Action::MoveUnit { unit_id: 99_002, path: by_sea };
+        assert!(engine.is_action_legal(&landing).is_err());
+
+        engine
+            .submit_action(Action::MoveUnit {
+                unit_id: 99_001,
+                path: vec![RegionId::Sea(SZ_NORWEGIAN_SEA), RegionId::Sea(SZ_OFF_ICELAND)],
+            })
+            .unwrap();
+        engine.submit_action(landing).unwrap();
+        let (_, transport) = movement::find_unit(engine.state(), 99_001).unwrap();
+        assert_eq!(transport.cargo, vec![99_002]);
+        assert!(engine.is_action_legal(&Action::UndoMove { unit_id: 99_001 }).is_err());
+        assert_eq!(engine.preview_pending_combats(), vec![RegionId::Land(ICELAND)]);
+
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        engine
+            .submit_action(Action::SelectBattle { location: RegionId::Land(ICELAND) })
+            .unwrap();
+        for _ in 0..100 {
+            let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+            let Some(combat) = &cs.active_combat else { break };
+            assert!(combat.is_amphibious);
+            let action = ai::ai_handle_battle(engine.state(), combat);
+            engine.submit_action(action).unwrap();
+        }
+
+        let record = engine.state().combat_history.last().unwrap();
+        assert_eq!(record.location, RegionId::Land(ICELAND));
+        let owner = engine.state().territories[ICELAND as usize].owner;
+        let expected = if record.attacker_won { Power::Germany } else { Power::UnitedKingdom };
+        assert_eq!(owner, Some(expected));
+        assert!(movement::find_unit(engine.state(), 99_001).unwrap().1.cargo.is_empty());
+    }
+
+    #[test]
+    fn test_landing_without_a_battle_leaves_the_transport() {
+        use data::sea_zone_ids::{SZ_NORWEGIAN_SEA, SZ_OFF_ICELAND};
+        use data::territory_ids::{ICELAND, NORWAY};
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let state = engine.state_mut();
+        for zone in [SZ_NORWEGIAN_SEA, SZ_OFF_ICELAND] {
+            state.sea_zones[zone as usize].units.clear();
+        }
+        state.sea_zones[SZ_NORWEGIAN_SEA as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Transport, Power::Germany));
+        state.territories[NORWAY as usize]
+            .units
+            .push(UnitInstance::new(99_002, UnitType::Tank, Power::Germany));
+        state.territories[ICELAND as usize].units.clear();
+        state.territories[ICELAND as usize].owner = Some(Power::Germany);
+
+        engine
+            .submit_action(Action::MoveUnit {
+                unit_id: 99_001,
+                path: vec![RegionId::Sea(SZ_NORWEGIAN_SEA), RegionId::Sea(SZ_OFF_ICELAND)],
+            })
+            .unwrap();
+        let landing = Action::MoveUnit {
+            unit_id: 99_002,
+            path: vec![
+                RegionId::Land(NORWAY),
+                RegionId::Sea(SZ_NORWEGIAN_SEA),
+                RegionId::Sea(SZ_OFF_ICELAND),
+                RegionId::Land(ICELAND),
+            ],
+        };
+        // Friendly shores are no assault
+        assert!(matches!(
+            engine.is_action_legal(&landing),
+            Err(EngineError::IllegalMove { reason }) if reason.contains("enemy territory")
+        ));
+
+        // An undefended enemy island is taken without a battle
+        engine.state_mut().territories[ICELAND as usize].owner = Some(Power::UnitedKingdom);
+        engine.submit_action(landing).unwrap();
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        let phase::PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+        assert!(cs.pending_battles.is_empty());
+        assert!(movement::find_unit(engine.state(), 99_001).unwrap().1.cargo.is_empty());
+    }
+
+    #[test]
+    fn test_cruiser_moving_onto_enemy_warships_starts_sea_battle() {
+        use data::sea_zone_ids as sz;
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let state = engine.state_mut();
+        state.sea_zones[sz::SZ_BALTIC_SEA as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Cruiser, Power::Germany));
+        state.sea_zones[sz::SZ_SKAGERRAK as usize]
+            .units
+            .push(UnitInstance::new(99_002, UnitType::Destroyer, Power::UnitedKingdom));
+
+        engine
+            .submit_action(Action::MoveUnit {
+                unit_id: 99_001,
+                path: vec![RegionId::Sea(sz::SZ_BALTIC_SEA), RegionId::Sea(sz::SZ_SKAGERRAK)],
+            })
+            .unwrap();
+        assert!(engine
+            .preview_pending_combats()
+            .contains(&RegionId::Sea(sz::SZ_SKAGERRAK)));
+    }
+
+    #[test]
+    fn test_same_seed_and_actions_give_same_game() {
+        let mut a = Engine::new_game(1234);
+        let mut b = Engine::new_game(a.seed());
+        assert_eq!(b.seed(), 1234);
+
+        for _ in 0..2 {
+            let power = a.state().current_power;
+            for action in ai::ai_play_turn(a.state(), &a.map, ai::AiDifficulty::Normal) {
+                let ra = a.submit_action(action.clone()).unwrap();
+                let rb = b.submit_action(action).unwrap();
+                assert_eq!(ra.state_checksum, rb.state_checksum);
+            }
+            assert_ne!(a.state().current_power, power);
+        }
+        assert_eq!(a.serialize_state().unwrap(), b.serialize_state().unwrap());
+    }
+
+    #[test]
+    fn test_setting_rng_needs_sandbox_mode() {
+        let mut engine = Engine::new_game(42);
+        assert!(engine.set_rng_state(7, 3).is_err());
+        assert_eq!(engine.rng_state(), dice::RngState { seed: 42, counter: 0 });
+
+        engine.set_sandbox_mode(true);
+        engine.set_rng_state(7, 3).unwrap();
+        assert_eq!(engine.rng_state(), dice::RngState { seed: 7, counter: 3 });
+        // The game is still the one created from seed 42
+        assert_eq!(engine.seed(), 42);
+
+        // Saves from before the creation seed was recorded use the current one
+        let mut legacy = engine.state().clone();
+        legacy.creation_seed = None;
+        assert_eq!(Engine::from_state(legacy).seed(), 7);
+    }
+
+    #[test]
+    fn test_events_since_catches_up_by_sequence() {
+        let mut engine = Engine::new_game(42);
+        let (events, next) = engine.events_since(0).unwrap();
+        assert!(events.is_empty());
+        assert_eq!(next, 0);
+
+        let purchase = Action::PurchaseUnit { unit_type: unit::UnitType::Infantry, count: 1 };
+        let first = engine.submit_action(purchase).unwrap().events;
+        let (seen, next) = engine.events_since(0).unwrap();
+        assert_eq!(seen.len(), first.len());
+        assert_eq!(next, first.len() as u64);
+
+        let second = engine.submit_action(Action::ConfirmPurchases).unwrap().events;
+        let (missed, latest) = engine.events_since(next).unwrap();
+        assert_eq!(missed.len(), second.len());
+        assert!(matches!(missed[0], GameEvent::PhaseChanged { .. }));
+        assert_eq!(latest, next + second.len() as u64);
+        assert_eq!(engine.events_since(0).unwrap().0.len(), first.len() + second.len());
+    }
+
+    #[test]
+    fn test_event_buffer_drops_oldest_past_capacity() {
+        assert!(action::EventBuffer::with_capacity(0).is_err());
+        let mut buffer = action::EventBuffer::with_capacity(3).unwrap();
+        buffer.extend((0..5).map(|power| GameEvent::PowerSurrendered { power: power::TURN_ORDER[power] }));
+
+        // Events 0 and 1 are gone, and asking for them says so
+        assert!(matches!(buffer.since(0), Err(EngineError::EventsDropped { oldest: 2 })));
+        let (events, next) = buffer.since(2).unwrap();
+        assert_eq!(next, 5);
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], GameEvent::PowerSurrendered { power } if power == power::TURN_ORDER[2]));
+        assert_eq!(buffer.since(4).unwrap().0.len(), 1);
+        assert!(buffer.since(5).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn test_preview_lists_battles_from_uncommitted_moves() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        assert!(engine.preview_pending_combats().is_empty());
+
+        let tank = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == unit::UnitType::Tank)
+            .unwrap()
+            .id;
+        engine.state_mut().territories[t::HOLLAND_BELGIUM as usize].owner = Some(Power::UnitedKingdom);
+        engine.state_mut().territories[t::HOLLAND_BELGIUM as usize]
+            .units
+            .push(unit::UnitInstance::new(99_001, unit::UnitType::Infantry, Power::UnitedKingdom));
+        engine
+            .submit_action(Action::MoveUnit {
+                unit_id: tank,
+                path: vec![RegionId::Land(t::GERMANY), RegionId::Land(t::HOLLAND_BELGIUM)],
+            })
+            .unwrap();
+
+        assert_eq!(engine.preview_pending_combats(), vec![RegionId::Land(t::HOLLAND_BELGIUM)]);
+        assert_eq!(engine.state().current_phase, Phase::CombatMovement);
+    }
+
+    #[test]
+    fn test_state_checksum_matches_across_engines() {
+        let mut a = Engine::new_game(42);
+        let mut b = Engine::new_game(42);
+        let action = Action::PurchaseUnit { unit_type: unit::UnitType::Infantry, count: 2 };
+        let ra = a.submit_action(action.clone()).unwrap();
+        let rb = b.submit_action(action).unwrap();
+        assert_eq!(ra.state_checksum, rb.state_checksum);
+        assert_eq!(ra.state_checksum, a.state_checksum());
+
+        // Unit order within a region doesn't matter
+        b.state_mut().territories[data::territory_ids::GERMANY as usize].units.reverse();
+        assert_eq!(a.state_checksum(), b.state_checksum());
+
+        // The economic-victory clock does
+        b.state_mut().economic_leader = Some((power::Team::Axis, 3));
+        assert_ne!(a.state_checksum(), b.state_checksum());
+        b.state_mut().economic_leader = None;
+
+        // A tampered treasury does
+        b.state_mut().powers[Power::Germany as usize].ipcs += 1;
+        assert_ne!(a.state_checksum(), b.state_checksum());
+    }
+
+    #[test]
+    fn test_endless_battle_ends_in_stalemate() {
+        use combat::CombatSubPhase;
+        use data::sea_zone_ids::SZ_NORTH_SEA;
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+
+        // Two unarmed transports can never hit each other
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        state.settings.max_combat_rounds = 5;
+        let zone = &mut state.sea_zones[SZ_NORTH_SEA as usize].units;
+        zone.clear();
+        let mut transport = unit::UnitInstance::new(99_001, unit::UnitType::Transport, Power::Germany);
+        transport.moved_this_turn = true;
+        zone.push(transport);
+        zone.push(unit::UnitInstance::new(99_002, unit::UnitType::Transport, Power::UnitedKingdom));
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles.push(RegionId::Sea(SZ_NORTH_SEA));
+        state.phase_state = PhaseState::Combat(cs);
+
+        engine
+            .submit_action(Action::SelectBattle { location: RegionId::Sea(SZ_NORTH_SEA) })
+            .unwrap();
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            let sub_phase = match &engine.state().phase_state {
+                PhaseState::Combat(cs) => match &cs.active_combat {
+                    Some(combat) => combat.sub_phase.clone(),
+                    None => break,
+                },
+                _ => panic!("left the combat phase"),
+            };
+            let action = match sub_phase {
+                CombatSubPhase::AttackerRolls => Action::RollAttack,
+                CombatSubPhase::DefenderRolls => Action::RollDefense,
+                other => panic!("unexpected sub-phase {:?}", other),
+            };
+            events.extend(engine.submit_action(action).unwrap().events);
+        }
+
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, GameEvent::BattleStalemate { location } if *location == RegionId::Sea(SZ_NORTH_SEA))));
+        let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+        assert!(cs.resolved_battles.contains(&RegionId::Sea(SZ_NORTH_SEA)));
+        assert_eq!(engine.state().sea_zones[SZ_NORTH_SEA as usize].units.len(), 2);
+    }
+
+    #[test]
+    fn test_battles_resolve_in_any_order() {
+        use combat::CombatSubPhase;
+        use data::sea_zone_ids::{SZ_BALTIC_SEA, SZ_BAY_OF_BISCAY, SZ_NORTH_SEA};
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+
+        // Unarmed transports facing off stall after a single round
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        state.settings.max_combat_rounds = 1;
+        let zones = [SZ_NORTH_SEA, SZ_BALTIC_SEA, SZ_BAY_OF_BISCAY];
+        for (i, &zone) in zones.iter().enumerate() {
+            let units = &mut state.sea_zones[zone as usize].units;
+            units.clear();
+            let id = 99_000 + 2 * i as u32;
+            let mut transport = unit::UnitInstance::new(id, unit::UnitType::Transport, Power::Germany);
+            transport.moved_this_turn = true;
+            units.push(transport);
+            units.push(unit::UnitInstance::new(id + 1, unit::UnitType::Transport, Power::UnitedKingdom));
+        }
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles = zones.iter().map(|&z| RegionId::Sea(z)).collect();
+        state.phase_state = PhaseState::Combat(cs);
+
+        let offered = engine
+            .legal_actions()
+            .into_iter()
+            .filter(|la| matches!(la.action, Action::SelectBattle { .. }))
+            .count();
+        assert_eq!(offered, 3);
+        let elsewhere = Action::SelectBattle { location: RegionId::Sea(data::sea_zone_ids::SZ_WESTERN_MED) };
+        assert!(engine.is_action_legal(&elsewhere).is_err());
+
+        for zone in [SZ_BAY_OF_BISCAY, SZ_NORTH_SEA, SZ_BALTIC_SEA] {
+            let location = RegionId::Sea(zone);
+            engine.submit_action(Action::SelectBattle { location }).unwrap();
+            for _ in 0..10 {
+                let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+                let Some(combat) = &cs.active_combat else { break };
+                let action = match combat.sub_phase {
+                    CombatSubPhase::AttackerRolls => Action::RollAttack,
+                    CombatSubPhase::DefenderRolls => Action::RollDefense,
+                    ref other => panic!("unexpected sub-phase {:?}", other),
+                };
+                engine.submit_action(action).unwrap();
+            }
+            let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+            assert_eq!(cs.resolved_battles.last(), Some(&location));
+            assert!(engine.is_action_legal(&Action::SelectBattle { location }).is_err());
+        }
+    }
+
+    #[test]
+    fn test_combat_history_records_each_battle() {
+        use combat::CombatSubPhase;
+        use data::sea_zone_ids::{SZ_BALTIC_SEA, SZ_NORTH_SEA};
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        state.settings.max_combat_rounds = 2;
+        // Unarmed transports stall in the North Sea; three battleships sink
+        // a lone transport in the Baltic without a roll
+        let north = &mut state.sea_zones[SZ_NORTH_SEA as usize].units;
+        north.clear();
+        let mut transport = UnitInstance::new(99_001, UnitType::Transport, Power::Germany);
+        transport.moved_this_turn = true;
+        north.push(transport);
+        north.push(UnitInstance::new(99_002, UnitType::Transport, Power::UnitedKingdom));
+        let baltic = &mut state.sea_zones[SZ_BALTIC_SEA as usize].units;
+        baltic.clear();
+        for id in 99_003..99_006 {
+            let mut battleship = UnitInstance::new(id, UnitType::Battleship, Power::Germany);
+            battleship.moved_this_turn = true;
+            baltic.push(battleship);
+        }
+        baltic.push(UnitInstance::new(99_006, UnitType::Transport, Power::UnitedKingdom));
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles = vec![RegionId::Sea(SZ_NORTH_SEA), RegionId::Sea(SZ_BALTIC_SEA)];
+        state.phase_state = PhaseState::Combat(cs);
+
+        let mut attack_rolls = Vec::new();
+        for zone in [SZ_NORTH_SEA, SZ_BALTIC_SEA] {
+            engine
+                .submit_action(Action::SelectBattle { location: RegionId::Sea(zone) })
+                .unwrap();
+            let mut rolls = 0;
+            for _ in 0..100 {
+                let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+                let Some(combat) = &cs.active_combat else { break };
+                let action = match combat.sub_phase {
+                    CombatSubPhase::AttackerRolls => {
+                        rolls += 1;
+                        Action::RollAttack
+                    }
+                    CombatSubPhase::DefenderRolls => Action::RollDefense,
+                    CombatSubPhase::DefenderSelectsCasualties => {
+                        Action::SelectCasualties { casualties: vec![99_006] }
+                    }
+                    CombatSubPhase::AttackerDecision => Action::ContinueCombatRound,
+                    ref other => panic!("unexpected sub-phase {:?}", other),
+                };
+                engine.submit_action(action).unwrap();
+            }
+            attack_rolls.push(rolls);
+        }
+
+        let history = engine.combat_history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].location, RegionId::Sea(SZ_NORTH_SEA));
+        assert!(history[0].stalemate && !history[0].attacker_won);
+        assert_eq!(history[0].rounds, 2);
+        assert_eq!(history[0].dice_rolled, 0);
+        assert_eq!(history[1].location, RegionId::Sea(SZ_BALTIC_SEA));
+        assert!(history[1].attacker_won && !history[1].stalemate);
+        assert_eq!(attack_rolls[1], 0);
+        assert_eq!(history[1].rounds, 1);
+        assert_eq!(history[1].defender_losses, vec![UnitType::Transport]);
+        assert!(history[1].attacker_losses.is_empty());
+        assert_eq!(history[1].dice_rolled, 0);
+    }
+
+    #[test]
+    fn test_available_actions_cover_every_phase_choice() {
+        use data::territory_ids as t;
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let offers = |engine: &Engine, wanted: &dyn Fn(&Action) -> bool| {
+            engine.available_actions().iter().any(|legal| wanted(&legal.action))
+        };
+
+        // Queued purchases can be taken back
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::PurchaseUnit { unit_type: UnitType::Tank, count: 1 }).unwrap();
+        assert!(offers(&engine, &|a| matches!(a, Action::RemovePurchase { unit_type: UnitType::Tank, .. })));
+
+        // Aircraft that flew in combat can land in non-combat
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        engine.submit_action(Action::ConfirmPhase).unwrap();
+        let mut fighter = UnitInstance::new(99_001, UnitType::Fighter, Power::Germany);
+        fighter.moved_this_turn = true;
+        engine.state_mut().territories[t::GERMANY as usize].units.push(fighter);
+        assert!(offers(&engine, &|a| matches!(
+            a,
+            Action::LandAirUnit { unit_id: 99_001, territory_id: RegionId::Land(t::WESTERN_GERMANY) }
+        )));
+
+        // Bombers sitting over an enemy factory can raid it
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        let mut bomber = UnitInstance::new(99_002, UnitType::StrategicBomber, Power::Germany);
+        bomber.moved_this_turn = true;
+        state.territories[t::UNITED_KINGDOM as usize].units.push(bomber);
+        state.current_phase = Phase::ConductCombat;
+        state.phase_state = PhaseState::Combat(CombatState::new());
+        assert!(offers(&engine, &|a| matches!(
+            a,
+            Action::StrategicBombingRaid { territory_id: t::UNITED_KINGDOM }
+        )));
+    }
+
+    #[test]
+    fn test_set_controller() {
+        use multiplayer::ControllerKind;
+
+        let mut engine = Engine::new_game(42);
+        assert_eq!(engine.controller_for(Power::Germany), ControllerKind::Human);
+        engine.set_controller(Power::Germany, ControllerKind::AI);
+        assert_eq!(engine.controller_for(Power::Germany), ControllerKind::AI);
+        assert_eq!(engine.controller_for(Power::Japan), ControllerKind::Human);
+    }
+
+    #[test]
+    fn test_amphibious_assault_waits_for_naval_battle() {
+        use combat::CombatSubPhase;
+        use data::sea_zone_ids::SZ_NORTH_SEA;
+        use data::territory_ids::SCOTLAND;
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        let zone = &mut state.sea_zones[SZ_NORTH_SEA as usize].units;
+        zone.clear();
+        let mut battleship = UnitInstance::new(99_001, UnitType::Battleship, Power::Germany);
+        battleship.moved_this_turn = true;
+        let mut transport = UnitInstance::new(99_002, UnitType::Transport, Power::Germany);
+        transport.moved_this_turn = true;
+        transport.cargo = vec![99_003];
+        zone.extend([battleship, transport]);
+        zone.push(UnitInstance::new(99_004, UnitType::Transport, Power::UnitedKingdom));
+        let mut infantry = UnitInstance::new(99_003, UnitType::Infantry, Power::Germany);
+        infantry.moved_this_turn = true;
+        state.territories[SCOTLAND as usize].units.push(infantry);
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles = vec![RegionId::Land(SCOTLAND), RegionId::Sea(SZ_NORTH_SEA)];
+        state.phase_state = PhaseState::Combat(cs);
+
+        let land = Action::SelectBattle { location: RegionId::Land(SCOTLAND) };
+        assert!(engine.is_action_legal(&land).is_err());
+
+        engine
+            .submit_action(Action::SelectBattle { location: RegionId::Sea(SZ_NORTH_SEA) })
+            .unwrap();
+        for _ in 0..100 {
+            let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+            let Some(combat) = &cs.active_combat else { break };
+            let action = match combat.sub_phase {
+                CombatSubPhase::AttackerRolls => Action::RollAttack,
+                CombatSubPhase::DefenderRolls => Action::RollDefense,
+                CombatSubPhase::DefenderSelectsCasualties => {
+                    Action::SelectCasualties { casualties: vec![99_004] }
+                }
+                ref other => panic!("unexpected sub-phase {:?}", other),
+            };
+            engine.submit_action(action).unwrap();
+        }
+
+        engine.submit_action(land).unwrap();
+        let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+        assert!(cs.active_combat.as_ref().unwrap().is_amphibious);
+    }
+
+    #[test]
+    fn test_sunk_transport_cancels_amphibious_assault() {
+        use combat::CombatSubPhase;
+        use data::sea_zone_ids::SZ_NORTH_SEA;
+        use data::territory_ids::SCOTLAND;
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        let zone = &mut state.sea_zones[SZ_NORTH_SEA as usize].units;
+        zone.clear();
+        let mut battleship = UnitInstance::new(99_001, UnitType::Battleship, Power::Germany);
+        battleship.moved_this_turn = true;
+        let mut transport = UnitInstance::new(99_002, UnitType::Transport, Power::Germany);
+        transport.moved_this_turn = true;
+        transport.cargo = vec![99_003];
+        zone.extend([battleship, transport]);
+        for id in 99_010..99_014 {
+            zone.push(UnitInstance::new(id, UnitType::Battleship, Power::UnitedKingdom));
+        }
+        let mut infantry = UnitInstance::new(99_003, UnitType::Infantry, Power::Germany);
+        infantry.moved_this_turn = true;
+        state.territories[SCOTLAND as usize].units.push(infantry);
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles = vec![RegionId::Land(SCOTLAND), RegionId::Sea(SZ_NORTH_SEA)];
+        state.phase_state = PhaseState::Combat(cs);
+
+        engine
+            .submit_action(Action::SelectBattle { location: RegionId::Sea(SZ_NORTH_SEA) })
+            .unwrap();
+        let mut events = Vec::new();
+        for _ in 0..200 {
+            let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+            let Some(combat) = &cs.active_combat else { break };
+            // The attacker gives up the loaded transport first
+            let action = if combat.sub_phase == CombatSubPhase::AttackerSelectsCasualties
+                && combat.attacker_units.contains(&99_002)
+            {
+                let mut casualties = engine.suggest_casualties();
+                if !casualties.contains(&99_002) {
+                    casualties[0] = 99_002;
+                }
+                Action::SelectCasualties { casualties }
+            } else {
+                ai::ai_handle_battle(engine.state(), combat)
+            };
+            events.extend(engine.submit_action(action).unwrap().events);
+        }
+
+        assert!(movement::find_unit(engine.state(), 99_002).is_none());
+        assert!(movement::find_unit(engine.state(), 99_003).is_none());
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, GameEvent::AmphibiousAssaultCancelled { territory_id: SCOTLAND })));
+        let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+        assert!(!cs.pending_battles.contains(&RegionId::Land(SCOTLAND)));
+    }
+
+    #[test]
+    fn test_sunk_transport_spares_troops_already_ashore() {
+        use data::sea_zone_ids::SZ_NORTH_SEA;
+        use data::territory_ids::NORWAY;
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        let zone = &mut state.sea_zones[SZ_NORTH_SEA as usize].units;
+        zone.clear();
+        let mut transport = UnitInstance::new(99_002, UnitType::Transport, Power::Germany);
+        transport.moved_this_turn = true;
+        // A stale entry for infantry that landed where no battle is pending
+        transport.cargo = vec![99_003];
+        zone.push(transport);
+        for id in 99_010..99_014 {
+            zone.push(UnitInstance::new(id, UnitType::Battleship, Power::UnitedKingdom));
+        }
+        state.territories[NORWAY as usize]
+            .units
+            .push(UnitInstance::new(99_003, UnitType::Infantry, Power::Germany));
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles = vec![RegionId::Sea(SZ_NORTH_SEA)];
+        state.phase_state = PhaseState::Combat(cs);
+
+        engine
+            .submit_action(Action::SelectBattle { location: RegionId::Sea(SZ_NORTH_SEA) })
+            .unwrap();
+        for _ in 0..200 {
+            let PhaseState::Combat(cs) = &engine.state().phase_state else { unreachable!() };
+            let Some(combat) = &cs.active_combat else { break };
+            let action = ai::ai_handle_battle(engine.state(), combat);
+            engine.submit_action(action).unwrap();
+        }
+
+        assert!(movement::find_unit(engine.state(), 99_002).is_none());
+        let (region, _) = movement::find_unit(engine.state(), 99_003).unwrap();
+        assert_eq!(region, RegionId::Land(NORWAY));
+    }
+
+    #[test]
+    fn test_units_can_move_again_next_turn() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        let tank = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == unit::UnitType::Tank)
+            .unwrap()
+            .id;
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        engine.submit_action(Action::ConfirmPhase).unwrap();
+        engine
+            .submit_action(Action::MoveUnitNonCombat {
+                unit_id: tank,
+                path: vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)],
+            })
+            .unwrap();
+        engine.submit_action(Action::ConfirmNonCombatMovement).unwrap();
+        engine.submit_action(Action::ConfirmMobilization).unwrap();
+        engine.submit_action(Action::ConfirmIncome).unwrap();
+
+        while engine.state().current_power != Power::Germany {
+            advance_through_phases(&mut engine);
+        }
+        let (_, unit) = movement::find_unit(engine.state(), tank).unwrap();
+        assert!(!unit.moved_this_turn);
+        assert_eq!(unit.movement_remaining, unit::get_unit_stats(unit.unit_type).movement);
+    }
+
+    #[test]
+    fn test_undo_one_of_two_recorded_moves() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let tanks: Vec<_> = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .filter(|u| u.unit_type == unit::UnitType::Tank)
+            .map(|u| u.id)
+            .take(2)
+            .collect();
+        let path = vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)];
+        for &unit_id in &tanks {
+            engine.submit_action(Action::MoveUnit { unit_id, path: path.clone() }).unwrap();
+        }
+        let moved: Vec<_> = engine.recorded_moves().iter().map(|m| m.unit_id).collect();
+        assert_eq!(moved, tanks);
+
+        engine.submit_action(Action::UndoMove { unit_id: tanks[0] }).unwrap();
+        let remaining = engine.recorded_moves();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].unit_id, tanks[1]);
+
+        let (region, unit) = movement::find_unit(engine.state(), tanks[0]).unwrap();
+        assert_eq!(region, RegionId::Land(t::GERMANY));
+        assert!(!unit.moved_this_turn);
+        let (region, _) = movement::find_unit(engine.state(), tanks[1]).unwrap();
+        assert_eq!(region, RegionId::Land(t::WESTERN_GERMANY));
+    }
+
+    #[test]
+    fn test_undo_operation_takes_back_all_its_moves() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let germany = &engine.state().territories[t::GERMANY as usize].units;
+        let of_type = |ut| germany.iter().filter(move |u| u.unit_type == ut).map(|u| u.id);
+        let units: Vec<_> = of_type(unit::UnitType::Tank)
+            .take(2)
+            .chain(of_type(unit::UnitType::Infantry).take(1))
+            .collect();
+        let path = vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)];
+        let name = "Push west".to_string();
+
+        engine.submit_action(Action::BeginOperation { name: name.clone() }).unwrap();
+        assert!(engine.is_action_legal(&Action::BeginOperation { name: "Other".into() }).is_err());
+        for &unit_id in &units[..2] {
+            engine.submit_action(Action::MoveUnit { unit_id, path: path.clone() }).unwrap();
+        }
+        engine.submit_action(Action::EndOperation).unwrap();
+        engine.submit_action(Action::MoveUnit { unit_id: units[2], path: path.clone() }).unwrap();
+
+        engine.submit_action(Action::UndoOperation { name: name.clone() }).unwrap();
+        for &unit_id in &units[..2] {
+            let (region, unit) = movement::find_unit(engine.state(), unit_id).unwrap();
+            assert_eq!(region, RegionId::Land(t::GERMANY));
+            assert!(!unit.moved_this_turn);
+        }
+        let remaining = engine.recorded_moves();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].unit_id, units[2]);
+        assert!(engine.is_action_legal(&Action::UndoOperation { name: name.clone() }).is_err());
+
+        // Undo brings the operation back, still under its name
+        engine.submit_action(Action::Undo).unwrap();
+        for &unit_id in &units[..2] {
+            let (region, unit) = movement::find_unit(engine.state(), unit_id).unwrap();
+            assert_eq!(region, RegionId::Land(t::WESTERN_GERMANY));
+            assert!(unit.moved_this_turn);
+        }
+        let operation_moves = engine
+            .recorded_moves()
+            .iter()
+            .filter(|m| m.operation.as_deref() == Some(name.as_str()))
+            .count();
+        assert_eq!(operation_moves, 2);
+        let phase::PhaseState::CombatMove(ref cms) = engine.state().phase_state else {
+            panic!("Expected combat movement");
+        };
+        assert_eq!(cms.open_operation, None);
+        engine.submit_action(Action::UndoOperation { name }).unwrap();
+        assert_eq!(engine.recorded_moves().len(), 1);
+    }
+
+    #[test]
+    fn test_unit_moves_once_unless_its_move_is_taken_back() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let tank = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == unit::UnitType::Tank)
+            .unwrap()
+            .id;
+        let west = vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)];
+        let back = vec![RegionId::Land(t::WESTERN_GERMANY), RegionId::Land(t::GERMANY)];
+        let move_west = Action::MoveUnit { unit_id: tank, path: west.clone() };
+
+        // Taken back, either way, the tank is free to move again
+        engine.submit_action(move_west.clone()).unwrap();
+        engine.submit_action(Action::UndoMove { unit_id: tank }).unwrap();
+        engine.submit_action(move_west.clone()).unwrap();
+        engine.submit_action(Action::Undo).unwrap();
+        engine.submit_action(move_west.clone()).unwrap();
+        assert!(engine
+            .is_action_legal(&Action::MoveUnit { unit_id: tank, path: back.clone() })
+            .is_err());
+
+        // Once combat moves are confirmed the move stands for the turn
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        assert!(engine.is_action_legal(&Action::UndoMove { unit_id: tank }).is_err());
+        engine.submit_action(Action::ConfirmPhase).unwrap();
+        assert_eq!(engine.state().current_phase, Phase::NonCombatMovement);
+        let error = engine
+            .is_action_legal(&Action::MoveUnitNonCombat { unit_id: tank, path: back })
+            .unwrap_err();
+        assert!(error.to_string().contains("already moved"), "{}", error);
+    }
+
+    #[test]
+    fn test_confirm_phase_matches_each_phase_confirm() {
+        let mut generic = Engine::new_game(42);
+        let mut specific = Engine::new_game(42);
+        for _ in 0..6 {
+            let phase = generic.state().current_phase;
+            let a = generic.submit_action(Action::ConfirmPhase).unwrap();
+            let b = specific.submit_action(phase.confirm_action()).unwrap();
+            assert_eq!(a.state_checksum, b.state_checksum, "after confirming {:?}", phase);
+            assert_eq!(generic.state().current_phase, specific.state().current_phase);
+        }
+        assert_eq!(generic.state().current_power, power::Power::SovietUnion);
+    }
+
+    #[test]
+    fn test_movement_block_reason_explains_moved_unit() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        let tanks: Vec<_> = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .filter(|u| u.unit_type == unit::UnitType::Tank)
+            .map(|u| u.id)
+            .collect();
+        assert!(engine.movement_block_reason(tanks[0]).unwrap().contains("PurchaseAndRepair"));
+
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let path = vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)];
+        engine.submit_action(Action::MoveUnit { unit_id: tanks[0], path }).unwrap();
+        assert!(engine.movement_block_reason(tanks[0]).unwrap().contains("already moved"));
+        assert_eq!(engine.movement_block_reason(tanks[1]), None);
+    }
+
+    #[test]
+    fn test_planned_paths_keep_every_hop() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let tank = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == unit::UnitType::Tank)
+            .map(|u| u.id)
+            .unwrap();
+        let path = vec![
+            RegionId::Land(t::GERMANY),
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+        ];
+        engine.submit_action(Action::MoveUnit { unit_id: tank, path: path.clone() }).unwrap();
+
+        assert_eq!(engine.planned_paths(), vec![(tank, path)]);
+    }
+
+    #[test]
+    fn test_infantry_cannot_walk_into_sea_zone() {
+        use data::sea_zone_ids::SZ_NORTH_SEA;
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let infantry = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == unit::UnitType::Infantry)
+            .map(|u| u.id)
+            .unwrap();
+
+        let path = vec![RegionId::Land(t::GERMANY), RegionId::Sea(SZ_NORTH_SEA)];
+        let err = engine.submit_action(Action::MoveUnit { unit_id: infantry, path }).unwrap_err();
+        match err {
+            EngineError::InvalidAction { reason } => assert!(reason.contains("cannot enter a sea zone")),
+            other => panic!("expected a domain mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_victory_only_declared_at_end_of_round() {
+        use data::territory_ids as t;
+
+        let mut engine = Engine::new_game(42);
+        advance_through_phases(&mut engine); // Germany
+
+        // The Allies take Berlin and Tokyo during the Soviet turn
+        engine.state.territories[t::GERMANY as usize].owner = Some(Power::SovietUnion);
+        engine.state.territories[t::JAPAN as usize].owner = Some(Power::UnitedStates);
+
+        let mut victory_turns = Vec::new();
+        while engine.state().current_power != Power::Germany {
+            let power = engine.state().current_power;
+            assert!(engine.check_victory().is_none(), "game ended before {:?} played", power);
+            for action in [
+                Action::ConfirmPurchases,
+                Action::ConfirmCombatMovement,
+                Action::ConfirmPhase,
+                Action::ConfirmNonCombatMovement,
+                Action::ConfirmMobilization,
+                Action::ConfirmIncome,
+            ] {
+                let events = engine.submit_action(action).unwrap().events;
+                if events.iter().any(|e| matches!(e, GameEvent::VictoryAchieved { .. })) {
+                    victory_turns.push(power);
+                }
+            }
+        }
+
+        assert_eq!(victory_turns, vec![Power::France]);
+        assert!(matches!(
+            engine.check_victory(),
+            Some(GameEvent::VictoryAchieved { winner: power::Team::Allies })
+        ));
+    }
+
+    #[test]
+    fn test_purchase_history_records_each_turn() {
+        use data::territory_ids as t;
+        use unit::UnitType;
+
+        let mut engine = Engine::new_game(42);
+        assert!(engine.last_purchase(Power::Germany).is_none());
+
+        engine.submit_action(Action::PurchaseUnit { unit_type: UnitType::Infantry, count: 3 }).unwrap();
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        assert_eq!(engine.last_purchase(Power::Germany), Some(&(1, vec![(UnitType::Infantry, 3)])));
+
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        engine.submit_action(Action::ConfirmPhase).unwrap();
+        engine.submit_action(Action::ConfirmNonCombatMovement).unwrap();
+        for _ in 0..3 {
+            engine
+                .submit_action(Action::PlaceUnit { unit_type: UnitType::Infantry, territory_id: t::GERMANY, sea_zone: None })
+                .unwrap();
+        }
+        engine.submit_action(Action::ConfirmMobilization).unwrap();
+        engine.submit_action(Action::ConfirmIncome).unwrap();
+        while engine.state().current_power != Power::Germany {
+            advance_through_phases(&mut engine);
+        }
+
+        engine.submit_action(Action::PurchaseUnit { unit_type: UnitType::Tank, count: 1 }).unwrap();
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let history = &engine.state().powers[Power::Germany as usize].purchase_history;
+        assert_eq!(history.len(), 2);
+        assert_eq!(engine.last_purchase(Power::Germany), Some(&(2, vec![(UnitType::Tank, 1)])));
+    }
+
+    #[test]
+    fn test_idle_unit_count_drops_after_a_move() {
+        use data::territory_ids as t;
+        use territory::RegionId;
+
+        let mut engine = Engine::new_game(42);
+        assert_eq!(engine.idle_unit_count(Power::Germany), 0);
+
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        let all = engine.state().units_of_power(Power::Germany).len();
+        assert_eq!(engine.idle_unit_count(Power::Germany), all);
+
+        let tank = engine.state().territories[t::GERMANY as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == unit::UnitType::Tank)
+            .map(|u| u.id)
+            .unwrap();
+        let path = vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)];
+        engine.submit_action(Action::MoveUnit { unit_id: tank, path }).unwrap();
+
+        assert_eq!(engine.idle_unit_count(Power::Germany), all - 1);
+        assert!(!engine.idle_units(Power::Germany).iter().any(|&(_, id)| id == tank));
+    }
+
+    #[test]
+    fn test_roll_attack_reports_combat_step() {
+        use combat::CombatSubPhase;
+        use data::sea_zone_ids::SZ_NORTH_SEA;
+        use phase::{CombatState, PhaseState};
+        use territory::RegionId;
+        use unit::{UnitInstance, UnitType};
+
+        let mut engine = Engine::new_game(42);
+        let state = engine.state_mut();
+        let zone = &mut state.sea_zones[SZ_NORTH_SEA as usize].units;
+        zone.clear();
+        let mut battleship = UnitInstance::new(99_001, UnitType::Battleship, Power::Germany);
+        battleship.moved_this_turn = true;
+        zone.push(battleship);
+        zone.push(UnitInstance::new(99_002, UnitType::Destroyer, Power::UnitedKingdom));
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles.push(RegionId::Sea(SZ_NORTH_SEA));
+        state.phase_state = PhaseState::Combat(cs);
+
+        let selected = engine
+            .submit_action(Action::SelectBattle { location: RegionId::Sea(SZ_NORTH_SEA) })
+            .unwrap();
+        assert_eq!(selected.combat_step.unwrap().sub_phase, CombatSubPhase::AttackerRolls);
+
+        let step = engine.submit_action(Action::RollAttack).unwrap().combat_step.unwrap();
+        assert_eq!(step.rolls.len(), 1);
+        assert_eq!(step.hits, step.rolls.iter().filter(|&&r| r <= 4).count() as u32);
+        assert_eq!(step.pending_attacker_hits, step.hits);
+        assert_eq!(step.sub_phase, CombatSubPhase::DefenderRolls);
+        assert!(!step.battle_over);
+    }
+
+    #[test]
+    fn test_income_breakdown_matches_collected_income() {
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        engine.submit_action(Action::ConfirmCombatMovement).unwrap();
+        engine.submit_action(Action::ConfirmPhase).unwrap();
+        engine.submit_action(Action::ConfirmNonCombatMovement).unwrap();
+        engine.submit_action(Action::ConfirmMobilization).unwrap();
+
+        let preview = engine.income_breakdown(Power::Germany);
+        let before = engine.state().powers[Power::Germany as usize].ipcs;
+        engine.submit_action(Action::ConfirmIncome).unwrap();
+        let credited = engine.state().powers[Power::Germany as usize].ipcs - before;
+
+        assert!(preview.base_income > 0);
+        assert_eq!(
+            preview.base_income + preview.objective_bonus - preview.convoy_losses - preview.upkeep,
+            credited
+        );
+        assert_eq!(preview.total, credited);
+    }
 }
@@ -5,8 +5,8 @@ use crate::error::EngineError;
 use crate::phase::{MobilizeState, PhaseState};
 use crate::power::Power;
 use crate::state::GameState;
-use crate::territory::{FacilityType, TerritoryId};
-use crate::unit::{get_unit_stats, UnitDomain, UnitType};
+use crate::territory::{FacilityType, SeaZoneId, TerritoryId};
+use crate::unit::{UnitDomain, UnitType};
 
 /// Validate a PlaceUnit action during the Mobilize phase.
 pub fn validate_place_unit(
@@ -14,6 +14,7 @@ pub fn validate_place_unit(
     map: &GameMap,
     unit_type: UnitType,
     territory_id: TerritoryId,
+    sea_zone: Option<SeaZoneId>,
 ) -> Result<(), EngineError> {
     let power = state.current_power;
 
@@ -36,18 +37,37 @@ pub fn validate_place_unit(
         });
     }
 
-    let stats = get_unit_stats(unit_type);
+    // Purchases are checked against the production rules too, but units
+    // can reach the mobilize queue without a purchase (scenario setup)
+    if !crate::purchase::can_produce(state, power, unit_type) {
+        return Err(EngineError::InvalidAction {
+            reason: format!("{} cannot build {:?}", power.name(), unit_type),
+        });
+    }
+
+    let stats = state.unit_stats(unit_type);
 
     if stats.domain == UnitDomain::Sea {
         // Naval units: must be placed in a sea zone adjacent to a territory with IC or naval base
-        validate_naval_placement(state, map, power, territory_id)?;
+        let zone = sea_zone.ok_or_else(|| EngineError::InvalidAction {
+            reason: format!("Choose a sea zone to launch the {:?} into", unit_type),
+        })?;
+        validate_naval_placement(state, map, power, territory_id, zone)?;
     } else {
+        if sea_zone.is_some() {
+            return Err(EngineError::InvalidAction {
+                reason: format!("{:?} is placed in the territory, not at sea", unit_type),
+            });
+        }
         // Land and air units: must be placed at a territory with an IC
         validate_land_placement(state, map, power, territory_id)?;
     }
 
     // Check factory production limit
-    check_production_limit(state, ms, territory_id)?;
+    check_production_limit(state, map, ms, territory_id)?;
+    if stats.domain != UnitDomain::Sea {
+        crate::movement::validate_stacking_limit(state, territory_id, power, 1)?;
+    }
 
     Ok(())
 }
@@ -146,14 +166,8 @@ fn validate_naval_placement(
     map: &GameMap,
     power: Power,
     territory_id: TerritoryId,
+    sea_zone: SeaZoneId,
 ) -> Result<(), EngineError> {
-    // For naval placement, territory_id is actually the sea zone where we're placing.
-    // But our action uses TerritoryId - we need to find a friendly territory with IC/naval base
-    // adjacent to this placement. Actually, looking at the action definition:
-    // PlaceUnit { unit_type, territory_id } - territory_id is the territory with the IC.
-    // The naval unit goes to an adjacent sea zone. But we need to figure out which sea zone.
-    // For simplicity, we validate that the territory has an IC or naval base and is coastal.
-
     let territory = state
         .territories
         .get(territory_id as usize)
@@ -186,11 +200,11 @@ fn validate_naval_placement(
         });
     }
 
-    // Must be coastal
+    // The chosen sea zone must border the territory
     let tdef = map.territory(territory_id);
-    if tdef.adjacent_sea.is_empty() {
+    if !tdef.adjacent_sea.contains(&sea_zone) {
         return Err(EngineError::InvalidAction {
-            reason: "Territory must be coastal to place naval units".into(),
+            reason: format!("{} does not border sea zone {}", tdef.name, sea_zone),
         });
     }
 
@@ -200,6 +214,7 @@ fn validate_naval_placement(
 /// Check that the factory production limit hasn't been exceeded.
 fn check_production_limit(
     state: &GameState,
+    map: &GameMap,
     ms: &MobilizeState,
     territory_id: TerritoryId,
 ) -> Result<(), EngineError> {
@@ -217,10 +232,7 @@ fn check_production_limit(
     });
 
     if let Some(ic) = ic {
-        // Get territory IPC value from map for production capacity
-        let ipc_value = 3; // default; we don't have map in this fn but territory state has it
-        // Actually we need the map to get ipc_value. Let's compute capacity from facility.
-        let capacity = ic.production_capacity(ipc_value);
+        let capacity = ic.production_capacity(map.territory(territory_id).ipc_value);
 
         // Count units already placed here this phase
         let placed_here = ms
@@ -264,6 +276,14 @@ pub fn get_production_capacity(state: &GameState, map: &GameMap, territory_id: T
         .sum()
 }
 
+/// Total number of land units the power's factories can place this turn.
+pub fn total_placement_capacity(state: &GameState, map: &GameMap, power: Power) -> u32 {
+    eligible_placement_territories(state, map, power, UnitType::Infantry)
+        .into_iter()
+        .map(|tid| get_production_capacity(state, map, tid))
+        .sum()
+}
+
 /// Get all eligible placement territories for a given unit type.
 pub fn eligible_placement_territories(
     state: &GameState,
@@ -271,7 +291,7 @@ pub fn eligible_placement_territories(
     power: Power,
     unit_type: UnitType,
 ) -> Vec<TerritoryId> {
-    let stats = get_unit_stats(unit_type);
+    let stats = state.unit_stats(unit_type);
     let mut result = Vec::new();
 
     for (i, territory) in state.territories.iter().enumerate() {
@@ -355,7 +375,7 @@ mod tests {
     #[test]
     fn test_validate_place_unit_valid() {
         let (state, map) = setup_mobilize_state();
-        let result = validate_place_unit(&state, &map, UnitType::Infantry, 0);
+        let result = validate_place_unit(&state, &map, UnitType::Infantry, 0, None);
         assert!(result.is_ok());
     }
 
@@ -366,7 +386,7 @@ mod tests {
         if let PhaseState::Mobilize(ref mut ms) = state.phase_state {
             ms.placements.push((UnitType::Tank, 0));
         }
-        let result = validate_place_unit(&state, &map, UnitType::Tank, 0);
+        let result = validate_place_unit(&state, &map, UnitType::Tank, 0, None);
         assert!(result.is_err());
     }
 
@@ -375,7 +395,7 @@ mod tests {
         let (state, map) = setup_mobilize_state();
         // Try to place at a territory not owned by Germany
         // Territory 5 (France) - may be owned by Germany at start, let's use another
-        let result = validate_place_unit(&state, &map, UnitType::Infantry, 126); // Japan
+        let result = validate_place_unit(&state, &map, UnitType::Infantry, 126, None); // Japan
         assert!(result.is_err());
     }
 
@@ -383,7 +403,7 @@ mod tests {
     fn test_validate_place_unit_just_captured() {
         let (mut state, map) = setup_mobilize_state();
         state.territories[0].just_captured = true;
-        let result = validate_place_unit(&state, &map, UnitType::Infantry, 0);
+        let result = validate_place_unit(&state, &map, UnitType::Infantry, 0, None);
         assert!(result.is_err());
     }
 
@@ -392,10 +412,27 @@ mod tests {
         let (mut state, map) = setup_mobilize_state();
         // Use Western Germany (territory 1) which has no IC
         state.territories[1].owner = Some(Power::Germany);
-        let result = validate_place_unit(&state, &map, UnitType::Infantry, 1);
+        let result = validate_place_unit(&state, &map, UnitType::Infantry, 1, None);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_production_limit_uses_territory_ipc_value() {
+        let (mut state, map) = setup_mobilize_state();
+        let capacity = get_production_capacity(&state, &map, 0);
+        assert!(capacity > 3);
+        if let PhaseState::Mobilize(ref mut ms) = state.phase_state {
+            ms.units_to_place = vec![(UnitType::Infantry, capacity + 1)];
+            ms.placements = vec![(UnitType::Infantry, 0); capacity as usize - 1];
+        }
+        assert!(validate_place_unit(&state, &map, UnitType::Infantry, 0, None).is_ok());
+
+        if let PhaseState::Mobilize(ref mut ms) = state.phase_state {
+            ms.placements.push((UnitType::Infantry, 0));
+        }
+        assert!(validate_place_unit(&state, &map, UnitType::Infantry, 0, None).is_err());
+    }
+
     #[test]
     fn test_validate_confirm_mobilization_all_placed() {
         let (mut state, _map) = setup_mobilize_state();
@@ -441,6 +478,38 @@ mod tests {
         assert_eq!(capacity, 3);
     }
 
+    #[test]
+    fn test_placement_matches_unit_domain_to_factory() {
+        use crate::data::sea_zone_ids as sz;
+        use crate::data::territory_ids as t;
+
+        let (mut state, map) = setup_mobilize_state();
+        assert!(map.territory(t::SLOVAKIA_HUNGARY).adjacent_sea.is_empty());
+        let inland = &mut state.territories[t::SLOVAKIA_HUNGARY as usize];
+        inland.owner = Some(Power::Germany);
+        inland.facilities = vec![Facility::new(FacilityType::MinorIndustrialComplex, 0)];
+        if let PhaseState::Mobilize(ref mut ms) = state.phase_state {
+            ms.units_to_place = vec![(UnitType::Submarine, 1), (UnitType::Fighter, 2)];
+        }
+
+        let err = validate_place_unit(&state, &map, UnitType::Submarine, t::SLOVAKIA_HUNGARY, Some(sz::SZ_BALTIC_SEA))
+            .unwrap_err();
+        assert!(err.to_string().contains("does not border"));
+        validate_place_unit(&state, &map, UnitType::Fighter, t::SLOVAKIA_HUNGARY, None).unwrap();
+        validate_place_unit(&state, &map, UnitType::Fighter, 0, None).unwrap();
+    }
+
+    #[test]
+    fn test_placement_respects_production_rules() {
+        let (mut state, map) = setup_mobilize_state();
+        state
+            .settings
+            .production_rules
+            .insert(Power::Germany, crate::purchase::AllowedUnits::AllExcept(vec![UnitType::Tank]));
+        assert!(validate_place_unit(&state, &map, UnitType::Tank, 0, None).is_err());
+        assert!(validate_place_unit(&state, &map, UnitType::Infantry, 0, None).is_ok());
+    }
+
     #[test]
     fn test_eligible_placement_territories() {
         let (state, map) = setup_mobilize_state();
@@ -3,12 +3,15 @@
 //! Covers land, sea, air movement, blitzing, transport loading/unloading,
 //! and strait/canal passage checks.
 
+use std::collections::HashSet;
+
 use crate::data::GameMap;
 use crate::error::EngineError;
+use crate::phase::{Phase, PhaseState};
 use crate::power::Power;
 use crate::state::GameState;
-use crate::territory::{RegionId, SeaZoneId, TerritoryId, TerritoryType};
-use crate::unit::{get_unit_stats, UnitDomain, UnitId, UnitInstance, UnitType, SpecialAbility};
+use crate::territory::{FacilityType, RegionId, SeaZoneId, SeaZoneState, TerritoryId, TerritoryType};
+use crate::unit::{UnitDomain, UnitId, UnitInstance, UnitType, SpecialAbility};
 
 /// Find a unit by ID across all territories and sea zones. Returns (RegionId, &UnitInstance).
 pub fn find_unit<'a>(state: &'a GameState, unit_id: UnitId) -> Option<(RegionId, &'a UnitInstance)> {
@@ -55,14 +58,38 @@ pub fn remove_unit(state: &mut GameState, unit_id: UnitId) -> Option<(RegionId,
     for i in 0..state.sea_zones.len() {
         if let Some(pos) = state.sea_zones[i].units.iter().position(|u| u.id == unit_id) {
             let unit = state.sea_zones[i].units.remove(pos);
+            // Aircraft leaving the zone are off their carrier's deck
+            for carrier in state.sea_zones[i].units.iter_mut() {
+                carrier.cargo.retain(|&id| id != unit_id);
+            }
             return Some((RegionId::Sea(i as SeaZoneId), unit));
         }
     }
     None
 }
 
+/// Whether a unit of `unit_type` may stand in `region`. Ships stay at sea
+/// and land units on land; troops aboard a transport are still listed in
+/// their territory (the transport's `cargo` links them). Aircraft go either.
+pub fn unit_can_occupy(state: &GameState, unit_type: UnitType, region: RegionId) -> bool {
+    !matches!(
+        (state.unit_stats(unit_type).domain, region),
+        (UnitDomain::Sea, RegionId::Land(_)) | (UnitDomain::Land, RegionId::Sea(_))
+    )
+}
+
 /// Place a unit at a region.
+///
+/// The caller must have checked the unit can stand there (see
+/// `unit_can_occupy`); the movement validators do. Debug builds assert it.
 pub fn place_unit_at(state: &mut GameState, region: RegionId, unit: UnitInstance) {
+    debug_assert!(
+        unit_can_occupy(state, unit.unit_type, region),
+        "{:?} {} cannot be placed in {:?}",
+        unit.unit_type,
+        unit.id,
+        region
+    );
     match region {
         RegionId::Land(tid) => {
             state.territories[tid as usize].units.push(unit);
@@ -94,6 +121,39 @@ pub fn has_enemy_units(state: &GameState, tid: TerritoryId, power: Power) -> boo
     state.territories[tid as usize].units.iter().any(|u| state.political.are_at_war(power, u.owner))
 }
 
+/// Check if any unit in a region (land or sea) belongs to a power at war with `power`.
+pub fn region_has_units_at_war(state: &GameState, region: RegionId, power: Power) -> bool {
+    let units = match region {
+        RegionId::Land(tid) => &state.territories[tid as usize].units,
+        RegionId::Sea(sid) => &state.sea_zones[sid as usize].units,
+    };
+    units.iter().any(|u| state.political.are_at_war(power, u.owner))
+}
+
+/// Enforce the optional stacking limit: `arriving` more units of `power`
+/// must fit in territory `tid` alongside those already there.
+pub fn validate_stacking_limit(
+    state: &GameState,
+    tid: TerritoryId,
+    power: Power,
+    arriving: usize,
+) -> Result<(), EngineError> {
+    let Some(limit) = state.settings.max_units_per_territory else {
+        return Ok(());
+    };
+    let present = state.territories[tid as usize]
+        .units
+        .iter()
+        .filter(|u| u.owner == power)
+        .count();
+    if present + arriving > limit as usize {
+        return Err(EngineError::InvalidAction {
+            reason: format!("No more than {} units may stack in one territory", limit),
+        });
+    }
+    Ok(())
+}
+
 /// Check if a sea zone contains enemy units (warships) for the given power.
 pub fn has_enemy_warships(state: &GameState, sid: SeaZoneId, power: Power) -> bool {
     state.sea_zones[sid as usize].units.iter().any(|u| {
@@ -101,6 +161,21 @@ pub fn has_enemy_warships(state: &GameState, sid: SeaZoneId, power: Power) -> bo
     })
 }
 
+/// Check if enemy ships in a sea zone stop the given naval unit from passing
+/// through it. Surface warships block surface ships; submarines slip past
+/// everything except an enemy destroyer.
+pub fn blocks_naval_transit(state: &GameState, sid: SeaZoneId, power: Power, unit_type: UnitType) -> bool {
+    state.sea_zones[sid as usize].units.iter().any(|u| {
+        state.political.are_at_war(power, u.owner)
+            && if unit_type == UnitType::Submarine {
+                u.unit_type == UnitType::Destroyer
+            } else {
+                !matches!(u.unit_type, UnitType::Transport | UnitType::Submarine)
+                    && state.unit_stats(u.unit_type).domain == UnitDomain::Sea
+            }
+    })
+}
+
 /// Check if a territory is unoccupied enemy territory (for blitzing).
 pub fn is_unoccupied_enemy(state: &GameState, tid: TerritoryId, power: Power) -> bool {
     is_enemy_territory(state, tid, power) && !has_enemy_units(state, tid, power)
@@ -111,6 +186,62 @@ pub fn is_strait_passable(state: &GameState, map: &GameMap, strait_id: u8, power
     map.strait_is_passable(strait_id, |tid| is_friendly_territory(state, tid, power))
 }
 
+/// Check one step between sea zones: they must be adjacent or joined by a
+/// strait, and a strait between them (even adjacent ones such as the Danish
+/// straits) must be open to `power`.
+fn validate_sea_step(
+    state: &GameState,
+    map: &GameMap,
+    power: Power,
+    from: SeaZoneId,
+    to: SeaZoneId,
+) -> Result<(), EngineError> {
+    let strait = map.strait_between(from, to);
+    if strait.is_none() && !map.is_sea_adjacent(from, to) {
+        return Err(EngineError::IllegalMove {
+            reason: "Sea zones are not adjacent".into(),
+        });
+    }
+    if let Some(strait_id) = strait {
+        if !is_strait_passable(state, map, strait_id, power) {
+            return Err(EngineError::IllegalMove {
+                reason: format!("Strait '{}' is blocked", map.straits[strait_id as usize].name),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Combat-movement allowance for a unit. Mechanized infantry only gets its
+/// full movement when a tank accompanies it along the path.
+pub fn combat_movement_allowance(state: &GameState, unit: &UnitInstance, tank_escort: bool) -> u8 {
+    let stats = state.unit_stats(unit.unit_type);
+    if stats.special_abilities.contains(&SpecialAbility::BlitzWithTank) && !tank_escort {
+        1
+    } else {
+        stats.movement
+    }
+}
+
+/// Extra range for an air unit taking off from `takeoff`: +1 from an
+/// undamaged air base in a territory friendly to `power`.
+pub fn air_base_range_bonus(state: &GameState, power: Power, takeoff: RegionId) -> u8 {
+    let RegionId::Land(tid) = takeoff else {
+        return 0;
+    };
+    let ts = &state.territories[tid as usize];
+    let friendly = ts.owner.is_some_and(|owner| state.political.are_friendly(owner, power));
+    let base = ts
+        .facilities
+        .iter()
+        .any(|f| f.facility_type == FacilityType::AirBase && f.damage == 0);
+    if friendly && base {
+        1
+    } else {
+        0
+    }
+}
+
 /// Validate a path for land movement during combat movement.
 /// Returns the number of movement points consumed or an error.
 pub fn validate_land_combat_path(
@@ -119,6 +250,19 @@ pub fn validate_land_combat_path(
     power: Power,
     unit: &UnitInstance,
     path: &[RegionId],
+) -> Result<u8, EngineError> {
+    validate_land_combat_path_escorted(state, map, power, unit, path, false)
+}
+
+/// Validate a land combat path, optionally with a tank escorting the unit
+/// (which lets mechanized infantry use its full movement and blitz).
+pub fn validate_land_combat_path_escorted(
+    state: &GameState,
+    map: &GameMap,
+    power: Power,
+    unit: &UnitInstance,
+    path: &[RegionId],
+    tank_escort: bool,
 ) -> Result<u8, EngineError> {
     if path.len() < 2 {
         return Err(EngineError::IllegalMove {
@@ -126,9 +270,10 @@ pub fn validate_land_combat_path(
         });
     }
 
-    let stats = get_unit_stats(unit.unit_type);
-    let max_move = stats.movement;
-    let can_blitz = stats.special_abilities.contains(&SpecialAbility::Blitz);
+    let stats = state.unit_stats(unit.unit_type);
+    let max_move = combat_movement_allowance(state, unit, tank_escort);
+    let can_blitz = stats.special_abilities.contains(&SpecialAbility::Blitz)
+        || (tank_escort && stats.special_abilities.contains(&SpecialAbility::BlitzWithTank));
 
     let mut movement_used: u8 = 0;
 
@@ -209,7 +354,7 @@ pub fn validate_sea_combat_path(
         });
     }
 
-    let stats = get_unit_stats(unit.unit_type);
+    let stats = state.unit_stats(unit.unit_type);
     let max_move = stats.movement;
     let mut movement_used: u8 = 0;
 
@@ -219,28 +364,7 @@ pub fn validate_sea_combat_path(
 
         match (from, to) {
             (RegionId::Sea(f), RegionId::Sea(t)) => {
-                if !map.is_sea_adjacent(f, t) {
-                    // Check strait connections
-                    let mut passable = false;
-                    for strait in &map.straits {
-                        let (a, b) = strait.connects_seas;
-                        if (f == a && t == b) || (f == b && t == a) {
-                            if is_strait_passable(state, map, strait.id, power) {
-                                passable = true;
-                                break;
-                            } else {
-                                return Err(EngineError::IllegalMove {
-                                    reason: format!("Strait '{}' is blocked", strait.name),
-                                });
-                            }
-                        }
-                    }
-                    if !passable {
-                        return Err(EngineError::IllegalMove {
-                            reason: "Sea zones are not adjacent".into(),
-                        });
-                    }
-                }
+                validate_sea_step(state, map, power, f, t)?;
             }
             _ => {
                 return Err(EngineError::IllegalMove {
@@ -249,6 +373,16 @@ pub fn validate_sea_combat_path(
             }
         }
 
+        // Ships must stop where enemy ships block them; the final zone is
+        // where combat happens, so only intermediate zones are checked
+        if let RegionId::Sea(t) = to {
+            if i + 2 < path.len() && blocks_naval_transit(state, t, power, unit.unit_type) {
+                return Err(EngineError::IllegalMove {
+                    reason: format!("Enemy ships in sea zone {} block passage", t),
+                });
+            }
+        }
+
         movement_used += 1;
     }
 
@@ -268,9 +402,9 @@ pub fn validate_sea_combat_path(
 /// Air units can fly over any territory type except impassable.
 /// Path steps can be Land or Sea regions.
 pub fn validate_air_combat_path(
-    _state: &GameState,
+    state: &GameState,
     map: &GameMap,
-    _power: Power,
+    power: Power,
     unit: &UnitInstance,
     path: &[RegionId],
 ) -> Result<u8, EngineError> {
@@ -280,8 +414,8 @@ pub fn validate_air_combat_path(
         });
     }
 
-    let stats = get_unit_stats(unit.unit_type);
-    let max_move = stats.movement;
+    let stats = state.unit_stats(unit.unit_type);
+    let max_move = stats.movement + air_base_range_bonus(state, power, path[0]);
     let mut movement_used: u8 = 0;
 
     for i in 0..path.len() - 1 {
@@ -320,6 +454,108 @@ pub fn validate_air_combat_path(
     Ok(movement_used)
 }
 
+/// Whether `path` carries a land unit across water: from its territory,
+/// through the sea zones a transport sails, onto a shore.
+pub fn is_amphibious_move(state: &GameState, unit_type: UnitType, path: &[RegionId]) -> bool {
+    state.unit_stats(unit_type).domain == UnitDomain::Land
+        && path.len() >= 3
+        && matches!(path[0], RegionId::Land(_))
+        && matches!(path[path.len() - 1], RegionId::Land(_))
+        && path[1..path.len() - 1].iter().all(|r| matches!(r, RegionId::Sea(_)))
+}
+
+/// One of `power`'s transports with room that sailed exactly `route` this
+/// turn: loaded in the first zone, now in the last. A transport that has
+/// not moved can only carry along a route of its own zone.
+pub fn transport_for_route(state: &GameState, power: Power, route: &[RegionId]) -> Option<UnitId> {
+    let (&RegionId::Sea(first), &RegionId::Sea(last)) = (route.first()?, route.last()?) else {
+        return None;
+    };
+    let planned = match &state.phase_state {
+        PhaseState::CombatMove(cms) => cms.moves.as_slice(),
+        _ => &[],
+    };
+    state.sea_zones[last as usize]
+        .units
+        .iter()
+        .filter(|u| u.owner == power && u.unit_type == UnitType::Transport)
+        .filter(|u| u.cargo.len() < state.unit_stats(u.unit_type).transport_capacity as usize)
+        .find(|u| match planned.iter().find(|m| m.unit_id == u.id) {
+            Some(m) => m.path == route,
+            None => first == last && route.len() == 1,
+        })
+        .map(|u| u.id)
+}
+
+/// Clear `power`'s transport cargo entries for units landed where no battle
+/// will be fought: they are ashore, not aboard. A landing on a pending
+/// battle, or from a transport that has a naval battle to fight first, stays
+/// aboard until its assault begins.
+pub fn release_unopposed_landings(state: &mut GameState, power: Power, battles: &[RegionId]) {
+    let ashore: HashSet<UnitId> = state
+        .territories
+        .iter()
+        .enumerate()
+        .filter(|&(tid, _)| !battles.contains(&RegionId::Land(tid as TerritoryId)))
+        .flat_map(|(_, t)| t.units.iter().map(|u| u.id))
+        .collect();
+    for (zone, sea_zone) in state.sea_zones.iter_mut().enumerate() {
+        if battles.contains(&RegionId::Sea(zone as SeaZoneId)) {
+            continue;
+        }
+        for transport in sea_zone
+            .units
+            .iter_mut()
+            .filter(|u| u.owner == power && u.unit_type == UnitType::Transport)
+        {
+            transport.cargo.retain(|id| !ashore.contains(id));
+        }
+    }
+}
+
+/// Validate an amphibious combat move (see [`is_amphibious_move`]) and
+/// return the transport that carries the unit. This is the only way onto an
+/// island with no land neighbours, such as Iceland. An assault must land on
+/// enemy ground; unloading onto friendly shores is not a combat move.
+pub fn validate_amphibious_move(
+    state: &GameState,
+    map: &GameMap,
+    power: Power,
+    path: &[RegionId],
+) -> Result<UnitId, EngineError> {
+    let (RegionId::Land(from), RegionId::Land(to)) = (path[0], path[path.len() - 1]) else {
+        return Err(EngineError::IllegalMove {
+            reason: "An amphibious move must start and end on land".into(),
+        });
+    };
+    let route = &path[1..path.len() - 1];
+    let touches = |tid: TerritoryId, region: RegionId| {
+        matches!(region, RegionId::Sea(zone) if map.territory(tid).adjacent_sea.contains(&zone))
+    };
+    if !touches(from, route[0]) || !touches(to, route[route.len() - 1]) {
+        return Err(EngineError::IllegalMove {
+            reason: "Units board and land from sea zones next to the shore".into(),
+        });
+    }
+    if map.territory(to).territory_type == TerritoryType::Impassable {
+        return Err(EngineError::IllegalMove {
+            reason: "Cannot move into impassable territory".into(),
+        });
+    }
+    let defended = state.territories[to as usize]
+        .units
+        .iter()
+        .any(|u| state.political.are_at_war(power, u.owner));
+    if !is_enemy_territory(state, to, power) && !defended {
+        return Err(EngineError::IllegalMove {
+            reason: "An amphibious assault must land on enemy territory".into(),
+        });
+    }
+    transport_for_route(state, power, route).ok_or_else(|| EngineError::IllegalMove {
+        reason: "No transport with room sailed that route this turn".into(),
+    })
+}
+
 /// Validate a combat movement path based on unit domain.
 pub fn validate_combat_move(
     state: &GameState,
@@ -328,14 +564,126 @@ pub fn validate_combat_move(
     unit: &UnitInstance,
     path: &[RegionId],
 ) -> Result<u8, EngineError> {
-    let stats = get_unit_stats(unit.unit_type);
+    validate_china_restriction(map, unit, path)?;
+    let stats = state.unit_stats(unit.unit_type);
     match stats.domain {
+        UnitDomain::Land if is_amphibious_move(state, unit.unit_type, path) => {
+            validate_amphibious_move(state, map, power, path)?;
+            Ok((path.len() - 1) as u8)
+        }
         UnitDomain::Land => validate_land_combat_path(state, map, power, unit, path),
         UnitDomain::Sea => validate_sea_combat_path(state, map, power, unit, path),
         UnitDomain::Air => validate_air_combat_path(state, map, power, unit, path),
     }
 }
 
+/// Check that a unit restricted to China (the AVG fighter) stays within
+/// territories originally controlled by China along its whole path.
+pub fn validate_china_restriction(
+    map: &GameMap,
+    unit: &UnitInstance,
+    path: &[RegionId],
+) -> Result<(), EngineError> {
+    if !unit.restricted_to_china {
+        return Ok(());
+    }
+    let outside = path.iter().any(|region| match region {
+        RegionId::Land(tid) => map.territory(*tid).original_owner != Some(Power::China),
+        RegionId::Sea(_) => true,
+    });
+    if outside {
+        return Err(EngineError::IllegalMove {
+            reason: "The AVG fighter cannot leave Chinese territory".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Shared movement limits for a group moving together as one stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupMovement {
+    /// The smallest combat movement allowance in the group.
+    pub movement: u8,
+    /// Whether every land unit in the group can blitz.
+    pub can_blitz: bool,
+}
+
+/// Pair the group's tanks with units that blitz alongside one: each tank
+/// escorts one, in group order. Returns whether each unit is escorted.
+fn tank_escorts(state: &GameState, units: &[&UnitInstance]) -> Vec<bool> {
+    let mut escorts = units.iter().filter(|u| u.unit_type == UnitType::Tank).count();
+    units
+        .iter()
+        .map(|unit| {
+            let escorted = state
+                .unit_stats(unit.unit_type)
+                .special_abilities
+                .contains(&SpecialAbility::BlitzWithTank)
+                && escorts > 0;
+            if escorted {
+                escorts -= 1;
+            }
+            escorted
+        })
+        .collect()
+}
+
+/// Work out how far a group can move together and whether it can blitz.
+/// Each tank in the group may escort one mechanized infantry.
+pub fn group_movement(state: &GameState, units: &[&UnitInstance]) -> GroupMovement {
+    let mut movement = u8::MAX;
+    let mut can_blitz = true;
+
+    for (unit, escorted) in units.iter().zip(tank_escorts(state, units)) {
+        let stats = state.unit_stats(unit.unit_type);
+        movement = movement.min(combat_movement_allowance(state, unit, escorted));
+        if stats.domain == UnitDomain::Land {
+            can_blitz &= stats.special_abilities.contains(&SpecialAbility::Blitz) || escorted;
+        }
+    }
+
+    GroupMovement { movement, can_blitz }
+}
+
+/// Validate a group of units moving together along the same combat path.
+/// The group shares the movement of its slowest unit and may only blitz
+/// through empty enemy territory if all of its land units can.
+pub fn validate_group_combat_move(
+    state: &GameState,
+    map: &GameMap,
+    power: Power,
+    units: &[&UnitInstance],
+    path: &[RegionId],
+) -> Result<(), EngineError> {
+    let group = group_movement(state, units);
+    let steps = path.len().saturating_sub(1);
+    if steps > group.movement as usize {
+        return Err(EngineError::IllegalMove {
+            reason: format!("This group can only move {} together", group.movement),
+        });
+    }
+    let blitzes = path.iter().take(steps).skip(1).any(|region| match region {
+        RegionId::Land(tid) => is_enemy_territory(state, *tid, power),
+        RegionId::Sea(_) => false,
+    });
+    if blitzes && !group.can_blitz {
+        return Err(EngineError::IllegalMove {
+            reason: "Not every unit in this group can blitz".into(),
+        });
+    }
+
+    for (unit, escorted) in units.iter().zip(tank_escorts(state, units)) {
+        let stats = state.unit_stats(unit.unit_type);
+        if stats.special_abilities.contains(&SpecialAbility::BlitzWithTank) {
+            validate_land_combat_path_escorted(state, map, power, unit, path, escorted)?;
+        } else {
+            validate_combat_move(state, map, power, unit, path)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Validate a non-combat movement path for land units.
 /// Land units cannot move into enemy territory during non-combat movement.
 pub fn validate_land_noncombat_path(
@@ -351,7 +699,7 @@ pub fn validate_land_noncombat_path(
         });
     }
 
-    let stats = get_unit_stats(unit.unit_type);
+    let stats = state.unit_stats(unit.unit_type);
     let max_move = stats.movement;
     let mut movement_used: u8 = 0;
 
@@ -424,7 +772,7 @@ pub fn validate_sea_noncombat_path(
         });
     }
 
-    let stats = get_unit_stats(unit.unit_type);
+    let stats = state.unit_stats(unit.unit_type);
     let max_move = stats.movement;
     let mut movement_used: u8 = 0;
 
@@ -434,28 +782,7 @@ pub fn validate_sea_noncombat_path(
 
         match (from, to) {
             (RegionId::Sea(f), RegionId::Sea(t)) => {
-                if !map.is_sea_adjacent(f, t) {
-                    // Check straits
-                    let mut passable = false;
-                    for strait in &map.straits {
-                        let (a, b) = strait.connects_seas;
-                        if (f == a && t == b) || (f == b && t == a) {
-                            if is_strait_passable(state, map, strait.id, power) {
-                                passable = true;
-                                break;
-                            } else {
-                                return Err(EngineError::IllegalMove {
-                                    reason: format!("Strait '{}' is blocked", strait.name),
-                                });
-                            }
-                        }
-                    }
-                    if !passable {
-                        return Err(EngineError::IllegalMove {
-                            reason: "Sea zones are not adjacent".into(),
-                        });
-                    }
-                }
+                validate_sea_step(state, map, power, f, t)?;
                 // Non-combat: cannot enter sea zone with enemy warships
                 if has_enemy_warships(state, t, power) {
                     return Err(EngineError::IllegalMove {
@@ -493,7 +820,18 @@ pub fn validate_noncombat_move(
     unit: &UnitInstance,
     path: &[RegionId],
 ) -> Result<u8, EngineError> {
-    let stats = get_unit_stats(unit.unit_type);
+    validate_china_restriction(map, unit, path)?;
+
+    // Powers at war may never end non-combat movement stacked together
+    if let Some(&dest) = path.last() {
+        if region_has_units_at_war(state, dest, power) {
+            return Err(EngineError::IllegalMove {
+                reason: "Destination contains units at war with the moving power".into(),
+            });
+        }
+    }
+
+    let stats = state.unit_stats(unit.unit_type);
     match stats.domain {
         UnitDomain::Land => validate_land_noncombat_path(state, map, power, unit, path),
         UnitDomain::Sea => validate_sea_noncombat_path(state, map, power, unit, path),
@@ -507,16 +845,39 @@ pub fn validate_noncombat_move(
 
 /// Check if an air unit has a potential landing spot within remaining movement.
 /// Used during ConfirmCombatMovement to verify air units can land.
+/// Ownership is read as it stands now, so a takeoff territory lost since
+/// the unit left is no landing spot.
 pub fn air_unit_has_potential_landing(
     state: &GameState,
     map: &GameMap,
     power: Power,
     unit: &UnitInstance,
+    takeoff: RegionId,
     current_location: RegionId,
     movement_used: u8,
 ) -> bool {
-    let stats = get_unit_stats(unit.unit_type);
-    let remaining = stats.movement.saturating_sub(movement_used);
+    let (land, carrier_zones) =
+        air_landing_options(state, map, power, unit, takeoff, current_location, movement_used);
+    land || !carrier_zones.is_empty()
+}
+
+/// Landing options for an air unit within remaining movement: whether any
+/// friendly land territory is reachable, and which reachable sea zones hold
+/// friendly carriers it could land on. Range counts from `takeoff`, so an
+/// air-base bonus there carries through the whole flight.
+fn air_landing_options(
+    state: &GameState,
+    map: &GameMap,
+    power: Power,
+    unit: &UnitInstance,
+    takeoff: RegionId,
+    current_location: RegionId,
+    movement_used: u8,
+) -> (bool, Vec<SeaZoneId>) {
+    let mut carrier_zones = Vec::new();
+    let stats = state.unit_stats(unit.unit_type);
+    let range = stats.movement + air_base_range_bonus(state, power, takeoff);
+    let remaining = range.saturating_sub(movement_used);
 
     // BFS from current location to find friendly land territories within remaining movement
     let mut visited = std::collections::HashSet::new();
@@ -534,7 +895,7 @@ pub fn air_unit_has_potential_landing(
             if is_friendly_territory(state, tid, power) {
                 let def = map.territory(tid);
                 if def.territory_type != TerritoryType::Impassable {
-                    return true;
+                    return (true, carrier_zones);
                 }
             }
         }
@@ -546,7 +907,7 @@ pub fn air_unit_has_potential_landing(
                     .iter()
                     .any(|u| u.unit_type == UnitType::Carrier && state.political.are_friendly(power, u.owner));
                 if has_friendly_carrier {
-                    return true;
+                    carrier_zones.push(sid);
                 }
             }
         }
@@ -596,7 +957,95 @@ pub fn air_unit_has_potential_landing(
         }
     }
 
-    false
+    (false, carrier_zones)
+}
+
+/// Check that all committed air units can land at once. Friendly territories
+/// take any number of aircraft, but carriers only have room for their free
+/// capacity, so aircraft that can only reach carriers are matched to carrier
+/// slots. Each entry is `(unit_id, takeoff, location, movement_used)`; returns the first
+/// unit (in the given order) left without a landing spot.
+pub fn find_unlandable_air_unit(
+    state: &GameState,
+    map: &GameMap,
+    power: Power,
+    air_units: &[(UnitId, RegionId, RegionId, u8)],
+) -> Option<UnitId> {
+    let committed: Vec<UnitId> = air_units.iter().map(|&(id, _, _, _)| id).collect();
+
+    // Aircraft that have no friendly land in reach, with their carrier options
+    let mut carrier_bound: Vec<(UnitId, Vec<SeaZoneId>)> = Vec::new();
+    for &(unit_id, takeoff, location, movement_used) in air_units {
+        let Some((_, unit)) = find_unit(state, unit_id) else {
+            continue;
+        };
+        let (land, zones) =
+            air_landing_options(state, map, power, unit, takeoff, location, movement_used);
+        if land {
+            continue;
+        }
+        if zones.is_empty() {
+            return Some(unit_id);
+        }
+        carrier_bound.push((unit_id, zones));
+    }
+
+    // Free carrier slots per zone, ignoring aircraft that are part of this move
+    let mut slots: Vec<SeaZoneId> = Vec::new();
+    let mut zones: Vec<SeaZoneId> = carrier_bound.iter().flat_map(|(_, z)| z.iter().copied()).collect();
+    zones.sort_unstable();
+    zones.dedup();
+    for sid in zones {
+        let units = &state.sea_zones[sid as usize].units;
+        let capacity: usize = units
+            .iter()
+            .filter(|u| u.unit_type == UnitType::Carrier && state.political.are_friendly(power, u.owner))
+            .map(|u| state.unit_stats(u.unit_type).can_carry_air as usize)
+            .sum();
+        let parked = units
+            .iter()
+            .filter(|u| {
+                matches!(u.unit_type, UnitType::Fighter | UnitType::TacticalBomber)
+                    && state.political.are_friendly(power, u.owner)
+                    && !committed.contains(&u.id)
+            })
+            .count();
+        slots.extend(std::iter::repeat_n(sid, capacity.saturating_sub(parked)));
+    }
+
+    // Bipartite matching of aircraft to slots (augmenting paths)
+    fn assign(
+        i: usize,
+        carrier_bound: &[(UnitId, Vec<SeaZoneId>)],
+        slots: &[SeaZoneId],
+        slot_owner: &mut [Option<usize>],
+        seen: &mut [bool],
+    ) -> bool {
+        for (s, zone) in slots.iter().enumerate() {
+            if seen[s] || !carrier_bound[i].1.contains(zone) {
+                continue;
+            }
+            seen[s] = true;
+            let free = match slot_owner[s] {
+                None => true,
+                Some(other) => assign(other, carrier_bound, slots, slot_owner, seen),
+            };
+            if free {
+                slot_owner[s] = Some(i);
+                return true;
+            }
+        }
+        false
+    }
+
+    let mut slot_owner = vec![None; slots.len()];
+    for i in 0..carrier_bound.len() {
+        let mut seen = vec![false; slots.len()];
+        if !assign(i, &carrier_bound, &slots, &mut slot_owner, &mut seen) {
+            return Some(carrier_bound[i].0);
+        }
+    }
+    None
 }
 
 /// Validate that an air unit can land at the given destination.
@@ -607,12 +1056,13 @@ pub fn validate_air_landing(
     unit: &UnitInstance,
     destination: RegionId,
 ) -> Result<(), EngineError> {
-    let stats = get_unit_stats(unit.unit_type);
+    let stats = state.unit_stats(unit.unit_type);
     if stats.domain != UnitDomain::Air {
         return Err(EngineError::InvalidAction {
             reason: "Only air units can use LandAirUnit".into(),
         });
     }
+    validate_china_restriction(map, unit, &[destination])?;
 
     match destination {
         RegionId::Land(tid) => {
@@ -662,7 +1112,7 @@ fn carrier_has_space(state: &GameState, _carrier_id: UnitId, sea_zone_id: SeaZon
         .units
         .iter()
         .filter(|u| u.unit_type == UnitType::Carrier)
-        .map(|u| get_unit_stats(u.unit_type).can_carry_air as usize)
+        .map(|u| state.unit_stats(u.unit_type).can_carry_air as usize)
         .sum();
 
     let air_on_carriers: usize = state.sea_zones[sea_zone_id as usize]
@@ -674,30 +1124,218 @@ fn carrier_has_space(state: &GameState, _carrier_id: UnitId, sea_zone_id: SeaZon
     air_on_carriers < carrier_capacity
 }
 
+/// Regions a unit of the given domain can step to from `region` in one move.
+fn region_neighbors(map: &GameMap, region: RegionId, domain: UnitDomain) -> Vec<RegionId> {
+    let mut neighbors = Vec::new();
+    match region {
+        RegionId::Land(tid) => {
+            let def = map.territory(tid);
+            if domain != UnitDomain::Sea {
+                neighbors.extend(def.adjacent_land.iter().map(|&t| RegionId::Land(t)));
+            }
+            if domain == UnitDomain::Air {
+                neighbors.extend(def.adjacent_sea.iter().map(|&s| RegionId::Sea(s)));
+            }
+        }
+        RegionId::Sea(sid) => {
+            let def = map.sea_zone(sid);
+            if domain != UnitDomain::Land {
+                neighbors.extend(def.adjacent_sea.iter().map(|&s| RegionId::Sea(s)));
+            }
+            if domain == UnitDomain::Air {
+                neighbors.extend(def.adjacent_land.iter().map(|&t| RegionId::Land(t)));
+            }
+        }
+    }
+    neighbors
+}
+
+/// Every region the unit could legally move to right now, for the current
+/// movement phase. Units that have already moved, have no movement left, or
+/// don't belong to the current power get an empty list, never their origin.
+/// The search carries on past blocked routes, so a destination is still
+/// listed when its shortest route is blocked but a longer one is open.
+pub fn legal_destinations(state: &GameState, map: &GameMap, unit_id: UnitId) -> Vec<RegionId> {
+    legal_paths(state, map, unit_id)
+        .into_iter()
+        .map(|path| *path.last().unwrap())
+        .collect()
+}
+
+/// The paths behind `legal_destinations`: one legal path from the unit's
+/// region to each destination.
+pub fn legal_paths(state: &GameState, map: &GameMap, unit_id: UnitId) -> Vec<Vec<RegionId>> {
+    let Some((origin, unit)) = find_unit(state, unit_id) else {
+        return Vec::new();
+    };
+    if unit.owner != state.current_power || unit.moved_this_turn {
+        return Vec::new();
+    }
+    let combat = match state.current_phase {
+        Phase::CombatMovement => true,
+        Phase::NonCombatMovement => false,
+        _ => return Vec::new(),
+    };
+    let max_move = if combat {
+        unit.movement_remaining.min(combat_movement_allowance(state, unit, false))
+    } else {
+        unit.movement_remaining
+    };
+    let domain = state.unit_stats(unit.unit_type).domain;
+
+    // A region counts as reached only once a legal path ends there, so a
+    // blocked route to it does not hide an open one. Each region is carried
+    // forward at most twice: along the first legal path to it, and before
+    // that along the first path of any kind, which may still lead on
+    let mut reached = HashSet::from([origin]);
+    let mut extended_legal = HashSet::from([origin]);
+    let mut extended_any = HashSet::from([origin]);
+    let mut frontier = vec![vec![origin]];
+    let mut paths = Vec::new();
+    for _ in 0..max_move {
+        let mut next = Vec::new();
+        for path in &frontier {
+            for neighbor in region_neighbors(map, *path.last().unwrap(), domain) {
+                if path.contains(&neighbor) {
+                    continue;
+                }
+                let mut extended = path.clone();
+                extended.push(neighbor);
+                let valid = if combat {
+                    validate_combat_move(state, map, unit.owner, unit, &extended).is_ok()
+                } else {
+                    validate_noncombat_move(state, map, unit.owner, unit, &extended).is_ok()
+                };
+                if valid && reached.insert(neighbor) {
+                    paths.push(extended.clone());
+                }
+                let carry = if valid {
+                    extended_legal.insert(neighbor)
+                } else {
+                    !extended_legal.contains(&neighbor) && extended_any.insert(neighbor)
+                };
+                if carry {
+                    next.push(extended);
+                }
+            }
+        }
+        frontier = next;
+    }
+    paths
+}
+
+/// Regions holding enemy units that the unit could legally move into this
+/// Combat Movement, starting a battle there. Empty outside Combat Movement
+/// and for units that cannot move. Built on `legal_paths`, so the same
+/// combat-move checks apply.
+pub fn attackable_targets(state: &GameState, map: &GameMap, unit_id: UnitId) -> Vec<RegionId> {
+    if state.current_phase != Phase::CombatMovement {
+        return Vec::new();
+    }
+    let power = state.current_power;
+    legal_paths(state, map, unit_id)
+        .into_iter()
+        .map(|path| *path.last().unwrap())
+        .filter(|&region| region_has_units_at_war(state, region, power))
+        .collect()
+}
+
+/// Why the unit cannot move right now, for explaining it to the player, or
+/// `None` if it has somewhere to go. Uses the same checks as move validation
+/// and `legal_destinations`.
+pub fn movement_block_reason(state: &GameState, map: &GameMap, unit_id: UnitId) -> Option<String> {
+    let Some((_, unit)) = find_unit(state, unit_id) else {
+        return Some(format!("Unit {} is not on the board", unit_id));
+    };
+    let combat = match state.current_phase {
+        Phase::CombatMovement => true,
+        Phase::NonCombatMovement => false,
+        phase => return Some(format!("Units cannot move during {:?}", phase)),
+    };
+    if unit.owner != state.current_power {
+        return Some(format!("Unit belongs to {:?}, not {:?}", unit.owner, state.current_power));
+    }
+    if unit.moved_this_turn {
+        return Some("Unit has already moved this turn".into());
+    }
+    let max_move = if combat {
+        unit.movement_remaining.min(combat_movement_allowance(state, unit, false))
+    } else {
+        unit.movement_remaining
+    };
+    if max_move == 0 {
+        return Some("Unit has no movement left".into());
+    }
+    if legal_destinations(state, map, unit_id).is_empty() {
+        return Some("Every region in reach is blocked".into());
+    }
+    None
+}
+
 /// Identify territories where combat should occur after combat movement.
 /// A battle occurs where the current power has moved units into a region containing enemy units.
 pub fn identify_pending_combats(state: &GameState, power: Power) -> Vec<RegionId> {
-    let mut combats = Vec::new();
+    let lands = (0..state.territories.len()).map(|i| RegionId::Land(i as TerritoryId));
+    let seas = (0..state.sea_zones.len()).map(|i| RegionId::Sea(i as SeaZoneId));
+    lands
+        .chain(seas)
+        .filter(|&region| is_pending_combat(state, region, power))
+        .collect()
+}
 
-    // Check land territories
-    for (i, t) in state.territories.iter().enumerate() {
-        let has_friendly = t.units.iter().any(|u| u.owner == power && u.moved_this_turn);
-        let has_enemy = t.units.iter().any(|u| state.political.are_at_war(power, u.owner));
-        if has_friendly && has_enemy {
-            combats.push(RegionId::Land(i as TerritoryId));
-        }
-    }
+/// `identify_pending_combats` checking only the regions this phase's
+/// recorded combat moves ended in. Only units that moved can start a battle
+/// and every combat move is recorded as it lands, so the result is the same
+/// without scanning the whole board. Falls back to the full scan outside
+/// Combat Movement.
+pub fn identify_pending_combats_from_moves(state: &GameState, power: Power) -> Vec<RegionId> {
+    let PhaseState::CombatMove(cms) = &state.phase_state else {
+        return identify_pending_combats(state, power);
+    };
+    let mut regions: Vec<RegionId> = cms.moves.iter().map(|m| m.to).collect();
+    // Same order as the full scan: territories, then sea zones
+    regions.sort_by_key(|&region| match region {
+        RegionId::Land(tid) => (0, tid),
+        RegionId::Sea(zone) => (1, zone as TerritoryId),
+    });
+    regions.dedup();
+    regions.retain(|&region| is_pending_combat(state, region, power));
+    regions
+}
 
-    // Check sea zones
-    for (i, sz) in state.sea_zones.iter().enumerate() {
-        let has_friendly = sz.units.iter().any(|u| u.owner == power && u.moved_this_turn);
-        let has_enemy = sz.units.iter().any(|u| state.political.are_at_war(power, u.owner));
-        if has_friendly && has_enemy {
-            combats.push(RegionId::Sea(i as SeaZoneId));
+/// Whether units `power` moved this turn share `region` with enemies they
+/// can fight.
+fn is_pending_combat(state: &GameState, region: RegionId, power: Power) -> bool {
+    let units = match region {
+        RegionId::Land(tid) => &state.territories[tid as usize].units,
+        RegionId::Sea(zone) => &state.sea_zones[zone as usize].units,
+    };
+    let has_friendly = units.iter().any(|u| u.owner == power && u.moved_this_turn);
+    let has_enemy = units.iter().any(|u| state.political.are_at_war(power, u.owner));
+    has_friendly
+        && has_enemy
+        && match region {
+            RegionId::Land(_) => true,
+            RegionId::Sea(zone) => sea_battle_possible(state, &state.sea_zones[zone as usize], power),
         }
-    }
+}
 
-    combats
+/// Whether `power`'s units in a sea zone can fight the enemies there.
+/// Enemy warships (or transports) always mean a battle, but aircraft cannot
+/// hit submarines, so planes over a zone holding only enemy submarines start
+/// one only if a ship of ours is there too.
+fn sea_battle_possible(state: &GameState, sz: &SeaZoneState, power: Power) -> bool {
+    let subs_only = sz
+        .units
+        .iter()
+        .filter(|u| state.political.are_at_war(power, u.owner))
+        .all(|u| u.unit_type == UnitType::Submarine);
+    if !subs_only {
+        return true;
+    }
+    sz.units.iter().any(|u| {
+        u.owner == power && state.unit_stats(u.unit_type).domain == UnitDomain::Sea
+    })
 }
 
 #[cfg(test)]
@@ -714,6 +1352,21 @@ mod tests {
         (state, map)
     }
 
+    #[test]
+    fn test_danish_straits_close_the_baltic() {
+        let (mut state, map) = test_state_and_map();
+        let destroyer = UnitInstance::new(900_001, UnitType::Destroyer, Power::Germany);
+        state.sea_zones[sz::SZ_BALTIC_SEA as usize].units.push(destroyer.clone());
+        let path = [RegionId::Sea(sz::SZ_BALTIC_SEA), RegionId::Sea(sz::SZ_SKAGERRAK)];
+
+        assert!(validate_combat_move(&state, &map, Power::Germany, &destroyer, &path).is_ok());
+
+        state.territories[t::DENMARK as usize].owner = Some(Power::UnitedKingdom);
+        let err = validate_combat_move(&state, &map, Power::Germany, &destroyer, &path).unwrap_err();
+        assert!(matches!(err, EngineError::IllegalMove { ref reason } if reason.contains("Danish Straits")));
+        assert!(validate_noncombat_move(&state, &map, Power::Germany, &destroyer, &path).is_err());
+    }
+
     #[test]
     fn test_find_unit_in_territory() {
         let (mut state, _map) = test_state_and_map();
@@ -784,6 +1437,85 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_lone_mech_moves_one() {
+        let (state, map) = test_state_and_map();
+        let mech = UnitInstance::new(1, UnitType::MechInfantry, Power::Germany);
+        let path = vec![
+            RegionId::Land(t::GERMANY),
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+        ];
+        assert!(validate_land_combat_path(&state, &map, Power::Germany, &mech, &path).is_err());
+        assert_eq!(
+            validate_land_combat_path(&state, &map, Power::Germany, &mech, &path[..2]).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_mech_with_tank_moves_two() {
+        let (state, map) = test_state_and_map();
+        let mech = UnitInstance::new(1, UnitType::MechInfantry, Power::Germany);
+        let tank = UnitInstance::new(2, UnitType::Tank, Power::Germany);
+        let path = vec![
+            RegionId::Land(t::GERMANY),
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+        ];
+        assert!(validate_group_combat_move(&state, &map, Power::Germany, &[&mech, &tank], &path).is_ok());
+        assert!(validate_group_combat_move(&state, &map, Power::Germany, &[&mech], &path).is_err());
+    }
+
+    #[test]
+    fn test_one_tank_escorts_one_mech() {
+        let (state, map) = test_state_and_map();
+        let mech1 = UnitInstance::new(1, UnitType::MechInfantry, Power::Germany);
+        let mech2 = UnitInstance::new(2, UnitType::MechInfantry, Power::Germany);
+        let tank = UnitInstance::new(3, UnitType::Tank, Power::Germany);
+        let path = vec![
+            RegionId::Land(t::GERMANY),
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+        ];
+        let result = validate_group_combat_move(&state, &map, Power::Germany, &[&mech1, &mech2, &tank], &path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_tank_and_mech_blitz_together() {
+        let (mut state, map) = test_state_and_map();
+        state.territories[t::HOLLAND_BELGIUM as usize].owner = Some(Power::UnitedKingdom);
+        state.territories[t::HOLLAND_BELGIUM as usize].units.clear();
+        let mech = UnitInstance::new(1, UnitType::MechInfantry, Power::Germany);
+        let tank = UnitInstance::new(2, UnitType::Tank, Power::Germany);
+        let path = vec![
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+            RegionId::Land(t::NORMANDY_BORDEAUX),
+        ];
+        assert_eq!(group_movement(&state, &[&mech, &tank]), GroupMovement { movement: 2, can_blitz: true });
+        assert!(validate_group_combat_move(&state, &map, Power::Germany, &[&mech, &tank], &path).is_ok());
+    }
+
+    #[test]
+    fn test_infantry_caps_group_movement() {
+        let (state, map) = test_state_and_map();
+        let mech = UnitInstance::new(1, UnitType::MechInfantry, Power::Germany);
+        let tank = UnitInstance::new(2, UnitType::Tank, Power::Germany);
+        let infantry = UnitInstance::new(3, UnitType::Infantry, Power::Germany);
+        let group = [&mech, &tank, &infantry];
+        assert_eq!(group_movement(&state, &group), GroupMovement { movement: 1, can_blitz: false });
+
+        let path = vec![
+            RegionId::Land(t::GERMANY),
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+        ];
+        assert!(validate_group_combat_move(&state, &map, Power::Germany, &group, &path).is_err());
+        assert!(validate_group_combat_move(&state, &map, Power::Germany, &group, &path[..2]).is_ok());
+    }
+
     #[test]
     fn test_tank_can_move_two() {
         let (state, map) = test_state_and_map();
@@ -851,4 +1583,299 @@ mod tests {
         assert_eq!(combats.len(), 1);
         assert_eq!(combats[0], RegionId::Land(t::FRANCE));
     }
+
+    #[test]
+    fn test_pending_combats_from_moves_match_full_scan() {
+        use crate::action::Action;
+        use crate::ai::{ai_next_action, AiDifficulty};
+        use crate::Engine;
+
+        let mut engine = Engine::new_game(42);
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+        loop {
+            let action = ai_next_action(engine.state(), &engine.map, AiDifficulty::Normal);
+            if matches!(action, Action::ConfirmCombatMovement) {
+                break;
+            }
+            engine.submit_action(action).unwrap();
+        }
+
+        let state = engine.state();
+        let full = identify_pending_combats(state, state.current_power);
+        assert!(!full.is_empty());
+        assert_eq!(identify_pending_combats_from_moves(state, state.current_power), full);
+    }
+
+    #[test]
+    fn test_noncombat_move_rejects_stacking_with_enemy() {
+        let (mut state, map) = test_state_and_map();
+        let unit = UnitInstance::new(99_001, UnitType::Infantry, Power::Germany);
+        // Western Germany is German-owned, but a British unit is sitting in it
+        state.territories[t::WESTERN_GERMANY as usize]
+            .units
+            .push(UnitInstance::new(99_002, UnitType::Infantry, Power::UnitedKingdom));
+        let path = vec![RegionId::Land(t::GERMANY), RegionId::Land(t::WESTERN_GERMANY)];
+
+        let result = validate_noncombat_move(&state, &map, Power::Germany, &unit, &path);
+        assert!(matches!(result, Err(EngineError::IllegalMove { .. })));
+
+        state.territories[t::WESTERN_GERMANY as usize].units.retain(|u| u.id != 99_002);
+        assert!(validate_noncombat_move(&state, &map, Power::Germany, &unit, &path).is_ok());
+    }
+
+    #[test]
+    fn test_air_base_extends_fighter_range() {
+        let (mut state, map) = test_state_and_map();
+        let fighter = UnitInstance::new(900, UnitType::Fighter, Power::Germany);
+        let path = [
+            RegionId::Land(t::GERMANY),
+            RegionId::Land(t::WESTERN_GERMANY),
+            RegionId::Land(t::HOLLAND_BELGIUM),
+            RegionId::Land(t::NORMANDY_BORDEAUX),
+            RegionId::Land(t::FRANCE),
+            RegionId::Land(t::SOUTHERN_FRANCE),
+        ];
+        assert_eq!(validate_air_combat_path(&state, &map, Power::Germany, &fighter, &path).unwrap(), 5);
+        // Four hops out, Holland is still within the extended range home
+        let takeoff = RegionId::Land(t::GERMANY);
+        let at = RegionId::Land(t::NORMANDY_BORDEAUX);
+        assert!(air_unit_has_potential_landing(&state, &map, Power::Germany, &fighter, takeoff, at, 4));
+
+        let base = state.territories[t::GERMANY as usize]
+            .facilities
+            .iter_mut()
+            .find(|f| f.facility_type == FacilityType::AirBase)
+            .unwrap();
+        base.damage = 1;
+        assert!(validate_air_combat_path(&state, &map, Power::Germany, &fighter, &path).is_err());
+        assert!(!air_unit_has_potential_landing(&state, &map, Power::Germany, &fighter, takeoff, at, 4));
+    }
+
+    #[test]
+    fn test_fighter_cannot_land_where_it_took_off_once_captured() {
+        let (mut state, map) = test_state_and_map();
+        let fighter = UnitInstance::new(900, UnitType::Fighter, Power::Germany);
+        let takeoff = RegionId::Land(t::HOLLAND_BELGIUM);
+        let at = RegionId::Land(t::NORMANDY_BORDEAUX);
+        // One move left, and Holland is the only friendly land in reach
+        state.territories[t::WESTERN_GERMANY as usize].owner = Some(Power::France);
+        assert!(air_unit_has_potential_landing(&state, &map, Power::Germany, &fighter, takeoff, at, 3));
+
+        state.territories[t::HOLLAND_BELGIUM as usize].owner = Some(Power::UnitedKingdom);
+        assert!(!air_unit_has_potential_landing(&state, &map, Power::Germany, &fighter, takeoff, at, 3));
+
+        // A carrier in the Channel is somewhere else to go
+        state.sea_zones[sz::SZ_ENGLISH_CHANNEL as usize]
+            .units
+            .push(UnitInstance::new(901, UnitType::Carrier, Power::Germany));
+        assert!(air_unit_has_potential_landing(&state, &map, Power::Germany, &fighter, takeoff, at, 3));
+    }
+
+    #[test]
+    fn test_air_landing_respects_carrier_capacity() {
+        let (mut state, map) = test_state_and_map();
+        // No friendly land anywhere: the carrier is the only landing spot
+        for t in state.territories.iter_mut() {
+            t.owner = Some(Power::UnitedKingdom);
+        }
+        for zone in state.sea_zones.iter_mut() {
+            zone.units.clear();
+        }
+        let zone = &mut state.sea_zones[sz::SZ_NORTH_SEA as usize].units;
+        zone.push(UnitInstance::new(900, UnitType::Carrier, Power::Germany));
+        // One slot is already taken by a fighter that isn't moving
+        zone.push(UnitInstance::new(901, UnitType::Fighter, Power::Germany));
+        zone.push(UnitInstance::new(902, UnitType::Fighter, Power::Germany));
+
+        let at_sea = RegionId::Sea(sz::SZ_NORTH_SEA);
+        let from_sea = RegionId::Sea(sz::SZ_BALTIC_SEA);
+        assert_eq!(
+            find_unlandable_air_unit(&state, &map, Power::Germany, &[(902, from_sea, at_sea, 3)]),
+            None
+        );
+
+        // A second committed fighter has nowhere to go
+        state.sea_zones[sz::SZ_NORTH_SEA as usize]
+            .units
+            .push(UnitInstance::new(903, UnitType::Fighter, Power::Germany));
+        assert_eq!(
+            find_unlandable_air_unit(
+                &state,
+                &map,
+                Power::Germany,
+                &[(902, from_sea, at_sea, 3), (903, from_sea, at_sea, 3)]
+            ),
+            Some(903)
+        );
+    }
+
+    #[test]
+    fn test_submarine_passes_enemy_surface_ships_but_destroyer_is_blocked() {
+        let (mut state, map) = test_state_and_map();
+        for zone in state.sea_zones.iter_mut() {
+            zone.units.clear();
+        }
+        state.sea_zones[sz::SZ_SKAGERRAK as usize]
+            .units
+            .push(UnitInstance::new(900, UnitType::Cruiser, Power::UnitedKingdom));
+        let path = [
+            RegionId::Sea(sz::SZ_BALTIC_SEA),
+            RegionId::Sea(sz::SZ_SKAGERRAK),
+            RegionId::Sea(sz::SZ_NORTH_SEA),
+        ];
+
+        let sub = UnitInstance::new(901, UnitType::Submarine, Power::Germany);
+        assert_eq!(validate_sea_combat_path(&state, &map, Power::Germany, &sub, &path).unwrap(), 2);
+
+        let destroyer = UnitInstance::new(902, UnitType::Destroyer, Power::Germany);
+        assert!(validate_sea_combat_path(&state, &map, Power::Germany, &destroyer, &path).is_err());
+
+        // Stopping in the occupied zone is still allowed (it becomes a battle)
+        assert!(validate_sea_combat_path(&state, &map, Power::Germany, &destroyer, &path[..2]).is_ok());
+
+        // An enemy destroyer stops submarines too
+        state.sea_zones[sz::SZ_SKAGERRAK as usize]
+            .units
+            .push(UnitInstance::new(903, UnitType::Destroyer, Power::UnitedKingdom));
+        assert!(validate_sea_combat_path(&state, &map, Power::Germany, &sub, &path).is_err());
+    }
+
+    #[test]
+    fn test_avg_fighter_cannot_leave_china() {
+        let (state, map) = test_state_and_map();
+        let avg = state.territories[t::SZECHWAN as usize]
+            .units
+            .iter()
+            .find(|u| u.unit_type == UnitType::Fighter)
+            .expect("AVG fighter in Szechwan");
+        assert!(avg.restricted_to_china);
+
+        let to_yunnan = [RegionId::Land(t::SZECHWAN), RegionId::Land(t::YUNNAN)];
+        let to_burma = [RegionId::Land(t::SZECHWAN), RegionId::Land(t::YUNNAN), RegionId::Land(t::BURMA)];
+        assert!(validate_noncombat_move(&state, &map, Power::China, avg, &to_yunnan).is_ok());
+        assert!(validate_noncombat_move(&state, &map, Power::China, avg, &to_burma).is_err());
+        assert!(validate_combat_move(&state, &map, Power::China, avg, &to_burma).is_err());
+        assert!(validate_air_landing(&state, &map, Power::China, avg, RegionId::Land(t::BURMA)).is_err());
+
+        // Chinese infantry are not restricted by this rule
+        let infantry = UnitInstance::new(99950, UnitType::Infantry, Power::China);
+        assert!(validate_china_restriction(&map, &infantry, &to_burma).is_ok());
+        let from_yunnan = [RegionId::Land(t::YUNNAN), RegionId::Land(t::BURMA)];
+        assert!(validate_noncombat_move(&state, &map, Power::China, &infantry, &from_yunnan).is_ok());
+    }
+
+    #[test]
+    fn test_legal_destinations_empty_for_moved_unit() {
+        let (mut state, map) = test_state_and_map();
+        state.current_phase = Phase::NonCombatMovement;
+        let mut moved = UnitInstance::new(99_001, UnitType::Infantry, Power::Germany);
+        moved.moved_this_turn = true;
+        state.territories[t::GERMANY as usize].units.push(moved);
+        let mut spent = UnitInstance::new(99_002, UnitType::Infantry, Power::Germany);
+        spent.movement_remaining = 0;
+        state.territories[t::GERMANY as usize].units.push(spent);
+
+        assert!(legal_destinations(&state, &map, 99_001).is_empty());
+        assert!(legal_destinations(&state, &map, 99_002).is_empty());
+    }
+
+    #[test]
+    fn test_legal_destinations_fresh_infantry_reaches_neighbors() {
+        let (mut state, map) = test_state_and_map();
+        state.current_phase = Phase::NonCombatMovement;
+        state.territories[t::GERMANY as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Infantry, Power::Germany));
+
+        let destinations = legal_destinations(&state, &map, 99_001);
+        assert!(destinations.contains(&RegionId::Land(t::WESTERN_GERMANY)));
+        assert!(!destinations.contains(&RegionId::Land(t::GERMANY)));
+        for dest in &destinations {
+            let RegionId::Land(tid) = *dest else { panic!("infantry can't enter sea zones") };
+            assert!(map.is_land_adjacent(t::GERMANY, tid));
+        }
+    }
+
+    #[test]
+    fn test_legal_destinations_route_around_a_blocked_region() {
+        let (mut state, map) = test_state_and_map();
+        state.current_phase = Phase::CombatMovement;
+        state.territories[t::GERMANY as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Tank, Power::Germany));
+        // A British-held Western Germany bars the road to France that way,
+        // but Greater Southern Germany is open
+        state.territories[t::WESTERN_GERMANY as usize].owner = Some(Power::UnitedKingdom);
+        state.territories[t::WESTERN_GERMANY as usize]
+            .units
+            .push(UnitInstance::new(99_010, UnitType::Infantry, Power::UnitedKingdom));
+
+        let paths = legal_paths(&state, &map, 99_001);
+        let to_france = paths
+            .iter()
+            .find(|path| path.last() == Some(&RegionId::Land(t::FRANCE)))
+            .expect("France is reachable through Greater Southern Germany");
+        assert_eq!(to_france[1], RegionId::Land(t::GREATER_SOUTHERN_GERMANY));
+    }
+
+    #[test]
+    fn test_attackable_targets_stay_on_land() {
+        let (mut state, map) = test_state_and_map();
+        state.current_phase = Phase::CombatMovement;
+        state.territories[t::FRANCE as usize].owner = Some(Power::France);
+        state.territories[t::FRANCE as usize]
+            .units
+            .push(UnitInstance::new(99_010, UnitType::Infantry, Power::France));
+        state.territories[t::WESTERN_GERMANY as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Tank, Power::Germany));
+        state.territories[t::NORWAY as usize]
+            .units
+            .push(UnitInstance::new(99_002, UnitType::Infantry, Power::Germany));
+
+        let targets = attackable_targets(&state, &map, 99_001);
+        assert!(targets.contains(&RegionId::Land(t::FRANCE)), "{:?}", targets);
+        // Britain's army is across the North Sea
+        assert!(attackable_targets(&state, &map, 99_002).is_empty());
+
+        state.current_phase = Phase::NonCombatMovement;
+        assert!(attackable_targets(&state, &map, 99_001).is_empty());
+    }
+
+    #[test]
+    fn test_ships_cannot_stand_on_land() {
+        let state = crate::setup::create_initial_state(42, &GameMap::new());
+        assert!(!unit_can_occupy(&state, UnitType::Destroyer, RegionId::Land(t::GERMANY)));
+        assert!(!unit_can_occupy(&state, UnitType::Infantry, RegionId::Sea(sz::SZ_BALTIC_SEA)));
+        assert!(unit_can_occupy(&state, UnitType::Fighter, RegionId::Sea(sz::SZ_BALTIC_SEA)));
+        assert!(unit_can_occupy(&state, UnitType::Fighter, RegionId::Land(t::GERMANY)));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "cannot be placed")]
+    fn test_placing_ship_on_land_panics() {
+        let (mut state, _map) = test_state_and_map();
+        let destroyer = UnitInstance::new(9999, UnitType::Destroyer, Power::Germany);
+        place_unit_at(&mut state, RegionId::Land(t::GERMANY), destroyer);
+    }
+
+    #[test]
+    fn test_submarines_alone_are_only_engaged_by_ships() {
+        let (mut state, _map) = test_state_and_map();
+        let zone = sz::SZ_BALTIC_SEA;
+        state.sea_zones[zone as usize].units.clear();
+        state.sea_zones[zone as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Submarine, Power::UnitedKingdom));
+        let mut fighter = UnitInstance::new(99_002, UnitType::Fighter, Power::Germany);
+        fighter.moved_this_turn = true;
+        state.sea_zones[zone as usize].units.push(fighter);
+        assert!(!identify_pending_combats(&state, Power::Germany).contains(&RegionId::Sea(zone)));
+
+        let mut destroyer = UnitInstance::new(99_003, UnitType::Destroyer, Power::Germany);
+        destroyer.moved_this_turn = true;
+        state.sea_zones[zone as usize].units.push(destroyer);
+        assert!(identify_pending_combats(&state, Power::Germany).contains(&RegionId::Sea(zone)));
+    }
 }
@@ -9,6 +9,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use ts_rs::TS;
 
 use crate::action::Action;
 use crate::power::Power;
@@ -28,6 +29,19 @@ pub enum PlayerRole {
     Spectator,
 }
 
+/// Who drives a power's turns.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export)]
+pub enum ControllerKind {
+    /// A player at this device (hotseat).
+    #[default]
+    Human,
+    /// The built-in AI.
+    AI,
+    /// A player connected over the network.
+    Remote,
+}
+
 /// A player in a game session.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlayerSession {
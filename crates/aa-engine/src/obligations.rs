@@ -0,0 +1,201 @@
+//! Pending obligations: things the current power must resolve before it can
+//! advance, for "you still need to…" prompts in the UI.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::data::GameMap;
+use crate::purchase;
+use crate::phase::PhaseState;
+use crate::state::GameState;
+use crate::territory::{RegionId, SeaZoneId, TerritoryId};
+use crate::unit::{UnitDomain, UnitId, UnitType};
+
+/// What kind of obligation is outstanding.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum ObligationKind {
+    /// A battle has not been fought yet.
+    UnresolvedBattle,
+    /// A battle is in progress and waiting for a decision or roll.
+    BattleInProgress,
+    /// An air unit is somewhere it cannot end the turn.
+    AirUnitMustLand { unit_id: UnitId },
+    /// Purchased units still have to be placed.
+    UnitsToPlace { unit_type: UnitType, count: u32 },
+    /// More units were bought than the factories can place.
+    PurchasesExceedCapacity { purchased: u32, capacity: u32 },
+}
+
+/// A single outstanding obligation for the current power.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Obligation {
+    pub kind: ObligationKind,
+    pub description: String,
+    /// The region the obligation is tied to, if any.
+    pub region: Option<RegionId>,
+}
+
+/// List everything the current power still has to resolve.
+pub fn pending_obligations(state: &GameState, map: &GameMap) -> Vec<Obligation> {
+    let mut obligations = Vec::new();
+
+    match &state.phase_state {
+        PhaseState::Purchase(_) => {
+            let (purchased, capacity) = purchase::placement_load(state, map).unwrap_or_default();
+            if purchased > capacity {
+                obligations.push(Obligation {
+                    kind: ObligationKind::PurchasesExceedCapacity { purchased, capacity },
+                    description: format!(
+                        "{} units purchased but factories can only place {}",
+                        purchased, capacity
+                    ),
+                    region: None,
+                });
+            }
+        }
+        PhaseState::Combat(cs) => {
+            if let Some(active) = &cs.active_combat {
+                obligations.push(Obligation {
+                    kind: ObligationKind::BattleInProgress,
+                    description: format!("Finish the battle in progress ({:?})", active.sub_phase),
+                    region: Some(active.location),
+                });
+            }
+            for &location in &cs.pending_battles {
+                let active_here = cs.active_combat.as_ref().is_some_and(|c| c.location == location);
+                if !cs.resolved_battles.contains(&location) && !active_here {
+                    obligations.push(Obligation {
+                        kind: ObligationKind::UnresolvedBattle,
+                        description: "Resolve the pending battle".into(),
+                        region: Some(location),
+                    });
+                }
+            }
+        }
+        PhaseState::Mobilize(ms) => {
+            for &(unit_type, count) in &ms.units_to_place {
+                let placed = ms.placements.iter().filter(|(ut, _)| *ut == unit_type).count() as u32;
+                if placed < count {
+                    obligations.push(Obligation {
+                        kind: ObligationKind::UnitsToPlace {
+                            unit_type,
+                            count: count - placed,
+                        },
+                        description: format!("Place {} more {:?}", count - placed, unit_type),
+                        region: None,
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+
+    for (region, unit_id) in unlanded_air_units(state) {
+        obligations.push(Obligation {
+            kind: ObligationKind::AirUnitMustLand { unit_id },
+            description: format!("Air unit {} must land before the turn ends", unit_id),
+            region: Some(region),
+        });
+    }
+
+    obligations
+}
+
+/// Air units of the current power that are somewhere they cannot stay:
+/// a territory it does not control as a friend, or a sea zone without room
+/// on a friendly carrier.
+fn unlanded_air_units(state: &GameState) -> Vec<(RegionId, UnitId)> {
+    let power = state.current_power;
+    let mut result = Vec::new();
+
+    for (i, territory) in state.territories.iter().enumerate() {
+        let friendly = territory
+            .owner
+            .is_some_and(|owner| state.political.are_friendly(power, owner));
+        if friendly {
+            continue;
+        }
+        for unit in &territory.units {
+            if unit.owner == power && state.unit_stats(unit.unit_type).domain == UnitDomain::Air {
+                result.push((RegionId::Land(i as TerritoryId), unit.id));
+            }
+        }
+    }
+
+    for (i, zone) in state.sea_zones.iter().enumerate() {
+        let mut room: usize = zone
+            .units
+            .iter()
+            .filter(|u| u.unit_type == UnitType::Carrier && state.political.are_friendly(power, u.owner))
+            .map(|u| state.unit_stats(u.unit_type).can_carry_air as usize)
+            .sum();
+        // Allied aircraft already aboard take room first
+        room = room.saturating_sub(
+            zone.units
+                .iter()
+                .filter(|u| {
+                    u.owner != power
+                        && state.political.are_friendly(power, u.owner)
+                        && state.unit_stats(u.unit_type).domain == UnitDomain::Air
+                })
+                .count(),
+        );
+        for unit in &zone.units {
+            if unit.owner != power || state.unit_stats(unit.unit_type).domain != UnitDomain::Air {
+                continue;
+            }
+            let carrier_capable = matches!(unit.unit_type, UnitType::Fighter | UnitType::TacticalBomber);
+            if carrier_capable && room > 0 {
+                room -= 1;
+            } else {
+                result.push((RegionId::Sea(i as SeaZoneId), unit.id));
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::sea_zone_ids as sz;
+    use crate::data::territory_ids as t;
+    use crate::phase::{CombatState, Phase};
+    use crate::power::Power;
+    use crate::unit::UnitInstance;
+
+    #[test]
+    fn test_pending_battle_and_unlanded_fighter_reported() {
+        let map = GameMap::new();
+        let mut state = crate::setup::create_initial_state(42, &map);
+        state.current_power = Power::Germany;
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles.push(RegionId::Land(t::FRANCE));
+        state.phase_state = PhaseState::Combat(cs);
+
+        for zone in state.sea_zones.iter_mut() {
+            zone.units.clear();
+        }
+        state.sea_zones[sz::SZ_NORTH_SEA as usize]
+            .units
+            .push(UnitInstance::new(99990, UnitType::Fighter, Power::Germany));
+
+        let obligations = pending_obligations(&state, &map);
+        assert_eq!(obligations.len(), 2);
+        assert!(obligations.iter().any(|o| o.kind == ObligationKind::UnresolvedBattle
+            && o.region == Some(RegionId::Land(t::FRANCE))));
+        assert!(obligations.iter().any(|o| o.kind == ObligationKind::AirUnitMustLand { unit_id: 99990 }
+            && o.region == Some(RegionId::Sea(sz::SZ_NORTH_SEA))));
+    }
+
+    #[test]
+    fn test_no_obligations_at_game_start() {
+        let map = GameMap::new();
+        let state = crate::setup::create_initial_state(42, &map);
+        assert!(pending_obligations(&state, &map).is_empty());
+    }
+}
@@ -3,6 +3,9 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use crate::action::Action;
+use crate::power::Power;
+use crate::state::GameState;
 use crate::territory::{RegionId, TerritoryId};
 use crate::unit::{UnitId, UnitType};
 
@@ -42,9 +45,50 @@ impl Phase {
             Phase::CollectIncome => "Collect Income",
         }
     }
+
+    /// The action that confirms this phase and moves on. `ConfirmPhase`
+    /// stands for it in every phase; Conduct Combat has no other.
+    pub fn confirm_action(&self) -> Action {
+        match self {
+            Phase::PurchaseAndRepair => Action::ConfirmPurchases,
+            Phase::CombatMovement => Action::ConfirmCombatMovement,
+            Phase::ConductCombat => Action::ConfirmPhase,
+            Phase::NonCombatMovement => Action::ConfirmNonCombatMovement,
+            Phase::Mobilize => Action::ConfirmMobilization,
+            Phase::CollectIncome => Action::ConfirmIncome,
+        }
+    }
+}
+
+/// Whether the current power has nothing productive to do in the current phase,
+/// so the phase's confirm action is effectively a skip. Only Purchase & Repair
+/// is checked: a power that cannot afford any unit or repair may skip it.
+pub fn can_skip_phase(state: &GameState) -> bool {
+    match state.current_phase {
+        Phase::PurchaseAndRepair => {
+            let can_buy = UnitType::all().iter().any(|&unit_type| {
+                crate::validate::validate_action(state, &Action::PurchaseUnit { unit_type, count: 1 }).is_ok()
+            });
+            let can_repair = state.territories.iter().enumerate().any(|(i, t)| {
+                t.facilities.iter().any(|f| f.damage > 0)
+                    && crate::validate::validate_action(
+                        state,
+                        &Action::RepairFacility {
+                            territory_id: i as TerritoryId,
+                            damage_to_repair: 1,
+                        },
+                    )
+                    .is_ok()
+            });
+            !can_buy && !can_repair
+        }
+        _ => false,
+    }
 }
 
 /// Per-phase sub-state. Tracks what has been done in the current phase.
+// There is only ever one, so the size of the combat variant doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub enum PhaseState {
@@ -62,7 +106,11 @@ pub enum PhaseState {
 pub struct PurchaseState {
     pub purchases: Vec<(UnitType, u32)>,
     pub repairs: Vec<(TerritoryId, u32)>,
-    pub ipcs_spent: u32,
+    /// IPCs committed this phase to purchases and repairs combined. They are
+    /// debited from the treasury as they are committed, so saves that predate
+    /// this field still see the right amount available.
+    #[serde(default)]
+    pub ipcs_committed: u32,
 }
 
 impl PurchaseState {
@@ -70,7 +118,7 @@ impl PurchaseState {
         PurchaseState {
             purchases: Vec::new(),
             repairs: Vec::new(),
-            ipcs_spent: 0,
+            ipcs_committed: 0,
         }
     }
 }
@@ -86,6 +134,12 @@ impl Default for PurchaseState {
 #[ts(export)]
 pub struct CombatMoveState {
     pub moves: Vec<PlannedMove>,
+    /// Territory owners as combat movement began, indexed by territory ID.
+    #[serde(default)]
+    pub owners_at_start: Vec<Option<Power>>,
+    /// The operation new moves join, between BeginOperation and EndOperation.
+    #[serde(default)]
+    pub open_operation: Option<String>,
 }
 
 /// A planned unit move during combat movement.
@@ -96,11 +150,18 @@ pub struct PlannedMove {
     pub path: Vec<RegionId>,
     pub from: RegionId,
     pub to: RegionId,
+    /// The named operation this move was made in, if any.
+    #[serde(default)]
+    pub operation: Option<String>,
 }
 
 impl CombatMoveState {
     pub fn new() -> Self {
-        CombatMoveState { moves: Vec::new() }
+        CombatMoveState {
+            moves: Vec::new(),
+            owners_at_start: Vec::new(),
+            open_operation: None,
+        }
     }
 }
 
@@ -117,6 +178,18 @@ pub struct CombatState {
     pub pending_battles: Vec<RegionId>,
     pub resolved_battles: Vec<RegionId>,
     pub current_battle: Option<RegionId>,
+    /// Territories already hit by a strategic bombing raid this turn.
+    #[serde(default)]
+    pub bombed_territories: Vec<TerritoryId>,
+    /// Territory owners as combat movement began (carried over from
+    /// `CombatMoveState`), for deciding where attackers may retreat.
+    /// Empty when unknown.
+    #[serde(default)]
+    pub owners_before_combat: Vec<Option<Power>>,
+    /// This turn's combat moves (carried over from `CombatMoveState`), for
+    /// working out where attackers came from.
+    #[serde(default)]
+    pub combat_moves: Vec<PlannedMove>,
     /// The active combat being resolved (if any).
     #[ts(skip)]
     pub active_combat: Option<crate::combat::ActiveCombat>,
@@ -128,6 +201,9 @@ impl CombatState {
             pending_battles: Vec::new(),
             resolved_battles: Vec::new(),
             current_battle: None,
+            bombed_territories: Vec::new(),
+            owners_before_combat: Vec::new(),
+            combat_moves: Vec::new(),
             active_combat: None,
         }
     }
@@ -188,7 +264,14 @@ pub struct CollectIncomeState {
     pub base_income: u32,
     pub objective_bonus: u32,
     pub convoy_losses: u32,
+    /// Unit upkeep paid under the optional upkeep rule.
+    #[serde(default)]
+    pub upkeep: u32,
     pub total_collected: u32,
+    /// Income owed, worked out from territory ownership as the phase began.
+    /// Ownership changes after that wait for the next turn's collection.
+    #[serde(default)]
+    pub owed: Option<crate::income::IncomeBreakdown>,
 }
 
 impl CollectIncomeState {
@@ -197,7 +280,9 @@ impl CollectIncomeState {
             base_income: 0,
             objective_bonus: 0,
             convoy_losses: 0,
+            upkeep: 0,
             total_collected: 0,
+            owed: None,
         }
     }
 }
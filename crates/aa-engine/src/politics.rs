@@ -1,11 +1,14 @@
 //! Political rules: war declarations, neutral nations, and political triggers.
 
-use crate::action::GameEvent;
+use crate::action::{GameEvent, IpcChangeReason};
 use crate::data::GameMap;
 use crate::error::EngineError;
 use crate::power::Power;
-use crate::state::GameState;
-use crate::territory::{TerritoryType, TerritoryId};
+use crate::phase::PhaseState;
+use crate::state::{GameState, Relation};
+use crate::territory::{RegionId, TerritoryType, TerritoryId};
+use crate::data::territory_ids as t;
+use crate::unit::{UnitInstance, UnitType};
 
 /// Validate a DeclareWar action.
 pub fn validate_declare_war(state: &GameState, against: Power) -> Result<(), EngineError> {
@@ -21,7 +24,7 @@ pub fn validate_declare_war(state: &GameState, against: Power) -> Result<(), Eng
     // Can't declare war on an ally
     if power.team() == against.team() {
         return Err(EngineError::InvalidAction {
-            reason: "Cannot declare war on an allied power".into(),
+            reason: format!("Cannot declare war on {:?}, an allied power", against),
         });
     }
 
@@ -32,6 +35,22 @@ pub fn validate_declare_war(state: &GameState, against: Power) -> Result<(), Eng
         });
     }
 
+    // War must come before the combat move that starts the battle
+    if let PhaseState::CombatMove(cms) = &state.phase_state {
+        let moved_in = cms.moves.iter().any(|m| match m.to {
+            RegionId::Land(tid) => state.territories[tid as usize].owner == Some(against),
+            RegionId::Sea(_) => false,
+        });
+        if moved_in {
+            return Err(EngineError::InvalidAction {
+                reason: format!(
+                    "Units have already moved into {:?} territory; war must be declared before moving",
+                    against
+                ),
+            });
+        }
+    }
+
     // Special restrictions:
     // Soviet Union cannot declare war on Japan until turn 4 (unless attacked)
     // US cannot declare war until turn 3 (unless attacked)
@@ -57,7 +76,13 @@ pub fn apply_declare_war(
         target: against,
     });
 
-    // Update political triggers
+    update_war_triggers(state, power, against);
+
+    events
+}
+
+/// Update the political triggers after `a` and `b` have gone to war.
+fn update_war_triggers(state: &mut GameState, power: Power, against: Power) {
     if power == Power::UnitedStates || against == Power::UnitedStates {
         state.political.triggers.us_at_war = true;
         if state.political.triggers.us_war_turn.is_none() {
@@ -72,7 +97,116 @@ pub fn apply_declare_war(
         state.political.triggers.soviet_at_war_with_axis = true;
         state.powers[Power::SovietUnion as usize].at_war = true;
     }
+}
+
+/// Validate a SetRelation action. Only allowed in sandbox mode.
+pub fn validate_set_relation(state: &GameState, a: Power, b: Power) -> Result<(), EngineError> {
+    if !state.sandbox_mode {
+        return Err(EngineError::InvalidAction {
+            reason: "Relations can only be set in sandbox mode".into(),
+        });
+    }
+    if a == b {
+        return Err(EngineError::InvalidAction {
+            reason: "Cannot set a power's relation with itself".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Apply a SetRelation action. If the change happens during combat, the
+/// pending battle list is recomputed so it matches the new relations.
+pub fn apply_set_relation(
+    state: &mut GameState,
+    a: Power,
+    b: Power,
+    relation: Relation,
+) -> Vec<GameEvent> {
+    let at_war = relation == Relation::War;
+    state.political.war_matrix[a as usize][b as usize] = at_war;
+    state.political.war_matrix[b as usize][a as usize] = at_war;
+    if at_war {
+        update_war_triggers(state, a, b);
+    }
 
+    let power = state.current_power;
+    let combats = crate::movement::identify_pending_combats(state, power);
+    if let PhaseState::Combat(cs) = &mut state.phase_state {
+        if cs.active_combat.is_none() {
+            cs.pending_battles = combats
+                .into_iter()
+                .filter(|r| !cs.resolved_battles.contains(r))
+                .collect();
+        }
+    }
+
+    vec![GameEvent::RelationChanged { a, b, relation }]
+}
+
+/// Validate a Surrender action.
+pub fn validate_surrender(state: &GameState, power: Power) -> Result<(), EngineError> {
+    if state.powers[power as usize].surrendered {
+        return Err(EngineError::InvalidAction {
+            reason: format!("{:?} has already surrendered", power),
+        });
+    }
+    Ok(())
+}
+
+/// Apply a Surrender: the power's units leave the board, its treasury is
+/// forfeited, and each of its territories goes to an enemy occupying it,
+/// otherwise back to its original owner, otherwise becomes neutral and
+/// loses its facilities.
+pub fn apply_surrender(state: &mut GameState, map: &GameMap, power: Power) -> Vec<GameEvent> {
+    let ps = &mut state.powers[power as usize];
+    ps.surrendered = true;
+    let forfeited = std::mem::take(&mut ps.ipcs);
+    let lost: std::collections::HashSet<_> =
+        state.units_of_power(power).into_iter().map(|(_, id)| id).collect();
+
+    for i in 0..state.territories.len() {
+        state.territories[i].units.retain(|u| u.owner != power);
+        if state.territories[i].owner != Some(power) {
+            continue;
+        }
+
+        let occupier = state.territories[i]
+            .units
+            .iter()
+            .map(|u| u.owner)
+            .find(|&o| state.political.are_at_war(o, power));
+        let original = map
+            .territories
+            .get(i)
+            .and_then(|def| def.original_owner)
+            .filter(|&o| o != power && !state.powers[o as usize].surrendered);
+
+        let territory = &mut state.territories[i];
+        territory.owner = occupier.or(original);
+        territory.just_captured = false;
+        if territory.owner.is_none() {
+            territory.facilities.clear();
+        }
+    }
+    for sz in &mut state.sea_zones {
+        sz.units.retain(|u| u.owner != power);
+    }
+    // Nothing of theirs is left aboard other powers' transports and carriers
+    let territory_units = state.territories.iter_mut().flat_map(|t| t.units.iter_mut());
+    let sea_units = state.sea_zones.iter_mut().flat_map(|z| z.units.iter_mut());
+    for unit in territory_units.chain(sea_units) {
+        unit.cargo.retain(|id| !lost.contains(id));
+    }
+
+    let mut events = vec![GameEvent::PowerSurrendered { power }];
+    if forfeited > 0 {
+        events.push(crate::income::ipc_changed(
+            state,
+            power,
+            -(forfeited as i32),
+            IpcChangeReason::Surrender,
+        ));
+    }
     events
 }
 
@@ -111,6 +245,66 @@ pub fn handle_neutral_attack(
     events
 }
 
+/// Mongolia's army: the infantry each Mongolian territory raises when it
+/// joins the Soviet Union.
+const MONGOLIAN_GARRISON: [(TerritoryId, usize); 6] = [
+    (t::OLGIY, 1),
+    (t::DZAVHAN, 1),
+    (t::TSAGAAN_OLOM, 1),
+    (t::CENTRAL_MONGOLIA, 2),
+    (t::ULAANBAATAR, 2),
+    (t::BUYANT_UHAA, 1),
+];
+
+/// Mongolia's special rule: a Japanese attack on any Mongolian territory
+/// brings all of Mongolia in on the Soviet side. Every Mongolian territory
+/// still unowned passes to the Soviet Union with its garrison, and Japan is
+/// at war with the Soviet Union. Checked as Japan confirms its combat
+/// moves, so the attack is fought against the garrison.
+pub fn check_mongolia_activation(state: &mut GameState) -> Vec<GameEvent> {
+    let mut events = Vec::new();
+    if state.current_power != Power::Japan {
+        return events;
+    }
+
+    let attacked = MONGOLIAN_GARRISON.iter().any(|&(tid, _)| {
+        let territory = &state.territories[tid as usize];
+        territory.owner.is_none()
+            && territory
+                .units
+                .iter()
+                .any(|u| u.owner == Power::Japan && u.moved_this_turn)
+    });
+    if !attacked {
+        return events;
+    }
+
+    if !state.political.are_at_war(Power::Japan, Power::SovietUnion) {
+        events.extend(apply_declare_war(state, Power::SovietUnion));
+    }
+
+    let mut next_id = crate::apply::next_unit_id(state);
+    for &(tid, infantry) in &MONGOLIAN_GARRISON {
+        let territory = &mut state.territories[tid as usize];
+        if territory.owner.is_some() {
+            continue;
+        }
+        territory.owner = Some(Power::SovietUnion);
+        for _ in 0..infantry {
+            territory
+                .units
+                .push(UnitInstance::new(next_id, UnitType::Infantry, Power::SovietUnion));
+            next_id += 1;
+        }
+        events.push(GameEvent::NeutralActivated {
+            territory_id: tid,
+            power: Power::SovietUnion,
+        });
+    }
+
+    events
+}
+
 /// Check if the US should automatically enter the war.
 /// US enters at the start of its turn on round 4 if not already at war.
 pub fn check_us_entry(state: &mut GameState) -> Vec<GameEvent> {
@@ -170,7 +364,11 @@ mod tests {
         let mut state = setup::create_initial_state(42, &map);
         state.current_power = Power::Germany;
         let result = validate_declare_war(&state, Power::Italy);
-        assert!(result.is_err());
+        assert!(
+            matches!(&result, Err(EngineError::InvalidAction { reason }) if reason.contains("allied")),
+            "{:?}",
+            result
+        );
     }
 
     #[test]
@@ -180,7 +378,11 @@ mod tests {
         state.current_power = Power::Germany;
         // Germany is already at war with UK
         let result = validate_declare_war(&state, Power::UnitedKingdom);
-        assert!(result.is_err());
+        assert!(
+            matches!(&result, Err(EngineError::InvalidAction { reason }) if reason.starts_with("Already at war")),
+            "{:?}",
+            result
+        );
     }
 
     #[test]
@@ -191,6 +393,10 @@ mod tests {
         // Germany is NOT at war with Soviet Union at start
         let result = validate_declare_war(&state, Power::SovietUnion);
         assert!(result.is_ok());
+
+        // Once declared, a second declaration is refused
+        apply_declare_war(&mut state, Power::SovietUnion);
+        assert!(validate_declare_war(&state, Power::SovietUnion).is_err());
     }
 
     #[test]
@@ -230,5 +436,95 @@ mod tests {
         assert!(!state.powers[Power::UnitedStates as usize].at_war);
         assert!(events.is_empty());
     }
-}
 
+    #[test]
+    fn test_surrender_twice_fails() {
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        assert!(validate_surrender(&state, Power::Italy).is_ok());
+        apply_surrender(&mut state, &map, Power::Italy);
+        assert!(validate_surrender(&state, Power::Italy).is_err());
+    }
+
+    #[test]
+    fn test_surrender_transfers_territories() {
+        use crate::data::territory_ids as t;
+        use crate::unit::{UnitInstance, UnitType};
+
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+
+        // Italian home territory occupied by a British tank goes to the UK
+        state.territories[t::SOUTHERN_ITALY as usize]
+            .units
+            .push(UnitInstance::new(99_001, UnitType::Tank, Power::UnitedKingdom));
+        // Territory Italy took from France reverts to France
+        state.territories[t::SOUTHERN_FRANCE as usize].owner = Some(Power::Italy);
+
+        let events = apply_surrender(&mut state, &map, Power::Italy);
+        assert!(matches!(events[0], GameEvent::PowerSurrendered { power: Power::Italy }));
+
+        assert_eq!(state.territories[t::SOUTHERN_ITALY as usize].owner, Some(Power::UnitedKingdom));
+        assert_eq!(state.territories[t::SOUTHERN_FRANCE as usize].owner, Some(Power::France));
+        // Unoccupied Italian homeland becomes neutral
+        assert_eq!(state.territories[t::NORTHERN_ITALY as usize].owner, None);
+        assert!(state.territories[t::NORTHERN_ITALY as usize].units.is_empty());
+        assert!(state
+            .sea_zones
+            .iter()
+            .all(|sz| sz.units.iter().all(|u| u.owner != Power::Italy)));
+        assert_eq!(state.powers[Power::Italy as usize].ipcs, 0);
+    }
+
+    #[test]
+    fn test_surrender_clears_units_aboard_allied_ships() {
+        use crate::data::sea_zone_ids::SZ_NORTH_SEA;
+
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        let mut carrier = UnitInstance::new(99_001, UnitType::Carrier, Power::Germany);
+        carrier.cargo = vec![99_002, 99_003];
+        let zone = &mut state.sea_zones[SZ_NORTH_SEA as usize].units;
+        zone.push(carrier);
+        zone.push(UnitInstance::new(99_002, UnitType::Fighter, Power::Italy));
+        zone.push(UnitInstance::new(99_003, UnitType::Fighter, Power::Germany));
+
+        apply_surrender(&mut state, &map, Power::Italy);
+        let carrier = state.sea_zones[SZ_NORTH_SEA as usize]
+            .units
+            .iter()
+            .find(|u| u.id == 99_001)
+            .unwrap();
+        assert_eq!(carrier.cargo, vec![99_003]);
+    }
+
+    #[test]
+    fn test_japan_attacking_mongolia_activates_it_for_soviets() {
+        use crate::movement::identify_pending_combats;
+
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        state.current_power = Power::Japan;
+        let mut infantry = UnitInstance::new(99_001, UnitType::Infantry, Power::Japan);
+        infantry.moved_this_turn = true;
+        state.territories[t::CENTRAL_MONGOLIA as usize].units.push(infantry);
+
+        let events = check_mongolia_activation(&mut state);
+        assert!(matches!(
+            events[0],
+            GameEvent::WarDeclared { aggressor: Power::Japan, target: Power::SovietUnion }
+        ));
+        for &(tid, infantry) in &MONGOLIAN_GARRISON {
+            let territory = &state.territories[tid as usize];
+            assert_eq!(territory.owner, Some(Power::SovietUnion));
+            let soviet = territory.units.iter().filter(|u| u.owner == Power::SovietUnion).count();
+            assert_eq!(soviet, infantry);
+        }
+        assert_eq!(
+            identify_pending_combats(&state, Power::Japan),
+            vec![RegionId::Land(t::CENTRAL_MONGOLIA)]
+        );
+        // Only once
+        assert!(check_mongolia_activation(&mut state).is_empty());
+    }
+}
@@ -1,5 +1,88 @@
 //! Purchase & Repair phase helpers and tests.
 
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::data::GameMap;
+use crate::mobilize;
+use crate::phase::PhaseState;
+use crate::power::Power;
+use crate::state::GameState;
+use crate::unit::UnitType;
+
+/// Which unit types a power may build.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum AllowedUnits {
+    /// Only these types.
+    Only(Vec<UnitType>),
+    /// Anything but these types.
+    AllExcept(Vec<UnitType>),
+}
+
+impl AllowedUnits {
+    pub fn permits(&self, unit_type: UnitType) -> bool {
+        match self {
+            AllowedUnits::Only(types) => types.contains(&unit_type),
+            AllowedUnits::AllExcept(types) => !types.contains(&unit_type),
+        }
+    }
+}
+
+/// The standard production rules: China builds only infantry; every other
+/// power may build anything.
+pub fn default_production_rules() -> BTreeMap<Power, AllowedUnits> {
+    BTreeMap::from([(Power::China, AllowedUnits::Only(vec![UnitType::Infantry]))])
+}
+
+/// Whether `power` may build `unit_type` under the game's production rules.
+/// Powers without a rule may build anything.
+pub fn can_produce(state: &GameState, power: Power, unit_type: UnitType) -> bool {
+    state
+        .settings
+        .production_rules
+        .get(&power)
+        .is_none_or(|rule| rule.permits(unit_type))
+}
+
+/// The current power's budget for this Purchase & Repair phase: its treasury
+/// plus whatever it has already committed to purchases and repairs.
+pub fn purchase_budget(state: &GameState) -> u32 {
+    let treasury = state.powers[state.current_power as usize].ipcs;
+    match state.phase_state {
+        PhaseState::Purchase(ref ps) => treasury + ps.ipcs_committed,
+        _ => treasury,
+    }
+}
+
+/// IPCs still available to spend: the phase budget minus everything
+/// committed so far. Purchases and repairs both draw from this one pool.
+pub fn available_ipcs(state: &GameState) -> u32 {
+    let committed = match state.phase_state {
+        PhaseState::Purchase(ref ps) => ps.ipcs_committed,
+        _ => 0,
+    };
+    purchase_budget(state).saturating_sub(committed)
+}
+
+/// Units the current power has bought this phase and how many its factories
+/// can place, as `(purchased, capacity)`. `None` outside the Purchase phase.
+pub fn placement_load(state: &GameState, map: &GameMap) -> Option<(u32, u32)> {
+    let PhaseState::Purchase(ref ps) = state.phase_state else {
+        return None;
+    };
+    let purchased: u32 = ps.purchases.iter().map(|&(_, n)| n).sum();
+    Some((purchased, mobilize::total_placement_capacity(state, map, state.current_power)))
+}
+
+/// How many more units the current power has bought this phase than its
+/// factories can place. Zero when everything fits.
+pub fn placement_shortfall(state: &GameState, map: &GameMap) -> u32 {
+    placement_load(state, map).map_or(0, |(purchased, capacity)| purchased.saturating_sub(capacity))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::action::Action;
@@ -29,14 +112,18 @@ mod tests {
             })
             .unwrap();
 
-        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events.len(), 2);
         let ipcs_after = engine.state().powers[Power::Germany as usize].ipcs;
         assert_eq!(ipcs_after, ipcs_before - 6); // 2 * 3 IPC
+        assert!(matches!(
+            result.events[1],
+            crate::action::GameEvent::IpcChanged { delta: -6, new_total, .. } if new_total == ipcs_after
+        ));
 
         if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
             assert_eq!(ps.purchases.len(), 1);
             assert_eq!(ps.purchases[0], (UnitType::Infantry, 2));
-            assert_eq!(ps.ipcs_spent, 6);
+            assert_eq!(ps.ipcs_committed, 6);
         } else {
             panic!("Expected Purchase phase state");
         }
@@ -61,7 +148,7 @@ mod tests {
 
         if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
             assert_eq!(ps.purchases.len(), 2);
-            assert_eq!(ps.ipcs_spent, 15); // 3*3 + 1*6
+            assert_eq!(ps.ipcs_committed, 15); // 3*3 + 1*6
         } else {
             panic!("Expected Purchase phase state");
         }
@@ -87,7 +174,7 @@ mod tests {
         if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
             assert_eq!(ps.purchases.len(), 1);
             assert_eq!(ps.purchases[0], (UnitType::Infantry, 5));
-            assert_eq!(ps.ipcs_spent, 15);
+            assert_eq!(ps.ipcs_committed, 15);
         } else {
             panic!("Expected Purchase phase state");
         }
@@ -162,6 +249,32 @@ mod tests {
         assert!(matches!(result, Err(EngineError::InvalidAction { .. })));
     }
 
+    #[test]
+    fn test_configured_rule_blocks_uk_battleships() {
+        use crate::purchase::AllowedUnits;
+
+        let mut engine = setup_engine();
+        let state = engine.state_mut();
+        state.current_power = Power::UnitedKingdom;
+        state.powers[Power::UnitedKingdom as usize].ipcs = 100;
+        state
+            .settings
+            .production_rules
+            .insert(Power::UnitedKingdom, AllowedUnits::AllExcept(vec![UnitType::Battleship]));
+
+        let result = engine.submit_action(Action::PurchaseUnit {
+            unit_type: UnitType::Battleship,
+            count: 1,
+        });
+        assert!(matches!(result, Err(EngineError::InvalidAction { .. })));
+        engine
+            .submit_action(Action::PurchaseUnit {
+                unit_type: UnitType::Destroyer,
+                count: 1,
+            })
+            .unwrap();
+    }
+
     // ---- RemovePurchase tests ----
 
     #[test]
@@ -188,7 +301,7 @@ mod tests {
 
         if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
             assert_eq!(ps.purchases[0], (UnitType::Infantry, 2));
-            assert_eq!(ps.ipcs_spent, 6);
+            assert_eq!(ps.ipcs_committed, 6);
         } else {
             panic!("Expected Purchase phase state");
         }
@@ -214,7 +327,7 @@ mod tests {
 
         if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
             assert!(ps.purchases.is_empty());
-            assert_eq!(ps.ipcs_spent, 0);
+            assert_eq!(ps.ipcs_committed, 0);
         } else {
             panic!("Expected Purchase phase state");
         }
@@ -263,14 +376,23 @@ mod tests {
             .unwrap();
 
         assert!(engine.can_undo());
-        engine.submit_action(Action::Undo).unwrap();
+        let result = engine.submit_action(Action::Undo).unwrap();
 
         let ipcs_after = engine.state().powers[Power::Germany as usize].ipcs;
         assert_eq!(ipcs_after, ipcs_before);
+        assert!(matches!(
+            result.events[..],
+            [crate::action::GameEvent::IpcChanged {
+                power: Power::Germany,
+                delta: 12,
+                new_total,
+                reason: crate::action::IpcChangeReason::PurchaseRefund,
+            }] if new_total == ipcs_before
+        ));
 
         if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
             assert!(ps.purchases.is_empty());
-            assert_eq!(ps.ipcs_spent, 0);
+            assert_eq!(ps.ipcs_committed, 0);
         } else {
             panic!("Expected Purchase phase state");
         }
@@ -295,11 +417,19 @@ mod tests {
             .unwrap();
 
         // Undo the remove — should restore the 3rd infantry
-        engine.submit_action(Action::Undo).unwrap();
+        let result = engine.submit_action(Action::Undo).unwrap();
+        assert!(matches!(
+            result.events[..],
+            [crate::action::GameEvent::IpcChanged {
+                delta: -3,
+                reason: crate::action::IpcChangeReason::Purchase,
+                ..
+            }]
+        ));
 
         if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
             assert_eq!(ps.purchases[0], (UnitType::Infantry, 3));
-            assert_eq!(ps.ipcs_spent, 9);
+            assert_eq!(ps.ipcs_committed, 9);
         } else {
             panic!("Expected Purchase phase state");
         }
@@ -441,4 +571,92 @@ mod tests {
             Err(EngineError::InsufficientIPCs { .. })
         ));
     }
+
+    // ---- Shared budget tests ----
+
+    /// Give Germany a damaged factory in its first territory to repair.
+    fn add_damaged_factory(engine: &mut Engine, damage: u32) -> u16 {
+        let tid = 0;
+        engine.state_mut().territories[tid].owner = Some(Power::Germany);
+        engine.state_mut().territories[tid].facilities.push(Facility {
+            facility_type: FacilityType::MajorIndustrialComplex,
+            damage,
+            max_damage: 20,
+            operational: true,
+        });
+        tid as u16
+    }
+
+    #[test]
+    fn test_repair_then_purchase_share_budget() {
+        let mut engine = setup_engine();
+        let tid = add_damaged_factory(&mut engine, 12);
+
+        // Germany has 30 IPCs: 10 on repairs leaves exactly 20
+        engine
+            .submit_action(Action::RepairFacility { territory_id: tid, damage_to_repair: 10 })
+            .unwrap();
+        let result = engine.submit_action(Action::PurchaseUnit {
+            unit_type: UnitType::Tank,
+            count: 4,
+        });
+        assert!(matches!(
+            result,
+            Err(EngineError::InsufficientIPCs { needed: 24, available: 20 })
+        ));
+        engine
+            .submit_action(Action::PurchaseUnit { unit_type: UnitType::Battleship, count: 1 })
+            .unwrap();
+
+        if let PhaseState::Purchase(ref ps) = engine.state().phase_state {
+            assert_eq!(ps.ipcs_committed, 30);
+        } else {
+            panic!("Expected Purchase phase state");
+        }
+        assert_eq!(engine.state().powers[Power::Germany as usize].ipcs, 0);
+    }
+
+    #[test]
+    fn test_purchase_then_repair_share_budget() {
+        let mut engine = setup_engine();
+        let tid = add_damaged_factory(&mut engine, 12);
+
+        engine
+            .submit_action(Action::PurchaseUnit { unit_type: UnitType::Tank, count: 4 })
+            .unwrap();
+        let result = engine.submit_action(Action::RepairFacility {
+            territory_id: tid,
+            damage_to_repair: 7,
+        });
+        assert!(matches!(
+            result,
+            Err(EngineError::InsufficientIPCs { needed: 7, available: 6 })
+        ));
+        engine
+            .submit_action(Action::RepairFacility { territory_id: tid, damage_to_repair: 6 })
+            .unwrap();
+    }
+
+    // ---- Placement capacity tests ----
+
+    #[test]
+    fn test_strict_mode_rejects_unplaceable_purchases() {
+        let settings = crate::state::GameSettings {
+            strict_purchase_capacity: true,
+            ..Default::default()
+        };
+        let mut engine = Engine::new_game_with_settings(42, settings);
+        engine.state_mut().powers[Power::Germany as usize].ipcs = 600;
+        engine
+            .submit_action(Action::PurchaseUnit { unit_type: UnitType::Infantry, count: 200 })
+            .unwrap();
+
+        let shortfall = super::placement_shortfall(engine.state(), engine.map());
+        assert!(shortfall > 150);
+        let result = engine.submit_action(Action::ConfirmPurchases);
+        assert!(matches!(result, Err(EngineError::InvalidAction { .. })));
+
+        engine.state_mut().settings.strict_purchase_capacity = false;
+        engine.submit_action(Action::ConfirmPurchases).unwrap();
+    }
 }
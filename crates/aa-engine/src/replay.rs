@@ -0,0 +1,130 @@
+//! Recorded games for replay and determinism checks.
+//!
+//! A `Replay` holds the seed and settings a game started from plus every
+//! action taken, each with the state checksum the engine reported after it.
+//! Re-running the actions on a fresh engine must reproduce every checksum;
+//! the first mismatch pinpoints where nondeterminism crept in.
+
+use serde::{Deserialize, Serialize};
+
+use crate::action::{Action, ActionResult};
+use crate::state::GameSettings;
+
+/// One recorded action and the checksum of the state it produced.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub action: Action,
+    pub state_checksum: u64,
+}
+
+/// A game recorded from its initial seed and settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub settings: GameSettings,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    /// Start recording a game created with `seed` and `settings`.
+    pub fn new(seed: u64, settings: GameSettings) -> Self {
+        Replay {
+            seed,
+            settings,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Record an accepted action together with its result's checksum.
+    pub fn record(&mut self, action: Action, result: &ActionResult) {
+        self.steps.push(ReplayStep {
+            action,
+            state_checksum: result.state_checksum,
+        });
+    }
+}
+
+/// Where a replay stopped matching its recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DivergencePoint {
+    /// Index into `Replay::steps` of the first mismatching action.
+    pub index: usize,
+    /// Checksum stored in the replay.
+    pub expected: u64,
+    /// Checksum the engine produced, or `None` if it rejected the action.
+    pub actual: Option<u64>,
+}
+
+/// Re-execute `replay` on a fresh engine and report the first action whose
+/// resulting checksum differs from the recorded one.
+#[cfg(test)]
+pub(crate) fn replay_and_compare(replay: &Replay) -> Option<DivergencePoint> {
+    replay_and_compare_with(replay, |_, _| {})
+}
+
+/// As `replay_and_compare`, calling `tweak` on the engine after each action
+/// so tests can simulate a nondeterministic change.
+#[cfg(test)]
+pub(crate) fn replay_and_compare_with(
+    replay: &Replay,
+    mut tweak: impl FnMut(&mut crate::Engine, usize),
+) -> Option<DivergencePoint> {
+    let mut engine = crate::Engine::new_game_with_settings(replay.seed, replay.settings.clone());
+    for (index, step) in replay.steps.iter().enumerate() {
+        let actual = match engine.submit_action(step.action.clone()) {
+            Ok(_) => {
+                tweak(&mut engine, index);
+                Some(engine.state().checksum())
+            }
+            Err(_) => None,
+        };
+        if actual != Some(step.state_checksum) {
+            return Some(DivergencePoint {
+                index,
+                expected: step.state_checksum,
+                actual,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{ai_play_turn, AiDifficulty};
+    use crate::Engine;
+
+    /// Record Germany's opening turn as played by the AI, battles included.
+    fn recorded_opening() -> Replay {
+        let settings = GameSettings::default();
+        let mut engine = Engine::new_game_with_settings(7, settings.clone());
+        let mut replay = Replay::new(7, settings);
+        for action in ai_play_turn(engine.state(), &engine.map, AiDifficulty::Normal) {
+            let result = engine.submit_action(action.clone()).unwrap();
+            replay.record(action, &result);
+        }
+        replay
+    }
+
+    #[test]
+    fn test_replay_reproduces_every_checksum() {
+        let replay = recorded_opening();
+        assert!(replay.steps.len() > 5);
+        assert_eq!(replay_and_compare(&replay), None);
+    }
+
+    #[test]
+    fn test_replay_detects_injected_nondeterminism() {
+        let replay = recorded_opening();
+        let divergence = replay_and_compare_with(&replay, |engine, index| {
+            if index == 3 {
+                engine.state_mut().rng_counter += 1;
+            }
+        })
+        .expect("the tweak should be detected");
+        assert_eq!(divergence.index, 3);
+        assert_eq!(divergence.expected, replay.steps[3].state_checksum);
+        assert_ne!(divergence.actual, Some(divergence.expected));
+    }
+}
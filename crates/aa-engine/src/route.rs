@@ -0,0 +1,207 @@
+//! Invasion planning.
+//!
+//! Finds the cheapest way for a power to bring land units against a target
+//! territory: a march over land, a crossing by transport, or a march to the
+//! coast followed by a crossing. The search keeps clear of enemy ground and
+//! of sea zones holding enemy units, so a plan never has to fight its way to
+//! the target. Used for move suggestions in the client.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::data::GameMap;
+use crate::power::Power;
+use crate::state::GameState;
+use crate::territory::{RegionId, TerritoryId, TerritoryType};
+use crate::unit::{UnitDomain, UnitType};
+
+/// How an invasion reaches its target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum InvasionKind {
+    /// Marching over land the whole way.
+    Land,
+    /// Boarding a transport at the origin and landing on the target.
+    Amphibious,
+    /// Marching over land for part of the way and sailing for the rest.
+    Combined,
+}
+
+/// The cheapest route found for an invasion.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct InvasionPlan {
+    pub kind: InvasionKind,
+    /// Territory holding the land units that make the attack.
+    pub origin: TerritoryId,
+    /// Every region on the way, from `origin` to the target.
+    pub path: Vec<RegionId>,
+}
+
+/// Boarding a transport counts as an extra step, so a land march wins over
+/// a crossing of the same length.
+const EMBARK_COST: u32 = 1;
+
+/// The cheapest invasion of `target` by `power`'s land units, or `None` if
+/// the target is not held by an enemy or cannot be reached. Sea crossings
+/// are only considered while `power` has a transport afloat.
+pub fn invasion_route(
+    state: &GameState,
+    map: &GameMap,
+    target: TerritoryId,
+    power: Power,
+) -> Option<InvasionPlan> {
+    let owner = state.territories.get(target as usize)?.owner?;
+    if !state.political.are_at_war(power, owner) {
+        return None;
+    }
+
+    let land_count = state.territories.len();
+    let node = |region: RegionId| match region {
+        RegionId::Land(tid) => tid as usize,
+        RegionId::Sea(zone) => land_count + zone as usize,
+    };
+    let region_of = |index: usize| {
+        if index < land_count {
+            RegionId::Land(index as TerritoryId)
+        } else {
+            RegionId::Sea((index - land_count) as _)
+        }
+    };
+
+    let hostile = |units: &[crate::unit::UnitInstance]| {
+        units.iter().any(|u| state.political.are_at_war(power, u.owner))
+    };
+    // Land the invasion may cross (or land on) on the way to the target
+    let open_land = |tid: TerritoryId| {
+        let territory = &state.territories[tid as usize];
+        map.territory(tid).territory_type != TerritoryType::Impassable
+            && territory
+                .owner
+                .is_some_and(|o| state.political.are_friendly(power, o))
+            && !hostile(&territory.units)
+    };
+    let can_sail = state
+        .sea_zones
+        .iter()
+        .any(|z| z.units.iter().any(|u| u.owner == power && u.unit_type == UnitType::Transport));
+
+    let total = land_count + state.sea_zones.len();
+    let mut cost = vec![u32::MAX; total];
+    let mut parent: Vec<Option<usize>> = vec![None; total];
+    let mut queue = BinaryHeap::new();
+
+    for (tid, territory) in state.territories.iter().enumerate() {
+        let has_army = territory
+            .units
+            .iter()
+            .any(|u| u.owner == power && state.unit_stats(u.unit_type).domain == UnitDomain::Land);
+        if has_army && tid != target as usize && open_land(tid as TerritoryId) {
+            cost[tid] = 0;
+            queue.push(Reverse((0, tid)));
+        }
+    }
+
+    while let Some(Reverse((here_cost, here))) = queue.pop() {
+        if here_cost > cost[here] {
+            continue;
+        }
+        if here == target as usize {
+            break;
+        }
+
+        let mut next = Vec::new();
+        match region_of(here) {
+            RegionId::Land(tid) => {
+                for &land in map.land_neighbors(tid) {
+                    if land == target || open_land(land) {
+                        next.push((RegionId::Land(land), 1));
+                    }
+                }
+                if can_sail {
+                    for &zone in map.coastal_zones(tid) {
+                        if !hostile(&state.sea_zones[zone as usize].units) {
+                            next.push((RegionId::Sea(zone), 1 + EMBARK_COST));
+                        }
+                    }
+                }
+            }
+            RegionId::Sea(zone) => {
+                for &sea in map.sea_neighbors(zone) {
+                    if !hostile(&state.sea_zones[sea as usize].units) {
+                        next.push((RegionId::Sea(sea), 1));
+                    }
+                }
+                for &land in map.coastal_territories(zone) {
+                    if land == target || open_land(land) {
+                        next.push((RegionId::Land(land), 1));
+                    }
+                }
+            }
+        }
+
+        for (region, step) in next {
+            let to = node(region);
+            if here_cost + step < cost[to] {
+                cost[to] = here_cost + step;
+                parent[to] = Some(here);
+                queue.push(Reverse((cost[to], to)));
+            }
+        }
+    }
+
+    if cost[target as usize] == u32::MAX {
+        return None;
+    }
+    let mut path = vec![RegionId::Land(target)];
+    let mut current = target as usize;
+    while let Some(previous) = parent[current] {
+        path.push(region_of(previous));
+        current = previous;
+    }
+    path.reverse();
+
+    let RegionId::Land(origin) = path[0] else {
+        return None;
+    };
+    let sails = path.iter().any(|r| matches!(r, RegionId::Sea(_)));
+    let marches = path
+        .windows(2)
+        .any(|w| matches!(w, [RegionId::Land(_), RegionId::Land(_)]));
+    let kind = match (sails, marches) {
+        (false, _) => InvasionKind::Land,
+        (true, false) => InvasionKind::Amphibious,
+        (true, true) => InvasionKind::Combined,
+    };
+
+    Some(InvasionPlan { kind, origin, path })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::territory_ids as t;
+    use crate::Engine;
+
+    #[test]
+    fn test_inland_capital_by_land_and_island_by_sea() {
+        let engine = Engine::new_game(42);
+        let state = engine.state();
+
+        let paris = invasion_route(state, &engine.map, t::FRANCE, Power::Germany).unwrap();
+        assert_eq!(paris.kind, InvasionKind::Land);
+        assert_eq!(paris.path.first(), Some(&RegionId::Land(paris.origin)));
+        assert_eq!(paris.path.last(), Some(&RegionId::Land(t::FRANCE)));
+
+        let iceland = invasion_route(state, &engine.map, t::ICELAND, Power::Germany).unwrap();
+        assert_eq!(iceland.kind, InvasionKind::Amphibious);
+        assert!(matches!(iceland.path[iceland.path.len() - 2], RegionId::Sea(_)));
+        assert_eq!(iceland.path.last(), Some(&RegionId::Land(t::ICELAND)));
+
+        // Not an enemy of Germany's yet
+        assert_eq!(invasion_route(state, &engine.map, t::RUSSIA, Power::Germany), None);
+    }
+}
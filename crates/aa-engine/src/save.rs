@@ -6,6 +6,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::error::EngineError;
+use crate::multiplayer::ControllerKind;
 use crate::state::GameState;
 
 /// Current save file format version.
@@ -20,6 +21,19 @@ pub struct SaveFile {
     pub metadata: SaveMetadata,
     /// The full game state.
     pub state: GameState,
+    /// How the game was set up; absent in saves made before options were
+    /// recorded.
+    #[serde(default)]
+    pub options: Option<GameOptions>,
+}
+
+/// What a game was set up with that its state does not hold: who controls
+/// each power. The rule settings travel in `GameState::settings`. Restored
+/// with `Engine::from_state_with_options`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameOptions {
+    /// Indexed by `Power as usize`.
+    pub controllers: [ControllerKind; 9],
 }
 
 /// Metadata about a save file.
@@ -52,9 +66,16 @@ impl SaveFile {
                 action_count: state.action_log.len(),
             },
             state: state.clone(),
+            options: None,
         }
     }
 
+    /// Record the game's options alongside its state.
+    pub fn with_options(mut self, options: GameOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
     /// Serialize to JSON string.
     pub fn to_json(&self) -> Result<String, EngineError> {
         serde_json::to_string_pretty(self)
@@ -325,4 +346,25 @@ mod tests {
             json_bytes
         );
     }
+
+    #[test]
+    fn test_options_survive_save_and_load() {
+        use crate::multiplayer::ControllerKind;
+        use crate::state::GameSettings;
+        use crate::victory::VictoryCondition;
+
+        let settings = GameSettings {
+            veteran_units: true,
+            victory_condition: VictoryCondition::EconomicDomination { percent: 70 },
+            ..GameSettings::default()
+        };
+        let mut engine = Engine::new_game_with_settings(42, settings.clone());
+        engine.set_controller(Power::Japan, ControllerKind::AI);
+
+        let json = engine.save_file("Economic".into(), 0).to_json().unwrap();
+        let loaded = Engine::from_save(SaveFile::from_json(&json).unwrap());
+        assert_eq!(loaded.state().settings, settings);
+        assert_eq!(loaded.controller_for(Power::Japan), ControllerKind::AI);
+        assert_eq!(loaded.options(), engine.options());
+    }
 }
@@ -6,10 +6,11 @@
 use crate::data::sea_zone_ids as sz;
 use crate::data::territory_ids as t;
 use crate::data::GameMap;
+use crate::error::EngineError;
 use crate::power::Power;
 use crate::state::GameState;
-use crate::territory::{Facility, FacilityType, SeaZoneState, TerritoryState};
-use crate::unit::{UnitId, UnitInstance, UnitType};
+use crate::territory::{Facility, FacilityType, RegionId, SeaZoneState, TerritoryId, TerritoryState, TerritoryType};
+use crate::unit::{UnitDomain, UnitId, UnitInstance, UnitType};
 
 use Power::{
     China as Ch, France as Fr, Germany as Ge, Italy as It, Japan as Jp, SovietUnion as Su,
@@ -515,6 +516,11 @@ fn place_china(state: &mut GameState, gen: &mut IdGen) {
     land!(state, gen, t::SZECHWAN, Ch;
         4 Infantry, 1 Fighter
     );
+    for unit in state.territories[t::SZECHWAN as usize].units.iter_mut() {
+        if unit.unit_type == Fighter && unit.owner == Ch {
+            unit.restricted_to_china = true;
+        }
+    }
     land!(state, gen, t::YUNNAN, Ch;
         2 Infantry
     );
@@ -739,6 +745,84 @@ fn place_france(state: &mut GameState, gen: &mut IdGen) {
     );
 }
 
+// ===========================================================================
+// Bids
+// ===========================================================================
+
+/// Apply a pre-game bid: `extra_ipcs` are granted to `power`, the listed
+/// bonus units are paid out of them, and whatever is left goes to the
+/// treasury. Land and air units go in a territory the power controls; naval
+/// units go in a sea zone bordering one. Nothing is changed unless the whole
+/// bid is legal.
+pub fn apply_bid(
+    state: &mut GameState,
+    map: &GameMap,
+    power: Power,
+    extra_ipcs: u32,
+    placements: &[(UnitType, RegionId)],
+) -> Result<(), EngineError> {
+    let controls = |tid: TerritoryId| {
+        state.territories[tid as usize].owner == Some(power)
+            && map.territory(tid).territory_type != TerritoryType::Impassable
+    };
+    let mut cost = 0u32;
+    for &(unit_type, region) in placements {
+        let stats = state.unit_stats(unit_type);
+        match region {
+            RegionId::Land(tid) => {
+                if state.territories.get(tid as usize).is_none() {
+                    return Err(EngineError::TerritoryNotFound { territory_id: tid });
+                }
+                if !controls(tid) {
+                    return Err(EngineError::InvalidAction {
+                        reason: format!("{} does not control {}", power.name(), map.territory(tid).name),
+                    });
+                }
+                if stats.domain == UnitDomain::Sea {
+                    return Err(EngineError::InvalidAction {
+                        reason: format!("Naval units must be placed in a sea zone, not {}", map.territory(tid).name),
+                    });
+                }
+            }
+            RegionId::Sea(sid) => {
+                if state.sea_zones.get(sid as usize).is_none() {
+                    return Err(EngineError::InvalidAction {
+                        reason: format!("Sea zone {} does not exist", sid),
+                    });
+                }
+                let zone = map.sea_zone(sid);
+                if stats.domain != UnitDomain::Sea {
+                    return Err(EngineError::InvalidAction {
+                        reason: format!("Only naval units can be placed in {}", zone.name),
+                    });
+                }
+                if !zone.adjacent_land.iter().any(|&tid| controls(tid)) {
+                    return Err(EngineError::InvalidAction {
+                        reason: format!("{} controls no territory bordering {}", power.name(), zone.name),
+                    });
+                }
+            }
+        }
+        cost += stats.cost;
+    }
+    if cost > extra_ipcs {
+        return Err(EngineError::InsufficientIPCs {
+            needed: cost,
+            available: extra_ipcs,
+        });
+    }
+
+    for &(unit_type, region) in placements {
+        let unit = UnitInstance::new(crate::apply::next_unit_id(state), unit_type, power);
+        match region {
+            RegionId::Land(tid) => state.territories[tid as usize].units.push(unit),
+            RegionId::Sea(sid) => state.sea_zones[sid as usize].units.push(unit),
+        }
+    }
+    state.powers[power as usize].ipcs += extra_ipcs - cost;
+    Ok(())
+}
+
 // ===========================================================================
 // Tests
 // ===========================================================================
@@ -0,0 +1,261 @@
+//! Battle calculator.
+//!
+//! Estimates the odds of a hypothetical battle by fighting it many times
+//! with the real combat rules on a private copy of the game state, so unit
+//! pairings, submarine and AA rules all apply exactly as in play. Casualties
+//! are taken and rounds continued the way the AI would. The caller's state
+//! is never touched.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::data::GameMap;
+use crate::error::EngineError;
+use crate::phase::{CombatState, Phase, PhaseState};
+use crate::power::Power;
+use crate::state::GameState;
+use crate::territory::RegionId;
+use crate::unit::{UnitId, UnitInstance, UnitType};
+
+/// Most trials one simulation may run; each is a full battle on a copy of
+/// the game.
+pub const MAX_TRIALS: u32 = 10_000;
+
+/// One side of a simulated battle.
+#[derive(Clone, Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BattleSide {
+    pub power: Power,
+    pub units: Vec<UnitType>,
+}
+
+/// Outcome distribution over all trials.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BattleOdds {
+    pub trials: u32,
+    /// Share of trials the attacker won (took or cleared the region).
+    pub attacker_win_rate: f64,
+    /// Share of trials the defender held with units left.
+    pub defender_win_rate: f64,
+    /// Share of trials with no winner: mutual destruction or a stalemate.
+    pub draw_rate: f64,
+    /// `attacker_survivors[n]` counts trials ending with `n` attacking units alive.
+    pub attacker_survivors: Vec<u32>,
+    /// `defender_survivors[n]` counts trials ending with `n` defending units alive.
+    pub defender_survivors: Vec<u32>,
+}
+
+/// Fight the battle between `attacker` and `defender` at `location`
+/// `trials` times and tally the results. Only the given units take part;
+/// every other unit is cleared from the private copy.
+pub fn simulate_battle(
+    state: &GameState,
+    map: &GameMap,
+    attacker: &BattleSide,
+    defender: &BattleSide,
+    location: RegionId,
+    trials: u32,
+) -> Result<BattleOdds, EngineError> {
+    if trials == 0 {
+        return Err(EngineError::InvalidAction {
+            reason: "A simulation needs at least one trial".into(),
+        });
+    }
+    if trials > MAX_TRIALS {
+        return Err(EngineError::InvalidAction {
+            reason: format!("A simulation runs at most {} trials", MAX_TRIALS),
+        });
+    }
+    match location {
+        RegionId::Land(tid) if tid as usize >= state.territories.len() => {
+            return Err(EngineError::TerritoryNotFound { territory_id: tid });
+        }
+        RegionId::Sea(zone) if zone as usize >= state.sea_zones.len() => {
+            return Err(EngineError::InvalidAction {
+                reason: format!("Sea zone {} does not exist", zone),
+            });
+        }
+        _ => {}
+    }
+    if attacker.units.is_empty() {
+        return Err(EngineError::InvalidAction {
+            reason: "The attacker needs at least one unit".into(),
+        });
+    }
+    if attacker.power.team() == defender.power.team() {
+        return Err(EngineError::InvalidAction {
+            reason: "Attacker and defender must be on opposing sides".into(),
+        });
+    }
+
+    if let Some(&unit_type) = attacker
+        .units
+        .iter()
+        .chain(&defender.units)
+        .find(|&&u| !crate::movement::unit_can_occupy(state, u, location))
+    {
+        return Err(EngineError::InvalidAction {
+            reason: format!("{:?} cannot fight in {:?}", unit_type, location),
+        });
+    }
+
+    let (base, attacker_ids, defender_ids) = battle_setup(state, attacker, defender, location);
+
+    let mut odds = BattleOdds {
+        trials,
+        attacker_win_rate: 0.0,
+        defender_win_rate: 0.0,
+        draw_rate: 0.0,
+        attacker_survivors: vec![0; attacker_ids.len() + 1],
+        defender_survivors: vec![0; defender_ids.len() + 1],
+    };
+    let (mut attacker_wins, mut defender_wins) = (0u32, 0u32);
+
+    for trial in 0..trials {
+        let mut sim = base.clone();
+        sim.rng_seed = base.rng_seed.wrapping_add(trial as u64);
+        let attacker_won = fight(&mut sim, map, location)?;
+
+        let alive = |ids: &[UnitId]| {
+            ids.iter()
+                .filter(|&&id| crate::movement::find_unit(&sim, id).is_some())
+                .count()
+        };
+        let attackers_left = alive(&attacker_ids);
+        let defenders_left = alive(&defender_ids);
+        odds.attacker_survivors[attackers_left] += 1;
+        odds.defender_survivors[defenders_left] += 1;
+
+        if attacker_won {
+            attacker_wins += 1;
+        } else if defenders_left > 0 && attackers_left == 0 {
+            defender_wins += 1;
+        }
+    }
+
+    let total = trials as f64;
+    odds.attacker_win_rate = attacker_wins as f64 / total;
+    odds.defender_win_rate = defender_wins as f64 / total;
+    odds.draw_rate = (trials - attacker_wins - defender_wins) as f64 / total;
+    Ok(odds)
+}
+
+/// A copy of `state` with only the two sides at `location`, the attacker to
+/// move in Conduct Combat and the battle pending. Returns the unit IDs
+/// given to each side.
+fn battle_setup(
+    state: &GameState,
+    attacker: &BattleSide,
+    defender: &BattleSide,
+    location: RegionId,
+) -> (GameState, Vec<UnitId>, Vec<UnitId>) {
+    let mut base = state.clone();
+    base.action_log.clear();
+    base.undo_checkpoints = vec![0];
+    base.combat_history.clear();
+    base.rng_mode = crate::dice::RngMode::SharedCounter;
+    base.rng_counter = 0;
+
+    let (a, d) = (attacker.power as usize, defender.power as usize);
+    base.political.war_matrix[a][d] = true;
+    base.political.war_matrix[d][a] = true;
+
+    // Nothing outside the battle matters, and an empty board keeps the
+    // per-trial copies cheap
+    let mut next_id = crate::apply::next_unit_id(&base);
+    for territory in base.territories.iter_mut() {
+        territory.units.clear();
+    }
+    for zone in base.sea_zones.iter_mut() {
+        zone.units.clear();
+    }
+
+    let mut place = |base: &mut GameState, side: &BattleSide, moved: bool| -> Vec<UnitId> {
+        let mut ids = Vec::with_capacity(side.units.len());
+        for &unit_type in &side.units {
+            let mut unit = UnitInstance::new(next_id, unit_type, side.power);
+            unit.moved_this_turn = moved;
+            ids.push(next_id);
+            next_id += 1;
+            crate::movement::place_unit_at(base, location, unit);
+        }
+        ids
+    };
+    let attacker_ids = place(&mut base, attacker, true);
+    let defender_ids = place(&mut base, defender, false);
+
+    base.current_power = attacker.power;
+    base.current_phase = Phase::ConductCombat;
+    let mut cs = CombatState::new();
+    cs.pending_battles.push(location);
+    base.phase_state = PhaseState::Combat(cs);
+
+    (base, attacker_ids, defender_ids)
+}
+
+/// Fight the pending battle at `location` to the end, each side's choices
+/// checked like a player's. Returns whether the attacker won.
+fn fight(sim: &mut GameState, map: &GameMap, location: RegionId) -> Result<bool, EngineError> {
+    crate::apply::apply_action_inner(sim, crate::action::Action::SelectBattle { location }, map)?;
+    while let PhaseState::Combat(CombatState { active_combat: Some(combat), .. }) = &sim.phase_state {
+        let action = crate::ai::ai_handle_battle(sim, combat);
+        crate::validate::validate_action_with_map(sim, &action, Some(map))?;
+        crate::apply::apply_action_inner(sim, action, map)?;
+    }
+    Ok(sim
+        .combat_history
+        .last()
+        .is_some_and(|record| record.attacker_won))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::territory_ids as t;
+    use crate::Engine;
+
+    #[test]
+    fn test_three_infantry_usually_beat_one() {
+        let engine = Engine::new_game(42);
+        let before = engine.state().checksum();
+        let attacker = BattleSide {
+            power: Power::Germany,
+            units: vec![UnitType::Infantry; 3],
+        };
+        let defender = BattleSide {
+            power: Power::France,
+            units: vec![UnitType::Infantry],
+        };
+
+        let odds = simulate_battle(
+            engine.state(),
+            &engine.map,
+            &attacker,
+            &defender,
+            RegionId::Land(t::FRANCE),
+            1000,
+        )
+        .unwrap();
+
+        assert!(odds.attacker_win_rate > 0.8, "win rate {}", odds.attacker_win_rate);
+        assert_eq!(odds.attacker_survivors.iter().sum::<u32>(), 1000);
+        assert_eq!(odds.defender_survivors.len(), 2);
+        let rates = odds.attacker_win_rate + odds.defender_win_rate + odds.draw_rate;
+        assert!((rates - 1.0).abs() < 1e-9);
+        assert_eq!(engine.state().checksum(), before);
+    }
+
+    #[test]
+    fn test_rejects_unknown_regions_and_too_many_trials() {
+        let engine = Engine::new_game(42);
+        let side = |power| BattleSide { power, units: vec![UnitType::Infantry] };
+        let run = |location, trials| {
+            simulate_battle(engine.state(), &engine.map, &side(Power::Germany), &side(Power::France), location, trials)
+        };
+
+        assert!(matches!(run(RegionId::Land(u16::MAX), 10), Err(EngineError::TerritoryNotFound { .. })));
+        assert!(matches!(run(RegionId::Sea(u16::MAX), 10), Err(EngineError::InvalidAction { .. })));
+        assert!(matches!(run(RegionId::Land(t::FRANCE), MAX_TRIALS + 1), Err(EngineError::InvalidAction { .. })));
+    }
+}
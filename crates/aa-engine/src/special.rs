@@ -1,11 +1,12 @@
-//! Special rules: China, Kamikaze, straits/canals, capital capture/liberation.
+//! Special rules: China, Kamikaze, straits/canals, capital capture/liberation,
+//! naval base repair.
 
 use crate::data::GameMap;
-use crate::action::GameEvent;
+use crate::action::{GameEvent, IpcChangeReason};
 use crate::error::EngineError;
 use crate::power::Power;
 use crate::state::GameState;
-use crate::territory::{RegionId, TerritoryId};
+use crate::territory::{FacilityType, RegionId, SeaZoneId, TerritoryId};
 use crate::unit::{UnitInstance, UnitType};
 
 // =========================================================================
@@ -160,6 +161,11 @@ pub fn apply_capital_capture(
             territory_id,
             by: conquering_power,
         });
+        if seized > 0 {
+            let reason = IpcChangeReason::CapitalCaptured;
+            events.push(crate::income::ipc_changed(state, capital_power, -(seized as i32), reason));
+            events.push(crate::income::ipc_changed(state, conquering_power, seized as i32, reason));
+        }
     }
 
     events
@@ -216,10 +222,50 @@ pub fn check_liberation(
     None
 }
 
+// =========================================================================
+// Naval base repair
+// =========================================================================
+
+/// Whether a sea zone is served by an operational naval base in a territory
+/// friendly to the given power.
+pub fn has_friendly_naval_base(state: &GameState, map: &GameMap, zone: SeaZoneId, power: Power) -> bool {
+    map.sea_zone(zone).adjacent_land.iter().any(|&tid| {
+        let ts = &state.territories[tid as usize];
+        ts.owner.is_some_and(|owner| state.political.are_friendly(owner, power))
+            && ts
+                .facilities
+                .iter()
+                .any(|f| f.facility_type == FacilityType::NavalBase && f.operational)
+    })
+}
+
+/// End-of-turn repair: the power's damaged capital ships in a sea zone with a
+/// friendly naval base are restored to full strength. Returns the number repaired.
+pub fn repair_ships_at_naval_bases(state: &mut GameState, map: &GameMap, power: Power) -> u32 {
+    let multi_hit: Vec<UnitType> =
+        UnitType::all().iter().copied().filter(|&t| state.unit_stats(t).hit_points > 1).collect();
+    let mut repaired = 0;
+    for zone in 0..state.sea_zones.len() {
+        let zone_id = zone as SeaZoneId;
+        let needs_repair = state.sea_zones[zone].units.iter().any(|u| u.owner == power && u.is_damaged());
+        if !needs_repair || !has_friendly_naval_base(state, map, zone_id, power) {
+            continue;
+        }
+        for unit in &mut state.sea_zones[zone].units {
+            if unit.owner == power && unit.is_damaged() && multi_hit.contains(&unit.unit_type) {
+                unit.hits_taken = 0;
+                repaired += 1;
+            }
+        }
+    }
+    repaired
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::data::GameMap;
+    use crate::data::sea_zone_ids as sz;
     use crate::setup;
 
     #[test]
@@ -354,5 +400,41 @@ mod tests {
         let result = check_liberation(&state, &map, 5, Power::UnitedKingdom);
         assert_eq!(result, None);
     }
-}
 
+    #[test]
+    fn test_damaged_battleship_repairs_at_friendly_naval_base() {
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        // Germany has a naval base adjacent to the Baltic
+        let mut bb = UnitInstance::new(99_001, UnitType::Battleship, Power::Germany);
+        bb.hits_taken = 1;
+        state.sea_zones[sz::SZ_BALTIC_SEA as usize].units.push(bb);
+
+        let repaired = repair_ships_at_naval_bases(&mut state, &map, Power::Germany);
+        assert_eq!(repaired, 1);
+        let bb = state.sea_zones[sz::SZ_BALTIC_SEA as usize]
+            .units
+            .iter()
+            .find(|u| u.id == 99_001)
+            .unwrap();
+        assert!(!bb.is_damaged());
+    }
+
+    #[test]
+    fn test_damaged_battleship_in_open_sea_stays_damaged() {
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        let mut bb = UnitInstance::new(99_001, UnitType::Battleship, Power::Germany);
+        bb.hits_taken = 1;
+        state.sea_zones[sz::SZ_CENTRAL_ATLANTIC as usize].units.push(bb);
+
+        let repaired = repair_ships_at_naval_bases(&mut state, &map, Power::Germany);
+        assert_eq!(repaired, 0);
+        let bb = state.sea_zones[sz::SZ_CENTRAL_ATLANTIC as usize]
+            .units
+            .iter()
+            .find(|u| u.id == 99_001)
+            .unwrap();
+        assert!(bb.is_damaged());
+    }
+}
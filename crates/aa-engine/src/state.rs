@@ -3,10 +3,15 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
+use std::collections::HashSet;
+
 use crate::action::AppliedAction;
+use crate::data::GameMap;
+use crate::dice::RngMode;
 use crate::phase::{Phase, PhaseState, PurchaseState};
 use crate::power::Power;
-use crate::territory::{SeaZoneState, TerritoryState};
+use crate::territory::{RegionId, SeaZoneId, SeaZoneState, TerritoryId, TerritoryState, TerritoryType};
+use crate::unit::{get_unit_stats, UnitId, UnitInstance, UnitStats, UnitStatsTable, UnitType};
 
 /// Per-power mutable state.
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
@@ -19,6 +24,12 @@ pub struct PowerState {
     pub at_war: bool,
     pub capital_captured: bool,
     pub researched_techs: Vec<String>,
+    /// The power has conceded and no longer takes turns.
+    #[serde(default)]
+    pub surrendered: bool,
+    /// Every confirmed purchase, oldest first, tagged with its turn number.
+    #[serde(default)]
+    pub purchase_history: Vec<(u32, Vec<(UnitType, u32)>)>,
 }
 
 impl PowerState {
@@ -34,10 +45,61 @@ impl PowerState {
             ),
             capital_captured: false,
             researched_techs: Vec::new(),
+            surrendered: false,
+            purchase_history: Vec::new(),
+        }
+    }
+}
+
+/// Optional rule settings chosen when the game is created.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct GameSettings {
+    /// IPCs deducted from income per unit a power has on the board.
+    pub upkeep_per_unit: u32,
+    /// Rounds after which an undecided battle ends in a stalemate.
+    pub max_combat_rounds: u32,
+    /// Reject confirming purchases the factories can't place, rather than
+    /// only warning about them.
+    pub strict_purchase_capacity: bool,
+    /// How the game is won.
+    pub victory_condition: crate::victory::VictoryCondition,
+    /// House rule: units that survive a battle become veterans and get +1
+    /// on attack and defense.
+    pub veteran_units: bool,
+    /// Most units one power may have in a single territory; `None` for no limit.
+    pub max_units_per_territory: Option<u32>,
+    /// What each listed power may build; unlisted powers may build anything.
+    pub production_rules: std::collections::BTreeMap<Power, crate::purchase::AllowedUnits>,
+    /// Scorched earth: damage a captured industrial complex takes as it
+    /// changes hands, to be repaired before full use. 0 leaves it intact.
+    pub scorched_earth_damage: u32,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        GameSettings {
+            upkeep_per_unit: 0,
+            max_combat_rounds: 100,
+            strict_purchase_capacity: false,
+            victory_condition: crate::victory::VictoryCondition::VictoryCities,
+            veteran_units: false,
+            max_units_per_territory: None,
+            production_rules: crate::purchase::default_production_rules(),
+            scorched_earth_damage: 0,
         }
     }
 }
 
+/// Diplomatic relation between two powers, as set by `Action::SetRelation`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum Relation {
+    War,
+    Peace,
+}
+
 /// Political state tracking wars, triggers, and neutrals.
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(export)]
@@ -90,6 +152,17 @@ impl PoliticalState {
     pub fn are_friendly(&self, a: Power, b: Power) -> bool {
         a == b || (a.team() == b.team() && !self.are_at_war(a, b))
     }
+
+    /// The powers whose units defend in a battle `attacker` starts: those it
+    /// is at war with. Allied units only fight on their own turn, and neutrals
+    /// or a rival not yet at war take no part.
+    pub fn combat_enemies(&self, attacker: Power) -> HashSet<Power> {
+        Power::all()
+            .iter()
+            .copied()
+            .filter(|&p| p != attacker && self.are_at_war(attacker, p))
+            .collect()
+    }
 }
 
 impl Default for PoliticalState {
@@ -129,6 +202,16 @@ impl Default for PoliticalTriggers {
     }
 }
 
+/// Structured snapshot of whose turn it is, for UI headers and save metadata.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TurnInfo {
+    pub turn_number: u32,
+    pub power: Power,
+    pub phase: Phase,
+    pub current_ipcs: u32,
+}
+
 /// The complete game state. This is the single source of truth.
 #[derive(Clone, Debug, Serialize, Deserialize, TS)]
 #[ts(export)]
@@ -154,6 +237,35 @@ pub struct GameState {
 
     pub rng_seed: u64,
     pub rng_counter: u64,
+    /// The seed the game was created with. `rng_seed` starts out equal to it
+    /// but can be moved by `Engine::set_rng_state`. `None` in older saves.
+    #[serde(default)]
+    pub creation_seed: Option<u64>,
+    /// Saves predating per-battle dice fall back to the shared counter.
+    #[serde(default)]
+    pub rng_mode: RngMode,
+    /// Scenario-setup mode: allows editing actions such as `SetRelation`
+    /// that are never legal in normal play.
+    #[serde(default)]
+    pub sandbox_mode: bool,
+    /// Optional house rules; the defaults play the standard game.
+    #[serde(default)]
+    pub settings: GameSettings,
+    /// The team that won, once victory has been declared at the end of a
+    /// round (or by surrender).
+    #[serde(default)]
+    pub winner: Option<crate::power::Team>,
+    /// Under economic victory, the team that held the IPC threshold at the
+    /// end of the last round and the turn number it first did so.
+    #[serde(default)]
+    pub economic_leader: Option<(crate::power::Team, u32)>,
+    /// Every battle resolved so far, oldest first.
+    #[serde(default)]
+    pub combat_history: Vec<crate::combat::BattleRecord>,
+    /// Modded unit stats, or `None` for the built-in table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(skip)]
+    pub stats_table: Option<Box<UnitStatsTable>>,
 }
 
 impl GameState {
@@ -185,6 +297,463 @@ impl GameState {
             undo_checkpoints: vec![0],
             rng_seed: seed,
             rng_counter: 0,
+            creation_seed: Some(seed),
+            rng_mode: RngMode::PerBattle,
+            sandbox_mode: false,
+            settings: GameSettings::default(),
+            winner: None,
+            economic_leader: None,
+            combat_history: Vec::new(),
+            stats_table: None,
+        }
+    }
+
+    /// Stats for `unit_type` under this game's table.
+    pub fn unit_stats(&self, unit_type: UnitType) -> UnitStats {
+        match &self.stats_table {
+            Some(table) => table.get(unit_type),
+            None => get_unit_stats(unit_type),
+        }
+    }
+
+    /// Stable 64-bit checksum of the game position, for detecting desyncs
+    /// between client and server. Units are hashed in ID order within each
+    /// region, so storage order doesn't matter; the action log and undo
+    /// checkpoints are left out.
+    pub fn checksum(&self) -> u64 {
+        let mut h = Fnv1a64::new();
+        h.add(&self.turn_number);
+        h.add(&self.current_power);
+        h.add(&self.current_phase);
+        h.add(&self.phase_state);
+        for t in &self.territories {
+            let mut t = t.clone();
+            t.units.sort_by_key(|u| u.id);
+            h.add(&t);
+        }
+        for sz in &self.sea_zones {
+            let mut sz = sz.clone();
+            sz.units.sort_by_key(|u| u.id);
+            h.add(&sz);
+        }
+        h.add(&self.powers);
+        h.add(&self.political);
+        h.add(&self.pending_purchases);
+        h.add(&self.rng_seed);
+        h.add(&self.rng_counter);
+        h.add(&self.rng_mode);
+        h.add(&self.sandbox_mode);
+        h.add(&self.settings);
+        h.add(&self.winner);
+        h.add(&self.economic_leader);
+        // A modded stats table is hashed in unit-type order, as the table
+        // itself is unordered
+        if let Some(table) = &self.stats_table {
+            for &unit_type in UnitType::all() {
+                h.add(&table.get(unit_type));
+            }
+        }
+        h.0
+    }
+
+    /// Find every unit on the board matching `predicate`, in a single pass
+    /// over all territories and sea zones.
+    pub fn find_units(
+        &self,
+        predicate: impl Fn(&UnitInstance, RegionId) -> bool,
+    ) -> Vec<(RegionId, UnitId)> {
+        let land = self
+            .territories
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (RegionId::Land(i as TerritoryId), &t.units));
+        let sea = self
+            .sea_zones
+            .iter()
+            .enumerate()
+            .map(|(i, sz)| (RegionId::Sea(i as SeaZoneId), &sz.units));
+
+        land.chain(sea)
+            .flat_map(|(region, units)| units.iter().map(move |u| (region, u)))
+            .filter(|&(region, u)| predicate(u, region))
+            .map(|(region, u)| (region, u.id))
+            .collect()
+    }
+
+    /// All units owned by `power`.
+    pub fn units_of_power(&self, power: Power) -> Vec<(RegionId, UnitId)> {
+        self.find_units(|u, _| u.owner == power)
+    }
+
+    /// All units of `unit_type`, whoever owns them.
+    pub fn units_of_type(&self, unit_type: UnitType) -> Vec<(RegionId, UnitId)> {
+        self.find_units(|u, _| u.unit_type == unit_type)
+    }
+
+    /// Regions `power` can see into: everywhere it has units or owns the
+    /// territory, plus every region one hop from those (land, sea or coast).
+    pub fn observed_regions(&self, map: &GameMap, power: Power) -> HashSet<RegionId> {
+        let mut held: HashSet<RegionId> = self
+            .units_of_power(power)
+            .into_iter()
+            .map(|(region, _)| region)
+            .collect();
+        held.extend(
+            self.territories
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.owner == Some(power))
+                .map(|(i, _)| RegionId::Land(i as TerritoryId)),
+        );
+
+        let mut observed = held.clone();
+        for region in held {
+            match region {
+                RegionId::Land(tid) => {
+                    observed.extend(map.land_neighbors(tid).iter().map(|&n| RegionId::Land(n)));
+                    observed.extend(map.coastal_zones(tid).iter().map(|&z| RegionId::Sea(z)));
+                }
+                RegionId::Sea(zid) => {
+                    observed.extend(map.sea_neighbors(zid).iter().map(|&z| RegionId::Sea(z)));
+                    observed.extend(map.coastal_territories(zid).iter().map(|&n| RegionId::Land(n)));
+                }
+            }
+        }
+        observed
+    }
+
+    /// Fog-of-war projection of the state for `power`: units of the other team
+    /// are removed from every region outside `observed_regions`, the action
+    /// log (which would reveal their moves) is dropped, and the dice seeds and
+    /// counter are cleared so upcoming rolls can't be predicted. On the other
+    /// team's turn its planned moves, purchases and any battle out of sight
+    /// are hidden too, as are its purchase histories and battles fought where
+    /// `power` could not see.
+    pub fn state_view_for(&self, map: &GameMap, power: Power) -> GameState {
+        let observed = self.observed_regions(map, power);
+        let mut view = self.clone();
+        let team = power.team();
+
+        for (i, t) in view.territories.iter_mut().enumerate() {
+            if !observed.contains(&RegionId::Land(i as TerritoryId)) {
+                t.units.retain(|u| u.owner.team() == team);
+            }
         }
+        for (i, sz) in view.sea_zones.iter_mut().enumerate() {
+            if !observed.contains(&RegionId::Sea(i as SeaZoneId)) {
+                sz.units.retain(|u| u.owner.team() == team);
+            }
+        }
+        if self.current_power.team() != team {
+            match &mut view.phase_state {
+                PhaseState::Purchase(ps) => ps.purchases.clear(),
+                PhaseState::CombatMove(cm) => {
+                    cm.moves.clear();
+                    cm.open_operation = None;
+                }
+                PhaseState::Combat(cs) => {
+                    cs.combat_moves.clear();
+                    if cs.active_combat.as_ref().is_some_and(|c| !observed.contains(&c.location)) {
+                        cs.active_combat = None;
+                    }
+                }
+                PhaseState::NonCombatMove(ncm) => ncm.moves.clear(),
+                PhaseState::Mobilize(_) | PhaseState::CollectIncome(_) => {}
+            }
+            view.pending_purchases.clear();
+        }
+        for ps in view.powers.iter_mut().filter(|ps| ps.power.team() != team) {
+            ps.purchase_history.clear();
+        }
+        view.combat_history.retain(|battle| {
+            battle.attacker.team() == team || battle.defender.team() == team || observed.contains(&battle.location)
+        });
+        view.action_log.clear();
+        view.undo_checkpoints = vec![0];
+        view.rng_seed = 0;
+        view.rng_counter = 0;
+        view.creation_seed = None;
+        view
+    }
+
+    /// Check structural invariants of the state (for debugging desyncs).
+    /// Builds the static map internally; see `validate_integrity_with_map`.
+    pub fn validate_integrity(&self) -> Result<(), Vec<String>> {
+        self.validate_integrity_with_map(&GameMap::new())
+    }
+
+    /// Check structural invariants of the state against the given map.
+    /// Returns every violation found rather than stopping at the first.
+    pub fn validate_integrity_with_map(&self, map: &GameMap) -> Result<(), Vec<String>> {
+        let mut violations = Vec::new();
+        let mut seen_ids = HashSet::new();
+
+        let mut check_unit = |unit: &UnitInstance, region: RegionId, location: &str, violations: &mut Vec<String>| {
+            if !seen_ids.insert(unit.id) {
+                violations.push(format!("Duplicate unit ID {} in {}", unit.id, location));
+            }
+            if !crate::movement::unit_can_occupy(self, unit.unit_type, region) {
+                violations.push(format!("Unit {} ({:?}) cannot stand in {}", unit.id, unit.unit_type, location));
+            }
+            let hit_points = self.unit_stats(unit.unit_type).hit_points;
+            if unit.hits_taken >= hit_points {
+                violations.push(format!(
+                    "Unit {} ({:?}) in {} has {} hits taken but only {} hit points",
+                    unit.id, unit.unit_type, location, unit.hits_taken, hit_points
+                ));
+            }
+        };
+
+        for (i, t) in self.territories.iter().enumerate() {
+            let location = match map.territories.get(i) {
+                Some(def) => def.name.clone(),
+                None => {
+                    violations.push(format!("Territory index {} has no map definition", i));
+                    format!("territory {}", i)
+                }
+            };
+            if let Some(def) = map.territories.get(i) {
+                if def.territory_type == TerritoryType::Impassable && !t.units.is_empty() {
+                    violations.push(format!("Impassable territory {} contains units", location));
+                }
+            }
+            if t.owner.is_none() && !t.facilities.is_empty() {
+                violations.push(format!("Unowned territory {} has facilities", location));
+            }
+            if let Some(owner) = t.owner {
+                if self.powers.get(owner as usize).is_some_and(|ps| ps.surrendered) {
+                    violations.push(format!("Territory {} is owned by surrendered {:?}", location, owner));
+                }
+            }
+            for unit in &t.units {
+                check_unit(unit, RegionId::Land(i as TerritoryId), &location, &mut violations);
+            }
+        }
+
+        for (i, sz) in self.sea_zones.iter().enumerate() {
+            let location = match map.sea_zones.get(i) {
+                Some(def) => def.name.clone(),
+                None => {
+                    violations.push(format!("Sea zone index {} has no map definition", i));
+                    format!("sea zone {}", i)
+                }
+            };
+            for unit in &sz.units {
+                check_unit(unit, RegionId::Sea(i as SeaZoneId), &location, &mut violations);
+            }
+        }
+
+        // Power state is indexed by `Power as usize` throughout the engine
+        for (i, ps) in self.powers.iter().enumerate() {
+            if ps.power as usize != i {
+                violations.push(format!("Power state at index {} belongs to {:?}", i, ps.power));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// 64-bit FNV-1a over MessagePack-encoded values. Unlike `std`'s hashers
+/// its output is fixed, so the client and server always agree.
+struct Fnv1a64(u64);
+
+impl Fnv1a64 {
+    fn new() -> Self {
+        Fnv1a64(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn add<T: Serialize>(&mut self, value: &T) {
+        rmp_serde::encode::write(self, value).expect("game state is always serializable");
+    }
+}
+
+impl std::io::Write for Fnv1a64 {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &byte in buf {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::territory_ids as t;
+    use crate::setup::create_initial_state;
+
+    #[test]
+    fn test_clean_setup_passes_integrity() {
+        let map = GameMap::new();
+        let state = create_initial_state(42, &map);
+        assert!(state.validate_integrity_with_map(&map).is_ok());
+    }
+
+    #[test]
+    fn test_combat_enemies_for_germany() {
+        let political = PoliticalState::new();
+        let enemies = political.combat_enemies(Power::Germany);
+        assert!(enemies.contains(&Power::UnitedKingdom) && enemies.contains(&Power::France));
+        assert!(!enemies.contains(&Power::Germany));
+        assert!(!enemies.contains(&Power::Italy) && !enemies.contains(&Power::Japan));
+
+        // The Soviet Union is not yet at war with Germany
+        assert!(!enemies.contains(&Power::SovietUnion));
+    }
+
+    #[test]
+    fn test_duplicate_unit_ids_reported() {
+        let map = GameMap::new();
+        let mut state = create_initial_state(42, &map);
+        let dup = state.territories[t::GERMANY as usize].units[0].clone();
+        state.territories[t::WESTERN_GERMANY as usize].units.push(dup.clone());
+
+        let violations = state.validate_integrity_with_map(&map).unwrap_err();
+        assert!(violations.iter().any(|v| v.contains(&format!("Duplicate unit ID {}", dup.id))));
+    }
+
+    #[test]
+    fn test_overdamaged_unit_reported() {
+        let map = GameMap::new();
+        let mut state = create_initial_state(42, &map);
+        let mut inf = UnitInstance::new(99_999, UnitType::Infantry, Power::Germany);
+        inf.hits_taken = 1;
+        state.territories[t::GERMANY as usize].units.push(inf);
+
+        let violations = state.validate_integrity_with_map(&map).unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].contains("hits taken"));
+    }
+
+    #[test]
+    fn test_units_in_impassable_territory_reported() {
+        let map = GameMap::new();
+        let mut state = create_initial_state(42, &map);
+        let inf = UnitInstance::new(99_999, UnitType::Infantry, Power::Germany);
+        state.territories[t::SWITZERLAND as usize].units.push(inf);
+
+        let violations = state.validate_integrity_with_map(&map).unwrap_err();
+        assert!(violations.iter().any(|v| v.contains("Impassable")));
+    }
+
+    #[test]
+    fn test_units_of_power_matches_japanese_setup() {
+        let map = GameMap::new();
+        let state = create_initial_state(42, &map);
+        let units = state.units_of_power(Power::Japan);
+
+        // 41 land and air units on Japanese territories, 28 in the fleets
+        assert_eq!(units.len(), 69);
+        assert_eq!(units.iter().filter(|(r, _)| matches!(r, RegionId::Sea(_))).count(), 28);
+        for (region, id) in &units {
+            let list = match region {
+                RegionId::Land(t) => &state.territories[*t as usize].units,
+                RegionId::Sea(s) => &state.sea_zones[*s as usize].units,
+            };
+            let unit = list.iter().find(|u| u.id == *id).unwrap();
+            assert_eq!(unit.owner, Power::Japan);
+        }
+    }
+
+    #[test]
+    fn test_view_reveals_only_observed_enemy_stacks() {
+        let map = GameMap::new();
+        let mut state = create_initial_state(42, &map);
+        state.territories[t::DENMARK as usize]
+            .units
+            .push(UnitInstance::new(900_001, UnitType::Infantry, Power::SovietUnion));
+
+        let observed = state.observed_regions(&map, Power::Germany);
+        assert!(observed.contains(&RegionId::Land(t::DENMARK)));
+        assert!(!observed.contains(&RegionId::Land(t::EASTERN_UNITED_STATES)));
+
+        let view = state.state_view_for(&map, Power::Germany);
+        assert!(view.territories[t::DENMARK as usize].units.iter().any(|u| u.id == 900_001));
+        assert!(!state.territories[t::EASTERN_UNITED_STATES as usize].units.is_empty());
+        assert!(view.territories[t::EASTERN_UNITED_STATES as usize].units.is_empty());
+        assert_eq!(
+            view.territories[t::GERMANY as usize].units.len(),
+            state.territories[t::GERMANY as usize].units.len()
+        );
+    }
+
+    #[test]
+    fn test_view_hides_the_dice_stream() {
+        let map = GameMap::new();
+        let mut state = create_initial_state(42, &map);
+        state.rng_counter = 17;
+
+        let view = state.state_view_for(&map, Power::Germany);
+        assert_eq!((view.rng_seed, view.rng_counter, view.creation_seed), (0, 0, None));
+        assert_eq!((state.rng_seed, state.rng_counter), (42, 17));
+    }
+
+    #[test]
+    fn test_view_hides_enemy_plans_and_purchases() {
+        use crate::phase::{CombatMoveState, PlannedMove};
+
+        let map = GameMap::new();
+        let mut state = create_initial_state(42, &map);
+        state.current_power = Power::SovietUnion;
+        state.current_phase = Phase::CombatMovement;
+        let mut moves = CombatMoveState::new();
+        moves.moves.push(PlannedMove {
+            unit_id: 900_001,
+            path: vec![RegionId::Land(t::EASTERN_UNITED_STATES)],
+            from: RegionId::Land(t::EASTERN_UNITED_STATES),
+            to: RegionId::Land(t::EASTERN_UNITED_STATES),
+            operation: Some("Torch".into()),
+        });
+        moves.open_operation = Some("Torch".into());
+        state.phase_state = PhaseState::CombatMove(moves);
+        state.pending_purchases = vec![(UnitType::Tank, 3)];
+        state.powers[Power::SovietUnion as usize].purchase_history = vec![(1, vec![(UnitType::Tank, 3)])];
+        state.powers[Power::Germany as usize].purchase_history = vec![(1, vec![(UnitType::Infantry, 2)])];
+
+        let view = state.state_view_for(&map, Power::Germany);
+        let PhaseState::CombatMove(cm) = &view.phase_state else {
+            panic!("expected combat movement");
+        };
+        assert!(cm.moves.is_empty());
+        assert_eq!(cm.open_operation, None);
+        assert!(view.pending_purchases.is_empty());
+        assert!(view.powers[Power::SovietUnion as usize].purchase_history.is_empty());
+        assert_eq!(view.powers[Power::Germany as usize].purchase_history.len(), 1);
+
+        // The moving power's own team still sees everything
+        let ally = state.state_view_for(&map, Power::UnitedKingdom);
+        let PhaseState::CombatMove(cm) = &ally.phase_state else {
+            panic!("expected combat movement");
+        };
+        assert_eq!(cm.moves.len(), 1);
+        assert_eq!(ally.pending_purchases, vec![(UnitType::Tank, 3)]);
+
+        // Nor can an enemy see what is being bought
+        let mut purchase = PurchaseState::new();
+        purchase.purchases.push((UnitType::StrategicBomber, 2));
+        state.phase_state = PhaseState::Purchase(purchase);
+        let view = state.state_view_for(&map, Power::Germany);
+        assert!(matches!(&view.phase_state, PhaseState::Purchase(ps) if ps.purchases.is_empty()));
+    }
+
+    #[test]
+    fn test_find_units_by_type_and_location() {
+        let map = GameMap::new();
+        let state = create_initial_state(42, &map);
+        let japan_aaa = state.find_units(|u, r| {
+            u.unit_type == UnitType::AAA && r == RegionId::Land(t::JAPAN)
+        });
+        assert_eq!(japan_aaa.len(), 3);
+        assert!(state.units_of_type(UnitType::AAA).len() >= 3);
     }
 }
@@ -86,7 +86,33 @@ pub struct Facility {
     pub operational: bool,
 }
 
+/// Lowest territory IPC value that can support a major industrial complex.
+pub const MAJOR_IC_MIN_IPC_VALUE: u32 = 3;
+
 impl Facility {
+    /// Update a facility when its territory is captured. A major IC in a
+    /// territory worth less than `MAJOR_IC_MIN_IPC_VALUE` is downgraded to a
+    /// minor IC, keeping any damage up to the minor IC's maximum. An
+    /// industrial complex then takes `scorched` damage from the side losing it.
+    pub fn on_capture(&mut self, territory_ipc: u32, scorched: u32) {
+        if self.facility_type == FacilityType::MajorIndustrialComplex
+            && territory_ipc < MAJOR_IC_MIN_IPC_VALUE
+        {
+            let minor = Facility::new(FacilityType::MinorIndustrialComplex, territory_ipc);
+            self.facility_type = minor.facility_type;
+            self.max_damage = minor.max_damage;
+            self.damage = self.damage.min(self.max_damage);
+            self.operational = self.damage < self.max_damage;
+        }
+        if matches!(
+            self.facility_type,
+            FacilityType::MinorIndustrialComplex | FacilityType::MajorIndustrialComplex
+        ) {
+            self.damage = (self.damage + scorched).min(self.max_damage);
+            self.operational = self.damage < self.max_damage;
+        }
+    }
+
     pub fn new(facility_type: FacilityType, territory_ipc: u32) -> Self {
         let max_damage = match facility_type {
             FacilityType::MinorIndustrialComplex | FacilityType::MajorIndustrialComplex => {
@@ -1,5 +1,7 @@
 //! Unit type definitions, stats, and unit instances.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -101,6 +103,13 @@ pub struct UnitInstance {
     pub moved_this_turn: bool,
     pub movement_remaining: u8,
     pub cargo: Vec<UnitId>,
+    /// Set on the AVG ("Flying Tigers") fighter, which may never leave
+    /// territories originally controlled by China.
+    #[serde(default)]
+    pub restricted_to_china: bool,
+    /// Survived a battle under the veteran rule; fights at +1.
+    #[serde(default)]
+    pub veteran: bool,
 }
 
 impl UnitInstance {
@@ -115,6 +124,8 @@ impl UnitInstance {
             moved_this_turn: false,
             movement_remaining: stats.movement,
             cargo: Vec::new(),
+            restricted_to_china: false,
+            veteran: false,
         }
     }
 
@@ -124,7 +135,8 @@ impl UnitInstance {
     }
 }
 
-/// Get the static stats for a unit type.
+/// Get the built-in stats for a unit type. Engine code with a game state at
+/// hand reads `GameState::unit_stats` instead, which honours a modded table.
 pub fn get_unit_stats(unit_type: UnitType) -> UnitStats {
     match unit_type {
         UnitType::Infantry => UnitStats {
@@ -329,6 +341,33 @@ pub fn get_unit_stats(unit_type: UnitType) -> UnitStats {
     }
 }
 
+/// Stats for every unit type, for balance mods and variants that change
+/// costs or values without touching the rules. Starts from the built-in
+/// 2nd Edition table.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnitStatsTable {
+    stats: HashMap<UnitType, UnitStats>,
+}
+
+impl UnitStatsTable {
+    /// Stats for `unit_type`, or the built-in ones if the table has no entry.
+    pub fn get(&self, unit_type: UnitType) -> UnitStats {
+        self.stats.get(&unit_type).cloned().unwrap_or_else(|| get_unit_stats(unit_type))
+    }
+
+    /// Replace the entry for `stats.unit_type`.
+    pub fn set(&mut self, stats: UnitStats) {
+        self.stats.insert(stats.unit_type, stats);
+    }
+}
+
+impl Default for UnitStatsTable {
+    fn default() -> Self {
+        let stats = UnitType::all().iter().map(|&t| (t, get_unit_stats(t))).collect();
+        UnitStatsTable { stats }
+    }
+}
+
 impl UnitType {
     /// Returns all unit types as a slice.
     pub fn all() -> &'static [UnitType] {
@@ -8,10 +8,9 @@ use crate::data::GameMap;
 use crate::error::EngineError;
 use crate::movement;
 use crate::phase::{Phase, PhaseState};
-use crate::power::Power;
 use crate::state::GameState;
 use crate::territory::{RegionId, TerritoryId};
-use crate::unit::{get_unit_stats, UnitDomain, UnitId, UnitType};
+use crate::unit::{UnitDomain, UnitId, UnitType};
 
 /// Validate that an action is legal in the current game state.
 pub fn validate_action(state: &GameState, action: &Action) -> Result<(), EngineError> {
@@ -20,6 +19,10 @@ pub fn validate_action(state: &GameState, action: &Action) -> Result<(), EngineE
 
 /// Validate that an action is legal, with optional map for movement validation.
 pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<&GameMap>) -> Result<(), EngineError> {
+    if matches!(action, Action::ConfirmPhase) && state.current_phase != Phase::ConductCombat {
+        return validate_action_with_map(state, &state.current_phase.confirm_action(), map);
+    }
+
     // Basic phase validation
     match action {
         Action::PurchaseUnit { .. }
@@ -33,7 +36,13 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
                 });
             }
         }
-        Action::MoveUnit { .. } | Action::UndoMove { .. } | Action::ConfirmCombatMovement => {
+        Action::MoveUnit { .. }
+        | Action::MoveUnits { .. }
+        | Action::UndoMove { .. }
+        | Action::BeginOperation { .. }
+        | Action::EndOperation
+        | Action::UndoOperation { .. }
+        | Action::ConfirmCombatMovement => {
             if state.current_phase != Phase::CombatMovement {
                 return Err(EngineError::WrongPhase {
                     expected: "CombatMovement".into(),
@@ -47,7 +56,8 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
         | Action::SelectCasualties { .. }
         | Action::AttackerRetreat { .. }
         | Action::SubmergeSubmarine { .. }
-        | Action::ContinueCombatRound => {
+        | Action::ContinueCombatRound
+        | Action::StrategicBombingRaid { .. } => {
             if state.current_phase != Phase::ConductCombat {
                 return Err(EngineError::WrongPhase {
                     expected: "ConductCombat".into(),
@@ -100,6 +110,12 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
             // War declarations can happen during the Combat Movement phase
             // (or at the start of a turn, before combat moves)
         }
+        Action::Surrender { .. } => {
+            // A power may concede at any time, even outside its own turn
+        }
+        Action::SetRelation { .. } => {
+            // Sandbox edits are allowed in any phase
+        }
         Action::ConfirmPhase => {}
     }
 
@@ -118,14 +134,26 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
             validate_repair_facility(state, *territory_id, *damage_to_repair)?;
         }
         Action::ConfirmPurchases => {
-            // Always valid if in correct phase (already checked above)
+            validate_confirm_purchases(state, map)?;
         }
         Action::MoveUnit { unit_id, path } => {
             validate_move_unit(state, map, *unit_id, path)?;
         }
+        Action::MoveUnits { unit_ids, path } => {
+            validate_move_units(state, map, unit_ids, path)?;
+        }
         Action::UndoMove { unit_id } => {
             validate_undo_move(state, *unit_id)?;
         }
+        Action::BeginOperation { name } => {
+            validate_begin_operation(state, name)?;
+        }
+        Action::EndOperation => {
+            validate_end_operation(state)?;
+        }
+        Action::UndoOperation { name } => {
+            validate_undo_operation(state, name)?;
+        }
         Action::ConfirmCombatMovement => {
             validate_confirm_combat_movement(state, map)?;
         }
@@ -141,6 +169,9 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
         Action::SelectBattle { location } => {
             validate_select_battle(state, *location)?;
         }
+        Action::StrategicBombingRaid { territory_id } => {
+            crate::combat::validate_strategic_bombing(state, *territory_id)?;
+        }
         Action::RollAttack => {
             validate_roll_attack(state)?;
         }
@@ -159,9 +190,9 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
         Action::ContinueCombatRound => {
             validate_continue_combat_round(state)?;
         }
-        Action::PlaceUnit { unit_type, territory_id } => {
+        Action::PlaceUnit { unit_type, territory_id, sea_zone } => {
             if let Some(m) = map {
-                crate::mobilize::validate_place_unit(state, m, *unit_type, *territory_id)?;
+                crate::mobilize::validate_place_unit(state, m, *unit_type, *territory_id, *sea_zone)?;
             }
         }
         Action::ConfirmMobilization => {
@@ -173,10 +204,14 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
         Action::DeclareWar { against } => {
             crate::politics::validate_declare_war(state, *against)?;
         }
+        Action::Surrender { power } => {
+            crate::politics::validate_surrender(state, *power)?;
+        }
+        Action::SetRelation { a, b, .. } => {
+            crate::politics::validate_set_relation(state, *a, *b)?;
+        }
         Action::ConfirmPhase => {
-            if state.current_phase == Phase::ConductCombat {
-                validate_confirm_combat(state)?;
-            }
+            validate_confirm_combat(state)?;
         }
         Action::Undo => {
             // Already handled above
@@ -186,17 +221,6 @@ pub fn validate_action_with_map(state: &GameState, action: &Action, map: Option<
     Ok(())
 }
 
-/// Get the current power's available IPCs (total minus already spent in this phase).
-fn available_ipcs(state: &GameState) -> u32 {
-    let power_idx = state.current_power as usize;
-    let total = state.powers[power_idx].ipcs;
-    if let PhaseState::Purchase(ref ps) = state.phase_state {
-        total.saturating_sub(ps.ipcs_spent)
-    } else {
-        total
-    }
-}
-
 /// Validate a PurchaseUnit action.
 fn validate_purchase_unit(
     state: &GameState,
@@ -209,16 +233,15 @@ fn validate_purchase_unit(
         });
     }
 
-    // China can only buy Infantry
-    if state.current_power == Power::China && unit_type != UnitType::Infantry {
+    if !crate::purchase::can_produce(state, state.current_power, unit_type) {
         return Err(EngineError::InvalidAction {
-            reason: "China can only purchase Infantry".into(),
+            reason: format!("{} cannot build {:?}", state.current_power.name(), unit_type),
         });
     }
 
-    let stats = get_unit_stats(unit_type);
+    let stats = state.unit_stats(unit_type);
     let total_cost = stats.cost * count;
-    let available = available_ipcs(state);
+    let available = crate::purchase::available_ipcs(state);
 
     if total_cost > available {
         return Err(EngineError::InsufficientIPCs {
@@ -272,6 +295,24 @@ fn validate_remove_purchase(
     Ok(())
 }
 
+/// Validate ConfirmPurchases. Buying more than the factories can place is
+/// only an advisory obligation unless strict purchase capacity is enabled.
+fn validate_confirm_purchases(state: &GameState, map: Option<&GameMap>) -> Result<(), EngineError> {
+    let Some(map) = map else {
+        return Ok(());
+    };
+    if !state.settings.strict_purchase_capacity {
+        return Ok(());
+    }
+    let shortfall = crate::purchase::placement_shortfall(state, map);
+    if shortfall > 0 {
+        return Err(EngineError::InvalidAction {
+            reason: format!("{} purchased units exceed what your factories can place", shortfall),
+        });
+    }
+    Ok(())
+}
+
 /// Validate a RepairFacility action.
 fn validate_repair_facility(
     state: &GameState,
@@ -330,7 +371,7 @@ fn validate_repair_facility(
     }
 
     // Each point of repair costs 1 IPC
-    let available = available_ipcs(state);
+    let available = crate::purchase::available_ipcs(state);
     if damage_to_repair > available {
         return Err(EngineError::InsufficientIPCs {
             needed: damage_to_repair,
@@ -378,12 +419,118 @@ fn validate_move_unit(
         });
     }
 
+    // Crossing water aboard a transport is the one way a land unit's path
+    // may touch the sea
+    if !movement::is_amphibious_move(state, unit.unit_type, path) {
+        validate_path_domain(state, unit.unit_type, path)?;
+    }
+    validate_destination_at_war(state, path)?;
+    validate_destination_stacking(state, path, 1)?;
+
     // Validate the path
     movement::validate_combat_move(state, map, state.current_power, unit, path)?;
 
     Ok(())
 }
 
+/// War comes before the attack: a combat move may not end in territory held
+/// by a power the mover is at peace with, to be turned into a battle by a
+/// later declaration. Unowned neutrals are handled by the path rules.
+fn validate_destination_at_war(state: &GameState, path: &[RegionId]) -> Result<(), EngineError> {
+    let power = state.current_power;
+    let Some(&RegionId::Land(tid)) = path.last() else {
+        return Ok(());
+    };
+    match state.territories[tid as usize].owner {
+        Some(owner)
+            if !state.political.are_friendly(power, owner)
+                && !state.political.are_at_war(power, owner) =>
+        {
+            Err(EngineError::InvalidAction {
+                reason: format!("Declare war on {:?} before moving into its territory", owner),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Apply the stacking limit to a move of `count` units ending on land.
+fn validate_destination_stacking(state: &GameState, path: &[RegionId], count: usize) -> Result<(), EngineError> {
+    match path.last() {
+        Some(&RegionId::Land(tid)) => movement::validate_stacking_limit(state, tid, state.current_power, count),
+        _ => Ok(()),
+    }
+}
+
+/// Reject a path that leaves the unit's domain: land units never step into a
+/// sea zone (they cross water as transport cargo, see
+/// [`movement::is_amphibious_move`]) and ships
+/// never step onto land. Air units may fly over either.
+fn validate_path_domain(state: &GameState, unit_type: UnitType, path: &[RegionId]) -> Result<(), EngineError> {
+    let reason = match state.unit_stats(unit_type).domain {
+        UnitDomain::Land if path.iter().any(|r| matches!(r, RegionId::Sea(_))) => {
+            format!("{:?} is a land unit and cannot enter a sea zone", unit_type)
+        }
+        UnitDomain::Sea if path.iter().any(|r| matches!(r, RegionId::Land(_))) => {
+            format!("{:?} is a naval unit and cannot move onto land", unit_type)
+        }
+        _ => return Ok(()),
+    };
+    Err(EngineError::InvalidAction { reason })
+}
+
+/// Validate a MoveUnits group action during Combat Movement.
+fn validate_move_units(
+    state: &GameState,
+    map: Option<&GameMap>,
+    unit_ids: &[UnitId],
+    path: &[RegionId],
+) -> Result<(), EngineError> {
+    let map = map.ok_or(EngineError::Internal("Map required for movement validation".into()))?;
+
+    if unit_ids.is_empty() {
+        return Err(EngineError::InvalidAction {
+            reason: "Group move must include at least one unit".into(),
+        });
+    }
+
+    let mut units = Vec::with_capacity(unit_ids.len());
+    for (i, &unit_id) in unit_ids.iter().enumerate() {
+        if unit_ids[..i].contains(&unit_id) {
+            return Err(EngineError::InvalidAction {
+                reason: format!("Unit {} is listed more than once", unit_id),
+            });
+        }
+
+        let (current_region, unit) = movement::find_unit(state, unit_id)
+            .ok_or(EngineError::UnitNotFound { unit_id })?;
+
+        if unit.owner != state.current_power {
+            return Err(EngineError::InvalidAction {
+                reason: "Unit does not belong to current power".into(),
+            });
+        }
+
+        if unit.moved_this_turn {
+            return Err(EngineError::InvalidAction {
+                reason: "Unit has already moved this turn".into(),
+            });
+        }
+
+        if path.is_empty() || path[0] != current_region {
+            return Err(EngineError::IllegalMove {
+                reason: "Path must start at every unit's current location".into(),
+            });
+        }
+
+        units.push(unit);
+    }
+    validate_destination_at_war(state, path)?;
+    validate_destination_stacking(state, path, units.len())?;
+
+    movement::validate_group_combat_move(state, map, state.current_power, &units, path)
+}
+
 /// Validate an UndoMove action.
 fn validate_undo_move(state: &GameState, unit_id: u32) -> Result<(), EngineError> {
     // Check that this unit has a recorded move in the phase state
@@ -403,6 +550,83 @@ fn validate_undo_move(state: &GameState, unit_id: u32) -> Result<(), EngineError
         });
     }
 
+    // Its cargo was landed from where it is now
+    let landed_cargo = movement::find_unit(state, unit_id)
+        .is_some_and(|(_, u)| u.unit_type == UnitType::Transport && !u.cargo.is_empty());
+    if landed_cargo {
+        return Err(EngineError::InvalidAction {
+            reason: "Take back the units this transport landed first".into(),
+        });
+    }
+
+    Ok(())
+}
+
+/// The combat movement state, or WrongPhase outside Combat Movement.
+fn combat_move_state(state: &GameState) -> Result<&crate::phase::CombatMoveState, EngineError> {
+    match &state.phase_state {
+        PhaseState::CombatMove(cms) => Ok(cms),
+        _ => Err(EngineError::WrongPhase {
+            expected: "CombatMovement".into(),
+            actual: format!("{:?}", state.current_phase),
+        }),
+    }
+}
+
+/// Validate a BeginOperation action. Operations do not nest; naming an
+/// earlier operation again adds to it.
+fn validate_begin_operation(state: &GameState, name: &str) -> Result<(), EngineError> {
+    let cms = combat_move_state(state)?;
+    if name.trim().is_empty() {
+        return Err(EngineError::InvalidAction {
+            reason: "An operation needs a name".into(),
+        });
+    }
+    if let Some(open) = &cms.open_operation {
+        return Err(EngineError::InvalidAction {
+            reason: format!("End operation '{}' before starting another", open),
+        });
+    }
+    Ok(())
+}
+
+/// Validate an EndOperation action.
+fn validate_end_operation(state: &GameState) -> Result<(), EngineError> {
+    if combat_move_state(state)?.open_operation.is_none() {
+        return Err(EngineError::InvalidAction {
+            reason: "No operation is open".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Validate an UndoOperation action: every move in it must be undoable,
+/// taken back latest first.
+fn validate_undo_operation(state: &GameState, name: &str) -> Result<(), EngineError> {
+    let cms = combat_move_state(state)?;
+    let moves: Vec<UnitId> = cms
+        .moves
+        .iter()
+        .filter(|m| m.operation.as_deref() == Some(name))
+        .map(|m| m.unit_id)
+        .collect();
+    if moves.is_empty() {
+        return Err(EngineError::InvalidAction {
+            reason: format!("Operation '{}' has no moves to undo", name),
+        });
+    }
+    // A transport's landed cargo only blocks its undo if the cargo stays
+    let staying = |id: UnitId| !moves.contains(&id);
+    for &unit_id in &moves {
+        let landed_elsewhere = movement::find_unit(state, unit_id).is_some_and(|(_, u)| {
+            u.unit_type == UnitType::Transport && u.cargo.iter().any(|&id| staying(id))
+        });
+        if landed_elsewhere {
+            return Err(EngineError::InvalidAction {
+                reason: "Take back the units this transport landed first".into(),
+            });
+        }
+    }
     Ok(())
 }
 
@@ -423,7 +647,7 @@ fn validate_confirm_combat_movement(
     for planned in &cms.moves {
         // Find the unit at its destination
         if let Some((_region, unit)) = movement::find_unit(state, planned.unit_id) {
-            let stats = get_unit_stats(unit.unit_type);
+            let stats = state.unit_stats(unit.unit_type);
             if stats.domain == UnitDomain::Air {
                 let movement_used = (planned.path.len() as u8).saturating_sub(1);
                 if !movement::air_unit_has_potential_landing(
@@ -431,6 +655,7 @@ fn validate_confirm_combat_movement(
                     map,
                     power,
                     unit,
+                    planned.path[0],
                     planned.to,
                     movement_used,
                 ) {
@@ -445,6 +670,34 @@ fn validate_confirm_combat_movement(
         }
     }
 
+    // All air units together must fit into the available landing spots
+    let mut air_units: Vec<(UnitId, RegionId, RegionId, u8)> = Vec::new();
+    for planned in &cms.moves {
+        let is_air = movement::find_unit(state, planned.unit_id)
+            .map(|(_, u)| state.unit_stats(u.unit_type).domain == UnitDomain::Air)
+            .unwrap_or(false);
+        if !is_air {
+            continue;
+        }
+        let movement_used = (planned.path.len() as u8).saturating_sub(1);
+        // A later move of the same unit supersedes an earlier one, but the
+        // unit still took off from where its first move began
+        let takeoff = air_units
+            .iter()
+            .find(|&&(id, _, _, _)| id == planned.unit_id)
+            .map_or(planned.path[0], |&(_, takeoff, _, _)| takeoff);
+        air_units.retain(|&(id, _, _, _)| id != planned.unit_id);
+        air_units.push((planned.unit_id, takeoff, planned.to, movement_used));
+    }
+    if let Some(unit_id) = movement::find_unlandable_air_unit(state, map, power, &air_units) {
+        return Err(EngineError::IllegalMove {
+            reason: format!(
+                "Air unit {} has no landing spot left: carriers in reach are already full",
+                unit_id
+            ),
+        });
+    }
+
     Ok(())
 }
 
@@ -482,6 +735,9 @@ fn validate_move_unit_noncombat(
         });
     }
 
+    validate_path_domain(state, unit.unit_type, path)?;
+    validate_destination_stacking(state, path, 1)?;
+
     movement::validate_noncombat_move(state, map, state.current_power, unit, path)?;
 
     Ok(())
@@ -496,7 +752,7 @@ fn validate_land_air_unit(
 ) -> Result<(), EngineError> {
     let map = map.ok_or(EngineError::Internal("Map required for movement validation".into()))?;
 
-    let (_current_region, unit) = movement::find_unit(state, unit_id)
+    let (current_region, unit) = movement::find_unit(state, unit_id)
         .ok_or(EngineError::UnitNotFound { unit_id })?;
 
     if unit.owner != state.current_power {
@@ -506,6 +762,9 @@ fn validate_land_air_unit(
     }
 
     movement::validate_air_landing(state, map, state.current_power, unit, destination)?;
+    // Landing where it already is adds nothing to the stack
+    let arriving = usize::from(current_region != destination);
+    validate_destination_stacking(state, &[destination], arriving)?;
 
     Ok(())
 }
@@ -543,6 +802,23 @@ fn validate_select_battle(state: &GameState, location: RegionId) -> Result<(), E
         });
     }
 
+    if cs.resolved_battles.contains(&location) {
+        return Err(EngineError::InvalidAction {
+            reason: "The battle at this location has already been fought".into(),
+        });
+    }
+
+    if let RegionId::Land(tid) = location {
+        if let Some(zone) = crate::combat::blocking_naval_battle(state, tid) {
+            return Err(EngineError::InvalidAction {
+                reason: format!(
+                    "The naval battle in sea zone {} must be fought before this amphibious assault",
+                    zone
+                ),
+            });
+        }
+    }
+
     Ok(())
 }
 
@@ -610,12 +886,12 @@ fn validate_select_casualties(state: &GameState, casualties: &[UnitId]) -> Resul
 
     // Check we're in a casualty selection sub-phase
     let defender_side = match combat.sub_phase {
-        CombatSubPhase::AAFireCasualties
-        | CombatSubPhase::DefenderSelectsCasualties
+        CombatSubPhase::DefenderSelectsCasualties
         | CombatSubPhase::ShoreBombardmentCasualties
         | CombatSubPhase::DefenderSubmarineStrikeCasualties => true,
         CombatSubPhase::AttackerSelectsCasualties
-        | CombatSubPhase::AttackerSubmarineStrikeCasualties => false,
+        | CombatSubPhase::AttackerSubmarineStrikeCasualties
+        | CombatSubPhase::AAFireCasualties => false,
         _ => return Err(EngineError::InvalidAction {
             reason: format!("Not in casualty selection phase: {:?}", combat.sub_phase),
         }),
@@ -636,11 +912,12 @@ fn validate_select_casualties(state: &GameState, casualties: &[UnitId]) -> Resul
         }
     }
 
-    Ok(())
+    let air_only = combat.sub_phase == CombatSubPhase::AAFireCasualties;
+    crate::combat::validate_casualty_selection(state, combat, casualties, defender_side, air_only)
 }
 
 /// Validate AttackerRetreat action.
-fn validate_attacker_retreat(state: &GameState, _to: RegionId) -> Result<(), EngineError> {
+fn validate_attacker_retreat(state: &GameState, to: RegionId) -> Result<(), EngineError> {
     let cs = match &state.phase_state {
         PhaseState::Combat(cs) => cs,
         _ => return Err(EngineError::WrongPhase {
@@ -659,7 +936,19 @@ fn validate_attacker_retreat(state: &GameState, _to: RegionId) -> Result<(), Eng
         });
     }
 
-    // TODO: validate retreat destination is valid
+    // All attackers retreat together, to one region they attacked from
+    if !combat.retreat_options.contains(&to) {
+        return Err(EngineError::InvalidAction {
+            reason: format!("Cannot retreat to {:?}; it is not a retreat option for this battle", to),
+        });
+    }
+
+    // Nor into a battle still to be fought
+    if to == combat.location || cs.pending_battles.contains(&to) {
+        return Err(EngineError::InvalidAction {
+            reason: format!("Cannot retreat to {:?}; a battle is pending there", to),
+        });
+    }
 
     Ok(())
 }
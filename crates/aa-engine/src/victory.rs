@@ -1,11 +1,31 @@
 //! Victory condition checking for Global 1940 2nd Edition.
 
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
 use crate::action::GameEvent;
 use crate::data::GameMap;
 use crate::power::Team;
 use crate::state::GameState;
 use crate::territory::TerritoryId;
 
+/// The win condition chosen when the game is created. Surrender of a whole
+/// team ends the game under every variant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum VictoryCondition {
+    /// The standard rules: Axis victory cities on either board, or the
+    /// Allies holding Berlin and Tokyo.
+    #[default]
+    VictoryCities,
+    /// A team wins once it has controlled at least `percent` of the map's
+    /// total IPC value at the end of two rounds in a row, holding it for a
+    /// full round.
+    EconomicDomination { percent: u32 },
+    /// A team wins once it holds every enemy capital at the end of a round.
+    CapitalCapture,
+}
+
 /// Victory cities and their territory IDs.
 pub const VICTORY_CITIES: &[(TerritoryId, &str)] = &[
     // Europe board
@@ -44,7 +64,23 @@ pub struct VictoryResult {
 }
 
 /// Check if a victory condition has been met.
-pub fn check_victory(state: &GameState, _map: &GameMap) -> Option<VictoryResult> {
+///
+/// Board positions only decide the game at the end of a complete round, so
+/// the engine calls this once the last power in the turn order has collected
+/// income (see `apply::begin_next_power_turn`), never straight after a capture.
+pub fn check_victory(state: &GameState, map: &GameMap) -> Option<VictoryResult> {
+    if let Some(result) = check_surrender_victory(state) {
+        return Some(result);
+    }
+    match state.settings.victory_condition {
+        VictoryCondition::VictoryCities => check_victory_cities(state),
+        VictoryCondition::EconomicDomination { percent } => check_economic_victory(state, map, percent),
+        VictoryCondition::CapitalCapture => check_capital_victory(state, map),
+    }
+}
+
+/// The standard victory city conditions.
+fn check_victory_cities(state: &GameState) -> Option<VictoryResult> {
     // Count Axis VCs on each board
     let mut axis_europe = 0;
     let mut axis_pacific = 0;
@@ -110,6 +146,100 @@ pub fn check_victory(state: &GameState, _map: &GameMap) -> Option<VictoryResult>
     None
 }
 
+/// Economic variant: a team wins once it still controls `percent`% of all
+/// map IPCs a full round after `track_economic_leader` first saw it do so.
+fn check_economic_victory(state: &GameState, map: &GameMap, percent: u32) -> Option<VictoryResult> {
+    let (team, held, total) = economic_dominance(state, map, percent)?;
+    let (leader, since) = state.economic_leader?;
+    if leader != team || since >= state.turn_number {
+        return None;
+    }
+    Some(VictoryResult {
+        winner: team,
+        reason: format!("{:?} has controlled {} of {} IPCs for a full round", team, held, total),
+    })
+}
+
+/// The team controlling at least `percent`% of all map IPCs, with its share
+/// and the map total.
+fn economic_dominance(state: &GameState, map: &GameMap, percent: u32) -> Option<(Team, u32, u32)> {
+    let total: u32 = map.territories.iter().map(|t| t.ipc_value).sum();
+    for team in [Team::Axis, Team::Allies] {
+        let held: u32 = state
+            .territories
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.owner.is_some_and(|o| o.team() == team))
+            .map(|(i, _)| map.territories[i].ipc_value)
+            .sum();
+        if total > 0 && held * 100 >= total * percent {
+            return Some((team, held, total));
+        }
+    }
+    None
+}
+
+/// At the end of a round, note which team holds the economic threshold and
+/// since when, so `check_victory` can tell whether it has held it for a
+/// full round. Does nothing under the other victory conditions.
+pub fn track_economic_leader(state: &mut GameState, map: &GameMap) {
+    let VictoryCondition::EconomicDomination { percent } = state.settings.victory_condition else {
+        return;
+    };
+    let team = economic_dominance(state, map, percent).map(|(team, _, _)| team);
+    state.economic_leader = match (team, state.economic_leader) {
+        (Some(team), Some((leader, since))) if leader == team => Some((leader, since)),
+        (Some(team), _) => Some((team, state.turn_number)),
+        (None, _) => None,
+    };
+}
+
+/// Capital variant: a team holding every enemy capital wins.
+fn check_capital_victory(state: &GameState, map: &GameMap) -> Option<VictoryResult> {
+    for team in [Team::Axis, Team::Allies] {
+        let mut enemy_capitals = map
+            .territories
+            .iter()
+            .filter(|t| t.is_capital.is_some_and(|p| p.team() != team))
+            .peekable();
+        if enemy_capitals.peek().is_none() {
+            continue;
+        }
+        let holds_all = enemy_capitals.all(|t| {
+            state.territories[t.id as usize]
+                .owner
+                .is_some_and(|o| o.team() == team)
+        });
+        if holds_all {
+            return Some(VictoryResult {
+                winner: team,
+                reason: format!("{:?} holds every enemy capital", team),
+            });
+        }
+    }
+    None
+}
+
+/// A team wins outright once every power on the other team has surrendered.
+/// Unlike the board conditions this ends the game immediately.
+pub fn check_surrender_victory(state: &GameState) -> Option<VictoryResult> {
+    for team in [Team::Axis, Team::Allies] {
+        let all_surrendered = state
+            .powers
+            .iter()
+            .filter(|ps| ps.power.team() == team)
+            .all(|ps| ps.surrendered);
+        if all_surrendered && !state.powers.is_empty() {
+            let winner = if team == Team::Axis { Team::Allies } else { Team::Axis };
+            return Some(VictoryResult {
+                winner,
+                reason: format!("Every {:?} power has surrendered", team),
+            });
+        }
+    }
+    None
+}
+
 /// Convert victory result to a game event.
 pub fn victory_event(result: &VictoryResult) -> GameEvent {
     GameEvent::VictoryAchieved {
@@ -158,6 +288,73 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_economic_victory_at_threshold() {
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        state.settings.victory_condition = VictoryCondition::EconomicDomination { percent: 80 };
+        assert!(check_victory(&state, &map).is_none());
+
+        // Hand the Allies Axis territory until they cross 80% of the map
+        let total: u32 = map.territories.iter().map(|t| t.ipc_value).sum();
+        let allied_share = |state: &GameState| -> u32 {
+            state
+                .territories
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.owner.is_some_and(|o| o.is_allies()))
+                .map(|(i, _)| map.territories[i].ipc_value)
+                .sum()
+        };
+        for i in 0..state.territories.len() {
+            if allied_share(&state) * 100 >= total * 80 {
+                break;
+            }
+            if state.territories[i].owner.is_some_and(|o| o.is_axis()) {
+                assert!(check_victory(&state, &map).is_none());
+                state.territories[i].owner = Some(Power::UnitedKingdom);
+            }
+        }
+
+        // Crossing it isn't enough; the Allies must still hold it a round later
+        assert!(check_victory(&state, &map).is_none());
+        track_economic_leader(&mut state, &map);
+        assert_eq!(state.economic_leader, Some((Team::Allies, state.turn_number)));
+        assert!(check_victory(&state, &map).is_none());
+
+        state.turn_number += 1;
+        let result = check_victory(&state, &map).expect("the Allies held the threshold for a round");
+        assert_eq!(result.winner, Team::Allies);
+
+        // Losing the lead starts the count over
+        state.territories = setup::create_initial_state(42, &map).territories;
+        track_economic_leader(&mut state, &map);
+        assert_eq!(state.economic_leader, None);
+        assert!(check_victory(&state, &map).is_none());
+    }
+
+    #[test]
+    fn test_capital_victory_needs_every_enemy_capital() {
+        let map = GameMap::new();
+        let mut state = setup::create_initial_state(42, &map);
+        state.settings.victory_condition = VictoryCondition::CapitalCapture;
+
+        let allied_capitals: Vec<_> = map
+            .territories
+            .iter()
+            .filter(|t| t.is_capital.is_some_and(|p| p.is_allies()))
+            .map(|t| t.id)
+            .collect();
+        let (last, rest) = allied_capitals.split_last().unwrap();
+        for &tid in rest {
+            state.territories[tid as usize].owner = Some(Power::Japan);
+        }
+        assert!(check_victory(&state, &map).is_none());
+
+        state.territories[*last as usize].owner = Some(Power::Japan);
+        assert_eq!(check_victory(&state, &map).unwrap().winner, Team::Axis);
+    }
+
     #[test]
     fn test_victory_cities_exist() {
         assert_eq!(VICTORY_CITIES.len(), 18);
@@ -4,9 +4,14 @@
 //! Each game room has its own authoritative Engine instance.
 
 mod protocol;
+mod rooms;
+mod store;
+
+use std::sync::{Arc, Mutex};
 
 use axum::{
     extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::State,
     response::IntoResponse,
     routing::get,
     Router,
@@ -14,15 +19,41 @@ use axum::{
 use tower_http::cors::CorsLayer;
 use tracing_subscriber;
 
+use rooms::RoomManager;
+use store::FsGameStore;
+
+/// Shared room state for all connections.
+type SharedRooms = Arc<Mutex<RoomManager>>;
+
+/// Directory for persisted rooms, overridable with `AA_DATA_DIR`.
+const DEFAULT_DATA_DIR: &str = "data/rooms";
+
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
 
+    if let Err(problems) = aa_engine::data::GameMap::new().self_check() {
+        for problem in &problems {
+            tracing::error!("Map check failed: {}", problem);
+        }
+        panic!("Map data is inconsistent ({} problem(s))", problems.len());
+    }
+
+    let data_dir = std::env::var("AA_DATA_DIR").unwrap_or_else(|_| DEFAULT_DATA_DIR.to_string());
+    let store = FsGameStore::new(&data_dir).expect("Failed to open room data directory");
+    let mut manager = RoomManager::with_store(Box::new(store));
+    match manager.load_all() {
+        Ok(count) => tracing::info!("Restored {} room(s) from {}", count, data_dir),
+        Err(e) => tracing::warn!("Failed to restore rooms from {}: {}", data_dir, e),
+    }
+    let rooms: SharedRooms = Arc::new(Mutex::new(manager));
+
     let app = Router::new()
         .route("/", get(index))
         .route("/health", get(health))
         .route("/ws", get(ws_handler))
-        .layer(CorsLayer::permissive());
+        .layer(CorsLayer::permissive())
+        .with_state(rooms);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3001")
         .await
@@ -42,11 +73,11 @@ async fn health() -> &'static str {
     "ok"
 }
 
-async fn ws_handler(ws: WebSocketUpgrade) -> impl IntoResponse {
-    ws.on_upgrade(handle_socket)
+async fn ws_handler(ws: WebSocketUpgrade, State(rooms): State<SharedRooms>) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, rooms))
 }
 
-async fn handle_socket(mut socket: WebSocket) {
+async fn handle_socket(mut socket: WebSocket, rooms: SharedRooms) {
     // Send a welcome message
     let welcome = protocol::ServerMessage::Welcome {
         version: env!("CARGO_PKG_VERSION").to_string(),
@@ -56,13 +87,18 @@ async fn handle_socket(mut socket: WebSocket) {
         return;
     }
 
-    // Basic message loop (will be expanded in Phase 14)
+    // Message loop; the player name is set once the connection creates or
+    // joins a room
+    let mut player = None;
     while let Some(Ok(msg)) = socket.recv().await {
         match msg {
             Message::Text(text) => {
                 match serde_json::from_str::<protocol::ClientMessage>(&text) {
                     Ok(client_msg) => {
-                        let response = protocol::handle_message(client_msg);
+                        let response = {
+                            let mut rooms = rooms.lock().unwrap();
+                            protocol::handle_message(&mut rooms, &mut player, client_msg)
+                        };
                         let resp_json = serde_json::to_string(&response).unwrap();
                         if socket.send(Message::Text(resp_json.into())).await.is_err() {
                             break;
@@ -1,17 +1,36 @@
 //! WebSocket protocol message types.
 //!
-//! Defines the client->server and server->client message formats.
-//! Full room management and game logic will be added in Phase 14.
+//! Defines the client->server and server->client message formats and
+//! dispatches client messages to the `RoomManager`.
 
+use aa_engine::action::{Action, GameEvent};
+use aa_engine::power::Power;
 use serde::{Deserialize, Serialize};
 
+use crate::rooms::{unix_now, RoomManager};
+
 /// Messages sent from client to server.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientMessage {
     Ping,
     CreateRoom { player_name: String },
-    JoinRoom { room_id: String, player_name: String },
+    /// Join a room. Rejoining under a name already in the room requires the
+    /// token that name was issued.
+    JoinRoom {
+        room_id: String,
+        player_name: String,
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// Play `power` instead of the host.
+    ClaimPower { room_id: String, power: Power },
+    SubmitAction { room_id: String, action: Action },
+    /// Give each power `seconds` per turn; `None` turns the timer off. Only
+    /// the host or a player with a power may run the clock.
+    SetTurnTimer { room_id: String, seconds: Option<u64> },
+    /// End the current power's turn after its deadline has passed.
+    ForceAdvance { room_id: String },
 }
 
 /// Messages sent from server to client.
@@ -21,22 +40,83 @@ pub enum ServerMessage {
     Welcome { version: String },
     Pong,
     Error { message: String },
-    RoomCreated { room_id: String },
+    /// `token` lets the player rejoin the room under the same name.
+    RoomCreated { room_id: String, token: String },
+    RoomJoined { room_id: String, players: Vec<String>, token: String },
+    PowerClaimed { room_id: String, power: Power, player_name: String },
+    ActionAccepted { room_id: String, events: Vec<GameEvent>, state_checksum: u64 },
+    /// `turn_deadline` is a Unix timestamp in seconds, `None` if untimed.
+    TurnTimerSet { room_id: String, turn_deadline: Option<u64> },
 }
 
-/// Handle an incoming client message and return a response.
-/// This is a placeholder; full implementation in Phase 14.
-pub fn handle_message(msg: ClientMessage) -> ServerMessage {
+/// Handle an incoming client message and return a response. `player` is the
+/// name the connection created or joined a room under, if it has.
+pub fn handle_message(rooms: &mut RoomManager, player: &mut Option<String>, msg: ClientMessage) -> ServerMessage {
+    let sender = player.clone();
+    let not_joined = || ServerMessage::Error {
+        message: "Create or join a room first".into(),
+    };
     match msg {
         ClientMessage::Ping => ServerMessage::Pong,
-        ClientMessage::CreateRoom { player_name: _ } => {
-            // TODO: actual room creation
-            ServerMessage::RoomCreated {
-                room_id: "PLACEHOLDER".to_string(),
+        ClientMessage::CreateRoom { player_name } => {
+            let seed = uuid::Uuid::new_v4().as_u64_pair().0;
+            match rooms.create_room(player_name.clone(), seed) {
+                Ok((room_id, token)) => {
+                    *player = Some(player_name);
+                    ServerMessage::RoomCreated { room_id, token }
+                }
+                Err(message) => ServerMessage::Error { message },
+            }
+        }
+        ClientMessage::JoinRoom { room_id, player_name, token } => {
+            match rooms.join_room(&room_id, player_name.clone(), token.as_deref()) {
+                Ok((room, token)) => {
+                    let players = room.players.clone();
+                    *player = Some(player_name);
+                    ServerMessage::RoomJoined { players, room_id, token }
+                }
+                Err(message) => ServerMessage::Error { message },
+            }
+        }
+        ClientMessage::ClaimPower { room_id, power } => {
+            let Some(sender) = sender else { return not_joined() };
+            match rooms.claim_power(&room_id, &sender, power) {
+                Ok(()) => ServerMessage::PowerClaimed {
+                    room_id,
+                    power,
+                    player_name: sender,
+                },
+                Err(message) => ServerMessage::Error { message },
+            }
+        }
+        ClientMessage::SubmitAction { room_id, action } => {
+            let Some(sender) = sender else { return not_joined() };
+            match rooms.submit_action(&room_id, &sender, action) {
+                Ok(result) => ServerMessage::ActionAccepted {
+                    room_id,
+                    events: result.events,
+                    state_checksum: result.state_checksum,
+                },
+                Err(message) => ServerMessage::Error { message },
+            }
+        }
+        ClientMessage::SetTurnTimer { room_id, seconds } => {
+            let Some(sender) = sender else { return not_joined() };
+            match rooms.set_turn_timer(&room_id, &sender, seconds, unix_now()) {
+                Ok(turn_deadline) => ServerMessage::TurnTimerSet { room_id, turn_deadline },
+                Err(message) => ServerMessage::Error { message },
+            }
+        }
+        ClientMessage::ForceAdvance { room_id } => {
+            let Some(sender) = sender else { return not_joined() };
+            match rooms.force_advance(&room_id, &sender, unix_now()) {
+                Ok((events, state_checksum)) => ServerMessage::ActionAccepted {
+                    room_id,
+                    events,
+                    state_checksum,
+                },
+                Err(message) => ServerMessage::Error { message },
             }
         }
-        ClientMessage::JoinRoom { room_id, .. } => ServerMessage::Error {
-            message: format!("Room {} not found (not yet implemented)", room_id),
-        },
     }
 }
@@ -0,0 +1,526 @@
+//! Game room management.
+//!
+//! Each room owns an authoritative `Engine`. When a `GameStore` is attached,
+//! every change to a room is persisted before it is acknowledged, and rooms
+//! are loaded back on demand, so a server restart does not lose games in
+//! progress. A change that cannot be saved is undone and reported as an error.
+//!
+//! The player who creates a room is its host and plays every power no one
+//! else has claimed. Each player is issued a token when they first enter a
+//! room, and must present it to rejoin under the same name.
+//!
+//! Rooms may run a turn timer: once a power's deadline passes, the host or
+//! any player with a power can force the turn over and the AI plays out the
+//! rest of it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use aa_engine::action::{Action, ActionResult, GameEvent};
+use aa_engine::ai::AiDifficulty;
+use aa_engine::combat::CombatSubPhase;
+use aa_engine::phase::{CombatState, PhaseState};
+use aa_engine::power::{next_power, Power};
+use aa_engine::state::GameState;
+use aa_engine::Engine;
+
+use crate::store::GameStore;
+
+/// A single game room.
+pub struct Room {
+    pub engine: Engine,
+    /// Everyone who has joined, host first.
+    pub players: Vec<String>,
+    /// Powers claimed by a player other than the host.
+    pub seats: BTreeMap<Power, String>,
+    /// Each player's rejoin token.
+    pub tokens: BTreeMap<String, String>,
+    /// Seconds each power gets per turn, if the room is timed.
+    pub turn_seconds: Option<u64>,
+    /// Unix time (seconds) at which the current power's turn expires.
+    pub turn_deadline: Option<u64>,
+}
+
+impl Room {
+    pub(crate) fn new(engine: Engine, players: Vec<String>) -> Self {
+        Room {
+            engine,
+            players,
+            seats: BTreeMap::new(),
+            tokens: BTreeMap::new(),
+            turn_seconds: None,
+            turn_deadline: None,
+        }
+    }
+
+    /// The player who plays `power`: whoever claimed it, otherwise the host.
+    pub fn controller(&self, power: Power) -> Option<&str> {
+        self.seats.get(&power).or(self.players.first()).map(String::as_str)
+    }
+
+    /// Whether `player` is the host or plays a power: the players who may
+    /// run the room's clock.
+    pub fn is_host_or_controller(&self, player: &str) -> bool {
+        self.players.first().is_some_and(|host| host == player) || self.seats.values().any(|p| p == player)
+    }
+
+    /// Issue `player` a fresh rejoin token.
+    fn issue_token(&mut self, player: &str) -> String {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        self.tokens.insert(player.to_string(), token.clone());
+        token
+    }
+
+    /// Start the current power's clock, if the room is timed.
+    fn restart_clock(&mut self, now: u64) {
+        self.turn_deadline = self.turn_seconds.map(|secs| now + secs);
+    }
+}
+
+/// The power `action` is taken for: the one surrendering, the defender in
+/// its steps of a battle, otherwise the power whose turn it is.
+fn acting_power(state: &GameState, action: &Action) -> Power {
+    if let Action::Surrender { power } = action {
+        return *power;
+    }
+    if let PhaseState::Combat(CombatState { active_combat: Some(combat), .. }) = &state.phase_state {
+        let defender_step = matches!(
+            combat.sub_phase,
+            CombatSubPhase::DefenderSubmarineStrike
+                | CombatSubPhase::DefenderRolls
+                | CombatSubPhase::DefenderSelectsCasualties
+                | CombatSubPhase::DefenderSubmarineStrikeCasualties
+                | CombatSubPhase::ShoreBombardmentCasualties
+        );
+        if defender_step && matches!(action, Action::RollDefense | Action::SelectCasualties { .. }) {
+            return combat.defender;
+        }
+    }
+    state.current_power
+}
+
+/// Current Unix time in seconds.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// All rooms known to the server, plus the optional persistence backend.
+pub struct RoomManager {
+    rooms: HashMap<String, Room>,
+    store: Option<Box<dyn GameStore>>,
+}
+
+impl RoomManager {
+    /// Create a manager that keeps rooms in memory only.
+    pub fn new() -> Self {
+        RoomManager {
+            rooms: HashMap::new(),
+            store: None,
+        }
+    }
+
+    /// Create a manager that persists rooms to `store`.
+    pub fn with_store(store: Box<dyn GameStore>) -> Self {
+        RoomManager {
+            rooms: HashMap::new(),
+            store: Some(store),
+        }
+    }
+
+    /// Load every persisted room into memory. Returns the number loaded.
+    pub fn load_all(&mut self) -> Result<usize, String> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let ids = store.list().map_err(|e| e.to_string())?;
+        let mut loaded = 0;
+        for id in ids {
+            if self.rooms.contains_key(&id) {
+                continue;
+            }
+            match store.load(&id) {
+                Ok(Some(room)) => {
+                    self.rooms.insert(id, room);
+                    loaded += 1;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to load room {}: {}", id, e),
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Create a new room with a fresh game. Returns its ID and the host's token.
+    pub fn create_room(&mut self, player_name: String, seed: u64) -> Result<(String, String), String> {
+        let room_id = uuid::Uuid::new_v4().simple().to_string();
+        let mut room = Room::new(Engine::new_game(seed), vec![player_name.clone()]);
+        let token = room.issue_token(&player_name);
+        self.persist(&room_id, &room)?;
+        self.rooms.insert(room_id.clone(), room);
+        Ok((room_id, token))
+    }
+
+    /// Join a room, loading it from the store if it is not in memory.
+    /// Returns the room and the player's token. A name already in the room
+    /// can only be rejoined with the token it was issued; a name saved
+    /// without one is given one by the first player to rejoin under it.
+    pub fn join_room(
+        &mut self,
+        room_id: &str,
+        player_name: String,
+        token: Option<&str>,
+    ) -> Result<(&Room, String), String> {
+        if !self.rooms.contains_key(room_id) {
+            let room = match &self.store {
+                Some(store) => store.load(room_id).map_err(|e| e.to_string())?,
+                None => None,
+            }
+            .ok_or_else(|| format!("Room {} not found", room_id))?;
+            self.rooms.insert(room_id.to_string(), room);
+        }
+
+        let room = self.rooms.get_mut(room_id).expect("room was just inserted");
+        if room.players.contains(&player_name) {
+            if let Some(issued) = room.tokens.get(&player_name) {
+                return match token {
+                    Some(token) if token == issued => {
+                        let token = issued.clone();
+                        Ok((&self.rooms[room_id], token))
+                    }
+                    _ => Err(format!("{} is already in room {}", player_name, room_id)),
+                };
+            }
+            let token = room.issue_token(&player_name);
+            if let Err(e) = self.persist(room_id, &self.rooms[room_id]) {
+                self.rooms.get_mut(room_id).expect("room is loaded").tokens.remove(&player_name);
+                return Err(e);
+            }
+            return Ok((&self.rooms[room_id], token));
+        }
+
+        room.players.push(player_name.clone());
+        let token = room.issue_token(&player_name);
+        if let Err(e) = self.persist(room_id, &self.rooms[room_id]) {
+            let room = self.rooms.get_mut(room_id).expect("room is loaded");
+            room.players.pop();
+            room.tokens.remove(&player_name);
+            return Err(e);
+        }
+        Ok((&self.rooms[room_id], token))
+    }
+
+    /// Let `player` take over `power` from the host. A power someone else
+    /// has already claimed cannot be taken.
+    pub fn claim_power(&mut self, room_id: &str, player: &str, power: Power) -> Result<(), String> {
+        let room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| format!("Room {} not found", room_id))?;
+        if !room.players.iter().any(|p| p == player) {
+            return Err(format!("{} has not joined room {}", player, room_id));
+        }
+        match room.seats.get(&power) {
+            Some(holder) if holder == player => return Ok(()),
+            Some(holder) => return Err(format!("{:?} is already played by {}", power, holder)),
+            None => {}
+        }
+        room.seats.insert(power, player.to_string());
+        if let Err(e) = self.persist(room_id, &self.rooms[room_id]) {
+            self.rooms.get_mut(room_id).expect("room is loaded").seats.remove(&power);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Submit `player`'s action to a room's engine, persisting the room if
+    /// accepted. Only the controller of the power acting may submit it.
+    pub fn submit_action(&mut self, room_id: &str, player: &str, action: Action) -> Result<ActionResult, String> {
+        let room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| format!("Room {} not found", room_id))?;
+        let acting = acting_power(room.engine.state(), &action);
+        if room.controller(acting) != Some(player) {
+            return Err(format!("{} does not control {:?}", player, acting));
+        }
+        let before = (room.engine.snapshot(), room.turn_deadline);
+        let power = room.engine.state().current_power;
+        let result = room.engine.submit_action(action).map_err(|e| e.to_string())?;
+        if room.engine.state().current_power != power {
+            room.restart_clock(unix_now());
+        }
+        if let Err(e) = self.persist(room_id, &self.rooms[room_id]) {
+            let room = self.rooms.get_mut(room_id).expect("room is loaded");
+            room.engine.restore(before.0);
+            room.turn_deadline = before.1;
+            return Err(e);
+        }
+        Ok(result)
+    }
+
+    /// Give each power `seconds` per turn, or turn the timer off with `None`.
+    /// The current power's clock starts over at `now`.
+    pub fn set_turn_timer(
+        &mut self,
+        room_id: &str,
+        player: &str,
+        seconds: Option<u64>,
+        now: u64,
+    ) -> Result<Option<u64>, String> {
+        let room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| format!("Room {} not found", room_id))?;
+        if !room.is_host_or_controller(player) {
+            return Err(format!("{} may not set the turn timer", player));
+        }
+        let before = (room.turn_seconds, room.turn_deadline);
+        room.turn_seconds = seconds;
+        room.restart_clock(now);
+        if let Err(e) = self.persist(room_id, &self.rooms[room_id]) {
+            let room = self.rooms.get_mut(room_id).expect("room is loaded");
+            (room.turn_seconds, room.turn_deadline) = before;
+            return Err(e);
+        }
+        Ok(self.rooms[room_id].turn_deadline)
+    }
+
+    /// End the current power's turn once its deadline has passed, letting
+    /// the AI play out whatever is left of it. Returns the events produced and
+    /// the new state checksum.
+    pub fn force_advance(&mut self, room_id: &str, player: &str, now: u64) -> Result<(Vec<GameEvent>, u64), String> {
+        let room = self
+            .rooms
+            .get_mut(room_id)
+            .ok_or_else(|| format!("Room {} not found", room_id))?;
+        if !room.is_host_or_controller(player) {
+            return Err(format!("{} may not force the turn over", player));
+        }
+        match room.turn_deadline {
+            None => return Err("This room has no turn timer".into()),
+            Some(deadline) if now < deadline => {
+                return Err(format!("The turn has {} second(s) left", deadline - now));
+            }
+            Some(_) => {}
+        }
+        if room.engine.check_victory().is_some() {
+            return Err("The game is over".into());
+        }
+
+        let before = (room.engine.snapshot(), room.turn_deadline);
+        let state = room.engine.state();
+        let mut next = next_power(state.current_power);
+        while state.powers[next as usize].surrendered && next != state.current_power {
+            next = next_power(next);
+        }
+        let events = room
+            .engine
+            .advance_to_power(next, AiDifficulty::Normal)
+            .map_err(|e| e.to_string())?;
+        room.restart_clock(now);
+        let checksum = room.engine.state().checksum();
+        if let Err(e) = self.persist(room_id, &self.rooms[room_id]) {
+            let room = self.rooms.get_mut(room_id).expect("room is loaded");
+            room.engine.restore(before.0);
+            room.turn_deadline = before.1;
+            return Err(e);
+        }
+        Ok((events, checksum))
+    }
+
+    /// Get a loaded room.
+    #[allow(dead_code)]
+    pub fn room(&self, room_id: &str) -> Option<&Room> {
+        self.rooms.get(room_id)
+    }
+
+    /// Drop a room from memory. Its persisted copy (if any) is kept.
+    #[allow(dead_code)]
+    pub fn unload(&mut self, room_id: &str) -> Option<Room> {
+        self.rooms.remove(room_id)
+    }
+
+    fn persist(&self, room_id: &str, room: &Room) -> Result<(), String> {
+        match &self.store {
+            Some(store) => store
+                .save(room_id, room)
+                .map_err(|e| format!("Failed to persist room {}: {}", room_id, e)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for RoomManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::FsGameStore;
+
+    fn temp_store() -> (std::path::PathBuf, Box<dyn GameStore>) {
+        let dir = std::env::temp_dir().join(format!("aa-server-test-{}", uuid::Uuid::new_v4()));
+        let store = FsGameStore::new(&dir).unwrap();
+        (dir, Box::new(store))
+    }
+
+    #[test]
+    fn test_rejoin_restores_persisted_room() {
+        let (dir, store) = temp_store();
+        let mut manager = RoomManager::with_store(store);
+
+        let room_id = manager.create_room("alice".into(), 42).unwrap().0;
+        manager.submit_action(&room_id, "alice", Action::ConfirmPurchases).unwrap();
+        let before = manager.room(&room_id).unwrap().engine.serialize_state().unwrap();
+
+        manager.unload(&room_id);
+        assert!(manager.room(&room_id).is_none());
+
+        manager.join_room(&room_id, "bob".into(), None).unwrap();
+        manager.claim_power(&room_id, "bob", Power::Japan).unwrap();
+        manager.set_turn_timer(&room_id, "alice", Some(90), 1_000).unwrap();
+        manager.unload(&room_id);
+
+        let room = manager.join_room(&room_id, "carol".into(), None).unwrap().0;
+        assert_eq!(room.engine.serialize_state().unwrap(), before);
+        assert_eq!(room.players, ["alice", "bob", "carol"]);
+        assert_eq!(room.controller(Power::Japan), Some("bob"));
+        assert_eq!(room.turn_seconds, Some(90));
+        assert_eq!(room.turn_deadline, Some(1_090));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejoining_a_name_needs_its_token() {
+        let (dir, store) = temp_store();
+        let mut manager = RoomManager::with_store(store);
+        let (room_id, host_token) = manager.create_room("alice".into(), 42).unwrap();
+
+        // Nobody can take over the host's name without the host's token
+        assert!(manager.join_room(&room_id, "alice".into(), None).is_err());
+        assert!(manager.join_room(&room_id, "alice".into(), Some("guess")).is_err());
+        let bob_token = manager.join_room(&room_id, "bob".into(), None).unwrap().1;
+        assert!(manager.join_room(&room_id, "alice".into(), Some(&bob_token)).is_err());
+
+        // Tokens survive a restart
+        manager.unload(&room_id);
+        let (room, token) = manager.join_room(&room_id, "alice".into(), Some(&host_token)).unwrap();
+        assert_eq!(room.players, ["alice", "bob"]);
+        assert_eq!(token, host_token);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_all_after_restart() {
+        let (dir, store) = temp_store();
+        let mut manager = RoomManager::with_store(store);
+        let room_id = manager.create_room("alice".into(), 7).unwrap().0;
+        drop(manager);
+
+        let mut restarted = RoomManager::with_store(Box::new(FsGameStore::new(&dir).unwrap()));
+        assert_eq!(restarted.load_all().unwrap(), 1);
+        assert!(restarted.room(&room_id).is_some());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_join_unknown_room_fails() {
+        let mut manager = RoomManager::new();
+        assert!(manager.join_room("missing", "alice".into(), None).is_err());
+    }
+
+    #[test]
+    fn test_force_advance_after_deadline() {
+        let mut manager = RoomManager::new();
+        let room_id = manager.create_room("alice".into(), 42).unwrap().0;
+        manager.join_room(&room_id, "bob".into(), None).unwrap();
+        assert!(manager.force_advance(&room_id, "alice", 1_000).is_err());
+
+        assert!(manager.set_turn_timer(&room_id, "bob", Some(60), 1_000).is_err());
+        let deadline = manager.set_turn_timer(&room_id, "alice", Some(60), 1_000).unwrap();
+        assert_eq!(deadline, Some(1_060));
+        let err = manager.force_advance(&room_id, "alice", 1_030).unwrap_err();
+        assert!(err.contains("30 second"));
+        let engine = &manager.room(&room_id).unwrap().engine;
+        assert_eq!(engine.state().current_power, Power::Germany);
+
+        // A spectator cannot end the turn, a player with a power can
+        assert!(manager.force_advance(&room_id, "bob", 1_060).is_err());
+        manager.claim_power(&room_id, "bob", Power::Japan).unwrap();
+        manager.force_advance(&room_id, "bob", 1_060).unwrap();
+        let room = manager.room(&room_id).unwrap();
+        assert_eq!(room.engine.state().current_power, Power::SovietUnion);
+        assert_eq!(room.turn_deadline, Some(1_120));
+    }
+
+    /// A store whose saves always fail.
+    struct BrokenStore;
+
+    impl GameStore for BrokenStore {
+        fn save(&self, _room_id: &str, _room: &Room) -> std::io::Result<()> {
+            Err(std::io::Error::other("disk full"))
+        }
+        fn load(&self, _room_id: &str) -> std::io::Result<Option<Room>> {
+            Ok(None)
+        }
+        fn list(&self) -> std::io::Result<Vec<String>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_unsaved_action_is_rolled_back() {
+        let mut manager = RoomManager::new();
+        let room_id = manager.create_room("alice".into(), 42).unwrap().0;
+        let before = manager.room(&room_id).unwrap().engine.state().checksum();
+        manager.store = Some(Box::new(BrokenStore));
+
+        let err = manager.submit_action(&room_id, "alice", Action::ConfirmPurchases).unwrap_err();
+        assert!(err.contains("disk full"));
+        assert_eq!(manager.room(&room_id).unwrap().engine.state().checksum(), before);
+        let (events, next) = manager.room(&room_id).unwrap().engine.events_since(0).unwrap();
+        assert!(events.is_empty());
+        assert_eq!(next, 0);
+        assert!(manager.join_room(&room_id, "bob".into(), None).is_err());
+        assert_eq!(manager.room(&room_id).unwrap().players, ["alice"]);
+        assert!(manager.set_turn_timer(&room_id, "alice", Some(60), 1_000).is_err());
+        assert_eq!(manager.room(&room_id).unwrap().turn_deadline, None);
+    }
+
+    #[test]
+    fn test_only_the_acting_powers_controller_may_submit() {
+        let mut manager = RoomManager::new();
+        let room_id = manager.create_room("alice".into(), 42).unwrap().0;
+        manager.join_room(&room_id, "bob".into(), None).unwrap();
+        manager.claim_power(&room_id, "bob", Power::Germany).unwrap();
+
+        assert!(manager.submit_action(&room_id, "alice", Action::ConfirmPurchases).is_err());
+        manager.submit_action(&room_id, "bob", Action::ConfirmPurchases).unwrap();
+    }
+
+    #[test]
+    fn test_only_the_controller_may_surrender() {
+        let mut manager = RoomManager::new();
+        let room_id = manager.create_room("alice".into(), 42).unwrap().0;
+        manager.join_room(&room_id, "bob".into(), None).unwrap();
+
+        let surrender = |power| Action::Surrender { power };
+        assert!(manager.submit_action(&room_id, "bob", surrender(Power::Italy)).is_err());
+        manager.claim_power(&room_id, "bob", Power::Italy).unwrap();
+        assert!(manager.claim_power(&room_id, "alice", Power::Italy).is_err());
+        assert!(manager.submit_action(&room_id, "alice", surrender(Power::Italy)).is_err());
+        manager.submit_action(&room_id, "bob", surrender(Power::Italy)).unwrap();
+
+        let room = manager.room(&room_id).unwrap();
+        assert!(room.engine.state().powers[Power::Italy as usize].surrendered);
+        assert_eq!(room.controller(Power::Japan), Some("alice"));
+    }
+}
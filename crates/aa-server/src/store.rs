@@ -0,0 +1,135 @@
+//! Persistence for game rooms.
+//!
+//! Rooms are saved through the `GameStore` trait so the backing storage can be
+//! swapped out. `FsGameStore` writes one MessagePack file per room holding
+//! the game and who is playing it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use aa_engine::power::Power;
+use aa_engine::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::rooms::Room;
+
+/// Storage backend for rooms.
+pub trait GameStore: Send + Sync {
+    /// Persist the room, replacing any previous save.
+    fn save(&self, room_id: &str, room: &Room) -> io::Result<()>;
+
+    /// Load a room, or `None` if the room was never saved.
+    fn load(&self, room_id: &str) -> io::Result<Option<Room>>;
+
+    /// List the IDs of all persisted rooms.
+    fn list(&self) -> io::Result<Vec<String>>;
+}
+
+/// A room as written to disk. The game is kept in the engine's own
+/// serialized form.
+#[derive(Serialize, Deserialize)]
+struct RoomRecord {
+    state: Vec<u8>,
+    players: Vec<String>,
+    seats: BTreeMap<Power, String>,
+    #[serde(default)]
+    tokens: BTreeMap<String, String>,
+    #[serde(default)]
+    turn_seconds: Option<u64>,
+    #[serde(default)]
+    turn_deadline: Option<u64>,
+}
+
+fn invalid_data(e: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// Filesystem store: `<dir>/<room_id>.msgpack` per room.
+pub struct FsGameStore {
+    dir: PathBuf,
+}
+
+const EXTENSION: &str = "msgpack";
+
+impl FsGameStore {
+    /// Create a store rooted at `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(FsGameStore { dir })
+    }
+
+    fn path_for(&self, room_id: &str) -> io::Result<PathBuf> {
+        // Room IDs become file names, so keep them to a safe character set
+        let valid = !room_id.is_empty()
+            && room_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid room ID: {:?}", room_id),
+            ));
+        }
+        Ok(self.dir.join(format!("{}.{}", room_id, EXTENSION)))
+    }
+}
+
+impl GameStore for FsGameStore {
+    fn save(&self, room_id: &str, room: &Room) -> io::Result<()> {
+        let path = self.path_for(room_id)?;
+        let record = RoomRecord {
+            state: room.engine.serialize_state().map_err(invalid_data)?,
+            players: room.players.clone(),
+            seats: room.seats.clone(),
+            tokens: room.tokens.clone(),
+            turn_seconds: room.turn_seconds,
+            turn_deadline: room.turn_deadline,
+        };
+        let bytes = rmp_serde::to_vec(&record).map_err(invalid_data)?;
+        // Write to a temporary file and rename so a crash never leaves a torn save
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &path)
+    }
+
+    fn load(&self, room_id: &str) -> io::Result<Option<Room>> {
+        let path = self.path_for(room_id)?;
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // Rooms saved before players were recorded hold just the game
+        let record = rmp_serde::from_slice(&bytes).unwrap_or(RoomRecord {
+            state: bytes,
+            players: Vec::new(),
+            seats: BTreeMap::new(),
+            tokens: BTreeMap::new(),
+            turn_seconds: None,
+            turn_deadline: None,
+        });
+        let state = Engine::deserialize_state(&record.state).map_err(invalid_data)?;
+        let mut room = Room::new(Engine::from_state(state), record.players);
+        room.seats = record.seats;
+        room.tokens = record.tokens;
+        room.turn_seconds = record.turn_seconds;
+        room.turn_deadline = record.turn_deadline;
+        Ok(Some(room))
+    }
+
+    fn list(&self) -> io::Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                ids.push(stem.to_string());
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+}
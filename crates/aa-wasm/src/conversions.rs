@@ -1,10 +1,20 @@
 //! JsValue conversion helpers for the WASM bridge.
 
+use aa_engine::error::EngineError;
+
 /// Create a JSON error string for returning errors across the WASM boundary.
-pub fn error_json(message: &str) -> String {
+/// `code` is a stable category such as `"INVALID_INPUT"`; engine errors use
+/// the codes from `EngineError::code`.
+pub fn error_json(code: &str, message: &str) -> String {
     serde_json::json!({
         "error": true,
+        "code": code,
         "message": message
     })
     .to_string()
 }
+
+/// Create a JSON error string for an engine error, tagged with its code.
+pub fn engine_error_json(error: &EngineError) -> String {
+    error_json(error.code(), &error.to_string())
+}
@@ -1,7 +1,9 @@
 //! WASM bridge for the Axis & Allies game engine.
 //!
 //! This is a thin wrapper that exposes the `Engine` API to JavaScript
-//! via `wasm-bindgen`. All data crosses the boundary as JSON strings.
+//! via `wasm-bindgen`. Data crosses the boundary as JSON strings, except
+//! save files and the optional MessagePack action encoding for clients on
+//! slow connections.
 
 mod conversions;
 
@@ -45,15 +47,33 @@ impl WasmEngine {
     pub fn submit_action(&mut self, action_json: &str) -> String {
         let action: Action = match serde_json::from_str(action_json) {
             Ok(a) => a,
-            Err(e) => return conversions::error_json(&format!("Invalid action JSON: {}", e)),
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid action JSON: {}", e));
+            }
         };
+        self.submit(action)
+    }
 
-        match self.engine.submit_action(action) {
-            Ok(result) => serde_json::to_string(&result).unwrap_or_else(|e| {
-                conversions::error_json(&format!("Failed to serialize result: {}", e))
-            }),
-            Err(e) => conversions::error_json(&format!("{}", e)),
-        }
+    /// Submit a player action encoded as MessagePack (see `encodeAction`).
+    /// Returns the same JSON result or error as `submitAction`.
+    #[wasm_bindgen(js_name = submitActionBinary)]
+    pub fn submit_action_binary(&mut self, bytes: &[u8]) -> String {
+        let action: Action = match rmp_serde::from_slice(bytes) {
+            Ok(a) => a,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid action bytes: {}", e));
+            }
+        };
+        self.submit(action)
+    }
+
+    /// Encode a JSON action as the MessagePack bytes `submitActionBinary` expects.
+    #[wasm_bindgen(js_name = encodeAction)]
+    pub fn encode_action(action_json: &str) -> Result<Vec<u8>, JsValue> {
+        let action: Action = serde_json::from_str(action_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid action JSON: {}", e)))?;
+        rmp_serde::to_vec(&action)
+            .map_err(|e| JsValue::from_str(&format!("Failed to encode action: {}", e)))
     }
 
     /// Get the current game state as JSON.
@@ -61,7 +81,9 @@ impl WasmEngine {
     pub fn get_state(&self) -> String {
         self.engine
             .serialize_state_json()
-            .unwrap_or_else(|e| conversions::error_json(&format!("Failed to serialize state: {}", e)))
+            .unwrap_or_else(|e| {
+                conversions::error_json("SERIALIZATION", &format!("Failed to serialize state: {}", e))
+            })
     }
 
     /// Check if the last action can be undone.
@@ -70,12 +92,131 @@ impl WasmEngine {
         self.engine.can_undo()
     }
 
+    /// Enable or disable sandbox mode for scenario editing.
+    #[wasm_bindgen(js_name = setSandboxMode)]
+    pub fn set_sandbox_mode(&mut self, enabled: bool) {
+        self.engine.set_sandbox_mode(enabled);
+    }
+
+    /// Get the dice stream position as JSON: `{seed, counter}`, both as
+    /// decimal strings, since a u64 does not fit in a JS number.
+    #[wasm_bindgen(js_name = rngState)]
+    pub fn rng_state(&self) -> String {
+        let rng = self.engine.rng_state();
+        serde_json::json!({
+            "seed": rng.seed.to_string(),
+            "counter": rng.counter.to_string(),
+        })
+        .to_string()
+    }
+
+    /// Testing tool: move the dice stream to `seed` and `counter` to replay a
+    /// recorded roll sequence. Fails unless sandbox mode is on.
+    #[wasm_bindgen(js_name = setRngState)]
+    pub fn set_rng_state(&mut self, seed: u64, counter: u64) -> Result<(), JsValue> {
+        self.engine
+            .set_rng_state(seed, counter)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get a summary string for the current turn state.
     #[wasm_bindgen(js_name = turnSummary)]
     pub fn turn_summary(&self) -> String {
         self.engine.turn_summary()
     }
 
+    /// The seed the game was created with, for sharing a game setup.
+    pub fn seed(&self) -> u64 {
+        self.engine.seed()
+    }
+
+    /// Get structured turn information as JSON (`TurnInfo`).
+    #[wasm_bindgen(js_name = turnInfo)]
+    pub fn turn_info(&self) -> String {
+        serde_json::to_string(&self.engine.turn_info()).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize turn info: {}", e))
+        })
+    }
+
+    /// Get everything the current power still has to resolve as JSON (`Obligation[]`).
+    #[wasm_bindgen(js_name = pendingObligations)]
+    pub fn pending_obligations(&self) -> String {
+        serde_json::to_string(&self.engine.pending_obligations()).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize obligations: {}", e))
+        })
+    }
+
+    /// Get the regions a unit can legally move to as JSON (`RegionId[]`).
+    #[wasm_bindgen(js_name = legalDestinations)]
+    pub fn legal_destinations(&self, unit_id: u32) -> String {
+        serde_json::to_string(&self.engine.legal_destinations(unit_id)).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize destinations: {}", e))
+        })
+    }
+
+    /// Get the regions with enemy units a unit could attack this Combat
+    /// Movement as JSON (`RegionId[]`).
+    #[wasm_bindgen(js_name = attackableTargets)]
+    pub fn attackable_targets(&self, unit_id: u32) -> String {
+        serde_json::to_string(&self.engine.attackable_targets(unit_id)).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize targets: {}", e))
+        })
+    }
+
+    /// Why a unit cannot move right now, or `undefined` if it can.
+    #[wasm_bindgen(js_name = movementBlockReason)]
+    pub fn movement_block_reason(&self, unit_id: u32) -> Option<String> {
+        self.engine.movement_block_reason(unit_id)
+    }
+
+    /// Number of the current power's units that have not moved yet this
+    /// turn (zero outside the movement phases).
+    #[wasm_bindgen(js_name = idleUnitCount)]
+    pub fn idle_unit_count(&self) -> usize {
+        self.engine.idle_unit_count(self.engine.state().current_power)
+    }
+
+    /// Get the combat moves recorded this phase as JSON (`PlannedMove[]`).
+    #[wasm_bindgen(js_name = recordedMoves)]
+    pub fn recorded_moves(&self) -> String {
+        serde_json::to_string(&self.engine.recorded_moves()).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize moves: {}", e))
+        })
+    }
+
+    /// Get the full path of each move recorded this phase as JSON
+    /// (`[unitId, RegionId[]][]`).
+    #[wasm_bindgen(js_name = plannedPaths)]
+    pub fn planned_paths(&self) -> String {
+        serde_json::to_string(&self.engine.planned_paths()).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize paths: {}", e))
+        })
+    }
+
+    /// Get every battle resolved so far as JSON (`BattleRecord[]`).
+    #[wasm_bindgen(js_name = combatHistory)]
+    pub fn combat_history(&self) -> String {
+        serde_json::to_string(&self.engine.combat_history()).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize combat history: {}", e))
+        })
+    }
+
+    /// Get the battles the current combat moves would trigger as JSON (`RegionId[]`).
+    #[wasm_bindgen(js_name = previewPendingCombats)]
+    pub fn preview_pending_combats(&self) -> String {
+        serde_json::to_string(&self.engine.preview_pending_combats()).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize pending combats: {}", e))
+        })
+    }
+
+    /// Get suggested casualties for the pending selection as a JSON array of unit IDs.
+    #[wasm_bindgen(js_name = suggestCasualties)]
+    pub fn suggest_casualties(&self) -> String {
+        serde_json::to_string(&self.engine.suggest_casualties()).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize casualties: {}", e))
+        })
+    }
+
     /// Serialize the game state to MessagePack bytes (for save files).
     #[wasm_bindgen(js_name = serializeForSave)]
     pub fn serialize_for_save(&self) -> Result<Vec<u8>, JsValue> {
@@ -94,12 +235,14 @@ impl WasmEngine {
         })
     }
 
-    /// Get the list of currently legal actions as JSON.
+    /// Get the list of currently legal actions as JSON: the confirm and
+    /// undo actions plus the purchases, moves, placements and battle steps
+    /// open in the current phase, each with a description.
     #[wasm_bindgen(js_name = legalActions)]
     pub fn legal_actions(&self) -> String {
-        let actions = self.engine.legal_actions();
+        let actions = self.engine.available_actions();
         serde_json::to_string(&actions).unwrap_or_else(|e| {
-            conversions::error_json(&format!("Failed to serialize legal actions: {}", e))
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize legal actions: {}", e))
         })
     }
 
@@ -108,7 +251,7 @@ impl WasmEngine {
     pub fn check_victory(&self) -> String {
         let result = self.engine.check_victory();
         serde_json::to_string(&result).unwrap_or_else(|e| {
-            conversions::error_json(&format!("Failed to serialize victory check: {}", e))
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize victory check: {}", e))
         })
     }
 
@@ -129,7 +272,7 @@ impl WasmEngine {
         };
         let action = aa_engine::ai::ai_next_action(self.engine.state(), self.engine.map(), diff);
         serde_json::to_string(&action).unwrap_or_else(|e| {
-            conversions::error_json(&format!("Failed to serialize AI action: {}", e))
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize AI action: {}", e))
         })
     }
 
@@ -143,18 +286,147 @@ impl WasmEngine {
         };
         let actions = aa_engine::ai::ai_play_turn(self.engine.state(), self.engine.map(), diff);
         serde_json::to_string(&actions).unwrap_or_else(|e| {
-            conversions::error_json(&format!("Failed to serialize AI actions: {}", e))
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize AI actions: {}", e))
+        })
+    }
+
+    /// Let the AI play until it is `power`'s turn (JSON-encoded Power).
+    /// Returns a JSON array of the events produced, or an error.
+    #[wasm_bindgen(js_name = advanceToPower)]
+    pub fn advance_to_power(&mut self, power_json: &str, difficulty: &str) -> String {
+        let power: aa_engine::power::Power = match serde_json::from_str(power_json) {
+            Ok(p) => p,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid power JSON: {}", e));
+            }
+        };
+        let diff = match difficulty {
+            "easy" | "Easy" => aa_engine::ai::AiDifficulty::Easy,
+            "hard" | "Hard" => aa_engine::ai::AiDifficulty::Hard,
+            _ => aa_engine::ai::AiDifficulty::Normal,
+        };
+        match self.engine.advance_to_power(power, diff) {
+            Ok(events) => serde_json::to_string(&events).unwrap_or_else(|e| {
+                conversions::error_json("SERIALIZATION", &format!("Failed to serialize events: {}", e))
+            }),
+            Err(e) => conversions::engine_error_json(&e),
+        }
+    }
+
+    /// Preview the income `power` (JSON-encoded Power) would collect now as
+    /// JSON (`IncomeBreakdown`).
+    #[wasm_bindgen(js_name = incomeBreakdown)]
+    pub fn income_breakdown(&self, power_json: &str) -> String {
+        let power: aa_engine::power::Power = match serde_json::from_str(power_json) {
+            Ok(p) => p,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid power JSON: {}", e));
+            }
+        };
+        serde_json::to_string(&self.engine.income_breakdown(power)).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize income: {}", e))
+        })
+    }
+
+    /// The cheapest invasion of territory `target` by `power` (JSON-encoded
+    /// Power) as JSON: an `InvasionPlan`, or `null` if there is none.
+    #[wasm_bindgen(js_name = invasionRoute)]
+    pub fn invasion_route(&self, target: u16, power_json: &str) -> String {
+        let power: aa_engine::power::Power = match serde_json::from_str(power_json) {
+            Ok(p) => p,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid power JSON: {}", e));
+            }
+        };
+        serde_json::to_string(&self.engine.invasion_route(target, power)).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize invasion plan: {}", e))
         })
     }
 
-    /// Create a save file with metadata. Returns JSON string.
+    /// Combat context of the region `region_json` (a JSON `RegionId`) as
+    /// JSON: a `RegionCombatContext`, or `null` if it is not on the map.
+    #[wasm_bindgen(js_name = regionCombatContext)]
+    pub fn region_combat_context(&self, region_json: &str) -> String {
+        let region = match serde_json::from_str(region_json) {
+            Ok(region) => region,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid region JSON: {}", e));
+            }
+        };
+        serde_json::to_string(&self.engine.region_combat_context(region)).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize combat context: {}", e))
+        })
+    }
+
+    /// Battle calculator: fight the battle between `attackers_json` and
+    /// `defenders_json` (each a JSON `BattleSide`) at `location_json` (a JSON
+    /// `RegionId`) `trials` times (at most 10,000) on a copy of the game. Returns JSON
+    /// `BattleOdds` or an error; the game itself is left untouched.
+    #[wasm_bindgen(js_name = simulateBattle)]
+    pub fn simulate_battle(
+        &self,
+        attackers_json: &str,
+        defenders_json: &str,
+        location_json: &str,
+        trials: u32,
+    ) -> String {
+        use aa_engine::simulate::BattleSide;
+
+        let attacker: BattleSide = match serde_json::from_str(attackers_json) {
+            Ok(side) => side,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid attackers JSON: {}", e));
+            }
+        };
+        let defender: BattleSide = match serde_json::from_str(defenders_json) {
+            Ok(side) => side,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid defenders JSON: {}", e));
+            }
+        };
+        let location = match serde_json::from_str(location_json) {
+            Ok(region) => region,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid location JSON: {}", e));
+            }
+        };
+        match self.engine.simulate_battle(&attacker, &defender, location, trials) {
+            Ok(odds) => serde_json::to_string(&odds).unwrap_or_else(|e| {
+                conversions::error_json("SERIALIZATION", &format!("Failed to serialize odds: {}", e))
+            }),
+            Err(e) => conversions::engine_error_json(&e),
+        }
+    }
+
+    /// Get who controls `power` (JSON-encoded Power) as a JSON `ControllerKind`.
+    #[wasm_bindgen(js_name = controllerFor)]
+    pub fn controller_for(&self, power_json: &str) -> String {
+        let power: aa_engine::power::Power = match serde_json::from_str(power_json) {
+            Ok(p) => p,
+            Err(e) => {
+                return conversions::error_json("INVALID_INPUT", &format!("Invalid power JSON: {}", e));
+            }
+        };
+        serde_json::to_string(&self.engine.controller_for(power)).unwrap_or_else(|e| {
+            conversions::error_json("SERIALIZATION", &format!("Failed to serialize controller: {}", e))
+        })
+    }
+
+    /// Set who controls `power`. Both arguments are JSON-encoded.
+    #[wasm_bindgen(js_name = setController)]
+    pub fn set_controller(&mut self, power_json: &str, kind_json: &str) -> Result<(), JsValue> {
+        let power: aa_engine::power::Power = serde_json::from_str(power_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid power JSON: {}", e)))?;
+        let kind: aa_engine::multiplayer::ControllerKind = serde_json::from_str(kind_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid controller JSON: {}", e)))?;
+        self.engine.set_controller(power, kind);
+        Ok(())
+    }
+
+    /// Create a save file with metadata and the game's options. Returns JSON string.
     #[wasm_bindgen(js_name = createSaveFile)]
     pub fn create_save_file(&self, name: &str, timestamp: f64) -> Result<String, JsValue> {
-        let save = aa_engine::save::SaveFile::from_state(
-            self.engine.state(),
-            name.to_string(),
-            timestamp as u64,
-        );
+        let save = self.engine.save_file(name.to_string(), timestamp as u64);
         save.to_json_compact()
             .map_err(|e| JsValue::from_str(&format!("Save failed: {}", e)))
     }
@@ -165,10 +437,36 @@ impl WasmEngine {
         let save = aa_engine::save::SaveFile::from_json(json)
             .map_err(|e| JsValue::from_str(&format!("Load failed: {}", e)))?;
         Ok(WasmEngine {
-            engine: aa_engine::Engine::from_state(save.state),
+            engine: aa_engine::Engine::from_save(save),
         })
     }
 
+    /// Get one territory's state as JSON (`TerritoryState`).
+    #[wasm_bindgen(js_name = territoryState)]
+    pub fn territory_state(&self, id: u16) -> String {
+        match self.engine.state().territories.get(id as usize) {
+            Some(territory) => serde_json::to_string(territory).unwrap_or_else(|e| {
+                conversions::error_json("SERIALIZATION", &format!("Failed to serialize territory: {}", e))
+            }),
+            None => {
+                conversions::error_json("TERRITORY_NOT_FOUND", &format!("Territory {} does not exist", id))
+            }
+        }
+    }
+
+    /// Get one sea zone's state as JSON (`SeaZoneState`).
+    #[wasm_bindgen(js_name = seaZoneState)]
+    pub fn sea_zone_state(&self, id: u16) -> String {
+        match self.engine.state().sea_zones.get(id as usize) {
+            Some(sea_zone) => serde_json::to_string(sea_zone).unwrap_or_else(|e| {
+                conversions::error_json("SERIALIZATION", &format!("Failed to serialize sea zone: {}", e))
+            }),
+            None => {
+                conversions::error_json("SEA_ZONE_NOT_FOUND", &format!("Sea zone {} does not exist", id))
+            }
+        }
+    }
+
     /// Get the current power name.
     #[wasm_bindgen(js_name = currentPower)]
     pub fn current_power(&self) -> String {
@@ -187,3 +485,191 @@ impl WasmEngine {
         self.engine.state().turn_number
     }
 }
+
+impl WasmEngine {
+    /// Apply a decoded action and report the result as JSON.
+    fn submit(&mut self, action: Action) -> String {
+        match self.engine.submit_action(action) {
+            Ok(result) => serde_json::to_string(&result).unwrap_or_else(|e| {
+                conversions::error_json("SERIALIZATION", &format!("Failed to serialize result: {}", e))
+            }),
+            Err(e) => conversions::engine_error_json(&e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aa_engine::data::sea_zone_ids as sz;
+    use aa_engine::data::territory_ids as t;
+    use aa_engine::territory::TerritoryState;
+
+    #[test]
+    fn test_territory_state_for_germany() {
+        let engine = WasmEngine::new(42);
+        let germany: TerritoryState =
+            serde_json::from_str(&engine.territory_state(t::GERMANY)).unwrap();
+        assert_eq!(germany.owner, Some(aa_engine::power::Power::Germany));
+        assert_eq!(
+            germany.units.len(),
+            engine.engine.state().territories[t::GERMANY as usize].units.len()
+        );
+        assert!(!germany.units.is_empty());
+    }
+
+    #[test]
+    fn test_binary_actions_match_json() {
+        use aa_engine::power::Power;
+        use aa_engine::state::Relation;
+        use aa_engine::territory::RegionId;
+        use aa_engine::unit::UnitType;
+
+        let land = RegionId::Land(t::GERMANY);
+        let actions = vec![
+            Action::PurchaseUnit { unit_type: UnitType::Infantry, count: 2 },
+            Action::RemovePurchase { unit_type: UnitType::Infantry, count: 1 },
+            Action::RepairFacility { territory_id: t::GERMANY, damage_to_repair: 1 },
+            Action::ConfirmPurchases,
+            Action::MoveUnit { unit_id: 1, path: vec![land, RegionId::Land(t::WESTERN_GERMANY)] },
+            Action::MoveUnits { unit_ids: vec![1, 2], path: vec![land] },
+            Action::UndoMove { unit_id: 1 },
+            Action::BeginOperation { name: "Sea Lion".into() },
+            Action::EndOperation,
+            Action::UndoOperation { name: "Sea Lion".into() },
+            Action::ConfirmCombatMovement,
+            Action::SelectBattle { location: land },
+            Action::RollAttack,
+            Action::RollDefense,
+            Action::SelectCasualties { casualties: vec![3, 4] },
+            Action::AttackerRetreat { to: land },
+            Action::SubmergeSubmarine { unit_id: 5 },
+            Action::ContinueCombatRound,
+            Action::StrategicBombingRaid { territory_id: t::GERMANY },
+            Action::MoveUnitNonCombat { unit_id: 1, path: vec![land] },
+            Action::LandAirUnit { unit_id: 6, territory_id: land },
+            Action::ConfirmNonCombatMovement,
+            Action::PlaceUnit { unit_type: UnitType::Tank, territory_id: t::GERMANY, sea_zone: None },
+            Action::PlaceUnit { unit_type: UnitType::Submarine, territory_id: t::GERMANY, sea_zone: Some(sz::SZ_BALTIC_SEA) },
+            Action::ConfirmMobilization,
+            Action::ConfirmIncome,
+            Action::DeclareWar { against: Power::SovietUnion },
+            Action::Surrender { power: Power::Germany },
+            Action::SetRelation { a: Power::Germany, b: Power::Italy, relation: Relation::Peace },
+            Action::Undo,
+            Action::ConfirmPhase,
+        ];
+
+        for action in actions {
+            let json = serde_json::to_string(&action).unwrap();
+            let bytes = WasmEngine::encode_action(&json).unwrap();
+            let decoded: Action = rmp_serde::from_slice(&bytes).unwrap();
+            assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+
+            let mut via_json = WasmEngine::new(42);
+            let mut via_binary = WasmEngine::new(42);
+            assert_eq!(via_json.submit_action(&json), via_binary.submit_action_binary(&bytes));
+            assert_eq!(via_json.get_state(), via_binary.get_state());
+        }
+    }
+
+    #[test]
+    fn test_out_of_phase_action_reports_error_code() {
+        let mut engine = WasmEngine::new(42);
+        let json = engine.submit_action("\"ConfirmCombatMovement\"");
+        assert!(json.contains("\"code\":\"WRONG_PHASE\""), "{}", json);
+    }
+
+    #[test]
+    fn test_region_state_out_of_range() {
+        let engine = WasmEngine::new(42);
+        let error: serde_json::Value = serde_json::from_str(&engine.territory_state(u16::MAX)).unwrap();
+        assert_eq!(error["error"], true);
+        let error: serde_json::Value = serde_json::from_str(&engine.sea_zone_state(u16::MAX)).unwrap();
+        assert_eq!(error["error"], true);
+    }
+
+    #[test]
+    fn test_legal_actions_offer_phase_choices() {
+        use aa_engine::action::LegalAction;
+        use aa_engine::phase::{CombatState, Phase, PhaseState};
+        use aa_engine::power::Power;
+        use aa_engine::territory::RegionId;
+        use aa_engine::unit::{UnitInstance, UnitType};
+
+        let mut engine = WasmEngine::new(42);
+        let actions: Vec<LegalAction> = serde_json::from_str(&engine.legal_actions()).unwrap();
+        assert!(actions.iter().any(|la| matches!(la.action, Action::PurchaseUnit { .. })));
+        assert!(actions.iter().all(|la| !la.description.is_empty()));
+
+        let state = engine.engine.state_mut();
+        let mut tank = UnitInstance::new(99_001, UnitType::Tank, Power::Germany);
+        tank.moved_this_turn = true;
+        state.territories[t::FRANCE as usize].units.push(tank);
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles.push(RegionId::Land(t::FRANCE));
+        state.phase_state = PhaseState::Combat(cs);
+
+        let actions: Vec<LegalAction> = serde_json::from_str(&engine.legal_actions()).unwrap();
+        let select = actions
+            .iter()
+            .find(|la| matches!(la.action, Action::SelectBattle { location } if location == RegionId::Land(t::FRANCE)))
+            .expect("the pending battle can be selected");
+        assert!(select.description.contains("France"), "{}", select.description);
+    }
+
+    #[test]
+    fn test_rng_state_replays_rolls() {
+        use aa_engine::phase::{CombatState, Phase, PhaseState};
+        use aa_engine::power::Power;
+        use aa_engine::territory::RegionId;
+        use aa_engine::unit::{UnitInstance, UnitType};
+
+        let mut a = WasmEngine::new(42);
+        let state = a.engine.state_mut();
+        let france = &mut state.territories[t::FRANCE as usize].units;
+        france.clear();
+        for id in 99_001..99_007 {
+            let mut infantry = UnitInstance::new(id, UnitType::Infantry, Power::Germany);
+            infantry.moved_this_turn = true;
+            france.push(infantry);
+        }
+        france.push(UnitInstance::new(99_010, UnitType::Infantry, Power::France));
+        state.current_phase = Phase::ConductCombat;
+        let mut cs = CombatState::new();
+        cs.pending_battles.push(RegionId::Land(t::FRANCE));
+        state.phase_state = PhaseState::Combat(cs);
+        let before = a.get_state();
+        let rng_state = |engine: &WasmEngine| {
+            let json: serde_json::Value = serde_json::from_str(&engine.rng_state()).unwrap();
+            let field = |name: &str| json[name].as_str().unwrap().parse::<u64>().unwrap();
+            aa_engine::dice::RngState { seed: field("seed"), counter: field("counter") }
+        };
+        let start = rng_state(&a);
+
+        let select = serde_json::to_string(&Action::SelectBattle { location: RegionId::Land(t::FRANCE) }).unwrap();
+        let rolls = |engine: &mut WasmEngine| {
+            engine.submit_action(&select);
+            let result: serde_json::Value = serde_json::from_str(&engine.submit_action(r#""RollAttack""#)).unwrap();
+            result["combat_step"]["rolls"].clone()
+        };
+        let first = rolls(&mut a);
+        assert_eq!(first.as_array().unwrap().len(), 6);
+        let after = rng_state(&a);
+        assert_eq!(after.seed, start.seed);
+        assert!(after.counter > start.counter);
+
+        // A copy whose dice stream has moved on rolls the same again once reset
+        let mut b = WasmEngine::from_state(&before).unwrap();
+        b.engine.state_mut().rng_seed = 7;
+        b.engine.state_mut().rng_counter = 30;
+        b.set_sandbox_mode(true);
+        b.set_rng_state(start.seed, start.counter).unwrap();
+        assert_eq!(rolls(&mut b), first);
+
+        // Seeds past 2^53 come through exactly
+        b.set_rng_state(u64::MAX, u64::MAX - 1).unwrap();
+        assert_eq!(rng_state(&b), aa_engine::dice::RngState { seed: u64::MAX, counter: u64::MAX - 1 });
+    }
+}
@@ -1,8 +1,104 @@
 //! Tauri commands exposed to the frontend.
-//! These provide desktop-specific functionality (file dialogs, etc.).
+//! These provide desktop-specific functionality (file dialogs, etc.) and a
+//! native engine held in Tauri state, mirroring the WASM API so the desktop
+//! build does not need the WASM layer.
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use aa_engine::action::{Action, ActionResult, LegalAction};
+use aa_engine::error::EngineError;
+use aa_engine::state::GameState;
+use aa_engine::Engine;
+use tauri::State;
+
+/// The engine managed by the desktop shell, shared by every command.
+pub type EngineState = Mutex<Engine>;
 
 /// Get the engine version string.
 #[tauri::command]
 pub fn get_engine_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
+
+/// Start a new game with the given seed, replacing the current one.
+/// Returns the opening state.
+#[tauri::command]
+pub fn new_game(engine: State<'_, EngineState>, seed: u64) -> GameState {
+    start_game(&engine, seed)
+}
+
+/// Submit an action as JSON. Returns the result, or the engine's error.
+#[tauri::command]
+pub fn submit_action(engine: State<'_, EngineState>, json: String) -> Result<ActionResult, EngineError> {
+    submit(&engine, &json)
+}
+
+/// Get the full game state.
+#[tauri::command]
+pub fn get_state(engine: State<'_, EngineState>) -> Result<GameState, EngineError> {
+    Ok(lock(&engine)?.state().clone())
+}
+
+/// Get the actions open to the current power, with descriptions.
+#[tauri::command]
+pub fn legal_actions(engine: State<'_, EngineState>) -> Result<Vec<LegalAction>, EngineError> {
+    Ok(lock(&engine)?.available_actions())
+}
+
+/// The engine, unless a command panicked while holding it. The game may be
+/// half updated then, so it is refused until a new game is started.
+fn lock(engine: &EngineState) -> Result<MutexGuard<'_, Engine>, EngineError> {
+    engine.lock().map_err(|_| {
+        EngineError::Internal("The engine failed during an earlier command; start a new game".into())
+    })
+}
+
+fn start_game(engine: &EngineState, seed: u64) -> GameState {
+    // A fresh game replaces whatever state a failed command left behind
+    let mut guard = engine.lock().unwrap_or_else(PoisonError::into_inner);
+    *guard = Engine::new_game(seed);
+    let state = guard.state().clone();
+    drop(guard);
+    engine.clear_poison();
+    state
+}
+
+fn submit(engine: &EngineState, json: &str) -> Result<ActionResult, EngineError> {
+    let action: Action =
+        serde_json::from_str(json).map_err(|e| EngineError::Deserialization(e.to_string()))?;
+    lock(engine)?.submit_action(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aa_engine::phase::Phase;
+
+    #[test]
+    fn test_managed_engine_advances_a_phase() {
+        let engine: EngineState = Mutex::new(Engine::new_game(0));
+        let state = start_game(&engine, 42);
+        assert_eq!(state.current_phase, Phase::PurchaseAndRepair);
+
+        submit(&engine, r#""ConfirmPurchases""#).unwrap();
+        assert_eq!(lock(&engine).unwrap().state().current_phase, Phase::CombatMovement);
+
+        assert!(matches!(
+            submit(&engine, "not an action"),
+            Err(EngineError::Deserialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_engine_refused_after_a_failed_command_until_new_game() {
+        let engine: EngineState = Mutex::new(Engine::new_game(42));
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = engine.lock().unwrap();
+            panic!("failed mid-action");
+        });
+        assert!(matches!(lock(&engine), Err(EngineError::Internal(_))));
+
+        start_game(&engine, 7);
+        assert_eq!(lock(&engine).unwrap().state().rng_seed, 7);
+    }
+}
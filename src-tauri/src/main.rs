@@ -4,13 +4,22 @@
 
 mod commands;
 
+use std::sync::Mutex;
+
+use aa_engine::Engine;
+
 fn main() {
     tauri::Builder::default()
+        .manage(Mutex::new(Engine::new_game(0)))
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .invoke_handler(tauri::generate_handler![
             commands::get_engine_version,
+            commands::new_game,
+            commands::submit_action,
+            commands::get_state,
+            commands::legal_actions,
         ])
         .run(tauri::generate_context!())
         .expect("error while running Axis & Allies");